@@ -1,54 +1,86 @@
-use tower_lsp::lsp_types::{Position, Range};
+use tower_lsp::lsp_types::Range;
 use tree_sitter::{Node, Tree};
 
-use super::{ScopeKind, SymbolFlags, SymbolTable};
+use super::modules::{collect_exports, exported_symbol};
+use super::{ScopeKind, Signature, SignatureParameter, SymbolFlags, SymbolId, SymbolTable};
+use crate::line_index::LineIndex;
 
 /// The binder walks the AST and creates symbols and scopes
 pub struct Binder<'a> {
     source: &'a str,
+    line_index: LineIndex<'a>,
     symbol_table: SymbolTable,
     current_scope: u32,
+    /// References resolved once every declaration is bound, so uses of
+    /// hoisted and later block-scoped declarations find their symbol
+    pending_references: Vec<(String, u32, Range)>,
 }
 
 impl<'a> Binder<'a> {
     pub fn new(source: &'a str) -> Self {
         Self {
             source,
+            line_index: LineIndex::new(source),
             symbol_table: SymbolTable::new(),
             current_scope: 0,
+            pending_references: Vec::new(),
         }
     }
 
     /// Bind a parsed tree and return the symbol table
     pub fn bind(mut self, tree: &Tree) -> SymbolTable {
         self.visit_node(tree.root_node());
+        self.resolve_references();
+        self.mark_exports(tree);
         self.symbol_table
     }
 
+    /// Flag the module-level symbols named by export statements, including
+    /// export lists (`export { a, b as c }`) that may precede the declaration
+    fn mark_exports(&mut self, tree: &Tree) {
+        for export in collect_exports(tree, self.source) {
+            let Some(id) = exported_symbol(&self.symbol_table, &export) else {
+                continue;
+            };
+            if let Some(symbol) = self.symbol_table.get_symbol_mut(id) {
+                symbol.flags |= SymbolFlags::EXPORTED;
+                if export.exported == "default" {
+                    symbol.flags |= SymbolFlags::DEFAULT;
+                }
+            }
+        }
+    }
+
     fn visit_node(&mut self, node: Node) {
         match node.kind() {
             // Declarations that create symbols
             "function_declaration" => self.bind_function_declaration(node),
-            "class_declaration" => self.bind_class_declaration(node),
+            "class_declaration" | "abstract_class_declaration" => self.bind_class_declaration(node),
             "interface_declaration" => self.bind_interface_declaration(node),
             "type_alias_declaration" => self.bind_type_alias_declaration(node),
             "enum_declaration" => self.bind_enum_declaration(node),
+            "internal_module" | "module" => self.bind_module_declaration(node),
+            "function_signature" => self.bind_function_signature(node),
             "lexical_declaration" => self.bind_lexical_declaration(node),
             "variable_declaration" => self.bind_variable_declaration(node),
             "import_statement" => self.bind_import_statement(node),
 
             // Scope-creating nodes
             "arrow_function" => self.bind_arrow_function(node),
+            "function_expression" | "generator_function" => self.bind_function_expression(node),
             "method_definition" => self.bind_method_definition(node),
             "statement_block" => self.bind_block(node),
-            "if_statement" | "for_statement" | "for_in_statement" | "for_of_statement"
-            | "while_statement" | "do_statement" | "switch_statement" => {
-                self.bind_control_flow(node)
-            }
+            "for_statement" | "for_in_statement" | "for_of_statement" => self.bind_loop(node),
+            "switch_statement" => self.bind_switch(node),
+            "if_statement" | "while_statement" | "do_statement" => self.bind_control_flow(node),
             "catch_clause" => self.bind_catch_clause(node),
 
-            // Identifiers (references)
-            "identifier" => self.bind_identifier_reference(node),
+            // Statement labels and the jumps that target them
+            "labeled_statement" => self.bind_labeled_statement(node),
+            "break_statement" | "continue_statement" => self.bind_jump_statement(node),
+
+            // Identifiers (references), including `{ x }` shorthand properties
+            "identifier" | "shorthand_property_identifier" => self.bind_identifier_reference(node),
 
             // Default: visit children
             _ => self.visit_children(node),
@@ -84,13 +116,14 @@ impl<'a> Binder<'a> {
                 }
             }
 
-            self.symbol_table.create_symbol(
+            let id = self.symbol_table.create_symbol(
                 name_text,
                 flags,
                 self.node_range(&node),
                 self.node_range(&name),
                 self.current_scope,
             );
+            self.record_signature(id, node);
         }
 
         // Create scope for function body
@@ -122,7 +155,7 @@ impl<'a> Binder<'a> {
 
         // Create scope for arrow function
         let scope_id = self.symbol_table.create_scope(
-            ScopeKind::Function,
+            ScopeKind::ArrowFunction,
             self.current_scope,
             self.node_range(&node),
         );
@@ -165,13 +198,122 @@ impl<'a> Binder<'a> {
         self.current_scope = old_scope;
     }
 
+    fn bind_function_expression(&mut self, node: Node) {
+        // The scope spans the whole expression: its own name is only visible
+        // inside, as in `const f = function g() { g(); }`
+        let scope_id = self.symbol_table.create_scope(
+            ScopeKind::Function,
+            self.current_scope,
+            self.node_range(&node),
+        );
+
+        let old_scope = self.current_scope;
+        self.current_scope = scope_id;
+
+        if let Some(name) = node.child_by_field_name("name") {
+            let mut flags = SymbolFlags::FUNCTION;
+            if self.has_child_kind(&node, "async") {
+                flags |= SymbolFlags::ASYNC;
+            }
+            let id = self.symbol_table.create_symbol(
+                self.node_text(&name),
+                flags,
+                self.node_range(&node),
+                self.node_range(&name),
+                self.current_scope,
+            );
+            self.record_signature(id, node);
+        }
+
+        if let Some(params) = node.child_by_field_name("parameters") {
+            self.bind_parameters(params);
+        }
+
+        if let Some(body) = node.child_by_field_name("body") {
+            self.visit_children(body);
+        }
+
+        self.current_scope = old_scope;
+    }
+
+    /// Store the parameter list and return type of a function-like declaration
+    /// `declare function f(): void;` and overload signatures. Only the last
+    /// declaration of an overload group is bound, so the implementation
+    /// that follows the signatures does not redeclare them.
+    fn bind_function_signature(&mut self, node: Node) {
+        let Some(name) = node.child_by_field_name("name") else {
+            return;
+        };
+        let name_text = self.node_text(&name);
+        if next_function_declaration(node, self.source) == Some(name_text.clone()) {
+            return;
+        }
+
+        let mut flags = SymbolFlags::FUNCTION | SymbolFlags::HOISTED;
+        if node
+            .parent()
+            .is_some_and(|p| p.kind() == "export_statement")
+        {
+            flags |= SymbolFlags::EXPORTED;
+        }
+        let id = self.symbol_table.create_symbol(
+            name_text,
+            flags,
+            self.node_range(&node),
+            self.node_range(&name),
+            self.current_scope,
+        );
+        self.record_signature(id, node);
+    }
+
+    /// `namespace N { ... }` and `declare module 'm' { ... }`
+    fn bind_module_declaration(&mut self, node: Node) {
+        if let Some(name) = node
+            .child_by_field_name("name")
+            .filter(|name| name.kind() != "string")
+        {
+            // `namespace A.B {}` declares `A`
+            let name = match name.kind() {
+                "nested_identifier" => name.named_child(0).unwrap_or(name),
+                _ => name,
+            };
+            let mut flags = SymbolFlags::NAMESPACE;
+            if node
+                .parent()
+                .is_some_and(|p| p.kind() == "export_statement")
+            {
+                flags |= SymbolFlags::EXPORTED;
+            }
+            self.symbol_table.create_symbol(
+                self.node_text(&name),
+                flags,
+                self.node_range(&node),
+                self.node_range(&name),
+                self.current_scope,
+            );
+        }
+
+        if let Some(body) = node.child_by_field_name("body") {
+            self.visit_node(body);
+        }
+    }
+
+    fn record_signature(&mut self, id: SymbolId, node: Node) {
+        let signature = signature_of(node, self.source);
+        if let Some(symbol) = self.symbol_table.get_symbol_mut(id) {
+            symbol.signature = signature;
+        }
+    }
+
     fn bind_parameters(&mut self, params: Node) {
         let mut cursor = params.walk();
         for child in params.children(&mut cursor) {
             match child.kind() {
                 "required_parameter" | "optional_parameter" | "rest_parameter" => {
+                    self.bind_decorators(child);
                     if let Some(pattern) = child.child_by_field_name("pattern") {
-                        self.bind_pattern(pattern, SymbolFlags::PARAMETER);
+                        let flags = SymbolFlags::PARAMETER | self.parameter_modifiers(child);
+                        self.bind_pattern(pattern, flags);
                     } else {
                         // Simple identifier parameter
                         let mut param_cursor = child.walk();
@@ -205,14 +347,46 @@ impl<'a> Binder<'a> {
         }
     }
 
+    /// The modifiers that make a constructor parameter a class property, as
+    /// in `constructor(private readonly x: number)`
+    fn parameter_modifiers(&self, parameter: Node) -> SymbolFlags {
+        let mut flags = SymbolFlags::NONE;
+        let mut cursor = parameter.walk();
+        for child in parameter.children(&mut cursor) {
+            flags |= match child.kind() {
+                "readonly" => SymbolFlags::READONLY,
+                "accessibility_modifier" => match self.node_text(&child).as_str() {
+                    "private" => SymbolFlags::PRIVATE,
+                    "protected" => SymbolFlags::PROTECTED,
+                    _ => SymbolFlags::PUBLIC,
+                },
+                _ => SymbolFlags::NONE,
+            };
+        }
+        flags
+    }
+
+    /// Decorators such as `@Component()` reference the function they apply
+    fn bind_decorators(&mut self, node: Node) {
+        let mut cursor = node.walk();
+        for decorator in node.children_by_field_name("decorator", &mut cursor) {
+            self.visit_children(decorator);
+        }
+    }
+
     fn bind_class_declaration(&mut self, node: Node) {
         let name_node = node.child_by_field_name("name");
         let body_node = node.child_by_field_name("body");
 
+        self.bind_decorators(node);
+
         // Create symbol for the class
         if let Some(name) = name_node {
             let name_text = self.node_text(&name);
             let mut flags = SymbolFlags::CLASS;
+            if node.kind() == "abstract_class_declaration" {
+                flags |= SymbolFlags::ABSTRACT;
+            }
 
             if let Some(parent) = node.parent() {
                 if parent.kind() == "export_statement" {
@@ -341,6 +515,15 @@ impl<'a> Binder<'a> {
     }
 
     fn bind_variable_declarators(&mut self, node: Node, base_flags: SymbolFlags) {
+        // Declarations exported from a namespace body; top-level exports
+        // are marked once the whole file is bound
+        let mut base_flags = base_flags;
+        if node
+            .parent()
+            .is_some_and(|p| p.kind() == "export_statement")
+        {
+            base_flags |= SymbolFlags::EXPORTED;
+        }
         let mut cursor = node.walk();
         for child in node.children(&mut cursor) {
             if child.kind() == "variable_declarator" {
@@ -357,6 +540,7 @@ impl<'a> Binder<'a> {
     }
 
     fn bind_pattern(&mut self, pattern: Node, flags: SymbolFlags) {
+        let scope = self.declaration_scope(flags);
         match pattern.kind() {
             "identifier" => {
                 let name = self.node_text(&pattern);
@@ -365,7 +549,7 @@ impl<'a> Binder<'a> {
                     flags,
                     self.node_range(&pattern),
                     self.node_range(&pattern),
-                    self.current_scope,
+                    scope,
                 );
             }
             "object_pattern" => {
@@ -378,7 +562,7 @@ impl<'a> Binder<'a> {
                             flags,
                             self.node_range(&child),
                             self.node_range(&child),
-                            self.current_scope,
+                            scope,
                         );
                     } else if child.kind() == "pair_pattern" {
                         if let Some(value) = child.child_by_field_name("value") {
@@ -394,7 +578,7 @@ impl<'a> Binder<'a> {
                                     flags,
                                     self.node_range(&rest_child),
                                     self.node_range(&rest_child),
-                                    self.current_scope,
+                                    scope,
                                 );
                             }
                         }
@@ -426,7 +610,11 @@ impl<'a> Binder<'a> {
                     self.bind_import_clause(child);
                 }
                 "namespace_import" => {
-                    if let Some(name) = child.child_by_field_name("name") {
+                    // `* as ns` - the identifier has no field name in the grammar
+                    if let Some(name) = child
+                        .child_by_field_name("name")
+                        .or_else(|| child.named_child(0))
+                    {
                         let name_text = self.node_text(&name);
                         self.symbol_table.create_symbol(
                             name_text,
@@ -480,7 +668,11 @@ impl<'a> Binder<'a> {
                     }
                 }
                 "namespace_import" => {
-                    if let Some(name) = child.child_by_field_name("name") {
+                    // `* as ns` - the identifier has no field name in the grammar
+                    if let Some(name) = child
+                        .child_by_field_name("name")
+                        .or_else(|| child.named_child(0))
+                    {
                         let name_text = self.node_text(&name);
                         self.symbol_table.create_symbol(
                             name_text,
@@ -501,8 +693,17 @@ impl<'a> Binder<'a> {
         let params_node = node.child_by_field_name("parameters");
         let body_node = node.child_by_field_name("body");
 
-        // Create symbol for the method
-        if let Some(name) = name_node {
+        // `[key]() {}` reads `key`
+        if let Some(name) = name_node.filter(|name| name.kind() == "computed_property_name") {
+            self.visit_node(name);
+        }
+
+        // Create symbol for the method. Methods of object literals are
+        // properties of the object, not names in the enclosing scope.
+        let in_object = node
+            .parent()
+            .is_some_and(|parent| parent.kind() == "object");
+        if let Some(name) = name_node.filter(|_| !in_object) {
             let name_text = self.node_text(&name);
             let mut flags = SymbolFlags::METHOD;
 
@@ -513,13 +714,14 @@ impl<'a> Binder<'a> {
                 flags |= SymbolFlags::ASYNC;
             }
 
-            self.symbol_table.create_symbol(
+            let id = self.symbol_table.create_symbol(
                 name_text,
                 flags,
                 self.node_range(&node),
                 self.node_range(&name),
                 self.current_scope,
             );
+            self.record_signature(id, node);
         }
 
         // Create scope for method body
@@ -578,6 +780,106 @@ impl<'a> Binder<'a> {
         self.visit_children(node);
     }
 
+    /// Loop headers get their own scope so `let`/`const` bindings in the
+    /// initializer are not visible after the loop
+    fn bind_loop(&mut self, node: Node) {
+        let scope_id = self.symbol_table.create_scope(
+            ScopeKind::ForLoop,
+            self.current_scope,
+            self.node_range(&node),
+        );
+
+        let old_scope = self.current_scope;
+        self.current_scope = scope_id;
+
+        // `for (const x of xs)` declares `x` without a variable_declarator
+        let declared = node
+            .child_by_field_name("kind")
+            .zip(node.child_by_field_name("left"));
+        if let Some((kind, left)) = declared {
+            let flags = match kind.kind() {
+                "const" => SymbolFlags::VARIABLE | SymbolFlags::CONST,
+                "let" => SymbolFlags::VARIABLE | SymbolFlags::LET,
+                _ => SymbolFlags::VARIABLE | SymbolFlags::HOISTED,
+            };
+            self.bind_pattern(left, flags);
+            if let Some(right) = node.child_by_field_name("right") {
+                self.visit_node(right);
+            }
+            if let Some(body) = node.child_by_field_name("body") {
+                self.visit_node(body);
+            }
+        } else {
+            self.visit_children(node);
+        }
+
+        self.current_scope = old_scope;
+    }
+
+    /// `case` clauses share one scope that ends with the switch body
+    fn bind_switch(&mut self, node: Node) {
+        if let Some(value) = node.child_by_field_name("value") {
+            self.visit_node(value);
+        }
+        let Some(body) = node.child_by_field_name("body") else {
+            return;
+        };
+
+        let scope_id = self.symbol_table.create_scope(
+            ScopeKind::Switch,
+            self.current_scope,
+            self.node_range(&body),
+        );
+
+        let old_scope = self.current_scope;
+        self.current_scope = scope_id;
+        self.visit_children(body);
+        self.current_scope = old_scope;
+    }
+
+    /// The scope a declaration with `flags` belongs to: `var` bindings
+    /// skip block, loop, switch and catch scopes up to the enclosing function
+    fn declaration_scope(&self, flags: SymbolFlags) -> u32 {
+        if !flags.contains(SymbolFlags::VARIABLE | SymbolFlags::HOISTED) {
+            return self.current_scope;
+        }
+        let mut scope_id = self.current_scope;
+        while let Some(scope) = self.symbol_table.get_scope(scope_id) {
+            match (scope.kind, scope.parent) {
+                (
+                    ScopeKind::Block | ScopeKind::ForLoop | ScopeKind::Switch | ScopeKind::Catch,
+                    Some(parent),
+                ) => scope_id = parent,
+                _ => break,
+            }
+        }
+        scope_id
+    }
+
+    fn bind_labeled_statement(&mut self, node: Node) {
+        if let Some(label) = node.child_by_field_name("label") {
+            self.symbol_table.create_symbol(
+                self.node_text(&label),
+                SymbolFlags::LABEL,
+                self.node_range(&node),
+                self.node_range(&label),
+                self.current_scope,
+            );
+        }
+        self.visit_children(node);
+    }
+
+    fn bind_jump_statement(&mut self, node: Node) {
+        let target = jump_target(node, self.source)
+            .and_then(|statement| statement.child_by_field_name("label"))
+            .map(|label| self.node_range(&label));
+        if let (Some(label), Some(target)) = (node.child_by_field_name("label"), target) {
+            if let Some(id) = self.symbol_table.label_at_position(target.start) {
+                self.symbol_table.add_reference(id, self.node_range(&label));
+            }
+        }
+    }
+
     fn bind_catch_clause(&mut self, node: Node) {
         let scope_id = self.symbol_table.create_scope(
             ScopeKind::Catch,
@@ -602,20 +904,18 @@ impl<'a> Binder<'a> {
         // Skip if this identifier is part of a declaration (already handled)
         if let Some(parent) = node.parent() {
             match parent.kind() {
-                "variable_declarator" => {
-                    if parent.child_by_field_name("name") == Some(node) {
-                        return;
-                    }
+                "variable_declarator" if parent.child_by_field_name("name") == Some(node) => {
+                    return;
                 }
                 "function_declaration"
                 | "class_declaration"
                 | "interface_declaration"
                 | "type_alias_declaration"
                 | "enum_declaration"
-                | "method_definition" => {
-                    if parent.child_by_field_name("name") == Some(node) {
-                        return;
-                    }
+                | "method_definition"
+                    if parent.child_by_field_name("name") == Some(node) =>
+                {
+                    return;
                 }
                 "import_specifier"
                 | "shorthand_property_identifier_pattern"
@@ -624,15 +924,26 @@ impl<'a> Binder<'a> {
                 | "rest_parameter" => {
                     return;
                 }
+                // `y` of `export { x as y }`, and names re-exported from elsewhere
+                "export_specifier" if is_foreign_export_name(node) => {
+                    return;
+                }
                 _ => {}
             }
         }
 
-        // This is a reference - try to resolve it
+        // This is a reference - resolve it once the whole tree is bound
         let name = self.node_text(&node);
-        if let Some(symbol_id) = self.symbol_table.lookup(&name, self.current_scope) {
-            self.symbol_table
-                .add_reference(symbol_id, self.node_range(&node));
+        let range = self.node_range(&node);
+        self.pending_references
+            .push((name, self.current_scope, range));
+    }
+
+    fn resolve_references(&mut self) {
+        for (name, scope_id, range) in std::mem::take(&mut self.pending_references) {
+            if let Some(symbol_id) = self.symbol_table.lookup(&name, scope_id) {
+                self.symbol_table.add_reference(symbol_id, range);
+            }
         }
     }
 
@@ -644,19 +955,148 @@ impl<'a> Binder<'a> {
     }
 
     fn node_range(&self, node: &Node) -> Range {
-        let start = node.start_position();
-        let end = node.end_position();
-        Range {
-            start: Position::new(start.row as u32, start.column as u32),
-            end: Position::new(end.row as u32, end.column as u32),
-        }
+        self.line_index.node_range(node)
     }
 
     fn has_child_kind(&self, node: &Node, kind: &str) -> bool {
         let mut cursor = node.walk();
-        let result = node.children(&mut cursor).any(|c| c.kind() == kind);
-        result
+        node.children(&mut cursor).any(|c| c.kind() == kind)
+    }
+}
+
+/// Read the parameter list and return type of a function-like node
+/// (a declaration, method, function expression or arrow function)
+pub fn signature_of(node: Node, source: &str) -> Option<Signature> {
+    let text = |node: Node| node.utf8_text(source.as_bytes()).unwrap_or("").to_string();
+    let annotation =
+        |node: Option<Node>| node.map(|n| text(n).trim_start_matches(':').trim().to_string());
+
+    // `x => ...` has a single parameter and no parameter list
+    if let Some(param) = node.child_by_field_name("parameter") {
+        return Some(Signature {
+            type_parameters: Vec::new(),
+            parameters: vec![SignatureParameter {
+                name: text(param),
+                type_annotation: None,
+                optional: false,
+            }],
+            return_type: None,
+        });
+    }
+
+    let params = node.child_by_field_name("parameters")?;
+    let mut parameters = Vec::new();
+    let mut cursor = params.walk();
+    for child in params.named_children(&mut cursor) {
+        // `this: T` declares the receiver's type, not a parameter
+        let pattern = child.child_by_field_name("pattern");
+        if pattern.is_some_and(|p| p.kind() == "this") {
+            continue;
+        }
+        let name = match child.kind() {
+            "required_parameter" | "optional_parameter" => pattern.map(text),
+            "identifier" => Some(text(child)),
+            _ => None,
+        };
+        let Some(name) = name else {
+            continue;
+        };
+        parameters.push(SignatureParameter {
+            name,
+            type_annotation: annotation(child.child_by_field_name("type")),
+            optional: child.kind() == "optional_parameter"
+                || child.child_by_field_name("value").is_some(),
+        });
+    }
+
+    let type_parameters = node
+        .child_by_field_name("type_parameters")
+        .map(|params| {
+            let mut cursor = params.walk();
+            params
+                .named_children(&mut cursor)
+                .filter(|param| param.kind() == "type_parameter")
+                .map(text)
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Some(Signature {
+        type_parameters,
+        parameters,
+        return_type: annotation(node.child_by_field_name("return_type")),
+    })
+}
+
+/// The enclosing labeled statement a labeled `break` or `continue` jumps to.
+/// Jumps cannot leave the function they are in.
+pub fn jump_target<'t>(jump: Node<'t>, source: &str) -> Option<Node<'t>> {
+    let label = jump.child_by_field_name("label")?;
+    let name = label.utf8_text(source.as_bytes()).ok()?;
+
+    let mut current = jump.parent();
+    while let Some(node) = current {
+        match node.kind() {
+            "labeled_statement"
+                if node
+                    .child_by_field_name("label")
+                    .is_some_and(|l| l.utf8_text(source.as_bytes()) == Ok(name)) =>
+            {
+                return Some(node);
+            }
+            "function_declaration"
+            | "function_expression"
+            | "generator_function"
+            | "generator_function_declaration"
+            | "arrow_function"
+            | "method_definition"
+            | "class_body" => return None,
+            _ => {}
+        }
+        current = node.parent();
     }
+    None
+}
+
+/// Whether an identifier of an export specifier names something other than a
+/// local: the alias in `export { x as y }`, or any name of a re-export
+pub fn is_foreign_export_name(node: Node) -> bool {
+    let Some(specifier) = node.parent().filter(|p| p.kind() == "export_specifier") else {
+        return false;
+    };
+    let is_reexport = specifier
+        .parent()
+        .and_then(|clause| clause.parent())
+        .is_some_and(|statement| statement.child_by_field_name("source").is_some());
+    is_reexport || specifier.child_by_field_name("alias") == Some(node)
+}
+
+/// The name of the function the statement after `signature` declares, so
+/// `function f(a: string): void;` can tell that `function f(a) {}` follows
+fn next_function_declaration(signature: Node, source: &str) -> Option<String> {
+    let statement = match signature.parent() {
+        Some(parent) if matches!(parent.kind(), "export_statement" | "ambient_declaration") => {
+            parent
+        }
+        _ => signature,
+    };
+    let mut next = statement.next_named_sibling()?;
+    while next.kind() == "comment" {
+        next = next.next_named_sibling()?;
+    }
+    let declaration = match next.kind() {
+        "export_statement" => next.child_by_field_name("declaration")?,
+        "ambient_declaration" => next.named_child(0)?,
+        _ => next,
+    };
+    if !matches!(
+        declaration.kind(),
+        "function_signature" | "function_declaration"
+    ) {
+        return None;
+    }
+    let name = declaration.child_by_field_name("name")?;
+    name.utf8_text(source.as_bytes()).ok().map(str::to_string)
 }
 
 /// Bind a document and return the symbol table
@@ -811,12 +1251,97 @@ mod tests {
         let symbol = table.lookup("fn", 0);
         assert!(symbol.is_some());
 
-        // x should exist in function scope
+        // x should exist in the arrow function's scope
         let scopes: Vec<_> = table.all_scopes().collect();
-        let function_scope = scopes.iter().find(|s| s.kind == ScopeKind::Function);
+        let function_scope = scopes.iter().find(|s| s.kind == ScopeKind::ArrowFunction);
         assert!(function_scope.is_some());
     }
 
+    #[test]
+    fn test_bind_function_expression() {
+        let code = "const f = function g(a) { g(a); };\ng;";
+        let table = parse_and_bind(code);
+
+        assert!(table.lookup("f", 0).is_some());
+        assert!(table.lookup("g", 0).is_none());
+
+        let g = table.all_symbols().find(|s| s.name == "g").unwrap();
+        assert!(g.flags.contains(SymbolFlags::FUNCTION));
+        assert_ne!(g.scope_id, 0);
+        // Only the call inside the body refers to the function
+        assert_eq!(g.references.len(), 1);
+        assert_eq!(g.references[0].start.character, 26);
+
+        let a = table.all_symbols().find(|s| s.name == "a").unwrap();
+        assert!(a.flags.contains(SymbolFlags::PARAMETER));
+        assert_eq!(a.scope_id, g.scope_id);
+    }
+
+    #[test]
+    fn test_bind_forward_references() {
+        let code = "f();\nfunction f() {}\nlet x = 1;\n{ x; let x = 2; }";
+        let table = parse_and_bind(code);
+
+        let f = table
+            .lookup("f", 0)
+            .and_then(|id| table.get_symbol(id))
+            .unwrap();
+        assert_eq!(f.references.len(), 1);
+
+        // `x` in the block refers to the block's own (uninitialized) `x`
+        let outer = table
+            .lookup("x", 0)
+            .and_then(|id| table.get_symbol(id))
+            .unwrap();
+        assert!(outer.references.is_empty());
+        let inner = table
+            .all_symbols()
+            .find(|s| s.name == "x" && s.scope_id != 0)
+            .unwrap();
+        assert_eq!(inner.references.len(), 1);
+    }
+
+    #[test]
+    fn test_bind_labels() {
+        let code = "outer: for (;;) {\n  inner: while (x) { continue outer; }\n  break outer;\n}";
+        let table = parse_and_bind(code);
+
+        let outer = table.all_symbols().find(|s| s.name == "outer").unwrap();
+        assert!(outer.flags.contains(SymbolFlags::LABEL));
+        assert_eq!(outer.references.len(), 2);
+        // Labels don't shadow values
+        assert!(table.lookup("outer", 0).is_none());
+
+        let inner = table.all_symbols().find(|s| s.name == "inner").unwrap();
+        assert!(inner.references.is_empty());
+        assert_eq!(
+            table.label_at_position(outer.references[1].start),
+            Some(outer.id)
+        );
+    }
+
+    #[test]
+    fn test_bind_export_lists() {
+        let code = "function a() {}\nconst b = 1, c = 2;\nexport { a, b as renamed };\nexport default c;\nexport { z } from './z';\nexport const d = 3;";
+        let table = parse_and_bind(code);
+        let flags = |name: &str| {
+            table
+                .get_symbol(table.lookup(name, 0).unwrap())
+                .unwrap()
+                .flags
+        };
+
+        assert!(flags("a").contains(SymbolFlags::EXPORTED));
+        assert!(flags("b").contains(SymbolFlags::EXPORTED));
+        assert!(!flags("b").contains(SymbolFlags::DEFAULT));
+        assert!(flags("c").contains(SymbolFlags::EXPORTED | SymbolFlags::DEFAULT));
+        assert!(flags("d").contains(SymbolFlags::EXPORTED));
+
+        // The local `a` is referenced by its export, the alias isn't a reference
+        let a = table.get_symbol(table.lookup("a", 0).unwrap()).unwrap();
+        assert_eq!(a.references.len(), 1);
+    }
+
     #[test]
     fn test_bind_class_method() {
         let code = r#"
@@ -915,6 +1440,69 @@ mod tests {
         assert!(!x_symbol.references.is_empty());
     }
 
+    #[test]
+    fn test_bind_references_through_chain_operators() {
+        let code = "let foo: any;\nfoo!.bar;\nfoo?.baz;\n(foo as any)!.qux;";
+        let table = parse_and_bind(code);
+
+        let foo = table
+            .lookup("foo", 0)
+            .and_then(|id| table.get_symbol(id))
+            .unwrap();
+        let lines: Vec<u32> = foo.references.iter().map(|r| r.start.line).collect();
+        assert_eq!(lines, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_bind_decorator_references() {
+        let code = "import { Component, Input } from 'x';\n@Component({})\nclass C {\n  @Input() name: string;\n  @Input\n  m(@Input p) {}\n}";
+        let table = parse_and_bind(code);
+
+        let references = |name: &str| {
+            table
+                .lookup(name, 0)
+                .and_then(|id| table.get_symbol(id))
+                .map(|symbol| symbol.references.len())
+        };
+        assert_eq!(references("Component"), Some(1));
+        assert_eq!(references("Input"), Some(3));
+    }
+
+    #[test]
+    fn test_bind_template_substitution_references() {
+        let code = "const user = 'u';\nconst s = `hello ${user}, ${`${user}!`}`;";
+        let table = parse_and_bind(code);
+
+        let user = table
+            .lookup("user", 0)
+            .and_then(|id| table.get_symbol(id))
+            .unwrap();
+        assert_eq!(user.references.len(), 2);
+    }
+
+    #[test]
+    fn test_bind_object_literal_members() {
+        let code = "const x = 1;\nconst k = 'k';\nconst o = { x, foo(a) { return a; }, [k]() {} };";
+        let table = parse_and_bind(code);
+
+        let x = table
+            .lookup("x", 0)
+            .and_then(|id| table.get_symbol(id))
+            .unwrap();
+        assert_eq!(x.references.len(), 1);
+        let k = table
+            .lookup("k", 0)
+            .and_then(|id| table.get_symbol(id))
+            .unwrap();
+        assert_eq!(k.references.len(), 1);
+
+        // The method is a property of `o` with its own function scope
+        assert!(table.lookup("foo", 0).is_none());
+        let a = table.all_symbols().find(|s| s.name == "a").unwrap();
+        assert_ne!(a.scope_id, 0);
+        assert_eq!(a.references.len(), 1);
+    }
+
     #[test]
     fn test_bind_nested_scopes() {
         let code = r#"
@@ -966,6 +1554,28 @@ mod tests {
         assert!(scopes.iter().any(|s| s.kind == ScopeKind::Catch));
     }
 
+    #[test]
+    fn test_bind_loop_and_switch_scopes() {
+        let code = "for (let i = 0; ; ) {}\nfor (const x in o) {}\nswitch (v) { case 1: let y = 1; }\nfor (var j of o) {}";
+        let table = parse_and_bind(code);
+        let root = table.root_scope_id();
+
+        let scopes: Vec<_> = table.all_scopes().collect();
+        assert_eq!(
+            scopes
+                .iter()
+                .filter(|s| s.kind == ScopeKind::ForLoop)
+                .count(),
+            3
+        );
+        assert!(scopes.iter().any(|s| s.kind == ScopeKind::Switch));
+
+        assert!(table.lookup("i", root).is_none());
+        assert!(table.lookup("x", root).is_none());
+        assert!(table.lookup("y", root).is_none());
+        assert!(table.lookup("j", root).is_some());
+    }
+
     #[test]
     fn test_bind_exported_function() {
         let code = "export function hello() { }";
@@ -1013,4 +1623,66 @@ mod tests {
         let table = parse_and_bind("// This is a comment");
         assert_eq!(table.root_scope_id(), 0);
     }
+
+    #[test]
+    fn test_bind_ambient_declarations() {
+        let table = parse_and_bind(
+            "namespace NS { export const a = 1; }\ndeclare function df(x: number): void;\nfunction ov(a: string): void;\nfunction ov(a: any) {}",
+        );
+        let flags = |name: &str| {
+            table
+                .get_symbol(table.lookup(name, 0).unwrap())
+                .unwrap()
+                .flags
+        };
+
+        assert!(flags("NS").contains(SymbolFlags::NAMESPACE));
+        assert!(flags("df").contains(SymbolFlags::FUNCTION));
+        assert!(
+            table
+                .get_symbol(table.lookup("df", 0).unwrap())
+                .unwrap()
+                .signature
+                .is_some()
+        );
+        // The implementation follows its overload without redeclaring it
+        assert!(!flags("ov").contains(SymbolFlags::DUPLICATE));
+        let a = table
+            .all_symbols()
+            .find(|s| s.name == "a" && s.flags.contains(SymbolFlags::CONST));
+        assert!(a.unwrap().flags.contains(SymbolFlags::EXPORTED));
+    }
+
+    #[test]
+    fn test_bind_parameter_property_modifiers() {
+        let table = parse_and_bind(
+            "class C { constructor(private x: number, readonly y: string, z: number) {} }",
+        );
+        let flags = |name: &str| table.all_symbols().find(|s| s.name == name).unwrap().flags;
+
+        assert!(flags("x").contains(SymbolFlags::PARAMETER | SymbolFlags::PRIVATE));
+        assert!(flags("y").contains(SymbolFlags::PARAMETER | SymbolFlags::READONLY));
+        assert_eq!(flags("z"), SymbolFlags::PARAMETER);
+    }
+
+    #[test]
+    fn test_bind_function_signature() {
+        let table = parse_and_bind("function f({ a }: Opts, n = 1, ...rest: string[]): void {}");
+        let f = table.get_symbol(table.lookup("f", 0).unwrap()).unwrap();
+        let signature = f.signature.as_ref().unwrap();
+
+        let names: Vec<_> = signature
+            .parameters
+            .iter()
+            .map(|p| p.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["{ a }", "n", "...rest"]);
+        assert_eq!(
+            signature.parameters[0].type_annotation.as_deref(),
+            Some("Opts")
+        );
+        assert!(signature.parameters[1].optional);
+        assert!(signature.parameters[2].is_rest());
+        assert_eq!(signature.return_type.as_deref(), Some("void"));
+    }
 }
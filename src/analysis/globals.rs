@@ -0,0 +1,300 @@
+//! Names the standard library and the host environments declare globally
+//! Grouped by the lib (or typings package) that declares them
+
+/// `lib.es5.d.ts`
+const ES5: &[&str] = &[
+    "NaN",
+    "Infinity",
+    "undefined",
+    "eval",
+    "parseInt",
+    "parseFloat",
+    "isNaN",
+    "isFinite",
+    "decodeURI",
+    "decodeURIComponent",
+    "encodeURI",
+    "encodeURIComponent",
+    "escape",
+    "unescape",
+    "Object",
+    "Function",
+    "String",
+    "Boolean",
+    "Number",
+    "Math",
+    "Date",
+    "RegExp",
+    "Error",
+    "EvalError",
+    "RangeError",
+    "ReferenceError",
+    "SyntaxError",
+    "TypeError",
+    "URIError",
+    "JSON",
+    "Array",
+    "ArrayBuffer",
+    "DataView",
+    "Int8Array",
+    "Uint8Array",
+    "Uint8ClampedArray",
+    "Int16Array",
+    "Uint16Array",
+    "Int32Array",
+    "Uint32Array",
+    "Float32Array",
+    "Float64Array",
+    "Intl",
+];
+
+/// `lib.es2015.*.d.ts` through `lib.esnext.*.d.ts`
+const ES2015_AND_LATER: &[&str] = &[
+    "Map",
+    "WeakMap",
+    "Set",
+    "WeakSet",
+    "Promise",
+    "Proxy",
+    "Reflect",
+    "Symbol",
+    "SharedArrayBuffer",
+    "Atomics",
+    "BigInt",
+    "BigInt64Array",
+    "BigUint64Array",
+    "globalThis",
+    "WeakRef",
+    "FinalizationRegistry",
+    "AggregateError",
+    "Iterator",
+    "SuppressedError",
+    "DisposableStack",
+    "AsyncDisposableStack",
+];
+
+/// `lib.dom.d.ts` and `lib.webworker.d.ts`
+const DOM: &[&str] = &[
+    // Functions and values of the global object
+    "window",
+    "self",
+    "document",
+    "navigator",
+    "location",
+    "history",
+    "screen",
+    "localStorage",
+    "sessionStorage",
+    "console",
+    "alert",
+    "confirm",
+    "prompt",
+    "fetch",
+    "setTimeout",
+    "clearTimeout",
+    "setInterval",
+    "clearInterval",
+    "queueMicrotask",
+    "structuredClone",
+    "requestAnimationFrame",
+    "cancelAnimationFrame",
+    "requestIdleCallback",
+    "cancelIdleCallback",
+    "atob",
+    "btoa",
+    "crypto",
+    "performance",
+    "indexedDB",
+    "caches",
+    "customElements",
+    "getComputedStyle",
+    "matchMedia",
+    "postMessage",
+    "addEventListener",
+    "removeEventListener",
+    "dispatchEvent",
+    "reportError",
+    "createImageBitmap",
+    "importScripts",
+    "origin",
+    "innerWidth",
+    "innerHeight",
+    "scrollX",
+    "scrollY",
+    "devicePixelRatio",
+    "isSecureContext",
+    "WebAssembly",
+    // Constructors
+    "URL",
+    "URLSearchParams",
+    "Headers",
+    "Request",
+    "Response",
+    "FormData",
+    "Blob",
+    "File",
+    "FileList",
+    "FileReader",
+    "AbortController",
+    "AbortSignal",
+    "TextEncoder",
+    "TextDecoder",
+    "TextEncoderStream",
+    "TextDecoderStream",
+    "ReadableStream",
+    "WritableStream",
+    "TransformStream",
+    "CompressionStream",
+    "DecompressionStream",
+    "Event",
+    "EventTarget",
+    "CustomEvent",
+    "ErrorEvent",
+    "MessageEvent",
+    "KeyboardEvent",
+    "MouseEvent",
+    "PointerEvent",
+    "TouchEvent",
+    "FocusEvent",
+    "InputEvent",
+    "WheelEvent",
+    "DragEvent",
+    "ProgressEvent",
+    "PopStateEvent",
+    "HashChangeEvent",
+    "StorageEvent",
+    "SubmitEvent",
+    "AnimationEvent",
+    "TransitionEvent",
+    "ClipboardEvent",
+    "Node",
+    "Element",
+    "Document",
+    "DocumentFragment",
+    "ShadowRoot",
+    "Text",
+    "Comment",
+    "Attr",
+    "NodeList",
+    "HTMLCollection",
+    "HTMLElement",
+    "HTMLAnchorElement",
+    "HTMLAudioElement",
+    "HTMLBodyElement",
+    "HTMLButtonElement",
+    "HTMLCanvasElement",
+    "HTMLDivElement",
+    "HTMLFormElement",
+    "HTMLHeadElement",
+    "HTMLIFrameElement",
+    "HTMLImageElement",
+    "HTMLInputElement",
+    "HTMLLabelElement",
+    "HTMLMediaElement",
+    "HTMLOptionElement",
+    "HTMLScriptElement",
+    "HTMLSelectElement",
+    "HTMLSpanElement",
+    "HTMLTableElement",
+    "HTMLTemplateElement",
+    "HTMLTextAreaElement",
+    "HTMLVideoElement",
+    "SVGElement",
+    "SVGSVGElement",
+    "Image",
+    "Audio",
+    "Option",
+    "DOMParser",
+    "XMLSerializer",
+    "XMLHttpRequest",
+    "WebSocket",
+    "Worker",
+    "SharedWorker",
+    "MessageChannel",
+    "MessagePort",
+    "BroadcastChannel",
+    "EventSource",
+    "MutationObserver",
+    "IntersectionObserver",
+    "ResizeObserver",
+    "PerformanceObserver",
+    "CanvasRenderingContext2D",
+    "OffscreenCanvas",
+    "ImageData",
+    "ImageBitmap",
+    "Path2D",
+    "WebGLRenderingContext",
+    "WebGL2RenderingContext",
+    "DOMRect",
+    "DOMException",
+    "DOMTokenList",
+    "CSSStyleDeclaration",
+    "CSS",
+    "Range",
+    "Selection",
+    "Storage",
+    "Location",
+    "History",
+    "Navigator",
+    "Window",
+    "Screen",
+    "Crypto",
+    "SubtleCrypto",
+    "CryptoKey",
+    "Notification",
+    "IDBDatabase",
+    "IDBKeyRange",
+    "Cache",
+    "CacheStorage",
+    "Clipboard",
+    "ClipboardItem",
+    "MediaStream",
+    "MediaRecorder",
+    "AudioContext",
+];
+
+/// `@types/node`
+const NODE: &[&str] = &[
+    "process",
+    "require",
+    "module",
+    "exports",
+    "__dirname",
+    "__filename",
+    "global",
+    "Buffer",
+    "setImmediate",
+    "clearImmediate",
+    "NodeJS",
+];
+
+/// `@types/react`, whose UMD global and `JSX` namespace are in scope in TSX
+const REACT: &[&str] = &["React", "JSX"];
+
+const LIBS: &[&[&str]] = &[ES5, ES2015_AND_LATER, DOM, NODE, REACT];
+
+/// Whether `name` is declared by the standard library or a host environment
+pub fn is_global(name: &str) -> bool {
+    LIBS.iter().any(|lib| lib.contains(&name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_global() {
+        for name in [
+            "Array",
+            "Promise",
+            "fetch",
+            "URL",
+            "structuredClone",
+            "process",
+        ] {
+            assert!(is_global(name), "{name}");
+        }
+        assert!(!is_global("myVariable"));
+        assert!(!is_global("arguments"));
+    }
+}
@@ -1,4 +1,6 @@
 pub mod binder;
+pub mod globals;
+pub mod modules;
 pub mod scope;
 pub mod symbol;
 pub mod symbol_table;
@@ -8,5 +10,5 @@ pub mod symbol_table;
 #[allow(unused_imports)]
 pub use binder::Binder;
 pub use scope::{Scope, ScopeKind};
-pub use symbol::{Symbol, SymbolFlags, SymbolId};
+pub use symbol::{Signature, SignatureParameter, Symbol, SymbolFlags, SymbolId};
 pub use symbol_table::SymbolTable;
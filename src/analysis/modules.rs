@@ -0,0 +1,348 @@
+//! Import and export declarations of a module
+//! Used to follow symbols across files
+
+use tower_lsp::lsp_types::Range;
+use tree_sitter::{Node, Tree};
+
+use crate::line_index::LineIndex;
+
+use super::{SymbolId, SymbolTable};
+
+/// What an import binding refers to in the imported module
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ImportedName {
+    /// `import x from '...'`
+    Default,
+    /// `import { x } from '...'` or `import { x as y } from '...'`
+    Named(String),
+    /// `import * as ns from '...'`
+    Namespace,
+}
+
+/// A local name introduced by an import statement
+#[derive(Debug, Clone)]
+pub struct ImportBinding {
+    /// The local name in the importing file
+    pub local: String,
+    /// The range of the local name
+    pub local_range: Range,
+    /// The name imported from the module
+    pub imported: ImportedName,
+    /// The module specifier
+    pub specifier: String,
+}
+
+/// A name exported by a module
+#[derive(Debug, Clone)]
+pub struct ExportBinding {
+    /// The exported name (`default` for the default export)
+    pub exported: String,
+    /// The local declaration being exported, if it has a name
+    pub local: Option<String>,
+    /// The range of the exported declaration's name, or of the export itself
+    pub range: Range,
+    /// The module specifier for re-exports (`export { x } from '...'`)
+    pub from: Option<String>,
+}
+
+impl ImportedName {
+    /// The name this import looks up among the module's exports
+    pub fn export_name(&self) -> Option<&str> {
+        match self {
+            ImportedName::Default => Some("default"),
+            ImportedName::Named(name) => Some(name),
+            ImportedName::Namespace => None,
+        }
+    }
+}
+
+/// Collect the bindings created by the top-level import statements
+pub fn collect_imports(tree: &Tree, source: &str) -> Vec<ImportBinding> {
+    let index = LineIndex::new(source);
+    let mut imports = Vec::new();
+
+    let root = tree.root_node();
+    let mut cursor = root.walk();
+    for statement in root.children(&mut cursor) {
+        if statement.kind() != "import_statement" {
+            continue;
+        }
+        let Some(specifier) = statement
+            .child_by_field_name("source")
+            .map(|s| string_value(s, source))
+        else {
+            continue;
+        };
+
+        let mut push = |name: Node, imported: ImportedName| {
+            imports.push(ImportBinding {
+                local: text_of(name, source).to_string(),
+                local_range: index.node_range(&name),
+                imported,
+                specifier: specifier.clone(),
+            });
+        };
+
+        let mut statement_cursor = statement.walk();
+        for clause in statement.children(&mut statement_cursor) {
+            if clause.kind() != "import_clause" {
+                continue;
+            }
+            let mut clause_cursor = clause.walk();
+            for child in clause.children(&mut clause_cursor) {
+                match child.kind() {
+                    "identifier" => push(child, ImportedName::Default),
+                    "namespace_import" => {
+                        if let Some(name) = child.named_child(0) {
+                            push(name, ImportedName::Namespace);
+                        }
+                    }
+                    "named_imports" => {
+                        let mut spec_cursor = child.walk();
+                        for spec in child.named_children(&mut spec_cursor) {
+                            let Some(name) = spec.child_by_field_name("name") else {
+                                continue;
+                            };
+                            let local = spec.child_by_field_name("alias").unwrap_or(name);
+                            let imported = match text_of(name, source) {
+                                "default" => ImportedName::Default,
+                                name => ImportedName::Named(name.to_string()),
+                            };
+                            push(local, imported);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    imports
+}
+
+/// Collect the names exported by the top-level export statements
+pub fn collect_exports(tree: &Tree, source: &str) -> Vec<ExportBinding> {
+    let index = LineIndex::new(source);
+    let mut exports = Vec::new();
+
+    let root = tree.root_node();
+    let mut cursor = root.walk();
+    for statement in root.children(&mut cursor) {
+        if statement.kind() != "export_statement" {
+            continue;
+        }
+        let is_default = statement
+            .children(&mut statement.walk())
+            .any(|c| c.kind() == "default");
+        let from = statement
+            .child_by_field_name("source")
+            .map(|s| string_value(s, source));
+
+        if let Some(declaration) = statement.child_by_field_name("declaration") {
+            for name in declared_names(declaration) {
+                exports.push(ExportBinding {
+                    exported: if is_default {
+                        "default".to_string()
+                    } else {
+                        text_of(name, source).to_string()
+                    },
+                    local: Some(text_of(name, source).to_string()),
+                    range: index.node_range(&name),
+                    from: None,
+                });
+            }
+            continue;
+        }
+
+        if let Some(value) = statement.child_by_field_name("value") {
+            // `export default expr` - only a plain identifier names a declaration
+            let local = (value.kind() == "identifier").then(|| text_of(value, source).to_string());
+            exports.push(ExportBinding {
+                exported: "default".to_string(),
+                local,
+                range: index.node_range(&value),
+                from: None,
+            });
+            continue;
+        }
+
+        let mut statement_cursor = statement.walk();
+        for clause in statement.children(&mut statement_cursor) {
+            // `export * as ns from '...'`
+            if clause.kind() == "namespace_export" {
+                if let Some(name) = clause.named_child(0) {
+                    exports.push(ExportBinding {
+                        exported: text_of(name, source).to_string(),
+                        local: None,
+                        range: index.node_range(&name),
+                        from: from.clone(),
+                    });
+                }
+                continue;
+            }
+            if clause.kind() != "export_clause" {
+                continue;
+            }
+            let mut clause_cursor = clause.walk();
+            for spec in clause.named_children(&mut clause_cursor) {
+                let Some(name) = spec.child_by_field_name("name") else {
+                    continue;
+                };
+                let exported = spec.child_by_field_name("alias").unwrap_or(name);
+                exports.push(ExportBinding {
+                    exported: text_of(exported, source).to_string(),
+                    local: Some(text_of(name, source).to_string()),
+                    range: index.node_range(&exported),
+                    from: from.clone(),
+                });
+            }
+        }
+    }
+
+    exports
+}
+
+/// Module specifiers of `export * from '...'` statements
+pub fn collect_star_exports(tree: &Tree, source: &str) -> Vec<String> {
+    let root = tree.root_node();
+    let mut cursor = root.walk();
+    root.children(&mut cursor)
+        .filter(|statement| statement.kind() == "export_statement")
+        .filter(|statement| {
+            statement
+                .children(&mut statement.walk())
+                .any(|c| c.kind() == "*")
+        })
+        .filter_map(|statement| statement.child_by_field_name("source"))
+        .map(|specifier| string_value(specifier, source))
+        .collect()
+}
+
+/// Find the module-level symbol behind an export, unless it is a re-export
+pub fn exported_symbol(symbol_table: &SymbolTable, export: &ExportBinding) -> Option<SymbolId> {
+    if export.from.is_some() {
+        return None;
+    }
+    let local = export.local.as_deref()?;
+    let root = symbol_table.root_scope_id();
+    symbol_table
+        .lookup(local, root)
+        .or_else(|| symbol_table.lookup_type(local, root))
+}
+
+/// Name nodes declared by an exported declaration
+fn declared_names(declaration: Node) -> Vec<Node> {
+    match declaration.kind() {
+        "lexical_declaration" | "variable_declaration" => {
+            let mut cursor = declaration.walk();
+            declaration
+                .named_children(&mut cursor)
+                .filter(|d| d.kind() == "variable_declarator")
+                .filter_map(|d| d.child_by_field_name("name"))
+                .filter(|name| name.kind() == "identifier")
+                .collect()
+        }
+        _ => declaration
+            .child_by_field_name("name")
+            .into_iter()
+            .collect(),
+    }
+}
+
+fn string_value(node: Node, source: &str) -> String {
+    text_of(node, source)
+        .trim_matches(|c| c == '"' || c == '\'' || c == '`')
+        .to_string()
+}
+
+fn text_of<'a>(node: Node, source: &'a str) -> &'a str {
+    node.utf8_text(source.as_bytes()).unwrap_or("")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::binder::bind_document;
+
+    fn parse_typescript(code: &str) -> Tree {
+        let mut parser = tree_sitter::Parser::new();
+        parser
+            .set_language(&tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into())
+            .unwrap();
+        parser.parse(code, None).unwrap()
+    }
+
+    #[test]
+    fn test_collect_imports() {
+        let code = "import d, { a, b as c } from './m';\nimport * as ns from \"./n\";";
+        let tree = parse_typescript(code);
+        let imports = collect_imports(&tree, code);
+
+        let summary: Vec<_> = imports
+            .iter()
+            .map(|i| (i.local.as_str(), i.imported.clone(), i.specifier.as_str()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("d", ImportedName::Default, "./m"),
+                ("a", ImportedName::Named("a".to_string()), "./m"),
+                ("c", ImportedName::Named("b".to_string()), "./m"),
+                ("ns", ImportedName::Namespace, "./n"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_collect_exports() {
+        let code = r#"
+export const x = 1, y = 2;
+export default function foo() {}
+export { p, q as r };
+export { s } from './t';
+export interface I {}
+"#;
+        let tree = parse_typescript(code);
+        let exports = collect_exports(&tree, code);
+
+        let summary: Vec<_> = exports
+            .iter()
+            .map(|e| (e.exported.as_str(), e.local.as_deref(), e.from.as_deref()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("x", Some("x"), None),
+                ("y", Some("y"), None),
+                ("default", Some("foo"), None),
+                ("p", Some("p"), None),
+                ("r", Some("q"), None),
+                ("s", Some("s"), Some("./t")),
+                ("I", Some("I"), None),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_collect_star_exports() {
+        let code = "export * from './a';\nexport * as b from './b';\nexport { c } from './c';";
+        let tree = parse_typescript(code);
+
+        assert_eq!(collect_star_exports(&tree, code), vec!["./a"]);
+        let exports = collect_exports(&tree, code);
+        assert_eq!(exports[0].exported, "b");
+        assert_eq!(exports[0].from.as_deref(), Some("./b"));
+    }
+
+    #[test]
+    fn test_exported_symbol() {
+        let code = "function helper() {}\nexport { helper as h };\nexport { z } from './z';";
+        let tree = parse_typescript(code);
+        let table = bind_document(&tree, code);
+        let exports = collect_exports(&tree, code);
+
+        let id = exported_symbol(&table, &exports[0]).unwrap();
+        assert_eq!(table.get_symbol(id).unwrap().name, "helper");
+        assert!(exported_symbol(&table, &exports[1]).is_none());
+    }
+}
@@ -14,8 +14,10 @@ use super::SymbolId;
 pub enum ScopeKind {
     /// Global/module scope
     Global,
-    /// Function scope (includes arrow functions)
+    /// Function scope (function declarations, expressions and methods)
     Function,
+    /// Arrow function scope, which shares `this` with its enclosing scope
+    ArrowFunction,
     /// Block scope (if, for, while, etc.)
     Block,
     /// Class body scope
@@ -84,10 +86,10 @@ impl Scope {
 
     /// Check if this scope can have var declarations hoisted through it
     pub fn allows_var_hoisting(&self) -> bool {
-        match self.kind {
-            ScopeKind::Global | ScopeKind::Function => false,
-            _ => true,
-        }
+        !matches!(
+            self.kind,
+            ScopeKind::Global | ScopeKind::Function | ScopeKind::ArrowFunction
+        )
     }
 
     /// Check if a position is within this scope
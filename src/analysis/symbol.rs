@@ -5,6 +5,9 @@
 
 use tower_lsp::lsp_types::{Position, Range};
 
+use crate::types::TypeId;
+use crate::types::types::FunctionType;
+
 /// Unique identifier for a symbol
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct SymbolId(pub u32);
@@ -34,6 +37,7 @@ bitflags::bitflags! {
         const NAMESPACE = 1 << 9;
         const ENUM_MEMBER = 1 << 10;
         const TYPE_PARAMETER = 1 << 11;
+        const LABEL = 1 << 12;
 
         // Modifiers
         const CONST = 1 << 16;
@@ -47,6 +51,7 @@ bitflags::bitflags! {
         const PROTECTED = 1 << 24;
         const PUBLIC = 1 << 25;
         const ABSTRACT = 1 << 26;
+        const DUPLICATE = 1 << 27; // redeclares a name already bound in its scope
 
         // Special
         const HOISTED = 1 << 28;  // var and function declarations
@@ -55,6 +60,147 @@ bitflags::bitflags! {
     }
 }
 
+/// A parameter of a function or method declaration
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignatureParameter {
+    /// The parameter name (or destructuring pattern, or `...rest`)
+    pub name: String,
+    /// The annotated type, without the leading colon
+    pub type_annotation: Option<String>,
+    /// Whether the parameter is optional (`x?: T` or has a default value)
+    pub optional: bool,
+}
+
+/// The declared parameters and return type of a function or method
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Signature {
+    /// Type parameters as written, e.g. `T extends object`
+    pub type_parameters: Vec<String>,
+    pub parameters: Vec<SignatureParameter>,
+    /// The annotated return type, without the leading colon
+    pub return_type: Option<String>,
+}
+
+impl SignatureParameter {
+    /// `name?: type` as written in a signature
+    pub fn label(&self) -> String {
+        let mut label = self.name.clone();
+        if self.optional {
+            label.push('?');
+        }
+        if let Some(ty) = &self.type_annotation {
+            label.push_str(": ");
+            label.push_str(ty);
+        }
+        label
+    }
+
+    pub fn is_rest(&self) -> bool {
+        self.name.starts_with("...")
+    }
+}
+
+impl Signature {
+    /// `name<T>(a: A, b?: B): R`
+    pub fn label(&self, name: &str) -> String {
+        let mut label = name.to_string();
+        if !self.type_parameters.is_empty() {
+            label.push_str(&format!("<{}>", self.type_parameters.join(", ")));
+        }
+        let parameters: Vec<String> = self.parameters.iter().map(|p| p.label()).collect();
+        label.push_str(&format!("({})", parameters.join(", ")));
+        if let Some(ty) = &self.return_type {
+            label.push_str(": ");
+            label.push_str(ty);
+        }
+        label
+    }
+
+    /// `[start, end)` of each type parameter and then each parameter within
+    /// `self.label(name)`, in UTF-16 code units as LSP label offsets expect
+    pub fn parameter_offsets(&self, name: &str) -> Vec<[u32; 2]> {
+        let utf16_len = |s: &str| s.encode_utf16().count() as u32;
+        let spans = |items: Vec<String>, mut start: u32| -> Vec<[u32; 2]> {
+            items
+                .iter()
+                .map(|item| {
+                    let end = start + utf16_len(item);
+                    let offsets = [start, end];
+                    start = end + 2;
+                    offsets
+                })
+                .collect()
+        };
+
+        let mut start = utf16_len(name);
+        let mut offsets = Vec::new();
+        if !self.type_parameters.is_empty() {
+            offsets.extend(spans(self.type_parameters.clone(), start + 1));
+            start += utf16_len(&self.type_parameters.join(", ")) + 2;
+        }
+        let parameters = self.parameters.iter().map(|p| p.label()).collect();
+        offsets.extend(spans(parameters, start + 1));
+        offsets
+    }
+
+    /// The signature with explicit type arguments (`f<string>(...)`)
+    /// substituted for its type parameters
+    pub fn instantiate(&self, type_arguments: &[String]) -> Signature {
+        let names: Vec<&str> = self
+            .type_parameters
+            .iter()
+            .map(|tp| {
+                tp.split(|c: char| !is_identifier_char(c))
+                    .next()
+                    .unwrap_or("")
+            })
+            .collect();
+        let substitute = |text: &str| substitute_names(text, &names, type_arguments);
+
+        Signature {
+            type_parameters: type_arguments.to_vec(),
+            parameters: self
+                .parameters
+                .iter()
+                .map(|p| SignatureParameter {
+                    type_annotation: p.type_annotation.as_deref().map(substitute),
+                    ..p.clone()
+                })
+                .collect(),
+            return_type: self.return_type.as_deref().map(substitute),
+        }
+    }
+}
+
+fn is_identifier_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '$'
+}
+
+/// Replace whole identifiers in `text` that appear in `names` by the
+/// corresponding entry of `replacements`
+fn substitute_names(text: &str, names: &[&str], replacements: &[String]) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut word = String::new();
+    let flush = |word: &mut String, result: &mut String| {
+        match names.iter().position(|name| name == word) {
+            Some(i) if i < replacements.len() => result.push_str(&replacements[i]),
+            _ => result.push_str(word),
+        }
+        word.clear();
+    };
+
+    for c in text.chars() {
+        if is_identifier_char(c) {
+            word.push(c);
+        } else {
+            flush(&mut word, &mut result);
+            result.push(c);
+        }
+    }
+    flush(&mut word, &mut result);
+    result
+}
+
 /// Represents a symbol (named declaration) in the program
 #[derive(Debug, Clone)]
 pub struct Symbol {
@@ -74,6 +220,14 @@ pub struct Symbol {
     pub scope_id: u32,
     /// JSDoc documentation if available
     pub documentation: Option<String>,
+    /// Type recorded by `TypeChecker::infer_symbol_types`.
+    /// For classes this is the instance type.
+    pub inferred_type: Option<TypeId>,
+    /// Declared signature of a function or method
+    pub signature: Option<Signature>,
+    /// Overload signatures declared before a function or method's
+    /// implementation, recorded by `TypeChecker::infer_symbol_types`
+    pub overloads: Vec<FunctionType>,
 }
 
 impl Symbol {
@@ -94,6 +248,9 @@ impl Symbol {
             references: Vec::new(),
             scope_id,
             documentation: None,
+            inferred_type: None,
+            signature: None,
+            overloads: Vec::new(),
         }
     }
 
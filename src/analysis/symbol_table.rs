@@ -10,7 +10,7 @@ use tower_lsp::lsp_types::{Position, Range};
 use super::{Scope, ScopeKind, Symbol, SymbolFlags, SymbolId};
 
 /// Stores all symbols and scopes for a document
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct SymbolTable {
     /// All symbols indexed by their id
     symbols: HashMap<SymbolId, Symbol>,
@@ -60,6 +60,21 @@ impl SymbolTable {
         let id = SymbolId::new(self.next_symbol_id);
         self.next_symbol_id += 1;
 
+        let is_type = flags.intersects(
+            SymbolFlags::INTERFACE | SymbolFlags::TYPE_ALIAS | SymbolFlags::TYPE_PARAMETER,
+        );
+        let mut flags = flags;
+        let duplicate = !is_type
+            && self
+                .scopes
+                .get(&scope_id)
+                .and_then(|scope| scope.lookup_local(&name))
+                .and_then(|existing| self.symbols.get(&existing))
+                .is_some_and(|existing| conflicts(existing.flags, flags));
+        if duplicate {
+            flags |= SymbolFlags::DUPLICATE;
+        }
+
         let symbol = Symbol::new(
             id,
             name.clone(),
@@ -69,13 +84,16 @@ impl SymbolTable {
             scope_id,
         );
 
-        // Add to scope
-        if let Some(scope) = self.scopes.get_mut(&scope_id) {
-            if flags.intersects(
-                SymbolFlags::INTERFACE | SymbolFlags::TYPE_ALIAS | SymbolFlags::TYPE_PARAMETER,
-            ) {
+        // Add to scope, where a duplicate leaves the first declaration bound.
+        // Labels have their own namespace, resolved through the syntax tree.
+        if let Some(scope) = self
+            .scopes
+            .get_mut(&scope_id)
+            .filter(|_| !flags.contains(SymbolFlags::LABEL))
+        {
+            if is_type {
                 scope.add_type_symbol(name, id);
-            } else {
+            } else if !duplicate {
                 scope.add_symbol(name, id);
             }
         }
@@ -145,6 +163,22 @@ impl SymbolTable {
         None
     }
 
+    /// Look up a symbol as seen from `position`. A `let`, `const` or class
+    /// declared after `position` still shadows outer bindings there, so the
+    /// name resolves to nothing rather than to an outer declaration.
+    /// Hoisted `var` and function declarations resolve from anywhere.
+    pub fn lookup_at(&self, name: &str, scope_id: u32, position: Position) -> Option<SymbolId> {
+        let symbol_id = self.lookup(name, scope_id)?;
+        let symbol = self.get_symbol(symbol_id)?;
+        let block_scoped = symbol
+            .flags
+            .intersects(SymbolFlags::LET | SymbolFlags::CONST | SymbolFlags::CLASS);
+        if block_scoped && !symbol.is_hoisted() && position < symbol.name_range.start {
+            return None;
+        }
+        Some(symbol_id)
+    }
+
     /// Look up a type symbol by name, searching from the given scope upward
     pub fn lookup_type(&self, name: &str, scope_id: u32) -> Option<SymbolId> {
         let mut current_scope_id = Some(scope_id);
@@ -188,6 +222,43 @@ impl SymbolTable {
         self.root_scope_id
     }
 
+    /// Find the class whose body encloses a position, i.e. the class `this`
+    /// refers to inside its methods and field initializers. Arrow functions
+    /// keep the class's `this`; any other function nested in a method
+    /// rebinds it.
+    pub fn enclosing_class(&self, pos: Position) -> Option<SymbolId> {
+        let mut scope = self.get_scope(self.scope_at_position(pos))?;
+        while scope.kind != ScopeKind::Class {
+            let parent = self.get_scope(scope.parent?)?;
+            // A function directly in the class body is one of its methods
+            if scope.kind == ScopeKind::Function && parent.kind != ScopeKind::Class {
+                return None;
+            }
+            scope = parent;
+        }
+
+        let body = scope.range;
+        self.symbols
+            .values()
+            .filter(|symbol| symbol.flags.contains(SymbolFlags::CLASS))
+            .filter(|symbol| Some(symbol.scope_id) == scope.parent)
+            .find(|symbol| {
+                symbol.declaration_range.start <= body.start
+                    && body.end <= symbol.declaration_range.end
+            })
+            .map(|symbol| symbol.id)
+    }
+
+    /// Find the statement label declared or referenced at a position
+    pub fn label_at_position(&self, pos: Position) -> Option<SymbolId> {
+        let contains = |range: &Range| range.start <= pos && pos <= range.end;
+        self.symbols
+            .values()
+            .filter(|symbol| symbol.flags.contains(SymbolFlags::LABEL))
+            .find(|symbol| contains(&symbol.name_range) || symbol.references.iter().any(contains))
+            .map(|symbol| symbol.id)
+    }
+
     /// Find symbol at a specific position
     pub fn symbol_at_position(&self, pos: Position) -> Option<SymbolId> {
         for symbol in self.symbols.values() {
@@ -240,6 +311,29 @@ impl SymbolTable {
     }
 }
 
+/// Whether declaring `new` in a scope that already binds the name as
+/// `existing` is an error. `var` may repeat and redeclare a parameter, enums
+/// and namespaces merge, and class members (accessor pairs) are not checked.
+fn conflicts(existing: SymbolFlags, new: SymbolFlags) -> bool {
+    let is_var = |flags: SymbolFlags| {
+        flags.contains(SymbolFlags::VARIABLE | SymbolFlags::HOISTED)
+            && !flags.intersects(SymbolFlags::LET | SymbolFlags::CONST)
+    };
+    let merges = |flags: SymbolFlags| is_var(flags) || flags.contains(SymbolFlags::PARAMETER);
+    let members = SymbolFlags::PROPERTY | SymbolFlags::METHOD | SymbolFlags::ENUM_MEMBER;
+
+    if existing.intersects(members) || new.intersects(members) {
+        return false;
+    }
+    if (is_var(new) && merges(existing)) || (is_var(existing) && merges(new)) {
+        return false;
+    }
+    if existing.contains(SymbolFlags::ENUM) && new.contains(SymbolFlags::ENUM) {
+        return false;
+    }
+    !existing.contains(SymbolFlags::NAMESPACE) && !new.contains(SymbolFlags::NAMESPACE)
+}
+
 impl Default for SymbolTable {
     fn default() -> Self {
         Self::new()
@@ -283,6 +377,36 @@ mod tests {
         assert_eq!(symbol.scope_id, 0);
     }
 
+    #[test]
+    fn test_create_duplicate_symbol() {
+        let mut table = SymbolTable::new();
+        let range = Range::default();
+        let var = SymbolFlags::VARIABLE | SymbolFlags::HOISTED;
+        let constant = SymbolFlags::VARIABLE | SymbolFlags::CONST;
+
+        let first = table.create_symbol("x".to_string(), constant, range, range, 0);
+        let second = table.create_symbol("x".to_string(), constant, range, range, 0);
+        assert!(
+            table
+                .get_symbol(second)
+                .unwrap()
+                .flags
+                .contains(SymbolFlags::DUPLICATE)
+        );
+        // The first declaration stays bound
+        assert_eq!(table.lookup("x", 0), Some(first));
+
+        table.create_symbol("v".to_string(), var, range, range, 0);
+        let again = table.create_symbol("v".to_string(), var, range, range, 0);
+        assert!(
+            !table
+                .get_symbol(again)
+                .unwrap()
+                .flags
+                .contains(SymbolFlags::DUPLICATE)
+        );
+    }
+
     #[test]
     fn test_create_type_symbol() {
         let mut table = SymbolTable::new();
@@ -384,6 +508,30 @@ mod tests {
         assert_eq!(table.lookup("x", 0), Some(outer_id));
     }
 
+    #[test]
+    fn test_lookup_at_position() {
+        let mut table = SymbolTable::new();
+        let at = |line| Range::new(Position::new(line, 0), Position::new(line, 1));
+        let constant = SymbolFlags::VARIABLE | SymbolFlags::CONST;
+        let var = SymbolFlags::VARIABLE | SymbolFlags::HOISTED;
+
+        table.create_symbol("x".to_string(), constant, at(0), at(0), 0);
+        let block = table.create_scope(ScopeKind::Block, 0, Range::default());
+        let inner = table.create_symbol("x".to_string(), constant, at(5), at(5), block);
+        let hoisted = table.create_symbol("v".to_string(), var, at(5), at(5), block);
+
+        // Before its declaration the inner `x` hides the outer one
+        assert_eq!(table.lookup_at("x", block, Position::new(2, 0)), None);
+        assert_eq!(
+            table.lookup_at("x", block, Position::new(6, 0)),
+            Some(inner)
+        );
+        assert_eq!(
+            table.lookup_at("v", block, Position::new(2, 0)),
+            Some(hoisted)
+        );
+    }
+
     #[test]
     fn test_lookup_type() {
         let mut table = SymbolTable::new();
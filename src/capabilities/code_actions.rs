@@ -1,31 +1,108 @@
 use std::collections::HashMap;
+use std::path::Path;
 use tower_lsp::lsp_types::{
-    CodeAction, CodeActionKind, CodeActionOrCommand, Diagnostic, Position, Range, TextEdit, Url,
-    WorkspaceEdit,
+    CodeAction, CodeActionKind, CodeActionOrCommand, CodeActionParams, Diagnostic, NumberOrString,
+    Position, Range, TextEdit, Url, WorkspaceEdit,
 };
-
-use crate::analysis::SymbolTable;
-
-/// Get code actions for a range and its diagnostics
+use tree_sitter::{Node, Tree};
+
+use crate::analysis::{SymbolFlags, SymbolId, SymbolTable};
+use crate::capabilities::type_diagnostics::{TypeDiagnosticCode, get_type_diagnostics};
+use crate::line_index::LineIndex;
+use crate::project::Project;
+use crate::types::printer::print_type;
+use crate::types::types::{Property, TypeReference};
+use crate::types::{Type, TypeChecker, TypeId};
+
+/// Get code actions for a range and its diagnostics. `checker` is the one
+/// that inferred the types in `symbol_table`; `project` supplies the exports
+/// of other files for "Add Missing Imports".
 pub fn get_code_actions(
-    uri: &Url,
-    range: Range,
-    diagnostics: &[Diagnostic],
+    params: &CodeActionParams,
+    tree: &Tree,
     symbol_table: &SymbolTable,
+    checker: &mut TypeChecker,
     source: &str,
+    project: &Project,
 ) -> Vec<CodeActionOrCommand> {
+    let uri = &params.text_document.uri;
+    let range = params.range;
+    let diagnostics = &params.context.diagnostics;
     let mut actions = Vec::new();
 
     // Generate quick fixes for diagnostics
     for diagnostic in diagnostics {
-        actions.extend(get_diagnostic_fixes(uri, diagnostic, source));
+        actions.extend(get_diagnostic_fixes(uri, diagnostic, tree, source));
     }
 
     // Generate refactoring actions based on selection
     actions.extend(get_refactoring_actions(uri, range, symbol_table, source));
 
+    // Offer to inline the const at the cursor
+    if let Some(symbol) = symbol_table
+        .symbol_at_position(range.start)
+        .and_then(|id| symbol_table.get_symbol(id))
+        .filter(|symbol| {
+            symbol
+                .flags
+                .contains(SymbolFlags::VARIABLE | SymbolFlags::CONST)
+        })
+    {
+        actions.extend(create_inline_variable_action(
+            uri,
+            symbol.id,
+            tree,
+            symbol_table,
+            source,
+        ));
+    }
+
+    // Offer to annotate an unannotated variable or parameter
+    actions.extend(get_add_type_annotation_action(
+        uri,
+        range,
+        tree,
+        symbol_table,
+        checker,
+        source,
+    ));
+
+    // Offer to rewrite a promise chain with async/await
+    actions.extend(create_then_to_await_action(uri, range, tree, source));
+
+    // Offer to guard the statement at the cursor with try/catch
+    actions.extend(create_wrap_try_catch_action(
+        uri,
+        range,
+        tree,
+        source,
+        symbol_table,
+    ));
+
+    // Offer to turn a string concatenation into a template literal
+    actions.extend(create_to_template_literal_action(uri, range, tree, source));
+
+    // Offer a constructor for a class that has fields but none
+    actions.extend(create_generate_constructor_action(uri, range, tree, source));
+
+    // Offer to implement the members of interfaces a class leaves out
+    actions.extend(get_implement_interface_actions(uri, range, tree, source));
+
     // Generate source actions
     actions.extend(get_source_actions(uri, range, source));
+    actions.push(create_remove_unused_imports_action(
+        uri,
+        tree,
+        source,
+        symbol_table,
+    ));
+    actions.extend(get_missing_import_actions(
+        uri,
+        tree,
+        source,
+        symbol_table,
+        project,
+    ));
 
     actions
 }
@@ -34,6 +111,7 @@ pub fn get_code_actions(
 fn get_diagnostic_fixes(
     uri: &Url,
     diagnostic: &Diagnostic,
+    tree: &Tree,
     source: &str,
 ) -> Vec<CodeActionOrCommand> {
     let mut actions = Vec::new();
@@ -41,6 +119,17 @@ fn get_diagnostic_fixes(
     // Check diagnostic code
     if let Some(ref code) = diagnostic.code {
         match code {
+            tower_lsp::lsp_types::NumberOrString::Number(6133 | 6192)
+                if import_statement_at(tree, source, diagnostic.range).is_some() =>
+            {
+                // Unused import - offer to remove it from the import clause
+                actions.extend(create_remove_unused_import_action(
+                    uri,
+                    diagnostic.range,
+                    tree,
+                    source,
+                ));
+            }
             tower_lsp::lsp_types::NumberOrString::Number(2304) => {
                 // Undefined variable - offer to declare it
                 let var_name = extract_name_from_message(&diagnostic.message);
@@ -119,47 +208,1675 @@ fn get_refactoring_actions(
 
 /// Get source-level actions
 fn get_source_actions(uri: &Url, _range: Range, source: &str) -> Vec<CodeActionOrCommand> {
-    let mut actions = Vec::new();
+    vec![
+        // Organize imports
+        CodeActionOrCommand::CodeAction(CodeAction {
+            title: "Organize Imports".to_string(),
+            kind: Some(CodeActionKind::SOURCE_ORGANIZE_IMPORTS),
+            diagnostics: None,
+            edit: Some(create_organize_imports_edit(uri, source)),
+            command: None,
+            is_preferred: Some(false),
+            disabled: None,
+            data: None,
+        }),
+        // Sort imports alphabetically
+        CodeActionOrCommand::CodeAction(CodeAction {
+            title: "Sort Imports".to_string(),
+            kind: Some(CodeActionKind::new("source.sortImports")),
+            diagnostics: None,
+            edit: Some(create_sort_imports_edit(uri, source)),
+            command: None,
+            is_preferred: Some(false),
+            disabled: None,
+            data: None,
+        }),
+    ]
+}
 
-    // Organize imports
-    actions.push(CodeActionOrCommand::CodeAction(CodeAction {
-        title: "Organize Imports".to_string(),
-        kind: Some(CodeActionKind::SOURCE_ORGANIZE_IMPORTS),
+/// "Add type annotation" for the unannotated variable or parameter whose
+/// name is at `range`. A variable's type is inferred from its initializer,
+/// a parameter's from its default value or from the arguments it is passed.
+/// `symbol_table` must hold the types `checker` inferred for the document.
+fn get_add_type_annotation_action(
+    uri: &Url,
+    range: Range,
+    tree: &Tree,
+    table: &SymbolTable,
+    checker: &mut TypeChecker,
+    source: &str,
+) -> Option<CodeActionOrCommand> {
+    let index = LineIndex::new(source);
+    let point = index.point(range.start);
+    let name = tree
+        .root_node()
+        .descendant_for_point_range(point, point)
+        .filter(|node| node.kind() == "identifier")?;
+    let declaration = name.parent()?;
+    if declaration.child_by_field_name("type").is_some() {
+        return None;
+    }
+
+    let root = tree.root_node();
+    let (ty, insert_after) = match declaration.kind() {
+        "variable_declarator" if declaration.child_by_field_name("name") == Some(name) => {
+            let value = declaration.child_by_field_name("value")?;
+            if let Some(class) = instantiated_class(value, source, table, checker) {
+                (checker.create_type(Type::Reference(class)), name)
+            } else {
+                (checker.infer_from_node(value, source, table)?, name)
+            }
+        }
+        "required_parameter" | "optional_parameter"
+            if declaration.child_by_field_name("pattern") == Some(name) =>
+        {
+            let ty = match declaration.child_by_field_name("value") {
+                Some(default) => checker.infer_from_node(default, source, table)?,
+                None => parameter_type_from_calls(declaration, root, source, table, checker)?,
+            };
+            // `x?: T`, not `x: T?`
+            let mut cursor = declaration.walk();
+            let question = declaration
+                .children(&mut cursor)
+                .find(|child| child.kind() == "?");
+            (ty, question.unwrap_or(name))
+        }
+        _ => return None,
+    };
+
+    let ty = checker.get_type(ty)?;
+    if contains_any(ty) || matches!(ty, Type::Unknown) {
+        return None;
+    }
+    let annotation = format!(": {}", print_type(ty));
+    let position = index.position(insert_after.end_byte());
+
+    let mut changes = HashMap::new();
+    changes.insert(
+        uri.clone(),
+        vec![TextEdit {
+            range: Range {
+                start: position,
+                end: position,
+            },
+            new_text: annotation.clone(),
+        }],
+    );
+
+    Some(CodeActionOrCommand::CodeAction(CodeAction {
+        title: format!("Add type annotation '{}'", annotation),
+        kind: Some(CodeActionKind::QUICKFIX),
         diagnostics: None,
-        edit: Some(create_organize_imports_edit(uri, source)),
+        edit: Some(WorkspaceEdit {
+            changes: Some(changes),
+            document_changes: None,
+            change_annotations: None,
+        }),
         command: None,
         is_preferred: Some(false),
         disabled: None,
         data: None,
-    }));
+    }))
+}
 
-    // Add missing imports (placeholder)
-    actions.push(CodeActionOrCommand::CodeAction(CodeAction {
-        title: "Add Missing Imports".to_string(),
-        kind: Some(CodeActionKind::new("source.addMissingImports")),
+/// The class `new C()` or `new C<T>()` instantiates, as a reference to be
+/// printed by name. Type arguments left for inference are `any`.
+fn instantiated_class(
+    value: Node,
+    source: &str,
+    table: &SymbolTable,
+    checker: &mut TypeChecker,
+) -> Option<TypeReference> {
+    if value.kind() != "new_expression" {
+        return None;
+    }
+    let constructor = value
+        .child_by_field_name("constructor")
+        .filter(|c| c.kind() == "identifier")?;
+    let name = constructor.utf8_text(source.as_bytes()).ok()?;
+    let index = LineIndex::new(source);
+    let scope = table.scope_at_position(index.position(value.start_byte()));
+    let class = table
+        .get_symbol(table.lookup(name, scope)?)
+        .filter(|symbol| symbol.flags.contains(SymbolFlags::CLASS))?;
+
+    let mut root = value;
+    while let Some(parent) = root.parent() {
+        root = parent;
+    }
+    let start = index.point(class.name_range.start);
+    let declaration = root.descendant_for_point_range(start, start)?.parent()?;
+    let mut arguments = Vec::new();
+    if let Some(type_arguments) = value.child_by_field_name("type_arguments") {
+        let mut cursor = type_arguments.walk();
+        for argument in type_arguments.named_children(&mut cursor) {
+            let ty = checker.type_from_node(argument, source);
+            arguments.push(checker.get_type(ty)?.clone());
+        }
+    } else if let Some(parameters) = declaration.child_by_field_name("type_parameters") {
+        arguments.resize(parameters.named_child_count(), Type::Any);
+    }
+    Some(TypeReference {
+        name: name.to_string(),
+        type_arguments: arguments,
+    })
+}
+
+/// Whether `any` appears anywhere in `ty`
+fn contains_any(ty: &Type) -> bool {
+    match ty {
+        Type::Any => true,
+        Type::Array(element) => contains_any(element),
+        Type::Tuple(members) | Type::Union(members) | Type::Intersection(members) => {
+            members.iter().any(contains_any)
+        }
+        Type::Function(function) => {
+            function.parameters.iter().any(|p| contains_any(&p.ty))
+                || contains_any(&function.return_type)
+        }
+        Type::Object(obj) => obj.properties.values().any(|p| contains_any(&p.ty)),
+        Type::Reference(reference) => reference.type_arguments.iter().any(contains_any),
+        _ => false,
+    }
+}
+
+/// Type of a parameter inferred from the arguments of the calls to its
+/// function, a union when the calls pass different types
+fn parameter_type_from_calls(
+    parameter: Node,
+    root: Node,
+    source: &str,
+    symbol_table: &SymbolTable,
+    checker: &mut TypeChecker,
+) -> Option<TypeId> {
+    let parameters = parameter.parent()?;
+    let mut cursor = parameters.walk();
+    let position = parameters
+        .named_children(&mut cursor)
+        .filter(|p| matches!(p.kind(), "required_parameter" | "optional_parameter"))
+        .position(|p| p == parameter)?;
+
+    let function = parameters.parent()?;
+    let name = match function.kind() {
+        "function_declaration" => function.child_by_field_name("name")?,
+        "arrow_function" | "function_expression" => function
+            .parent()
+            .filter(|p| p.kind() == "variable_declarator")?
+            .child_by_field_name("name")?,
+        _ => return None,
+    };
+    let name = name.utf8_text(source.as_bytes()).ok()?;
+
+    let mut calls = Vec::new();
+    collect_calls(root, name, source, &mut calls);
+
+    let mut types: Vec<(String, TypeId)> = Vec::new();
+    for call in calls {
+        let Some(argument) = call.child_by_field_name("arguments").and_then(|args| {
+            let mut cursor = args.walk();
+            args.named_children(&mut cursor)
+                .filter(|arg| arg.kind() != "comment")
+                .nth(position)
+        }) else {
+            continue;
+        };
+        let Some(id) = checker.infer_from_node(argument, source, symbol_table) else {
+            continue;
+        };
+        let printed = checker.get_type(id).map(print_type).unwrap_or_default();
+        if !types.iter().any(|(seen, _)| *seen == printed) {
+            types.push((printed, id));
+        }
+    }
+
+    match types.len() {
+        0 => None,
+        1 => Some(types[0].1),
+        _ => Some(checker.union_type(types.into_iter().map(|(_, id)| id).collect())),
+    }
+}
+
+/// Calls of the function named `name`
+fn collect_calls<'t>(node: Node<'t>, name: &str, source: &str, calls: &mut Vec<Node<'t>>) {
+    if node.kind() == "call_expression"
+        && node
+            .child_by_field_name("function")
+            .and_then(|f| f.utf8_text(source.as_bytes()).ok())
+            == Some(name)
+    {
+        calls.push(node);
+    }
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        collect_calls(child, name, source, calls);
+    }
+}
+
+/// "Convert to async/await" for a statement holding a `.then(...)` chain that
+/// ends in `.catch(...)` or in a `.then` with a rejection handler. The chain
+/// becomes a `try`/`catch` and the enclosing function is made `async`; its
+/// `Promise<T>` return annotation stays valid as it is.
+fn create_then_to_await_action(
+    uri: &Url,
+    range: Range,
+    tree: &Tree,
+    source: &str,
+) -> Option<CodeActionOrCommand> {
+    let index = LineIndex::new(source);
+    let point = index.point(range.start);
+    let mut statement = tree.root_node().descendant_for_point_range(point, point)?;
+    let (statement, rewritten) = loop {
+        if matches!(
+            statement.kind(),
+            "expression_statement" | "return_statement"
+        ) {
+            if let Some(rewritten) = rewrite_promise_chain(statement, source, &index) {
+                break (statement, rewritten);
+            }
+        }
+        statement = statement.parent()?;
+    };
+
+    let mut function = statement.parent()?;
+    while !matches!(
+        function.kind(),
+        "function_declaration" | "function_expression" | "arrow_function" | "method_definition"
+    ) {
+        function = function.parent()?;
+    }
+    let mut cursor = function.walk();
+    let keywords: Vec<&str> = function.children(&mut cursor).map(|c| c.kind()).collect();
+    if keywords.iter().any(|k| matches!(*k, "get" | "set" | "*")) {
+        return None;
+    }
+
+    let mut edits = Vec::new();
+    if !keywords.contains(&"async") {
+        // `static async m()`, so the keyword goes right before a method's name
+        let at = match function.kind() {
+            "method_definition" => function.child_by_field_name("name")?,
+            _ => function,
+        };
+        let position = index.position(at.start_byte());
+        edits.push(TextEdit {
+            range: Range::new(position, position),
+            new_text: "async ".to_string(),
+        });
+    }
+    edits.push(TextEdit {
+        range: index.node_range(&statement),
+        new_text: rewritten,
+    });
+
+    let mut changes = HashMap::new();
+    changes.insert(uri.clone(), edits);
+
+    Some(CodeActionOrCommand::CodeAction(CodeAction {
+        title: "Convert to async/await".to_string(),
+        kind: Some(CodeActionKind::REFACTOR_REWRITE),
+        diagnostics: None,
+        edit: Some(WorkspaceEdit {
+            changes: Some(changes),
+            document_changes: None,
+            change_annotations: None,
+        }),
+        command: None,
+        is_preferred: Some(false),
+        disabled: None,
+        data: None,
+    }))
+}
+
+/// The `try`/`catch` equivalent of the promise chain in `statement`
+fn rewrite_promise_chain(statement: Node, source: &str, index: &LineIndex) -> Option<String> {
+    let text = |node: Node| node.utf8_text(source.as_bytes()).unwrap_or("").to_string();
+    let is_return = statement.kind() == "return_statement";
+
+    // Unwind `base.then(a).then(b).catch(c)` into `base` and its links
+    let mut links = Vec::new();
+    let mut current = statement.named_child(0)?;
+    while current.kind() == "call_expression" {
+        let Some(member) = current
+            .child_by_field_name("function")
+            .filter(|f| f.kind() == "member_expression")
+        else {
+            break;
+        };
+        let method = text(member.child_by_field_name("property")?);
+        if method != "then" && method != "catch" {
+            break;
+        }
+        let arguments = current.child_by_field_name("arguments")?;
+        let mut cursor = arguments.walk();
+        let callbacks: Vec<Node> = arguments
+            .named_children(&mut cursor)
+            .filter(|arg| arg.kind() != "comment")
+            .collect();
+        links.push((method, callbacks));
+        current = member.child_by_field_name("object")?;
+    }
+    links.reverse();
+    let base = current;
+
+    // Every link but the last is a plain `.then(f)`, the last one handles
+    // the rejection
+    let (last_method, last_callbacks) = links.last()?;
+    let (fulfilled, rejected) = match (last_method.as_str(), last_callbacks.as_slice()) {
+        ("catch", [handler]) => (&links[..links.len() - 1], *handler),
+        // The rejection handler of `.then(a, b)` doesn't see errors thrown
+        // by `a`, which the `catch` would, so `a` must not be able to throw
+        ("then", [callback, handler]) if cannot_throw(*callback) => (&links[..], *handler),
+        _ => return None,
+    };
+    let mut thens = Vec::new();
+    for (i, (method, callbacks)) in fulfilled.iter().enumerate() {
+        let is_last = i == fulfilled.len() - 1;
+        match (method.as_str(), callbacks.as_slice()) {
+            ("then", [callback]) => thens.push(*callback),
+            ("then", [callback, _]) if is_last && last_method == "then" => thens.push(*callback),
+            _ => return None,
+        }
+    }
+
+    // A value the last callback produces is returned or dropped like the
+    // chain's own result
+    let finish = |expression: String| {
+        if is_return {
+            format!("return {};", expression)
+        } else {
+            format!("{};", expression)
+        }
+    };
+    let block_statements = |body: Node| -> Option<Vec<String>> {
+        // Outside of a return statement, a `return` in a callback would
+        // now leave the enclosing function
+        if !is_return && contains_return(body) {
+            return None;
+        }
+        let mut cursor = body.walk();
+        Some(body.named_children(&mut cursor).map(text).collect())
+    };
+
+    // Each parameter becomes a `const` in the `try` block, so its name must
+    // not be read by another callback or by the chain's base, where it would
+    // now refer to (or collide with) that `const`
+    for (i, callback) in thens.iter().enumerate() {
+        for name in parameter_names(*callback, source) {
+            let others = std::iter::once(base).chain(
+                thens
+                    .iter()
+                    .enumerate()
+                    .filter(|(j, _)| *j != i)
+                    .map(|(_, other)| *other),
+            );
+            if others
+                .into_iter()
+                .any(|other| mentions_identifier(other, &name, source))
+            {
+                return None;
+            }
+        }
+    }
+
+    let mut try_body = Vec::new();
+    let mut value = text(base);
+    for (i, callback) in thens.iter().enumerate() {
+        let (parameter, body) = callback_parts(*callback, source)?;
+        try_body.push(match parameter {
+            Some(parameter) => format!("const {} = await {};", parameter, value),
+            None => format!("await {};", value),
+        });
+
+        let is_last = i == thens.len() - 1;
+        if body.kind() == "statement_block" {
+            if !is_last {
+                return None;
+            }
+            try_body.extend(block_statements(body)?);
+        } else if is_last {
+            try_body.push(finish(text(body)));
+        } else if matches!(
+            body.kind(),
+            "call_expression"
+                | "member_expression"
+                | "subscript_expression"
+                | "identifier"
+                | "parenthesized_expression"
+        ) {
+            value = text(body);
+        } else {
+            value = format!("({})", text(body));
+        }
+    }
+    if thens.is_empty() {
+        try_body.push(finish(format!("await {}", value)));
+    }
+
+    let (error, handler) = callback_parts(rejected, source)?;
+    let catch_body = if handler.kind() == "statement_block" {
+        block_statements(handler)?
+    } else {
+        vec![finish(text(handler))]
+    };
+
+    let line_start = Position::new(statement.start_position().row as u32, 0);
+    let indent: String = index
+        .slice(Range::new(
+            line_start,
+            index.position(statement.start_byte()),
+        ))
+        .chars()
+        .take_while(|c| c.is_whitespace())
+        .collect();
+    let unit = if indent.starts_with('\t') {
+        "\t"
+    } else {
+        "    "
+    };
+    let lines = |statements: &[String]| -> String {
+        statements
+            .iter()
+            .map(|statement| format!("{}{}{}\n", indent, unit, statement))
+            .collect()
+    };
+
+    let catch_clause = match error {
+        Some(error) => format!("catch ({})", error),
+        None => "catch".to_string(),
+    };
+    Some(format!(
+        "try {{\n{}{}}} {} {{\n{}{}}}",
+        lines(&try_body),
+        indent,
+        catch_clause,
+        lines(&catch_body),
+        indent
+    ))
+}
+
+/// The parameter (as written, with any annotation) and body of a callback
+/// taking at most one parameter
+fn callback_parts<'t>(callback: Node<'t>, source: &str) -> Option<(Option<String>, Node<'t>)> {
+    if !matches!(callback.kind(), "arrow_function" | "function_expression") {
+        return None;
+    }
+    let body = callback.child_by_field_name("body")?;
+    let text = |node: Node| node.utf8_text(source.as_bytes()).unwrap_or("").to_string();
+
+    if let Some(parameter) = callback.child_by_field_name("parameter") {
+        return Some((Some(text(parameter)), body));
+    }
+    let parameters = callback.child_by_field_name("parameters")?;
+    let mut cursor = parameters.walk();
+    let parameters: Vec<Node> = parameters
+        .named_children(&mut cursor)
+        .filter(|p| p.kind() != "comment")
+        .collect();
+    match parameters.as_slice() {
+        [] => Some((None, body)),
+        [parameter]
+            if parameter.kind() == "required_parameter"
+                && parameter.child_by_field_name("value").is_none() =>
+        {
+            Some((Some(text(*parameter)), body))
+        }
+        _ => None,
+    }
+}
+
+/// Whether calling `callback` can't throw: it binds its parameters without
+/// destructuring and its body is an empty block or a plain value
+fn cannot_throw(callback: Node) -> bool {
+    let mut parameters = Vec::new();
+    if let Some(parameter) = callback.child_by_field_name("parameter") {
+        parameters.push(parameter);
+    } else if let Some(list) = callback.child_by_field_name("parameters") {
+        let mut cursor = list.walk();
+        parameters.extend(
+            list.named_children(&mut cursor)
+                .filter(|p| p.kind() != "comment")
+                .map(|p| p.child_by_field_name("pattern").unwrap_or(p)),
+        );
+    }
+    if parameters.iter().any(|p| p.kind() != "identifier") {
+        return false;
+    }
+
+    callback
+        .child_by_field_name("body")
+        .is_some_and(|body| match body.kind() {
+            "statement_block" => body.named_child_count() == 0,
+            kind => matches!(
+                kind,
+                "identifier" | "string" | "number" | "true" | "false" | "null" | "undefined"
+            ),
+        })
+}
+
+/// The names a callback's parameters bind
+fn parameter_names(callback: Node, source: &str) -> Vec<String> {
+    fn collect(pattern: Node, source: &str, names: &mut Vec<String>) {
+        match pattern.kind() {
+            "identifier" | "shorthand_property_identifier_pattern" => {
+                names.push(
+                    pattern
+                        .utf8_text(source.as_bytes())
+                        .unwrap_or("")
+                        .to_string(),
+                );
+            }
+            // Only the binding side of `{ key: value }` and `a = default`
+            "pair_pattern" | "assignment_pattern" | "object_assignment_pattern" => {
+                if let Some(value) = pattern
+                    .child_by_field_name("value")
+                    .or_else(|| pattern.child_by_field_name("left"))
+                {
+                    collect(value, source, names);
+                }
+            }
+            "type_annotation" => {}
+            _ => {
+                let mut cursor = pattern.walk();
+                for child in pattern.named_children(&mut cursor) {
+                    collect(child, source, names);
+                }
+            }
+        }
+    }
+
+    let mut names = Vec::new();
+    if let Some(parameter) = callback.child_by_field_name("parameter") {
+        collect(parameter, source, &mut names);
+    } else if let Some(list) = callback.child_by_field_name("parameters") {
+        let mut cursor = list.walk();
+        for parameter in list.named_children(&mut cursor) {
+            if let Some(pattern) = parameter.child_by_field_name("pattern") {
+                collect(pattern, source, &mut names);
+            }
+        }
+    }
+    names
+}
+
+/// Whether `node` contains the identifier `name`
+fn mentions_identifier(node: Node, name: &str, source: &str) -> bool {
+    if node.kind() == "identifier" {
+        return node.utf8_text(source.as_bytes()) == Ok(name);
+    }
+    let mut cursor = node.walk();
+    node.named_children(&mut cursor)
+        .any(|child| mentions_identifier(child, name, source))
+}
+
+/// Whether `node` has a `return` statement outside of nested functions
+fn contains_return(node: Node) -> bool {
+    let mut cursor = node.walk();
+    node.named_children(&mut cursor)
+        .any(|child| match child.kind() {
+            "return_statement" => true,
+            "function_declaration"
+            | "function_expression"
+            | "arrow_function"
+            | "method_definition"
+            | "class_declaration"
+            | "class" => false,
+            _ => contains_return(child),
+        })
+}
+
+/// "Wrap in try-catch" for the expression statement containing `range`.
+/// Inside an `async` function a call returning a promise is awaited so that
+/// its rejection reaches the `catch`.
+fn create_wrap_try_catch_action(
+    uri: &Url,
+    range: Range,
+    tree: &Tree,
+    source: &str,
+    symbol_table: &SymbolTable,
+) -> Option<CodeActionOrCommand> {
+    let index = LineIndex::new(source);
+    let point = index.point(range.start);
+    let mut statement = tree.root_node().descendant_for_point_range(point, point)?;
+    while statement.kind() != "expression_statement" {
+        statement = statement.parent()?;
+    }
+    let text = |node: Node| node.utf8_text(source.as_bytes()).unwrap_or("");
+    let expression = statement.named_child(0)?;
+
+    let mut wrapped = text(statement).to_string();
+    if enclosing_function_is_async(statement) && returns_promise(expression, source, symbol_table) {
+        wrapped = format!("await {}", wrapped);
+    }
+
+    // The catch parameter must not shadow a name the statement uses
+    let scope = symbol_table.scope_at_position(index.position(statement.start_byte()));
+    let error = ["error", "err", "e"]
+        .into_iter()
+        .map(str::to_string)
+        .chain((2..).map(|n| format!("error{}", n)))
+        .find(|name| symbol_table.lookup(name, scope).is_none())?;
+
+    let line_start = Position::new(statement.start_position().row as u32, 0);
+    let indent: String = index
+        .slice(Range::new(
+            line_start,
+            index.position(statement.start_byte()),
+        ))
+        .chars()
+        .take_while(|c| c.is_whitespace())
+        .collect();
+    let unit = if indent.starts_with('\t') {
+        "\t"
+    } else {
+        "    "
+    };
+    // Continuation lines of a multi-line statement move in by one level too
+    let body = wrapped.replace('\n', &format!("\n{}", unit));
+    let new_text = format!(
+        "try {{\n{indent}{unit}{body}\n{indent}}} catch ({error}) {{\n{indent}{unit}console.error({error});\n{indent}}}"
+    );
+
+    let mut changes = HashMap::new();
+    changes.insert(
+        uri.clone(),
+        vec![TextEdit {
+            range: index.node_range(&statement),
+            new_text,
+        }],
+    );
+
+    Some(CodeActionOrCommand::CodeAction(CodeAction {
+        title: "Wrap in try-catch".to_string(),
+        kind: Some(CodeActionKind::QUICKFIX),
+        diagnostics: None,
+        edit: Some(WorkspaceEdit {
+            changes: Some(changes),
+            document_changes: None,
+            change_annotations: None,
+        }),
+        command: None,
+        is_preferred: Some(false),
+        disabled: None,
+        data: None,
+    }))
+}
+
+/// Whether the innermost function around `node` is declared `async`
+fn enclosing_function_is_async(node: Node) -> bool {
+    let mut current = node.parent();
+    while let Some(function) = current {
+        if matches!(
+            function.kind(),
+            "function_declaration" | "function_expression" | "arrow_function" | "method_definition"
+        ) {
+            let mut cursor = function.walk();
+            return function.children(&mut cursor).any(|c| c.kind() == "async");
+        }
+        current = function.parent();
+    }
+    false
+}
+
+/// Whether `expression` is a call to `fetch` or to a function of this file
+/// that is `async` or declared to return a `Promise`
+fn returns_promise(expression: Node, source: &str, symbol_table: &SymbolTable) -> bool {
+    let Some(callee) = expression
+        .child_by_field_name("function")
+        .filter(|_| expression.kind() == "call_expression")
+        .filter(|callee| callee.kind() == "identifier")
+    else {
+        return false;
+    };
+    let name = callee.utf8_text(source.as_bytes()).unwrap_or("");
+    let index = LineIndex::new(source);
+    let scope = symbol_table.scope_at_position(index.position(callee.start_byte()));
+    let Some(symbol) = symbol_table
+        .lookup(name, scope)
+        .and_then(|id| symbol_table.get_symbol(id))
+    else {
+        return name == "fetch";
+    };
+    symbol.flags.contains(SymbolFlags::ASYNC)
+        || symbol
+            .signature
+            .as_ref()
+            .and_then(|signature| signature.return_type.as_deref())
+            .is_some_and(|return_type| return_type.starts_with("Promise<"))
+}
+
+/// "Convert to template literal" for the `+` chain containing `range`
+/// whose leftmost operand is a string literal
+fn create_to_template_literal_action(
+    uri: &Url,
+    range: Range,
+    tree: &Tree,
+    source: &str,
+) -> Option<CodeActionOrCommand> {
+    let index = LineIndex::new(source);
+    let point = index.point(range.start);
+    let mut node = tree.root_node().descendant_for_point_range(point, point)?;
+    while !is_concatenation(node) {
+        node = node.parent()?;
+    }
+    // The whole chain, not just the innermost `a + b` of `a + b + c`
+    while let Some(parent) = node.parent().filter(|p| is_concatenation(*p)) {
+        node = parent;
+    }
+    if !starts_with_string(node) {
+        return None;
+    }
+
+    let mut template = String::new();
+    append_template_parts(node, source, &mut template);
+
+    let mut changes = HashMap::new();
+    changes.insert(
+        uri.clone(),
+        vec![TextEdit {
+            range: index.node_range(&node),
+            new_text: format!("`{}`", template),
+        }],
+    );
+
+    Some(CodeActionOrCommand::CodeAction(CodeAction {
+        title: "Convert to template literal".to_string(),
+        kind: Some(CodeActionKind::REFACTOR_REWRITE),
+        diagnostics: None,
+        edit: Some(WorkspaceEdit {
+            changes: Some(changes),
+            document_changes: None,
+            change_annotations: None,
+        }),
+        command: None,
+        is_preferred: Some(false),
+        disabled: None,
+        data: None,
+    }))
+}
+
+/// Whether `node` is an `a + b` binary expression
+fn is_concatenation(node: Node) -> bool {
+    node.kind() == "binary_expression"
+        && node
+            .child_by_field_name("operator")
+            .is_some_and(|operator| operator.kind() == "+")
+}
+
+/// Whether the leftmost operand of a `+` chain is a string literal
+fn starts_with_string(node: Node) -> bool {
+    let mut leftmost = node;
+    while is_concatenation(leftmost) {
+        let Some(left) = leftmost.child_by_field_name("left") else {
+            return false;
+        };
+        leftmost = left;
+    }
+    leftmost.kind() == "string"
+}
+
+/// Append the template literal body for one operand of a `+` chain:
+/// literals are inlined and any other expression becomes a `${...}`
+fn append_template_parts(node: Node, source: &str, template: &mut String) {
+    let text = node.utf8_text(source.as_bytes()).unwrap_or("");
+    match node.kind() {
+        _ if is_concatenation(node) => {
+            for field in ["left", "right"] {
+                if let Some(operand) = node.child_by_field_name(field) {
+                    append_template_parts(operand, source, template);
+                }
+            }
+        }
+        "string" => {
+            let quote = text.chars().next().unwrap_or('"');
+            let body = &text[1..text.len() - 1];
+            let mut chars = body.chars().peekable();
+            while let Some(c) = chars.next() {
+                match c {
+                    // `\'` only needs escaping inside the original quotes
+                    '\\' if chars.peek() == Some(&quote) => {}
+                    '\\' => {
+                        template.push(c);
+                        template.extend(chars.next());
+                    }
+                    '`' => template.push_str("\\`"),
+                    '$' if chars.peek() == Some(&'{') => template.push_str("\\$"),
+                    _ => template.push(c),
+                }
+            }
+        }
+        "template_string" => template.push_str(&text[1..text.len() - 1]),
+        // `"a" + ("b" + c)` flattens, `"a" + (b + c)` keeps the sum
+        "parenthesized_expression"
+            if node
+                .named_child(0)
+                .is_some_and(|inner| is_concatenation(inner) && starts_with_string(inner)) =>
+        {
+            if let Some(inner) = node.named_child(0) {
+                append_template_parts(inner, source, template);
+            }
+        }
+        _ => {
+            template.push_str("${");
+            template.push_str(text);
+            template.push('}');
+        }
+    }
+}
+
+/// One "Implement interface" action per interface in the `implements`
+/// clause of the class containing `range` that has members the class lacks
+fn get_implement_interface_actions(
+    uri: &Url,
+    range: Range,
+    tree: &Tree,
+    source: &str,
+) -> Vec<CodeActionOrCommand> {
+    let index = LineIndex::new(source);
+    let point = index.point(range.start);
+    let Some(mut node) = tree.root_node().descendant_for_point_range(point, point) else {
+        return Vec::new();
+    };
+    while !matches!(
+        node.kind(),
+        "class_declaration" | "abstract_class_declaration"
+    ) {
+        match node.parent() {
+            Some(parent) => node = parent,
+            None => return Vec::new(),
+        }
+    }
+
+    let mut checker = TypeChecker::new();
+    checker.declare_types_from_tree(tree.root_node(), source);
+
+    let mut cursor = node.walk();
+    let interfaces: Vec<Node> = node
+        .children(&mut cursor)
+        .filter(|child| child.kind() == "class_heritage")
+        .flat_map(|heritage| {
+            let mut cursor = heritage.walk();
+            heritage.children(&mut cursor).collect::<Vec<_>>()
+        })
+        .filter(|clause| clause.kind() == "implements_clause")
+        .flat_map(|clause| {
+            let mut cursor = clause.walk();
+            clause.named_children(&mut cursor).collect::<Vec<_>>()
+        })
+        .collect();
+
+    interfaces
+        .into_iter()
+        .filter_map(|interface| {
+            let name = interface.utf8_text(source.as_bytes()).ok()?;
+            create_implement_interface_action(uri, node, name, source, &mut checker)
+        })
+        .collect()
+}
+
+/// Insert stubs for the members of `interface` that `class` doesn't declare,
+/// after the class's last member
+fn create_implement_interface_action(
+    uri: &Url,
+    class: Node,
+    interface: &str,
+    source: &str,
+    checker: &mut TypeChecker,
+) -> Option<CodeActionOrCommand> {
+    let body = class.child_by_field_name("body")?;
+    let mut cursor = body.walk();
+    let members: Vec<Node> = body
+        .named_children(&mut cursor)
+        .filter(|member| member.kind() != "comment")
+        .collect();
+    let existing: Vec<&str> = members
+        .iter()
+        .filter_map(|member| member.child_by_field_name("name"))
+        .filter_map(|name| name.utf8_text(source.as_bytes()).ok())
+        .collect();
+
+    let mut properties = Vec::new();
+    let mut visited = Vec::new();
+    collect_interface_members(checker, interface, &mut properties, &mut visited);
+    properties.sort_by(|a, b| a.name.cmp(&b.name));
+    properties.dedup_by(|a, b| a.name == b.name);
+    properties.retain(|property| !existing.contains(&property.name.as_str()));
+    if properties.is_empty() {
+        return None;
+    }
+
+    let index = LineIndex::new(source);
+    let line_indent = |node: Node| -> String {
+        let line = index.slice(Range::new(
+            Position::new(node.start_position().row as u32, 0),
+            index.position(node.start_byte()),
+        ));
+        line.chars().take_while(|c| c.is_whitespace()).collect()
+    };
+    let class_indent = line_indent(class);
+    let indent = members
+        .last()
+        .map(|member| line_indent(*member))
+        .unwrap_or_else(|| format!("{}    ", class_indent));
+
+    let stubs: String = properties
+        .iter()
+        .map(|property| format!("\n{}{}", indent, member_stub(property)))
+        .collect();
+    let (offset, new_text) = match members.last() {
+        Some(last) => (last.end_byte(), stubs),
+        // Right after the opening brace of an empty body
+        None => (
+            body.start_byte() + 1,
+            format!("{}\n{}", stubs, class_indent),
+        ),
+    };
+    let position = index.position(offset);
+
+    let mut changes = HashMap::new();
+    changes.insert(
+        uri.clone(),
+        vec![TextEdit {
+            range: Range {
+                start: position,
+                end: position,
+            },
+            new_text,
+        }],
+    );
+
+    Some(CodeActionOrCommand::CodeAction(CodeAction {
+        title: format!("Implement interface '{}'", interface),
+        kind: Some(CodeActionKind::QUICKFIX),
         diagnostics: None,
-        edit: None, // Would need proper implementation
+        edit: Some(WorkspaceEdit {
+            changes: Some(changes),
+            document_changes: None,
+            change_annotations: None,
+        }),
         command: None,
         is_preferred: Some(false),
-        disabled: Some(tower_lsp::lsp_types::CodeActionDisabled {
-            reason: "Not yet implemented".to_string(),
+        disabled: None,
+        data: None,
+    }))
+}
+
+/// "Generate constructor" for the class containing `range` when it has no
+/// constructor: one parameter per uninitialized instance field, assigned
+/// to the field. The constructor goes before the first method.
+fn create_generate_constructor_action(
+    uri: &Url,
+    range: Range,
+    tree: &Tree,
+    source: &str,
+) -> Option<CodeActionOrCommand> {
+    let index = LineIndex::new(source);
+    let point = index.point(range.start);
+    let mut class = tree.root_node().descendant_for_point_range(point, point)?;
+    while !matches!(
+        class.kind(),
+        "class_declaration" | "abstract_class_declaration" | "class"
+    ) {
+        class = class.parent()?;
+    }
+    let body = class.child_by_field_name("body")?;
+    let text = |node: Node| node.utf8_text(source.as_bytes()).unwrap_or("");
+
+    let mut cursor = body.walk();
+    let members: Vec<Node> = body.named_children(&mut cursor).collect();
+    let is_constructor = |member: &Node| {
+        member.kind() == "method_definition"
+            && member
+                .child_by_field_name("name")
+                .is_some_and(|name| text(name) == "constructor")
+    };
+    if members.iter().any(is_constructor) {
+        return None;
+    }
+
+    // (field, parameter) pairs, `#id` is assigned from `id`
+    let mut fields = Vec::new();
+    for member in members
+        .iter()
+        .filter(|member| member.kind() == "public_field_definition")
+        .filter(|member| member.child_by_field_name("value").is_none())
+    {
+        let mut cursor = member.walk();
+        let modifiers: Vec<&str> = member.children(&mut cursor).map(|c| c.kind()).collect();
+        if modifiers.iter().any(|m| matches!(*m, "static" | "declare")) {
+            continue;
+        }
+        let Some(name) = member
+            .child_by_field_name("name")
+            .filter(|name| name.kind() != "computed_property_name")
+        else {
+            continue;
+        };
+        let field = text(name);
+        let mut parameter = field.trim_start_matches('#').to_string();
+        if modifiers.contains(&"?") {
+            parameter.push('?');
+        }
+        if let Some(annotation) = member.child_by_field_name("type") {
+            parameter.push_str(text(annotation));
+        }
+        fields.push((field, parameter));
+    }
+    if fields.is_empty() {
+        return None;
+    }
+
+    let line_indent = |node: Node| -> String {
+        let line = index.slice(Range::new(
+            Position::new(node.start_position().row as u32, 0),
+            index.position(node.start_byte()),
+        ));
+        line.chars().take_while(|c| c.is_whitespace()).collect()
+    };
+    let indent = line_indent(members[0]);
+    // One level of indentation is whatever the members are indented by
+    let unit = indent
+        .strip_prefix(line_indent(class).as_str())
+        .filter(|unit| !unit.is_empty())
+        .unwrap_or("    ");
+    let parameters: Vec<&str> = fields.iter().map(|(_, p)| p.as_str()).collect();
+    let assignments: String = fields
+        .iter()
+        .map(|(field, parameter)| {
+            let name = parameter.split([':', '?']).next().unwrap_or(parameter);
+            format!("{}{}this.{} = {};\n", indent, unit, field, name)
+        })
+        .collect();
+    let constructor = format!(
+        "constructor({}) {{\n{}{}}}",
+        parameters.join(", "),
+        assignments,
+        indent
+    );
+
+    let (offset, new_text) = match members.iter().find(|m| m.kind() == "method_definition") {
+        Some(method) => (
+            method.start_byte(),
+            format!("{}\n\n{}", constructor, indent),
+        ),
+        None => {
+            // After the last field and its `;`
+            let mut cursor = body.walk();
+            let last = body
+                .children(&mut cursor)
+                .filter(|c| c.kind() != "}")
+                .last()?;
+            (last.end_byte(), format!("\n\n{}{}", indent, constructor))
+        }
+    };
+    let position = index.position(offset);
+
+    let mut changes = HashMap::new();
+    changes.insert(
+        uri.clone(),
+        vec![TextEdit {
+            range: Range::new(position, position),
+            new_text,
+        }],
+    );
+
+    Some(CodeActionOrCommand::CodeAction(CodeAction {
+        title: "Generate constructor".to_string(),
+        kind: Some(CodeActionKind::REFACTOR),
+        diagnostics: None,
+        edit: Some(WorkspaceEdit {
+            changes: Some(changes),
+            document_changes: None,
+            change_annotations: None,
         }),
+        command: None,
+        is_preferred: Some(false),
+        disabled: None,
         data: None,
-    }));
+    }))
+}
+
+/// Properties of the interface `name`, including those of the interfaces it
+/// extends
+fn collect_interface_members(
+    checker: &mut TypeChecker,
+    name: &str,
+    properties: &mut Vec<Property>,
+    visited: &mut Vec<String>,
+) {
+    if visited.iter().any(|seen| seen == name) {
+        return;
+    }
+    visited.push(name.to_string());
+
+    let id = checker.resolve_reference(&TypeReference {
+        name: name.to_string(),
+        type_arguments: Vec::new(),
+    });
+    let Some(ty) = checker.get_type(id).cloned() else {
+        return;
+    };
+    collect_type_members(checker, &ty, properties, visited);
+}
+
+fn collect_type_members(
+    checker: &mut TypeChecker,
+    ty: &Type,
+    properties: &mut Vec<Property>,
+    visited: &mut Vec<String>,
+) {
+    match ty {
+        Type::Object(obj) => properties.extend(obj.properties.values().cloned()),
+        Type::Intersection(members) => {
+            for member in members {
+                collect_type_members(checker, member, properties, visited);
+            }
+        }
+        Type::Reference(reference) => {
+            collect_interface_members(checker, &reference.name, properties, visited)
+        }
+        _ => {}
+    }
+}
+
+/// `name(a: A): R { throw ... }` for methods, `name: T;` for properties
+fn member_stub(property: &Property) -> String {
+    let optional = if property.optional { "?" } else { "" };
+    match property.ty.as_ref() {
+        Type::Function(function) => {
+            let parameters: Vec<String> = function
+                .parameters
+                .iter()
+                .map(|param| {
+                    format!(
+                        "{}{}{}: {}",
+                        if param.rest { "..." } else { "" },
+                        param.name,
+                        if param.optional { "?" } else { "" },
+                        print_type(&param.ty)
+                    )
+                })
+                .collect();
+            format!(
+                "{}{}({}): {} {{ throw new Error('Not implemented'); }}",
+                property.name,
+                optional,
+                parameters.join(", "),
+                print_type(&function.return_type)
+            )
+        }
+        ty => format!("{}{}: {};", property.name, optional, print_type(ty)),
+    }
+}
+
+/// Remove the imports that are never referenced, dropping single specifiers
+/// from a list or the whole statement when none of its bindings is used.
+/// Names that appear in type positions count as used.
+fn create_remove_unused_imports_action(
+    uri: &Url,
+    tree: &Tree,
+    source: &str,
+    symbol_table: &SymbolTable,
+) -> CodeActionOrCommand {
+    let index = LineIndex::new(source);
+    let root = tree.root_node();
+    let mut type_names = Vec::new();
+    collect_type_names(root, source, &mut type_names);
+
+    // Declarations of the unused bindings: the default import identifier,
+    // the `* as ns` clause or the import specifier
+    let unused: Vec<Range> = symbol_table
+        .all_symbols()
+        .filter(|symbol| symbol.flags.contains(SymbolFlags::IMPORT))
+        .filter(|symbol| symbol.references.is_empty())
+        .filter(|symbol| !type_names.contains(&symbol.name.as_str()))
+        .map(|symbol| symbol.declaration_range)
+        .collect();
+
+    let mut edits = Vec::new();
+    let mut cursor = root.walk();
+    for statement in root.children(&mut cursor) {
+        if statement.kind() == "import_statement" {
+            edits.extend(remove_unused_bindings(statement, source, &index, &unused));
+        }
+    }
+
+    let disabled = edits
+        .is_empty()
+        .then(|| tower_lsp::lsp_types::CodeActionDisabled {
+            reason: "No unused imports".to_string(),
+        });
+    let mut changes = HashMap::new();
+    changes.insert(uri.clone(), edits);
+
+    CodeActionOrCommand::CodeAction(CodeAction {
+        title: "Remove Unused Imports".to_string(),
+        kind: Some(CodeActionKind::SOURCE_ORGANIZE_IMPORTS),
+        diagnostics: None,
+        edit: Some(WorkspaceEdit {
+            changes: Some(changes),
+            document_changes: None,
+            change_annotations: None,
+        }),
+        command: None,
+        is_preferred: Some(true),
+        disabled,
+        data: None,
+    })
+}
+
+/// The top-level import statement enclosing `range`
+fn import_statement_at<'t>(tree: &'t Tree, source: &str, range: Range) -> Option<Node<'t>> {
+    let index = LineIndex::new(source);
+    let root = tree.root_node();
+    let mut cursor = root.walk();
+    root.children(&mut cursor)
+        .filter(|statement| statement.kind() == "import_statement")
+        .find(|statement| {
+            let statement_range = index.node_range(statement);
+            statement_range.start <= range.start && range.end <= statement_range.end
+        })
+}
+
+/// Remove the import bindings an unused-import diagnostic covers: a single
+/// specifier, or the whole statement when it covers all of them
+fn create_remove_unused_import_action(
+    uri: &Url,
+    range: Range,
+    tree: &Tree,
+    source: &str,
+) -> Option<CodeActionOrCommand> {
+    let statement = import_statement_at(tree, source, range)?;
+    let index = LineIndex::new(source);
+    let clause = statement
+        .children(&mut statement.walk())
+        .find(|child| child.kind() == "import_clause")?;
+
+    // The binding nodes: default identifier, `* as ns` and each specifier
+    let mut bindings = Vec::new();
+    let mut cursor = clause.walk();
+    for element in clause.named_children(&mut cursor) {
+        if element.kind() == "named_imports" {
+            let mut named_cursor = element.walk();
+            bindings.extend(
+                element
+                    .named_children(&mut named_cursor)
+                    .filter(|child| child.kind() == "import_specifier"),
+            );
+        } else {
+            bindings.push(element);
+        }
+    }
+    let covered: Vec<Node> = bindings
+        .iter()
+        .filter(|binding| {
+            let binding_range = index.node_range(binding);
+            range.start <= binding_range.start && binding_range.end <= range.end
+        })
+        .copied()
+        .collect();
+    let unused: Vec<Range> = covered.iter().map(|b| index.node_range(b)).collect();
+    let edits = remove_unused_bindings(statement, source, &index, &unused);
+    if edits.is_empty() {
+        return None;
+    }
+
+    let title = match covered.as_slice() {
+        [binding] if bindings.len() > 1 => {
+            let name = binding
+                .child_by_field_name("alias")
+                .or_else(|| binding.child_by_field_name("name"))
+                .unwrap_or(*binding);
+            format!(
+                "Remove unused declaration for: '{}'",
+                name.utf8_text(source.as_bytes()).unwrap_or("")
+            )
+        }
+        _ => {
+            let module = statement
+                .child_by_field_name("source")
+                .and_then(|s| s.utf8_text(source.as_bytes()).ok())
+                .unwrap_or("");
+            format!("Remove import from {}", module)
+        }
+    };
+
+    let mut changes = HashMap::new();
+    changes.insert(uri.clone(), edits);
+    Some(CodeActionOrCommand::CodeAction(CodeAction {
+        title,
+        kind: Some(CodeActionKind::QUICKFIX),
+        diagnostics: None,
+        edit: Some(WorkspaceEdit {
+            changes: Some(changes),
+            document_changes: None,
+            change_annotations: None,
+        }),
+        command: None,
+        is_preferred: Some(true),
+        disabled: None,
+        data: None,
+    }))
+}
+
+/// Names used as types: `T` in `x: T` and `ns` in `x: ns.T`
+pub fn collect_type_names<'s>(node: Node, source: &'s str, names: &mut Vec<&'s str>) {
+    match node.kind() {
+        "type_identifier" => names.extend(node.utf8_text(source.as_bytes()).ok()),
+        "nested_type_identifier" => {
+            if let Some(module) = node.child_by_field_name("module") {
+                collect_type_names(module, source, names);
+                names.extend(module.utf8_text(source.as_bytes()).ok());
+            }
+        }
+        _ => {}
+    }
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        collect_type_names(child, source, names);
+    }
+}
+
+/// Edits removing the bindings of an import statement whose declarations
+/// are in `unused`
+fn remove_unused_bindings(
+    statement: Node,
+    source: &str,
+    index: &LineIndex,
+    unused: &[Range],
+) -> Vec<TextEdit> {
+    let is_unused = |node: &Node| unused.contains(&index.node_range(node));
+    let Some(clause) = statement
+        .children(&mut statement.walk())
+        .find(|child| child.kind() == "import_clause")
+    else {
+        // `import './side-effect'`
+        return Vec::new();
+    };
+
+    let mut cursor = clause.walk();
+    let elements: Vec<Node> = clause.named_children(&mut cursor).collect();
+    let named = elements
+        .iter()
+        .find(|e| e.kind() == "named_imports")
+        .copied();
+    let specifiers: Vec<Node> = named
+        .map(|named| {
+            let mut cursor = named.walk();
+            named
+                .named_children(&mut cursor)
+                .filter(|child| child.kind() == "import_specifier")
+                .collect()
+        })
+        .unwrap_or_default();
+    let default = elements.iter().find(|e| e.kind() == "identifier").copied();
+    let namespace = elements
+        .iter()
+        .find(|e| e.kind() == "namespace_import")
+        .copied();
+
+    let default_unused = default.is_some_and(|d| is_unused(&d));
+    let namespace_unused = namespace.is_some_and(|n| is_unused(&n));
+    let unused_specifiers = specifiers.iter().filter(|s| is_unused(s)).count();
+    let all_specifiers_unused = unused_specifiers == specifiers.len();
+
+    if (default.is_none() || default_unused)
+        && (namespace.is_none() || namespace_unused)
+        && all_specifiers_unused
+    {
+        return vec![TextEdit {
+            range: statement_removal_range(statement, source, index),
+            new_text: String::new(),
+        }];
+    }
+
+    let mut edits = Vec::new();
+    let position = |offset: usize| index.position(offset);
+    let removal = |start: usize, end: usize| TextEdit {
+        range: Range::new(position(start), position(end)),
+        new_text: String::new(),
+    };
+
+    // `D, { a }` or `D, * as ns` - the default import is always first
+    let rest = named.or(namespace);
+    if let (Some(default), Some(rest)) = (default, rest) {
+        let rest_unused = if rest.kind() == "namespace_import" {
+            namespace_unused
+        } else {
+            all_specifiers_unused
+        };
+        if default_unused {
+            edits.push(removal(default.start_byte(), rest.start_byte()));
+        } else if rest_unused {
+            edits.push(removal(default.end_byte(), rest.end_byte()));
+            return edits;
+        }
+    }
+
+    if let Some(named) = named {
+        if unused_specifiers > 0 && !all_specifiers_unused {
+            let kept: Vec<&str> = specifiers
+                .iter()
+                .filter(|s| !is_unused(s))
+                .filter_map(|s| s.utf8_text(source.as_bytes()).ok())
+                .collect();
+            edits.push(TextEdit {
+                range: index.node_range(&named),
+                new_text: named_imports_text(named, &specifiers, source, &kept),
+            });
+        }
+    }
+    edits
+}
+
+/// `{ a, b }` with only the `kept` specifiers, keeping the brace spacing,
+/// line layout and trailing comma of the original list
+fn named_imports_text(named: Node, specifiers: &[Node], source: &str, kept: &[&str]) -> String {
+    let text = named.utf8_text(source.as_bytes()).unwrap_or("{}");
+    if text.contains('\n') {
+        let indent = specifiers
+            .first()
+            .map(|first| {
+                let line_start = source[..first.start_byte()]
+                    .rfind('\n')
+                    .map_or(0, |i| i + 1);
+                source[line_start..first.start_byte()].to_string()
+            })
+            .unwrap_or_default();
+        let closing_indent = text.rsplit('\n').next().unwrap_or("").trim_end_matches('}');
+        let trailing_comma = text.trim_end_matches('}').trim_end().ends_with(',');
+        let mut result = "{\n".to_string();
+        for (i, specifier) in kept.iter().enumerate() {
+            result.push_str(&indent);
+            result.push_str(specifier);
+            if trailing_comma || i + 1 < kept.len() {
+                result.push(',');
+            }
+            result.push('\n');
+        }
+        result.push_str(closing_indent);
+        result.push('}');
+        return result;
+    }
+
+    let padding = if text.starts_with("{ ") { " " } else { "" };
+    format!("{{{}{}{}}}", padding, kept.join(", "), padding)
+}
+
+/// The range of a statement together with its line break, when it is
+/// alone on its line
+fn statement_removal_range(statement: Node, source: &str, index: &LineIndex) -> Range {
+    let rest_of_line = source[statement.end_byte()..]
+        .split('\n')
+        .next()
+        .unwrap_or("");
+    let end = if rest_of_line.trim().is_empty() {
+        let offset = statement.end_byte() + rest_of_line.len();
+        if offset < source.len() {
+            Position::new(index.position(offset).line + 1, 0)
+        } else {
+            index.position(offset)
+        }
+    } else {
+        index.position(statement.end_byte())
+    };
+    Range::new(index.position(statement.start_byte()), end)
+}
+
+/// "Add Missing Imports" actions: one per module of the project that exports
+/// a name the document uses without declaring it
+fn get_missing_import_actions(
+    uri: &Url,
+    tree: &Tree,
+    source: &str,
+    symbol_table: &SymbolTable,
+    project: &Project,
+) -> Vec<CodeActionOrCommand> {
+    let Ok(file_path) = uri.to_file_path() else {
+        return Vec::new();
+    };
+    let options = project.compiler_options();
+
+    let undefined = NumberOrString::Number(TypeDiagnosticCode::UndefinedVariable.as_number());
+    let mut names: Vec<String> = get_type_diagnostics(tree, source, symbol_table, &options)
+        .iter()
+        .filter(|diagnostic| diagnostic.code.as_ref() == Some(&undefined))
+        .filter_map(|diagnostic| extract_name_from_message(&diagnostic.message))
+        .collect();
+    names.sort();
+    names.dedup();
+
+    let imports = import_specifiers(tree, source);
+    let quote = imports
+        .first()
+        .and_then(|(text, _)| text.chars().next())
+        .filter(|c| *c == '"' || *c == '\'')
+        .unwrap_or('\'');
+
+    let mut actions = Vec::new();
+    for name in &names {
+        for module in project.modules_exporting(name) {
+            if module == file_path {
+                continue;
+            }
+            let specifier = relative_specifier(&file_path, &module);
+            let line = import_insert_line(&imports, &specifier);
+            actions.push(create_add_import_action(uri, name, &specifier, quote, line));
+        }
+    }
+    actions
+}
+
+/// Quoted specifiers of the top-level imports with their line ranges
+pub fn import_specifiers(tree: &Tree, source: &str) -> Vec<(String, std::ops::Range<u32>)> {
+    let root = tree.root_node();
+    let mut cursor = root.walk();
+    root.children(&mut cursor)
+        .filter(|statement| statement.kind() == "import_statement")
+        .filter_map(|statement| {
+            let specifier = statement.child_by_field_name("source")?;
+            let text = specifier.utf8_text(source.as_bytes()).ok()?.to_string();
+            let lines =
+                statement.start_position().row as u32..statement.end_position().row as u32 + 1;
+            Some((text, lines))
+        })
+        .collect()
+}
+
+/// Line to insert an import of `specifier` at: in alphabetical order among
+/// the imports of its group (relative or package imports), after the last
+/// import if the group is empty, or at the top of the file without imports
+pub fn import_insert_line(imports: &[(String, std::ops::Range<u32>)], specifier: &str) -> u32 {
+    let unquote = |text: &str| text.trim_matches(|c| c == '"' || c == '\'').to_string();
+    let is_relative = |specifier: &str| specifier.starts_with('.');
+
+    let group: Vec<_> = imports
+        .iter()
+        .filter(|(text, _)| is_relative(&unquote(text)) == is_relative(specifier))
+        .collect();
+    if let Some((_, lines)) = group
+        .iter()
+        .find(|(text, _)| unquote(text).as_str() > specifier)
+    {
+        return lines.start;
+    }
+    group
+        .last()
+        .or(imports.last().as_ref())
+        .map_or(0, |(_, lines)| lines.end)
+}
+
+/// Module specifier for importing `to` from the file `from`, without the
+/// extension or a trailing `/index`
+pub fn relative_specifier(from: &Path, to: &Path) -> String {
+    let from_dir: Vec<_> = from
+        .parent()
+        .map_or(Vec::new(), |dir| dir.components().collect());
+    let to: Vec<_> = to.components().collect();
+    let common = from_dir.iter().zip(&to).take_while(|(a, b)| a == b).count();
+
+    let mut parts = vec!["..".to_string(); from_dir.len() - common];
+    parts.extend(
+        to[common..]
+            .iter()
+            .map(|component| component.as_os_str().to_string_lossy().into_owned()),
+    );
+    let mut specifier = parts.join("/");
+
+    for extension in [".d.ts", ".tsx", ".ts", ".mts", ".cts", ".jsx", ".js"] {
+        if let Some(stripped) = specifier.strip_suffix(extension) {
+            specifier = stripped.to_string();
+            break;
+        }
+    }
+    if specifier == "index" {
+        specifier = ".".to_string();
+    } else if let Some(dir) = specifier.strip_suffix("/index") {
+        specifier = dir.to_string();
+    }
+
+    if specifier.starts_with('.') {
+        specifier
+    } else {
+        format!("./{}", specifier)
+    }
+}
+
+fn create_add_import_action(
+    uri: &Url,
+    name: &str,
+    specifier: &str,
+    quote: char,
+    line: u32,
+) -> CodeActionOrCommand {
+    let mut changes = HashMap::new();
+    changes.insert(
+        uri.clone(),
+        vec![TextEdit {
+            range: Range {
+                start: Position::new(line, 0),
+                end: Position::new(line, 0),
+            },
+            new_text: format!(
+                "import {{ {} }} from {}{}{};\n",
+                name, quote, specifier, quote
+            ),
+        }],
+    );
 
-    // Sort imports alphabetically
-    actions.push(CodeActionOrCommand::CodeAction(CodeAction {
-        title: "Sort Imports".to_string(),
-        kind: Some(CodeActionKind::new("source.sortImports")),
+    CodeActionOrCommand::CodeAction(CodeAction {
+        title: format!("Import '{}' from \"{}\"", name, specifier),
+        kind: Some(CodeActionKind::new("source.addMissingImports")),
         diagnostics: None,
-        edit: Some(create_sort_imports_edit(uri, source)),
+        edit: Some(WorkspaceEdit {
+            changes: Some(changes),
+            document_changes: None,
+            change_annotations: None,
+        }),
         command: None,
         is_preferred: Some(false),
         disabled: None,
         data: None,
-    }));
-
-    actions
+    })
 }
 
 // Helper functions
@@ -176,44 +1893,7 @@ fn extract_name_from_message(message: &str) -> Option<String> {
 }
 
 fn get_text_in_range(source: &str, range: Range) -> String {
-    let lines: Vec<&str> = source.lines().collect();
-    let mut result = String::new();
-
-    for (i, line) in lines.iter().enumerate() {
-        let line_num = i as u32;
-
-        if line_num < range.start.line || line_num > range.end.line {
-            continue;
-        }
-
-        if line_num == range.start.line && line_num == range.end.line {
-            // Single line selection
-            let start = range.start.character as usize;
-            let end = range.end.character as usize;
-            if start < line.len() && end <= line.len() {
-                result.push_str(&line[start..end]);
-            }
-        } else if line_num == range.start.line {
-            // First line of multi-line selection
-            let start = range.start.character as usize;
-            if start < line.len() {
-                result.push_str(&line[start..]);
-                result.push('\n');
-            }
-        } else if line_num == range.end.line {
-            // Last line of multi-line selection
-            let end = range.end.character as usize;
-            if end <= line.len() {
-                result.push_str(&line[..end]);
-            }
-        } else {
-            // Middle line
-            result.push_str(line);
-            result.push('\n');
-        }
-    }
-
-    result
+    LineIndex::new(source).slice(range).to_string()
 }
 
 fn create_declare_variable_action(uri: &Url, range: &Range, name: &str) -> CodeActionOrCommand {
@@ -422,6 +2102,145 @@ fn create_extract_variable_action(uri: &Url, range: Range, text: &str) -> CodeAc
     })
 }
 
+/// "Inline variable": replace every reference to a variable with its
+/// initializer and drop the declaration. Disabled when an initializer that
+/// may have side effects would be evaluated more than once.
+fn create_inline_variable_action(
+    uri: &Url,
+    symbol_id: SymbolId,
+    tree: &Tree,
+    symbol_table: &SymbolTable,
+    source: &str,
+) -> Option<CodeActionOrCommand> {
+    let symbol = symbol_table.get_symbol(symbol_id)?;
+    let index = LineIndex::new(source);
+    let node_at = |range: Range| {
+        let start = index.point(range.start);
+        let end = index.point(range.end);
+        tree.root_node().descendant_for_point_range(start, end)
+    };
+
+    let name = node_at(symbol.declaration_range)?;
+    let declarator = name
+        .parent()
+        .filter(|p| p.kind() == "variable_declarator")
+        .filter(|p| p.child_by_field_name("name") == Some(name))?;
+    let value = declarator.child_by_field_name("value")?;
+    let initializer = value.utf8_text(source.as_bytes()).ok()?;
+
+    let mut edits = Vec::new();
+    for &reference in &symbol.references {
+        let site = node_at(reference)?;
+        let needs_parentheses = !is_primary_expression(value)
+            && !site.parent().is_some_and(|p| {
+                matches!(
+                    p.kind(),
+                    "arguments"
+                        | "array"
+                        | "parenthesized_expression"
+                        | "return_statement"
+                        | "expression_statement"
+                        | "template_substitution"
+                        | "variable_declarator"
+                        | "pair"
+                )
+            });
+        edits.push(TextEdit {
+            range: reference,
+            new_text: if needs_parentheses {
+                format!("({})", initializer)
+            } else {
+                initializer.to_string()
+            },
+        });
+    }
+
+    // Remove the whole statement, or only this declarator from a list
+    let declaration = declarator.parent()?;
+    let mut cursor = declaration.walk();
+    let declarators: Vec<Node> = declaration
+        .named_children(&mut cursor)
+        .filter(|d| d.kind() == "variable_declarator")
+        .collect();
+    let removal = if declarators.len() == 1 {
+        statement_removal_range(declaration, source, &index)
+    } else {
+        let position = declarators.iter().position(|d| *d == declarator)?;
+        let (start, end) = match declarators.get(position + 1) {
+            Some(next) => (declarator.start_byte(), next.start_byte()),
+            None => (declarators[position - 1].end_byte(), declarator.end_byte()),
+        };
+        Range::new(index.position(start), index.position(end))
+    };
+    edits.push(TextEdit {
+        range: removal,
+        new_text: String::new(),
+    });
+
+    let disabled = (symbol.references.len() > 1 && may_have_side_effects(value)).then(|| {
+        tower_lsp::lsp_types::CodeActionDisabled {
+            reason: "The initializer may have side effects and is used more than once".to_string(),
+        }
+    });
+    let mut changes = HashMap::new();
+    changes.insert(uri.clone(), edits);
+
+    Some(CodeActionOrCommand::CodeAction(CodeAction {
+        title: format!("Inline variable '{}'", symbol.name),
+        kind: Some(CodeActionKind::REFACTOR_INLINE),
+        diagnostics: None,
+        edit: Some(WorkspaceEdit {
+            changes: Some(changes),
+            document_changes: None,
+            change_annotations: None,
+        }),
+        command: None,
+        is_preferred: Some(false),
+        disabled,
+        data: None,
+    }))
+}
+
+/// Expressions that can replace an identifier anywhere without parentheses
+fn is_primary_expression(node: Node) -> bool {
+    matches!(
+        node.kind(),
+        "identifier"
+            | "number"
+            | "string"
+            | "template_string"
+            | "regex"
+            | "true"
+            | "false"
+            | "null"
+            | "undefined"
+            | "this"
+            | "array"
+            | "member_expression"
+            | "subscript_expression"
+            | "call_expression"
+            | "parenthesized_expression"
+    )
+}
+
+/// Whether evaluating `node` could do more than compute a value: it calls
+/// something or assigns to something
+fn may_have_side_effects(node: Node) -> bool {
+    if matches!(
+        node.kind(),
+        "call_expression"
+            | "new_expression"
+            | "assignment_expression"
+            | "augmented_assignment_expression"
+            | "update_expression"
+            | "await_expression"
+    ) {
+        return true;
+    }
+    let mut cursor = node.walk();
+    node.named_children(&mut cursor).any(may_have_side_effects)
+}
+
 fn create_extract_function_action(uri: &Url, range: Range, text: &str) -> CodeActionOrCommand {
     let fn_name = "extractedFunction";
 
@@ -553,11 +2372,45 @@ fn create_sort_imports_edit(uri: &Url, source: &str) -> WorkspaceEdit {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::parser::{SourceLanguage, SourceParser};
+    use std::path::PathBuf;
+    use tower_lsp::lsp_types::{CodeActionContext, TextDocumentIdentifier};
 
     fn test_uri() -> Url {
         Url::parse("file:///test/file.ts").unwrap()
     }
 
+    fn parse(source: &str) -> Tree {
+        SourceParser::new(SourceLanguage::TypeScript)
+            .parse(source, None)
+            .unwrap()
+    }
+
+    fn project_with(files: &[(&str, &str)]) -> Project {
+        let mut project = Project::new(PathBuf::from("/test"));
+        for (path, source) in files {
+            project.cache_file(PathBuf::from(path), &parse(source), source);
+        }
+        project
+    }
+
+    /// Titles and inserted text of the "Add Missing Imports" actions
+    fn missing_imports(source: &str, project: &Project) -> Vec<(String, Position, String)> {
+        let tree = parse(source);
+        let symbol_table = crate::analysis::binder::bind_document(&tree, source);
+        get_missing_import_actions(&test_uri(), &tree, source, &symbol_table, project)
+            .into_iter()
+            .filter_map(|action| match action {
+                CodeActionOrCommand::CodeAction(action) => Some(action),
+                _ => None,
+            })
+            .map(|action| {
+                let edit = &action.edit.unwrap().changes.unwrap()[&test_uri()][0];
+                (action.title, edit.range.start, edit.new_text.clone())
+            })
+            .collect()
+    }
+
     #[test]
     fn test_extract_name_from_message() {
         let message = "Cannot find name 'foo'.";
@@ -614,6 +2467,18 @@ mod tests {
         assert!(text.starts_with("short"));
     }
 
+    #[test]
+    fn test_get_text_in_range_after_multibyte() {
+        // 'é' is one UTF-16 unit, '😀' is two
+        let source = "const s = \"é😀\" + x;";
+        let range = Range {
+            start: Position::new(0, 18),
+            end: Position::new(0, 19),
+        };
+        let text = get_text_in_range(source, range);
+        assert_eq!(text, "x");
+    }
+
     #[test]
     fn test_get_text_in_range_empty() {
         let source = "const x = 1;";
@@ -909,7 +2774,7 @@ import { a } from 'a';"#;
             data: None,
         };
 
-        let actions = get_diagnostic_fixes(&uri, &diagnostic, "foo");
+        let actions = get_diagnostic_fixes(&uri, &diagnostic, &parse("foo"), "foo");
 
         // Should include declare variable and ts-ignore
         assert!(actions.len() >= 2);
@@ -942,7 +2807,8 @@ import { a } from 'a';"#;
             data: None,
         };
 
-        let actions = get_diagnostic_fixes(&uri, &diagnostic, "const x = 1;");
+        let source = "const x = 1;";
+        let actions = get_diagnostic_fixes(&uri, &diagnostic, &parse(source), source);
 
         // Should include prefix underscore, remove, and ts-ignore
         assert!(actions.len() >= 3);
@@ -967,7 +2833,7 @@ import { a } from 'a';"#;
         };
 
         let source = "const x = 1;\nx = 2;";
-        let actions = get_diagnostic_fixes(&uri, &diagnostic, source);
+        let actions = get_diagnostic_fixes(&uri, &diagnostic, &parse(source), source);
 
         // Should include change to let and ts-ignore
         assert!(actions.len() >= 2);
@@ -982,6 +2848,24 @@ import { a } from 'a';"#;
         assert!(has_let);
     }
 
+    fn code_action_params(
+        uri: Url,
+        range: Range,
+        diagnostics: Vec<Diagnostic>,
+    ) -> CodeActionParams {
+        CodeActionParams {
+            text_document: TextDocumentIdentifier { uri },
+            range,
+            context: CodeActionContext {
+                diagnostics,
+                only: None,
+                trigger_kind: None,
+            },
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        }
+    }
+
     #[test]
     fn test_get_code_actions() {
         let uri = test_uri();
@@ -991,8 +2875,17 @@ import { a } from 'a';"#;
             end: Position::new(0, 15),
         };
         let symbol_table = SymbolTable::new();
-
-        let actions = get_code_actions(&uri, range, &[], &symbol_table, source);
+        let tree = parse(source);
+        let project = Project::new(PathBuf::from("/test"));
+
+        let actions = get_code_actions(
+            &code_action_params(uri, range, Vec::new()),
+            &tree,
+            &symbol_table,
+            &mut TypeChecker::new(),
+            source,
+            &project,
+        );
 
         // Should have source actions and refactoring actions
         assert!(!actions.is_empty());
@@ -1019,7 +2912,17 @@ import { a } from 'a';"#;
             data: None,
         }];
 
-        let actions = get_code_actions(&uri, range, &diagnostics, &symbol_table, source);
+        let tree = parse(source);
+        let project = Project::new(PathBuf::from("/test"));
+
+        let actions = get_code_actions(
+            &code_action_params(uri, range, diagnostics),
+            &tree,
+            &symbol_table,
+            &mut TypeChecker::new(),
+            source,
+            &project,
+        );
 
         // Should have diagnostic fixes + source actions
         assert!(actions.len() >= 3);
@@ -1043,4 +2946,644 @@ import { a } from 'a';"#;
             panic!("Expected CodeAction");
         }
     }
+
+    #[test]
+    fn test_missing_import_action() {
+        let project = project_with(&[("/test/lib/utils.ts", "export function helper() {}")]);
+        let source = "import x from 'lib';\n\nhelper();";
+
+        assert_eq!(
+            missing_imports(source, &project),
+            vec![(
+                "Import 'helper' from \"./lib/utils\"".to_string(),
+                Position::new(1, 0),
+                "import { helper } from './lib/utils';\n".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn test_missing_import_one_action_per_candidate() {
+        let project = project_with(&[
+            ("/test/a.ts", "export const shared = 1;"),
+            ("/other/index.ts", "export const shared = 2;"),
+            ("/test/file.ts", "export const shared = 3;"),
+        ]);
+        let source = "shared;";
+
+        let specifiers: Vec<_> = missing_imports(source, &project)
+            .into_iter()
+            .map(|(_, position, text)| (position, text))
+            .collect();
+        assert_eq!(
+            specifiers,
+            vec![
+                (
+                    Position::new(0, 0),
+                    "import { shared } from '../other';\n".to_string()
+                ),
+                (
+                    Position::new(0, 0),
+                    "import { shared } from './a';\n".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_missing_import_respects_grouping() {
+        let project = project_with(&[("/test/m.ts", "export const value = 1;")]);
+        let source =
+            "import \"react\";\nimport { a } from \"./a\";\nimport { z } from \"./z\";\nvalue;";
+
+        let actions = missing_imports(source, &project);
+        assert_eq!(actions.len(), 1);
+        assert_eq!(actions[0].1, Position::new(2, 0));
+        assert_eq!(actions[0].2, "import { value } from \"./m\";\n");
+    }
+
+    #[test]
+    fn test_relative_specifier() {
+        let from = Path::new("/p/src/app/main.ts");
+        assert_eq!(
+            relative_specifier(from, Path::new("/p/src/app/x.ts")),
+            "./x"
+        );
+        assert_eq!(
+            relative_specifier(from, Path::new("/p/src/lib/y.tsx")),
+            "../lib/y"
+        );
+        assert_eq!(
+            relative_specifier(from, Path::new("/p/src/lib/index.ts")),
+            "../lib"
+        );
+        assert_eq!(
+            relative_specifier(from, Path::new("/p/src/app/index.ts")),
+            "."
+        );
+    }
+
+    fn implement_interface_edits(source: &str, position: Position) -> Vec<(String, TextEdit)> {
+        let tree = parse(source);
+        get_implement_interface_actions(&test_uri(), Range::new(position, position), &tree, source)
+            .into_iter()
+            .filter_map(|action| match action {
+                CodeActionOrCommand::CodeAction(action) => Some(action),
+                _ => None,
+            })
+            .map(|action| {
+                let edit = action.edit.unwrap().changes.unwrap()[&test_uri()][0].clone();
+                (action.title, edit)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_implement_interface_members() {
+        let source = "interface Shape {\n  name: string;\n  area(scale: number): number;\n  label?: string;\n}\nclass Square implements Shape {\n  name = 'square';\n}";
+        let edits = implement_interface_edits(source, Position::new(5, 8));
+
+        assert_eq!(edits.len(), 1);
+        let (title, edit) = &edits[0];
+        assert_eq!(title, "Implement interface 'Shape'");
+        assert_eq!(edit.range.start, Position::new(6, 17));
+        assert_eq!(
+            edit.new_text,
+            "\n  area(scale: number): number { throw new Error('Not implemented'); }\n  label?: string;"
+        );
+    }
+
+    #[test]
+    fn test_implement_interface_empty_class_and_base_interfaces() {
+        let source = "interface A { a(): void; }\ninterface B extends A { b: number; }\nclass C implements B {}";
+        let edits = implement_interface_edits(source, Position::new(2, 22));
+
+        assert_eq!(edits.len(), 1);
+        assert_eq!(edits[0].1.range.start, Position::new(2, 22));
+        assert_eq!(
+            edits[0].1.new_text,
+            "\n    a(): void { throw new Error('Not implemented'); }\n    b: number;\n"
+        );
+    }
+
+    #[test]
+    fn test_implement_interface_nothing_missing() {
+        let source = "interface I { x: number; }\nclass K implements I { x = 1; }\nclass L {}";
+        assert!(implement_interface_edits(source, Position::new(1, 7)).is_empty());
+        assert!(implement_interface_edits(source, Position::new(2, 7)).is_empty());
+    }
+
+    fn type_annotation_edit(source: &str, position: Position) -> Option<(String, TextEdit)> {
+        let tree = parse(source);
+        let mut symbol_table = crate::analysis::binder::bind_document(&tree, source);
+        let mut checker = TypeChecker::new();
+        checker.declare_types_from_tree(tree.root_node(), source);
+        checker.infer_symbol_types(&mut symbol_table, tree.root_node(), source);
+
+        let range = Range::new(position, position);
+        match get_add_type_annotation_action(
+            &test_uri(),
+            range,
+            &tree,
+            &symbol_table,
+            &mut checker,
+            source,
+        )? {
+            CodeActionOrCommand::CodeAction(action) => {
+                let edit = action.edit?.changes?[&test_uri()][0].clone();
+                Some((action.title, edit))
+            }
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_add_type_annotation_from_initializer() {
+        let source = "function make(): Map<string, number> { return new Map(); }\nconst m = make();\nconst n = 42;";
+
+        let (title, edit) = type_annotation_edit(source, Position::new(1, 6)).unwrap();
+        assert_eq!(title, "Add type annotation ': Map<string, number>'");
+        assert_eq!(edit.range.start, Position::new(1, 7));
+        assert_eq!(edit.new_text, ": Map<string, number>");
+
+        let (_, edit) = type_annotation_edit(source, Position::new(2, 6)).unwrap();
+        assert_eq!(edit.new_text, ": number");
+    }
+
+    #[test]
+    fn test_add_type_annotation_for_parameters() {
+        let source = "function f(a, b = 'x', c?) {}\nf(1, 'y', true);\nf('z');";
+
+        let (_, edit) = type_annotation_edit(source, Position::new(0, 11)).unwrap();
+        assert_eq!(edit.new_text, ": number | string");
+        assert_eq!(edit.range.start, Position::new(0, 12));
+
+        let (_, edit) = type_annotation_edit(source, Position::new(0, 14)).unwrap();
+        assert_eq!(edit.new_text, ": string");
+
+        let (_, edit) = type_annotation_edit(source, Position::new(0, 23)).unwrap();
+        assert_eq!(edit.new_text, ": boolean");
+        assert_eq!(edit.range.start, Position::new(0, 25));
+    }
+
+    #[test]
+    fn test_add_type_annotation_for_instances_and_arrays() {
+        let source = "class Foo { x = 1; }\nclass Box<T> { v: T; }\nconst f = new Foo();\nconst xs = [1, 2];\nconst ys = [1, 'a', 2];\nconst b = new Box<string>();\nconst c = new Box();\nconst e = [];";
+
+        let (_, edit) = type_annotation_edit(source, Position::new(2, 6)).unwrap();
+        assert_eq!(edit.new_text, ": Foo");
+        let (_, edit) = type_annotation_edit(source, Position::new(3, 6)).unwrap();
+        assert_eq!(edit.new_text, ": number[]");
+        let (_, edit) = type_annotation_edit(source, Position::new(4, 6)).unwrap();
+        assert_eq!(edit.new_text, ": (number | string)[]");
+        let (_, edit) = type_annotation_edit(source, Position::new(5, 6)).unwrap();
+        assert_eq!(edit.new_text, ": Box<string>");
+
+        // Type arguments that would need inferring, and `any[]`
+        assert!(type_annotation_edit(source, Position::new(6, 6)).is_none());
+        assert!(type_annotation_edit(source, Position::new(7, 6)).is_none());
+    }
+
+    #[test]
+    fn test_add_type_annotation_skips_annotated_and_unknown() {
+        let source = "const a: number = 1;\nconst b = unknownCall();";
+        assert!(type_annotation_edit(source, Position::new(0, 6)).is_none());
+        assert!(type_annotation_edit(source, Position::new(1, 6)).is_none());
+    }
+
+    fn then_to_await(source: &str, position: Position) -> Option<String> {
+        let tree = parse(source);
+        let range = Range::new(position, position);
+        let CodeActionOrCommand::CodeAction(action) =
+            create_then_to_await_action(&test_uri(), range, &tree, source)?
+        else {
+            return None;
+        };
+        let mut edits = action.edit?.changes?.remove(&test_uri())?;
+        // Apply from the end so earlier offsets stay valid
+        edits.sort_by_key(|edit| std::cmp::Reverse(edit.range.start));
+        let mut result = source.to_string();
+        for edit in edits {
+            let index = LineIndex::new(&result);
+            let start = index.offset(edit.range.start);
+            let end = index.offset(edit.range.end);
+            result.replace_range(start..end, &edit.new_text);
+        }
+        Some(result)
+    }
+
+    #[test]
+    fn test_then_catch_to_await() {
+        let source = "function load() {\n    doThing().then(r => use(r)).catch(e => handle(e));\n}";
+        assert_eq!(
+            then_to_await(source, Position::new(1, 20)).unwrap(),
+            "async function load() {\n    try {\n        const r = await doThing();\n        use(r);\n    } catch (e) {\n        handle(e);\n    }\n}"
+        );
+    }
+
+    #[test]
+    fn test_then_chain_with_rejection_handler_to_await() {
+        let source = "class A {\n  static get(): Promise<number> {\n    return fetch(u).then(res => res.json()).then((data: any) => data, () => 0);\n  }\n}";
+        assert_eq!(
+            then_to_await(source, Position::new(2, 12)).unwrap(),
+            "class A {\n  static async get(): Promise<number> {\n    try {\n        const res = await fetch(u);\n        const data: any = await res.json();\n        return data;\n    } catch {\n        return 0;\n    }\n  }\n}"
+        );
+    }
+
+    #[test]
+    fn test_then_to_await_not_offered_when_fulfilled_callback_can_throw() {
+        // `b` doesn't see errors thrown by the fulfilled callback, a `catch` would
+        let source = "function f() {\n  p.then(a => { log(a); }, e => b(e));\n}";
+        assert!(then_to_await(source, Position::new(1, 4)).is_none());
+        let source = "function f() {\n  p.then(a => a.x, e => b(e));\n}";
+        assert!(then_to_await(source, Position::new(1, 4)).is_none());
+    }
+
+    #[test]
+    fn test_then_to_await_not_offered_when_names_collide() {
+        // Two `const r`, the second reading itself in its initializer
+        let source =
+            "function f() {\n  fetch(u).then(r => r.json()).then(r => use(r)).catch(e => e);\n}";
+        assert!(then_to_await(source, Position::new(1, 4)).is_none());
+        // The base reads an outer `r` the new `const r` would shadow
+        let source = "function f(r) {\n  get(r).then(r => use(r)).catch(e => e);\n}";
+        assert!(then_to_await(source, Position::new(1, 4)).is_none());
+    }
+
+    #[test]
+    fn test_then_to_await_not_offered() {
+        // No rejection handler, top-level code, or an early return
+        assert!(
+            then_to_await("function f() { a().then(r => r); }", Position::new(0, 17)).is_none()
+        );
+        assert!(then_to_await("a().then(r => r).catch(e => e);", Position::new(0, 5)).is_none());
+        assert!(
+            then_to_await(
+                "function f() { a().then(r => { return; }).catch(e => e); }",
+                Position::new(0, 17)
+            )
+            .is_none()
+        );
+    }
+
+    fn remove_unused_imports(source: &str) -> String {
+        let tree = parse(source);
+        let symbol_table = crate::analysis::binder::bind_document(&tree, source);
+        let CodeActionOrCommand::CodeAction(action) =
+            create_remove_unused_imports_action(&test_uri(), &tree, source, &symbol_table)
+        else {
+            unreachable!();
+        };
+        assert_eq!(action.kind, Some(CodeActionKind::SOURCE_ORGANIZE_IMPORTS));
+        assert_eq!(action.is_preferred, Some(true));
+
+        let mut edits = action
+            .edit
+            .unwrap()
+            .changes
+            .unwrap()
+            .remove(&test_uri())
+            .unwrap();
+        edits.sort_by_key(|edit| std::cmp::Reverse(edit.range.start));
+        let mut result = source.to_string();
+        for edit in edits {
+            let index = LineIndex::new(&result);
+            let start = index.offset(edit.range.start);
+            let end = index.offset(edit.range.end);
+            result.replace_range(start..end, &edit.new_text);
+        }
+        result
+    }
+
+    #[test]
+    fn test_remove_unused_imports() {
+        let source = "import { a, b, c } from './abc';\nimport D from 'd';\nimport * as ns from 'ns';\nimport './side-effect';\na(c);";
+        assert_eq!(
+            remove_unused_imports(source),
+            "import { a, c } from './abc';\nimport './side-effect';\na(c);"
+        );
+    }
+
+    #[test]
+    fn test_remove_unused_default_and_named_imports() {
+        let source = "import D, { x } from 'm';\nimport E, { y } from 'n';\nimport F, * as g from 'o';\nD; y; g;";
+        assert_eq!(
+            remove_unused_imports(source),
+            "import D from 'm';\nimport { y } from 'n';\nimport * as g from 'o';\nD; y; g;"
+        );
+    }
+
+    #[test]
+    fn test_remove_unused_imports_keeps_type_uses() {
+        let source =
+            "import type { T, U } from 't';\nimport { ns } from 'ns';\nlet v: T = 1 as ns.V;\nv;";
+        assert_eq!(
+            remove_unused_imports(source),
+            "import type { T } from 't';\nimport { ns } from 'ns';\nlet v: T = 1 as ns.V;\nv;"
+        );
+    }
+
+    /// Apply the quick fixes offered for unused-import diagnostics
+    fn fix_unused_imports(source: &str) -> (Vec<Diagnostic>, Vec<String>, String) {
+        let tree = parse(source);
+        let symbol_table = crate::analysis::binder::bind_document(&tree, source);
+        let diagnostics: Vec<Diagnostic> =
+            crate::capabilities::type_diagnostics::get_type_diagnostics(
+                &tree,
+                source,
+                &symbol_table,
+                &crate::resolution::tsconfig::CompilerOptions {
+                    no_unused_locals: Some(true),
+                    ..Default::default()
+                },
+            )
+            .into_iter()
+            .filter(|d| {
+                matches!(
+                    d.code,
+                    Some(tower_lsp::lsp_types::NumberOrString::Number(6133 | 6192))
+                )
+            })
+            .collect();
+
+        let mut titles = Vec::new();
+        let mut edits = Vec::new();
+        for diagnostic in &diagnostics {
+            let Some(CodeActionOrCommand::CodeAction(action)) =
+                create_remove_unused_import_action(&test_uri(), diagnostic.range, &tree, source)
+            else {
+                continue;
+            };
+            titles.push(action.title);
+            edits.extend(
+                action
+                    .edit
+                    .unwrap()
+                    .changes
+                    .unwrap()
+                    .remove(&test_uri())
+                    .unwrap(),
+            );
+        }
+        edits.sort_by_key(|edit| std::cmp::Reverse(edit.range.start));
+        let mut result = source.to_string();
+        for edit in edits {
+            let index = LineIndex::new(&result);
+            let start = index.offset(edit.range.start);
+            let end = index.offset(edit.range.end);
+            result.replace_range(start..end, &edit.new_text);
+        }
+        (diagnostics, titles, result)
+    }
+
+    #[test]
+    fn test_unused_import_diagnostics_and_fixes() {
+        let source = "import { a, b as bee, c } from './abc';\nimport D from 'd';\nimport E, { f } from 'ef';\nlet t: c;\na(t);";
+        let (diagnostics, titles, fixed) = fix_unused_imports(source);
+
+        let reported: Vec<(&str, Option<Vec<tower_lsp::lsp_types::DiagnosticTag>>)> = diagnostics
+            .iter()
+            .map(|d| (d.message.as_str(), d.tags.clone()))
+            .collect();
+        let unnecessary = Some(vec![tower_lsp::lsp_types::DiagnosticTag::UNNECESSARY]);
+        assert_eq!(
+            reported,
+            vec![
+                (
+                    "'bee' is declared but its value is never read.",
+                    unnecessary.clone()
+                ),
+                (
+                    "'D' is declared but its value is never read.",
+                    unnecessary.clone()
+                ),
+                ("All imports in import declaration are unused.", unnecessary),
+            ]
+        );
+        assert_eq!(diagnostics[0].range.start, Position::new(0, 12));
+        assert_eq!(
+            titles,
+            vec![
+                "Remove unused declaration for: 'bee'",
+                "Remove import from 'd'",
+                "Remove import from 'ef'",
+            ]
+        );
+        assert_eq!(fixed, "import { a, c } from './abc';\nlet t: c;\na(t);");
+    }
+
+    #[test]
+    fn test_remove_unused_imports_keeps_decorators() {
+        let source = "import { Component, Input, Output } from 'x';\n@Component()\nclass C {\n  @Input() name: string;\n}\nC;";
+        assert_eq!(
+            remove_unused_imports(source),
+            "import { Component, Input } from 'x';\n@Component()\nclass C {\n  @Input() name: string;\n}\nC;"
+        );
+    }
+
+    #[test]
+    fn test_remove_unused_imports_multiline() {
+        let source = "import {\n  a,\n  b,\n  c,\n} from 'm';\na; c;";
+        assert_eq!(
+            remove_unused_imports(source),
+            "import {\n  a,\n  c,\n} from 'm';\na; c;"
+        );
+
+        let tree = parse("a;");
+        let CodeActionOrCommand::CodeAction(action) =
+            create_remove_unused_imports_action(&test_uri(), &tree, "a;", &SymbolTable::new())
+        else {
+            unreachable!();
+        };
+        assert!(action.disabled.is_some());
+    }
+
+    fn wrap_try_catch(source: &str, position: Position) -> Option<String> {
+        let tree = parse(source);
+        let symbol_table = crate::analysis::binder::bind_document(&tree, source);
+        let range = Range::new(position, position);
+        let CodeActionOrCommand::CodeAction(action) =
+            create_wrap_try_catch_action(&test_uri(), range, &tree, source, &symbol_table)?
+        else {
+            unreachable!();
+        };
+        let mut edits = action.edit?.changes?.remove(&test_uri())?;
+        let edit = edits.pop()?;
+        let index = LineIndex::new(source);
+        let mut result = source.to_string();
+        result.replace_range(
+            index.offset(edit.range.start)..index.offset(edit.range.end),
+            &edit.new_text,
+        );
+        Some(result)
+    }
+
+    #[test]
+    fn test_wrap_in_try_catch() {
+        let source = "function f() {\n    JSON.parse(text);\n}";
+        assert_eq!(
+            wrap_try_catch(source, Position::new(1, 8)).unwrap(),
+            "function f() {\n    try {\n        JSON.parse(text);\n    } catch (error) {\n        console.error(error);\n    }\n}"
+        );
+
+        // Nothing to wrap outside of an expression statement
+        assert!(wrap_try_catch("const x = 1;", Position::new(0, 6)).is_none());
+    }
+
+    #[test]
+    fn test_wrap_in_try_catch_awaits_promises() {
+        let source = "async function load(): Promise<void> {}\nasync function main() {\n\tload();\n\tfetch(url);\n}";
+        let result = wrap_try_catch(source, Position::new(2, 1)).unwrap();
+        assert!(result.contains("\ttry {\n\t\tawait load();\n\t} catch (error) {"));
+        let result = wrap_try_catch(source, Position::new(3, 1)).unwrap();
+        assert!(result.contains("await fetch(url);"));
+
+        // Not awaited outside of an async function
+        let source = "async function load() {}\nfunction main() {\n    load();\n}";
+        let result = wrap_try_catch(source, Position::new(2, 4)).unwrap();
+        assert!(result.contains("        load();"));
+        assert!(!result.contains("await"));
+    }
+
+    #[test]
+    fn test_wrap_in_try_catch_avoids_name_collisions() {
+        let source = "function f(error: string) {\n    const err = 1;\n    report(error, err);\n}";
+        let result = wrap_try_catch(source, Position::new(2, 4)).unwrap();
+        assert!(result.contains("catch (e) {\n        console.error(e);"));
+    }
+
+    fn inline_variable(source: &str, position: Position) -> Option<(String, bool)> {
+        let tree = parse(source);
+        let symbol_table = crate::analysis::binder::bind_document(&tree, source);
+        let id = symbol_table.symbol_at_position(position)?;
+        let CodeActionOrCommand::CodeAction(action) =
+            create_inline_variable_action(&test_uri(), id, &tree, &symbol_table, source)?
+        else {
+            unreachable!();
+        };
+        assert_eq!(action.kind, Some(CodeActionKind::REFACTOR_INLINE));
+
+        let mut edits = action.edit?.changes?.remove(&test_uri())?;
+        edits.sort_by_key(|edit| std::cmp::Reverse(edit.range.start));
+        let mut result = source.to_string();
+        for edit in edits {
+            let index = LineIndex::new(&result);
+            let range = index.offset(edit.range.start)..index.offset(edit.range.end);
+            result.replace_range(range, &edit.new_text);
+        }
+        Some((result, action.disabled.is_some()))
+    }
+
+    #[test]
+    fn test_inline_variable() {
+        let source = "const total = a + b;\nlog(total);\nconst twice = total * 2;";
+        assert_eq!(
+            inline_variable(source, Position::new(0, 6)),
+            Some(("log(a + b);\nconst twice = (a + b) * 2;".to_string(), false))
+        );
+
+        let source = "const x = 1, y = 2;\nf(y);";
+        assert_eq!(
+            inline_variable(source, Position::new(0, 13)),
+            Some(("const x = 1;\nf(2);".to_string(), false))
+        );
+    }
+
+    #[test]
+    fn test_inline_variable_with_side_effects() {
+        // Used once, the call still runs exactly once
+        let source = "const id = next();\nuse(id);";
+        assert_eq!(
+            inline_variable(source, Position::new(0, 6)),
+            Some(("use(next());".to_string(), false))
+        );
+
+        let source = "const id = next();\nuse(id, id);";
+        let (_, disabled) = inline_variable(source, Position::new(0, 6)).unwrap();
+        assert!(disabled);
+
+        // Destructured bindings have no single initializer
+        assert!(inline_variable("const { a } = obj;\na;", Position::new(0, 8)).is_none());
+    }
+
+    fn to_template_literal(source: &str, position: Position) -> Option<String> {
+        let tree = parse(source);
+        let range = Range::new(position, position);
+        let CodeActionOrCommand::CodeAction(action) =
+            create_to_template_literal_action(&test_uri(), range, &tree, source)?
+        else {
+            unreachable!();
+        };
+        assert_eq!(action.kind, Some(CodeActionKind::REFACTOR_REWRITE));
+        let edit = action.edit?.changes?.remove(&test_uri())?.pop()?;
+        let index = LineIndex::new(source);
+        let mut result = source.to_string();
+        result.replace_range(
+            index.offset(edit.range.start)..index.offset(edit.range.end),
+            &edit.new_text,
+        );
+        Some(result)
+    }
+
+    #[test]
+    fn test_to_template_literal() {
+        let source = r#"const m = "Hello, " + name + "! You have " + count + " messages.";"#;
+        assert_eq!(
+            to_template_literal(source, Position::new(0, 23)).unwrap(),
+            "const m = `Hello, ${name}! You have ${count} messages.`;"
+        );
+
+        let source = r#"s = 'it\'s ' + "`${x}`" + (a + b) + ("!" + c);"#;
+        assert_eq!(
+            to_template_literal(source, Position::new(0, 5)).unwrap(),
+            r#"s = `it's \`\${x}\`${(a + b)}!${c}`;"#
+        );
+    }
+
+    #[test]
+    fn test_to_template_literal_not_offered() {
+        // The leftmost operand is not a string
+        assert!(to_template_literal("x = a + \"b\";", Position::new(0, 4)).is_none());
+        assert!(to_template_literal("x = 1 - 2;", Position::new(0, 4)).is_none());
+    }
+
+    fn generate_constructor(source: &str, position: Position) -> Option<String> {
+        let tree = parse(source);
+        let range = Range::new(position, position);
+        let CodeActionOrCommand::CodeAction(action) =
+            create_generate_constructor_action(&test_uri(), range, &tree, source)?
+        else {
+            unreachable!();
+        };
+        assert_eq!(action.kind, Some(CodeActionKind::REFACTOR));
+        let edit = action.edit?.changes?.remove(&test_uri())?.pop()?;
+        let index = LineIndex::new(source);
+        let mut result = source.to_string();
+        let offset = index.offset(edit.range.start);
+        result.insert_str(offset, &edit.new_text);
+        Some(result)
+    }
+
+    #[test]
+    fn test_generate_constructor() {
+        let source = "class User {\n    name: string;\n    age: number;\n}";
+        assert_eq!(
+            generate_constructor(source, Position::new(0, 6)).unwrap(),
+            "class User {\n    name: string;\n    age: number;\n\n    constructor(name: string, age: number) {\n        this.name = name;\n        this.age = age;\n    }\n}"
+        );
+
+        // Initialized and static fields are left out, methods come after
+        let source = "class C {\n  #id: number;\n  label?: string;\n  count = 0;\n  static n: number;\n  run() {}\n}";
+        assert_eq!(
+            generate_constructor(source, Position::new(5, 3)).unwrap(),
+            "class C {\n  #id: number;\n  label?: string;\n  count = 0;\n  static n: number;\n  constructor(id: number, label?: string) {\n    this.#id = id;\n    this.label = label;\n  }\n\n  run() {}\n}"
+        );
+    }
+
+    #[test]
+    fn test_generate_constructor_not_offered() {
+        let source = "class C {\n  x: number;\n  constructor() { this.x = 1; }\n}";
+        assert!(generate_constructor(source, Position::new(1, 2)).is_none());
+        assert!(generate_constructor("class C {\n  x = 1;\n}", Position::new(1, 2)).is_none());
+    }
 }
@@ -1,28 +1,107 @@
 use tower_lsp::lsp_types::{
-    CompletionItem, CompletionItemKind, CompletionItemLabelDetails, CompletionParams,
-    Documentation, InsertTextFormat, MarkupContent, MarkupKind, Position,
+    CodeActionKind, CompletionItem, CompletionItemKind, CompletionItemLabelDetails,
+    CompletionParams, CompletionTextEdit, Documentation, InsertTextFormat, InsertTextMode,
+    MarkupContent, MarkupKind, Position, Range, TextEdit, Url,
 };
 use tree_sitter::{Node, Tree};
 
-use crate::analysis::{SymbolFlags, SymbolTable};
+use crate::analysis::{SymbolFlags, SymbolId, SymbolTable};
+use crate::capabilities::code_actions::{
+    import_insert_line, import_specifiers, relative_specifier,
+};
+use crate::capabilities::hover::find_jsdoc_comment;
+use crate::line_index::LineIndex;
+use crate::project::Project;
+use crate::types::printer::print_type;
+use crate::types::types::Property;
+use crate::types::{Type, TypeChecker, TypeFlags};
+
+/// The document completions are requested for
+pub struct DocumentContext {
+    pub uri: Url,
+}
 
 /// Get completions for a position in the document
 pub fn get_completions(
     tree: &Tree,
     source: &str,
     symbol_table: &SymbolTable,
+    checker: &TypeChecker,
+    project: &Project,
+    document: &DocumentContext,
+    params: &CompletionParams,
+) -> Vec<CompletionItem> {
+    let mut completions = collect_completions(
+        tree,
+        source,
+        symbol_table,
+        checker,
+        project,
+        document,
+        params,
+    );
+    for item in &mut completions {
+        apply_client_hints(item);
+    }
+    completions
+}
+
+fn collect_completions(
+    tree: &Tree,
+    source: &str,
+    symbol_table: &SymbolTable,
+    checker: &TypeChecker,
+    project: &Project,
+    document: &DocumentContext,
     params: &CompletionParams,
 ) -> Vec<CompletionItem> {
     let position = params.text_document_position.position;
     let mut completions = Vec::new();
 
+    // Inside the specifier string of an import: complete file paths
+    if let Some(partial) = import_specifier_prefix(tree, source, position) {
+        if partial.starts_with('.') {
+            return get_path_completions(&document.uri, &partial);
+        }
+        return get_import_completions();
+    }
+
+    // Inside `import { ... } from '...'`: complete the module's exports
+    if let Some(specifier) = named_import_specifier(tree, source, position) {
+        return get_named_import_completions(tree, source, project, document, &specifier);
+    }
+
+    // After `case` in a switch over a literal union: the uncovered members
+    if let Some(items) = get_case_completions(tree, source, symbol_table, checker, position) {
+        return ranked(items, SortTier::Local);
+    }
+
     // Get the context at the cursor position
     let context = get_completion_context(tree, source, position);
 
     match context {
         CompletionContext::MemberAccess(object_name) => {
             // Complete object members
-            completions.extend(get_member_completions(&object_name));
+            completions.extend(get_object_member_completions(
+                &object_name,
+                tree,
+                source,
+                symbol_table,
+                checker,
+                position,
+            ));
+        }
+        CompletionContext::Postfix(expression) => {
+            // Members of the expression, followed by postfix templates
+            completions.extend(get_object_member_completions(
+                &expression,
+                tree,
+                source,
+                symbol_table,
+                checker,
+                position,
+            ));
+            completions.extend(get_postfix_completions(&expression, source, position));
         }
         CompletionContext::Import => {
             // Complete import paths
@@ -30,33 +109,99 @@ pub fn get_completions(
         }
         CompletionContext::Type => {
             // Complete type names
-            completions.extend(get_type_completions(symbol_table));
-            completions.extend(get_builtin_type_completions());
+            completions.extend(ranked(get_type_completions(symbol_table), SortTier::Local));
+            completions.extend(ranked(get_builtin_type_completions(), SortTier::Builtin));
         }
         CompletionContext::General => {
             // Complete with symbols in scope
-            completions.extend(get_scope_completions(symbol_table, position));
-            completions.extend(get_keyword_completions());
-            completions.extend(get_snippet_completions());
+            completions.extend(ranked(
+                get_scope_completions(symbol_table, document, position),
+                SortTier::Local,
+            ));
+            completions.extend(ranked(get_keyword_completions(), SortTier::Keyword));
+            completions.extend(ranked(get_snippet_completions(), SortTier::Snippet));
+            completions.extend(ranked(
+                get_auto_import_completions(source, symbol_table, project, document, position),
+                SortTier::AutoImport,
+            ));
         }
         CompletionContext::JsxTag => {
             // Complete JSX tag names
-            completions.extend(get_jsx_completions());
+            completions.extend(get_jsx_completions(symbol_table, position));
         }
         CompletionContext::JsxAttribute => {
-            // Complete JSX attributes
-            completions.extend(get_jsx_attribute_completions());
+            // Complete the props of a component, or HTML attributes
+            completions.extend(
+                get_component_prop_completions(tree, source, symbol_table, checker, position)
+                    .unwrap_or_else(get_jsx_attribute_completions),
+            );
         }
+        CompletionContext::StringText => {}
     }
 
-    completions
+    dedupe_labels(completions)
+}
+
+/// Where an item comes from, in the order items are ranked
+#[derive(Debug, Clone, Copy)]
+enum SortTier {
+    Local,
+    Keyword,
+    Snippet,
+    Builtin,
+    AutoImport,
+}
+
+/// Rank items by source so clients don't interleave them by label
+fn ranked(mut items: Vec<CompletionItem>, tier: SortTier) -> Vec<CompletionItem> {
+    for item in &mut items {
+        item.sort_text = Some(format!("{}_{}", tier as u8, item.label));
+    }
+    items
+}
+
+/// Keep the first (highest ranked) item for each label. Snippets expand to
+/// a template rather than their label, so they don't collide with keywords.
+fn dedupe_labels(items: Vec<CompletionItem>) -> Vec<CompletionItem> {
+    let mut seen = std::collections::HashSet::new();
+    items
+        .into_iter()
+        .filter(|item| {
+            let snippet = item.kind == Some(CompletionItemKind::SNIPPET);
+            seen.insert((item.label.clone(), snippet))
+        })
+        .collect()
+}
+
+/// Fill in the fields that help editors filter and accept an item:
+/// `(` commits a function, labels like `[Symbol.iterator]` filter on the
+/// plain name, and snippets are re-indented to the cursor's line
+fn apply_client_hints(item: &mut CompletionItem) {
+    if item.kind == Some(CompletionItemKind::FUNCTION) && item.commit_characters.is_none() {
+        item.commit_characters = Some(vec!["(".to_string()]);
+    }
+
+    if item.filter_text.is_none() {
+        let plain = item
+            .label
+            .trim_matches(|c: char| matches!(c, '[' | ']' | '"' | '\'' | '#' | '@'));
+        if plain != item.label && !plain.is_empty() {
+            item.filter_text = Some(plain.to_string());
+        }
+    }
+
+    if item.insert_text_format == Some(InsertTextFormat::SNIPPET) {
+        item.insert_text_mode = Some(InsertTextMode::ADJUST_INDENTATION);
+    }
 }
 
 /// Completion context types
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 enum CompletionContext {
     /// After a dot, completing object members
     MemberAccess(String),
+    /// After `expr.name` where `expr` is not a known object: postfix templates
+    Postfix(String),
     /// Inside an import statement
     Import,
     /// Type position (after colon, extends, implements, etc.)
@@ -67,19 +212,31 @@ enum CompletionContext {
     JsxTag,
     /// Inside JSX attribute position
     JsxAttribute,
+    /// Inside the text of a string or template literal, where nothing is completed
+    StringText,
 }
 
 /// Determine the completion context at a position
 fn get_completion_context(tree: &Tree, source: &str, position: Position) -> CompletionContext {
     let root = tree.root_node();
-
-    let point = tree_sitter::Point {
-        row: position.line as usize,
-        column: position.character as usize,
-    };
+    let index = LineIndex::new(source);
+    let point = index.point(position);
 
     // Find the node at position
     if let Some(node) = root.descendant_for_point_range(point, point) {
+        if is_in_string_text(node, point) {
+            return CompletionContext::StringText;
+        }
+
+        // Between the attributes of an element, e.g. `<Button |/>`
+        if matches!(
+            node.kind(),
+            "jsx_self_closing_element" | "jsx_opening_element"
+        ) && is_in_jsx_attribute_position(&node, node, point)
+        {
+            return CompletionContext::JsxAttribute;
+        }
+
         // Check parent contexts
         let mut current = node;
         while let Some(parent) = current.parent() {
@@ -87,7 +244,12 @@ fn get_completion_context(tree: &Tree, source: &str, position: Position) -> Comp
                 "member_expression" => {
                     // Check if we're after a dot
                     if let Some(obj) = parent.child_by_field_name("object") {
-                        let obj_text = obj.utf8_text(source.as_bytes()).unwrap_or("").to_string();
+                        let obj_text = chain_base(obj.utf8_text(source.as_bytes()).unwrap_or(""));
+                        if get_member_completions(&obj_text).is_empty()
+                            && postfix_name_start(source, position).is_some()
+                        {
+                            return CompletionContext::Postfix(obj_text);
+                        }
                         return CompletionContext::MemberAccess(obj_text);
                     }
                 }
@@ -103,7 +265,7 @@ fn get_completion_context(tree: &Tree, source: &str, position: Position) -> Comp
                 }
                 "jsx_element" | "jsx_self_closing_element" | "jsx_opening_element" => {
                     // Check if we're in tag name position or attribute position
-                    if is_in_jsx_attribute_position(&node, source) {
+                    if is_in_jsx_attribute_position(&node, parent, point) {
                         return CompletionContext::JsxAttribute;
                     }
                     return CompletionContext::JsxTag;
@@ -113,25 +275,28 @@ fn get_completion_context(tree: &Tree, source: &str, position: Position) -> Comp
             current = parent;
         }
 
+        // Check if after `expr.name` outside a member expression node
+        if let Some(name_start) = postfix_name_start(source, position) {
+            let expression = trailing_expression(&source[..name_start - 1]);
+            if !expression.is_empty() && get_member_completions(expression).is_empty() {
+                return CompletionContext::Postfix(expression.to_string());
+            }
+        }
+
         // Check if right after a dot
-        if position.character > 0 {
-            let lines: Vec<&str> = source.lines().collect();
-            if let Some(line) = lines.get(position.line as usize) {
-                let chars: Vec<char> = line.chars().collect();
-                if position.character as usize > 0 {
-                    if let Some('.') = chars.get(position.character as usize - 1) {
-                        // Find what's before the dot
-                        let before_dot = &line[..position.character as usize - 1];
-                        let object_name = before_dot
-                            .split_whitespace()
-                            .last()
-                            .unwrap_or("")
-                            .to_string();
-                        if !object_name.is_empty() {
-                            return CompletionContext::MemberAccess(object_name);
-                        }
-                    }
-                }
+        let cursor = index.offset(position);
+        let line_start = source[..cursor].rfind('\n').map_or(0, |i| i + 1);
+        if let Some(before_dot) = source[line_start..cursor].strip_suffix('.') {
+            // Find what's before the dot, looking through `?.` and `!.`
+            let before_dot = before_dot.strip_suffix(['?', '!']).unwrap_or(before_dot);
+            let object_name = chain_base(trailing_expression(before_dot));
+            if !object_name.is_empty() {
+                return CompletionContext::MemberAccess(object_name);
+            }
+            // A literal such as `"abc".` or `[].`
+            if let Some(receiver) = receiver_node(tree, source, position) {
+                let text = receiver.utf8_text(source.as_bytes()).unwrap_or("");
+                return CompletionContext::MemberAccess(text.to_string());
             }
         }
     }
@@ -139,7 +304,259 @@ fn get_completion_context(tree: &Tree, source: &str, position: Position) -> Comp
     CompletionContext::General
 }
 
-fn is_in_jsx_attribute_position(node: &Node, _source: &str) -> bool {
+/// Whether `point` lies in the literal text of a string or template string,
+/// rather than in a `${...}` substitution or just outside the quotes
+fn is_in_string_text(node: Node, point: tree_sitter::Point) -> bool {
+    let mut current = Some(node);
+    while let Some(node) = current {
+        match node.kind() {
+            "template_substitution" => return false,
+            "string" | "template_string" => {
+                return node.start_position() < point && point < node.end_position();
+            }
+            _ => current = node.parent(),
+        }
+    }
+    false
+}
+
+/// If `position` is inside the module specifier string of an import
+/// (or `export ... from`), return the part of the specifier typed so far
+fn import_specifier_prefix(tree: &Tree, source: &str, position: Position) -> Option<String> {
+    let index = LineIndex::new(source);
+    let offset = index.offset(position);
+    let line_start = index.offset(Position::new(position.line, 0));
+    let before = &source[line_start..offset];
+
+    // The partial specifier runs from the last opening quote to the cursor
+    let quote = before.rfind(['\'', '"'])?;
+    let partial = &before[quote + 1..];
+
+    let point = index.point(index.position(line_start + quote + 1));
+    let mut node = tree.root_node().descendant_for_point_range(point, point)?;
+    loop {
+        let parent = node.parent();
+        match (node.kind(), parent.map(|p| p.kind())) {
+            // The cursor must be before the closing quote
+            ("string", Some("import_statement" | "export_statement")) => {
+                if offset >= node.end_byte() {
+                    return None;
+                }
+                break;
+            }
+            // An unterminated string leaves the import statement as an error node
+            ("ERROR", _) if node.child(0).is_some_and(|c| c.kind() == "import") => break,
+            _ => node = parent?,
+        }
+    }
+
+    Some(partial.to_string())
+}
+
+/// Complete the string literals of a switch discriminant's union type that
+/// no `case` covers yet, when the cursor follows a `case` keyword (and
+/// possibly the start of a literal). `None` outside such a clause, or when
+/// the discriminant's type isn't a union of string literals.
+fn get_case_completions(
+    tree: &Tree,
+    source: &str,
+    symbol_table: &SymbolTable,
+    checker: &TypeChecker,
+    position: Position,
+) -> Option<Vec<CompletionItem>> {
+    let index = LineIndex::new(source);
+    let offset = index.offset(position);
+    let line_start = index.offset(Position::new(position.line, 0));
+    let before = &source[line_start..offset];
+
+    // `case |`, `case "|` or `case "b|`
+    let partial = before.trim_end_matches(|c: char| c.is_alphanumeric() || c == '_' || c == '$');
+    let (keyword, quote) = match partial.strip_suffix(['"', '\'']) {
+        Some(rest) => (rest, partial.chars().last()),
+        None => (partial, None),
+    };
+    let keyword = keyword.trim_end().strip_suffix("case")?;
+    if keyword
+        .chars()
+        .last()
+        .is_some_and(|c| c.is_alphanumeric() || c == '_' || c == '$')
+    {
+        return None;
+    }
+
+    let point = index.point(position);
+    let mut node = tree.root_node().descendant_for_point_range(point, point)?;
+    while node.kind() != "switch_statement" {
+        node = node.parent()?;
+    }
+    let value = node.child_by_field_name("value")?;
+    let discriminant = if value.kind() == "parenthesized_expression" {
+        value.named_child(0)?
+    } else {
+        value
+    };
+    let discriminant = discriminant.utf8_text(source.as_bytes()).ok()?;
+    let Type::Union(members) = object_type(discriminant, symbol_table, checker, position)? else {
+        return None;
+    };
+
+    let mut literals = Vec::new();
+    for member in members {
+        match member {
+            Type::StringLiteral(value) => literals.push(value.as_str()),
+            Type::Null | Type::Undefined => {}
+            _ => return None,
+        }
+    }
+
+    let body = node.child_by_field_name("body")?;
+    let covered: Vec<&str> = body
+        .named_children(&mut body.walk())
+        .filter(|clause| clause.kind() == "switch_case")
+        .filter_map(|clause| clause.child_by_field_name("value"))
+        .filter(|value| value.kind() == "string")
+        .filter_map(|value| value.utf8_text(source.as_bytes()).ok())
+        .map(|text| text.trim_matches(|c| c == '"' || c == '\''))
+        .collect();
+
+    // Replace the literal typed so far, opening quote included
+    let typed_start = line_start + partial.len() - quote.map_or(0, char::len_utf8);
+    let replace = Range::new(index.position(typed_start), position);
+    let quote = quote.unwrap_or('"');
+    Some(
+        literals
+            .into_iter()
+            .filter(|literal| !covered.contains(literal))
+            .map(|literal| {
+                let text = format!("{}{}{}", quote, literal, quote);
+                CompletionItem {
+                    label: text.clone(),
+                    kind: Some(CompletionItemKind::VALUE),
+                    detail: Some(discriminant.to_string()),
+                    filter_text: Some(text.clone()),
+                    text_edit: Some(CompletionTextEdit::Edit(TextEdit::new(replace, text))),
+                    ..Default::default()
+                }
+            })
+            .collect(),
+    )
+}
+
+/// If `position` is inside the braces of `import { ... } from '...'`,
+/// return the module specifier
+pub fn named_import_specifier(tree: &Tree, source: &str, position: Position) -> Option<String> {
+    let index = LineIndex::new(source);
+    let offset = index.offset(position);
+    let point = index.point(position);
+
+    let mut node = tree.root_node().descendant_for_point_range(point, point)?;
+    while node.kind() != "import_statement" {
+        node = node.parent()?;
+    }
+
+    let clause = node
+        .children(&mut node.walk())
+        .find(|c| c.kind() == "import_clause")?;
+    let named = clause
+        .children(&mut clause.walk())
+        .find(|c| c.kind() == "named_imports")?;
+    if offset <= named.start_byte() || offset >= named.end_byte() {
+        return None;
+    }
+
+    let specifier = node.child_by_field_name("source")?;
+    let text = specifier.utf8_text(source.as_bytes()).ok()?;
+    Some(text.trim_matches(|c| c == '"' || c == '\'').to_string())
+}
+
+/// Complete the names exported by the module `specifier` resolves to,
+/// skipping names the import statement already lists
+fn get_named_import_completions(
+    tree: &Tree,
+    source: &str,
+    project: &Project,
+    document: &DocumentContext,
+    specifier: &str,
+) -> Vec<CompletionItem> {
+    let Some(document_path) = document.uri.to_file_path().ok() else {
+        return Vec::new();
+    };
+    let Some(resolved) = project.resolver.resolve(specifier, &document_path) else {
+        return Vec::new();
+    };
+
+    let already_imported: Vec<String> = crate::analysis::modules::collect_imports(tree, source)
+        .into_iter()
+        .filter(|import| import.specifier == specifier)
+        .filter_map(|import| import.imported.export_name().map(str::to_string))
+        .collect();
+
+    project
+        .exported_names(&resolved.path)
+        .into_iter()
+        .filter(|export| export.name != "default" && !already_imported.contains(&export.name))
+        .map(|export| CompletionItem {
+            detail: Some(if export.flags.is_empty() {
+                format!("export from '{}'", specifier)
+            } else {
+                format!("{} from '{}'", get_symbol_detail(export.flags), specifier)
+            }),
+            label: export.name,
+            kind: Some(CompletionItemKind::MODULE),
+            ..Default::default()
+        })
+        .collect()
+}
+
+/// Complete relative paths for an import specifier typed as `partial`
+fn get_path_completions(document_uri: &Url, partial: &str) -> Vec<CompletionItem> {
+    let Some(document_path) = document_uri.to_file_path().ok() else {
+        return Vec::new();
+    };
+    let Some(document_dir) = document_path.parent() else {
+        return Vec::new();
+    };
+
+    let (dir_part, name_prefix) = match partial.rfind('/') {
+        Some(slash) => (&partial[..=slash], &partial[slash + 1..]),
+        None => ("", partial),
+    };
+    let Ok(entries) = std::fs::read_dir(document_dir.join(dir_part)) else {
+        return Vec::new();
+    };
+
+    let mut completions: Vec<CompletionItem> = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let path = entry.path();
+            let file_name = entry.file_name().to_string_lossy().to_string();
+            if file_name.starts_with('.') || path == document_path {
+                return None;
+            }
+
+            let (label, kind) = if path.is_dir() {
+                (format!("{}/", file_name), CompletionItemKind::FOLDER)
+            } else {
+                let stem = [".d.ts", ".ts", ".tsx", ".js", ".mjs"]
+                    .iter()
+                    .find_map(|ext| file_name.strip_suffix(ext))?;
+                (stem.to_string(), CompletionItemKind::FILE)
+            };
+
+            label.starts_with(name_prefix).then(|| CompletionItem {
+                label,
+                kind: Some(kind),
+                ..Default::default()
+            })
+        })
+        .collect();
+
+    completions.sort_by(|a, b| a.label.cmp(&b.label));
+    completions.dedup_by(|a, b| a.label == b.label);
+    completions
+}
+
+fn is_in_jsx_attribute_position(node: &Node, element: Node, point: tree_sitter::Point) -> bool {
     let mut current = *node;
     while let Some(parent) = current.parent() {
         if parent.kind() == "jsx_attribute" {
@@ -147,34 +564,49 @@ fn is_in_jsx_attribute_position(node: &Node, _source: &str) -> bool {
         }
         current = parent;
     }
-    false
+    // Past the tag name, e.g. `<Button |/>`
+    element.kind() != "jsx_element"
+        && element
+            .child_by_field_name("name")
+            .is_some_and(|name| name.end_position() < point)
 }
 
-/// Get completions for symbols in the current scope
-fn get_scope_completions(symbol_table: &SymbolTable, position: Position) -> Vec<CompletionItem> {
+/// Get completions for symbols in the current scope. Details and
+/// documentation are left to `resolve_completion`.
+fn get_scope_completions(
+    symbol_table: &SymbolTable,
+    document: &DocumentContext,
+    position: Position,
+) -> Vec<CompletionItem> {
     let scope_id = symbol_table.scope_at_position(position);
     let mut completions = Vec::new();
 
+    // The latest declaration before the cursor is the likeliest pick
+    let latest = symbol_table
+        .all_symbols()
+        .filter(|symbol| symbol.name_range.end <= position)
+        .filter(|symbol| symbol_table.lookup(&symbol.name, scope_id) == Some(symbol.id))
+        .max_by_key(|symbol| symbol.name_range.start)
+        .map(|symbol| symbol.id);
+
     // Get symbols from current scope and parent scopes
     for symbol in symbol_table.all_symbols() {
-        // Only include symbols visible from this scope
-        if symbol_table.lookup(&symbol.name, scope_id).is_some() {
+        // Only include symbols visible from this scope, and not shadowed there
+        if symbol_table.lookup(&symbol.name, scope_id) == Some(symbol.id) {
             let kind = symbol_flags_to_completion_kind(symbol.flags);
 
             completions.push(CompletionItem {
                 label: symbol.name.clone(),
                 kind: Some(kind),
-                detail: Some(get_symbol_detail(symbol.flags)),
                 label_details: Some(CompletionItemLabelDetails {
                     detail: None,
                     description: Some(get_symbol_description(symbol.flags)),
                 }),
-                documentation: symbol.documentation.clone().map(|doc| {
-                    Documentation::MarkupContent(MarkupContent {
-                        kind: MarkupKind::Markdown,
-                        value: doc,
-                    })
-                }),
+                preselect: (latest == Some(symbol.id)).then_some(true),
+                data: Some(serde_json::json!({
+                    "uri": document.uri.as_str(),
+                    "symbolId": symbol.id.0,
+                })),
                 ..Default::default()
             });
         }
@@ -245,6 +677,128 @@ fn get_symbol_description(flags: SymbolFlags) -> String {
     parts.join(" ")
 }
 
+/// Names exported by other project modules that aren't in scope here.
+/// `resolve_completion` adds the edit inserting the named import.
+fn get_auto_import_completions(
+    source: &str,
+    symbol_table: &SymbolTable,
+    project: &Project,
+    document: &DocumentContext,
+    position: Position,
+) -> Vec<CompletionItem> {
+    let Ok(file_path) = document.uri.to_file_path() else {
+        return Vec::new();
+    };
+    let prefix = identifier_before(source, position);
+    if prefix.is_empty() {
+        return Vec::new();
+    }
+    let scope_id = symbol_table.scope_at_position(position);
+
+    let mut completions = Vec::new();
+    for module in project.cached_modules() {
+        if module == file_path {
+            continue;
+        }
+        let specifier = relative_specifier(&file_path, &module);
+        for export in project.exported_names(&module) {
+            if export.name == "default"
+                || !export.name.starts_with(prefix)
+                || symbol_table.lookup(&export.name, scope_id).is_some()
+                || symbol_table.lookup_type(&export.name, scope_id).is_some()
+            {
+                continue;
+            }
+            completions.push(CompletionItem {
+                label: export.name.clone(),
+                kind: Some(symbol_flags_to_completion_kind(export.flags)),
+                label_details: Some(CompletionItemLabelDetails {
+                    detail: None,
+                    description: Some(specifier.clone()),
+                }),
+                data: Some(serde_json::json!({
+                    "uri": document.uri.as_str(),
+                    "importFrom": specifier,
+                })),
+                ..Default::default()
+            });
+        }
+    }
+    completions
+}
+
+/// The document a completion item from `get_completions` was made for
+pub fn completion_uri(item: &CompletionItem) -> Option<Url> {
+    let uri = item.data.as_ref()?.get("uri")?.as_str()?;
+    Url::parse(uri).ok()
+}
+
+/// Fill in what the first pass leaves out of an item: the detail and
+/// documentation of a symbol in scope, or the edit adding an auto-import
+pub fn resolve_completion(
+    mut item: CompletionItem,
+    tree: &Tree,
+    source: &str,
+    symbol_table: &SymbolTable,
+) -> CompletionItem {
+    let Some(data) = item.data.clone() else {
+        return item;
+    };
+
+    if let Some(symbol) = data
+        .get("symbolId")
+        .and_then(|id| id.as_u64())
+        .and_then(|id| symbol_table.get_symbol(SymbolId(id as u32)))
+        .filter(|symbol| symbol.name == item.label)
+    {
+        let index = LineIndex::new(source);
+        let point = index.point(symbol.name_range.start);
+        let jsdoc = tree
+            .root_node()
+            .descendant_for_point_range(point, point)
+            .and_then(|name| find_jsdoc_comment(&name, source));
+
+        item.detail = Some(get_symbol_detail(symbol.flags));
+        item.documentation = jsdoc.or_else(|| symbol.documentation.clone()).map(|doc| {
+            Documentation::MarkupContent(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value: doc,
+            })
+        });
+    }
+
+    if let Some(specifier) = data.get("importFrom").and_then(|s| s.as_str()) {
+        let imports = import_specifiers(tree, source);
+        let quote = imports
+            .first()
+            .and_then(|(text, _)| text.chars().next())
+            .filter(|c| *c == '"' || *c == '\'')
+            .unwrap_or('\'');
+        let line = import_insert_line(&imports, specifier);
+
+        item.detail = Some(format!("Add import from \"{}\"", specifier));
+        item.additional_text_edits = Some(vec![TextEdit {
+            range: Range::new(Position::new(line, 0), Position::new(line, 0)),
+            new_text: format!(
+                "import {{ {} }} from {}{}{};\n",
+                item.label, quote, specifier, quote
+            ),
+        }]);
+    }
+
+    item
+}
+
+/// The identifier characters right before the cursor
+fn identifier_before(source: &str, position: Position) -> &str {
+    let cursor = LineIndex::new(source).offset(position);
+    let before = &source[..cursor];
+    let start = before
+        .rfind(|c: char| !(c.is_alphanumeric() || c == '_' || c == '$'))
+        .map_or(0, |i| i + 1);
+    &before[start..]
+}
+
 /// Get keyword completions
 fn get_keyword_completions() -> Vec<CompletionItem> {
     let keywords = [
@@ -455,6 +1009,153 @@ fn get_snippet_completions() -> Vec<CompletionItem> {
     ]
 }
 
+/// Postfix templates: label, snippet (`{expr}` stands for the expression) and description
+const POSTFIX_TEMPLATES: &[(&str, &str, &str)] = &[
+    ("if", "if ({expr}) {\n\t$0\n}", "if (expr) {}"),
+    ("const", "const ${1:name} = {expr};$0", "const name = expr;"),
+    ("return", "return {expr};", "return expr;"),
+    ("forEach", "{expr}.forEach($0)", "expr.forEach()"),
+    ("await", "await {expr}", "await expr"),
+    ("log", "console.log({expr})", "console.log(expr)"),
+];
+
+/// If the cursor follows `.name` (a dot and a non-empty identifier),
+/// return the byte offset where `name` starts
+fn postfix_name_start(source: &str, position: Position) -> Option<usize> {
+    let cursor = LineIndex::new(source).offset(position);
+    let before = &source[..cursor];
+    let name = before
+        .rfind(|c: char| !(c.is_alphanumeric() || c == '_' || c == '$'))
+        .map_or(0, |i| i + 1);
+    (name < cursor && before[..name].ends_with('.')).then_some(name)
+}
+
+/// Completions rewriting `expression.name` into a template around the expression.
+/// The main edit replaces `name`; an additional edit deletes `expression.`
+fn get_postfix_completions(
+    expression: &str,
+    source: &str,
+    position: Position,
+) -> Vec<CompletionItem> {
+    let Some(name_start) = postfix_name_start(source, position) else {
+        return Vec::new();
+    };
+    let before_dot = source[..name_start - 1].trim_end();
+    if expression.is_empty() || !before_dot.ends_with(expression) {
+        return Vec::new();
+    }
+    // Skip numeric literals like `1.` that are still being typed
+    if expression.starts_with(|c: char| c.is_ascii_digit()) {
+        return Vec::new();
+    }
+
+    let index = LineIndex::new(source);
+    let replace = Range::new(index.position(name_start), position);
+    let delete = Range::new(
+        index.position(before_dot.len() - expression.len()),
+        index.position(name_start),
+    );
+    let escaped = expression
+        .replace('\\', "\\\\")
+        .replace('$', "\\$")
+        .replace('}', "\\}");
+
+    POSTFIX_TEMPLATES
+        .iter()
+        .map(|(label, template, description)| CompletionItem {
+            label: label.to_string(),
+            kind: Some(CompletionItemKind::SNIPPET),
+            detail: Some(description.to_string()),
+            filter_text: Some(label.to_string()),
+            text_edit: Some(CompletionTextEdit::Edit(TextEdit::new(
+                replace,
+                template.replace("{expr}", &escaped),
+            ))),
+            additional_text_edits: Some(vec![TextEdit::new(delete, String::new())]),
+            insert_text_format: Some(InsertTextFormat::SNIPPET),
+            data: Some(serde_json::json!({
+                "codeActionKind": CodeActionKind::REFACTOR_REWRITE.as_str(),
+            })),
+            ..Default::default()
+        })
+        .collect()
+}
+
+/// Find the expression a member access applies to at the end of `text`,
+/// e.g. `new Foo()` in `const x = new Foo()`
+fn trailing_expression(text: &str) -> &str {
+    let text = text.trim_end();
+    let mut depth = 0usize;
+    let mut start = text.len();
+
+    for (i, ch) in text.char_indices().rev() {
+        match ch {
+            ')' | ']' => depth += 1,
+            '(' | '[' if depth > 0 => depth -= 1,
+            _ if depth > 0 => {}
+            c if c.is_alphanumeric() || matches!(c, '_' | '$' | '.') => {}
+            // `a?.b` and `a!.b` continue the chain
+            '?' | '!' if text[i + 1..].starts_with('.') => {}
+            _ => break,
+        }
+        start = i;
+    }
+
+    // Include a preceding `new` keyword
+    let before = text[..start].trim_end();
+    if let Some(rest) = before.strip_suffix("new") {
+        if !rest.ends_with(|c: char| c.is_alphanumeric() || c == '_' || c == '$') {
+            return &text[rest.len()..];
+        }
+    }
+
+    &text[start..]
+}
+
+/// The member chain of an expression with optional chaining and non-null
+/// assertions removed, e.g. `a?.b!` to `a.b`
+fn chain_base(expression: &str) -> String {
+    expression
+        .trim_end_matches('!')
+        .replace("?.", ".")
+        .replace("!.", ".")
+}
+
+/// Find the syntax node of the expression a member access applies to,
+/// for accesses the parser could not turn into a `member_expression`
+/// (e.g. `"abc".` at the end of a file)
+fn receiver_node<'t>(tree: &'t Tree, source: &str, position: Position) -> Option<Node<'t>> {
+    let cursor = LineIndex::new(source).offset(position);
+    let before =
+        source[..cursor].trim_end_matches(|c: char| c.is_alphanumeric() || c == '_' || c == '$');
+    let end = before.strip_suffix('.')?.trim_end().len();
+    if end == 0 {
+        return None;
+    }
+
+    let mut node = tree.root_node().descendant_for_byte_range(end - 1, end)?;
+    while let Some(parent) = node.parent() {
+        let continues = matches!(
+            parent.kind(),
+            "string"
+                | "template_string"
+                | "array"
+                | "arguments"
+                | "call_expression"
+                | "new_expression"
+                | "parenthesized_expression"
+                | "member_expression"
+                | "subscript_expression"
+        );
+        // Climb from punctuation tokens, and through expressions that end here
+        if parent.end_byte() != end || (node.is_named() && !continues) {
+            break;
+        }
+        node = parent;
+    }
+    node.is_named().then_some(node)
+}
+
 /// Get member completions for common objects
 fn get_member_completions(object_name: &str) -> Vec<CompletionItem> {
     match object_name {
@@ -464,11 +1165,147 @@ fn get_member_completions(object_name: &str) -> Vec<CompletionItem> {
         "Object" => get_object_completions(),
         "Array" => get_array_static_completions(),
         "String" => get_string_static_completions(),
+        "Number" => get_number_static_completions(),
+        "Date" => get_date_static_completions(),
+        "Symbol" => get_symbol_static_completions(),
         "Promise" => get_promise_completions(),
         _ => Vec::new(),
     }
 }
 
+/// Member completions for `expression.`: global objects by name first,
+/// then instance members by the type of the expression
+fn get_object_member_completions(
+    expression: &str,
+    tree: &Tree,
+    source: &str,
+    symbol_table: &SymbolTable,
+    checker: &TypeChecker,
+    position: Position,
+) -> Vec<CompletionItem> {
+    let completions = get_member_completions(expression);
+    if !completions.is_empty() {
+        return completions;
+    }
+
+    if let Some(ty) = object_type(expression, symbol_table, checker, position) {
+        return get_typed_member_completions(ty);
+    }
+
+    // Literals and built-in constructors, typed by the checker from the syntax
+    receiver_node(tree, source, position)
+        .filter(|node| node.utf8_text(source.as_bytes()).ok() == Some(expression))
+        .and_then(|node| checker.literal_type(node, source))
+        .map(|ty| get_typed_member_completions(&ty))
+        .unwrap_or_default()
+}
+
+/// Get member completions for a value of a known type
+fn get_typed_member_completions(ty: &Type) -> Vec<CompletionItem> {
+    if let Some(completions) = get_instance_completions(ty) {
+        return completions;
+    }
+
+    let mut props = Vec::new();
+    collect_properties(ty, &mut props);
+    props.sort_by(|a, b| a.name.cmp(&b.name));
+    props.dedup_by(|a, b| a.name == b.name);
+
+    props
+        .into_iter()
+        .map(|prop| {
+            let detail = print_type(&prop.ty);
+            match prop.ty.as_ref() {
+                Type::Function(_) => CompletionItem {
+                    label: prop.name.clone(),
+                    kind: Some(CompletionItemKind::METHOD),
+                    detail: Some(detail),
+                    insert_text: Some(format!("{}($0)", prop.name)),
+                    insert_text_format: Some(InsertTextFormat::SNIPPET),
+                    ..Default::default()
+                },
+                _ => CompletionItem {
+                    label: prop.name.clone(),
+                    kind: Some(CompletionItemKind::PROPERTY),
+                    detail: Some(detail),
+                    ..Default::default()
+                },
+            }
+        })
+        .collect()
+}
+
+/// Members of the built-in primitive and global object types
+fn get_instance_completions(ty: &Type) -> Option<Vec<CompletionItem>> {
+    let flags = ty.flags();
+    let (mut completions, length_of) = match ty {
+        Type::Array(_) | Type::Tuple(_) => (get_array_instance_completions(), Some("array")),
+        Type::Reference(reference) if reference.name == "Date" => {
+            (get_date_instance_completions(), None)
+        }
+        _ if flags.intersects(TypeFlags::STRING | TypeFlags::STRING_LITERAL) => {
+            (get_string_instance_completions(), Some("string"))
+        }
+        _ if flags.intersects(TypeFlags::NUMBER | TypeFlags::NUMBER_LITERAL) => {
+            (get_number_instance_completions(), None)
+        }
+        _ => return None,
+    };
+
+    if let Some(of) = length_of {
+        completions.insert(
+            0,
+            create_property_completion("length", "number", &format!("Length of the {}", of)),
+        );
+    }
+    Some(completions)
+}
+
+/// Gather the properties of an object type, including every member of an
+/// intersection (e.g. a class together with its base class)
+fn collect_properties<'a>(ty: &'a Type, props: &mut Vec<&'a Property>) {
+    match ty {
+        Type::Object(obj) => props.extend(obj.properties.values()),
+        Type::Intersection(members) => {
+            // Later members take precedence when names are deduplicated
+            for member in members.iter().rev() {
+                collect_properties(member, props);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Look up the type of `expression` (an identifier or `new Class(...)`)
+/// from the inferred types in the symbol table
+fn object_type<'a>(
+    expression: &str,
+    symbol_table: &SymbolTable,
+    checker: &'a TypeChecker,
+    position: Position,
+) -> Option<&'a Type> {
+    let expression = expression.trim();
+    if expression == "this" {
+        let class = symbol_table.get_symbol(symbol_table.enclosing_class(position)?)?;
+        return checker.get_type(class.inferred_type?);
+    }
+
+    let (name, constructed) = match expression.strip_prefix("new ") {
+        Some(rest) => (rest.split('(').next()?.trim(), true),
+        None => (expression, false),
+    };
+
+    let scope_id = symbol_table.scope_at_position(position);
+    let symbol = symbol_table.get_symbol(symbol_table.lookup(name, scope_id)?)?;
+
+    // A class name on its own refers to the constructor, not an instance
+    if symbol.flags.contains(SymbolFlags::CLASS) != constructed {
+        return None;
+    }
+
+    checker.get_type(symbol.inferred_type?)
+}
+
 fn get_console_completions() -> Vec<CompletionItem> {
     vec![
         create_method_completion("log", "(...data: any[]): void", "Log output to console"),
@@ -575,19 +1412,323 @@ fn get_array_static_completions() -> Vec<CompletionItem> {
     ]
 }
 
-fn get_string_static_completions() -> Vec<CompletionItem> {
-    vec![
-        create_method_completion(
-            "fromCharCode",
-            "(...codes: number[]): string",
-            "Create from char codes",
+/// `Array.prototype` methods, for array-typed values
+fn get_array_instance_completions() -> Vec<CompletionItem> {
+    let methods = [
+        (
+            "map",
+            "${1:callback}",
+            "<U>(callbackfn: (value: T, index: number, array: T[]) => U): U[]",
+            "Map each element to a new array",
         ),
-        create_method_completion(
-            "fromCodePoint",
-            "(...codePoints: number[]): string",
-            "Create from code points",
+        (
+            "filter",
+            "${1:predicate}",
+            "(predicate: (value: T, index: number, array: T[]) => unknown): T[]",
+            "Keep elements matching a predicate",
         ),
-        create_method_completion(
+        (
+            "reduce",
+            "${1:callback}, ${2:initialValue}",
+            "<U>(callbackfn: (previousValue: U, currentValue: T, currentIndex: number, array: T[]) => U, initialValue: U): U",
+            "Reduce the array to a single value",
+        ),
+        (
+            "forEach",
+            "${1:callback}",
+            "(callbackfn: (value: T, index: number, array: T[]) => void): void",
+            "Call a function for each element",
+        ),
+        (
+            "find",
+            "${1:predicate}",
+            "(predicate: (value: T, index: number, obj: T[]) => unknown): T | undefined",
+            "Find the first matching element",
+        ),
+        (
+            "findIndex",
+            "${1:predicate}",
+            "(predicate: (value: T, index: number, obj: T[]) => unknown): number",
+            "Find the index of the first matching element",
+        ),
+        (
+            "some",
+            "${1:predicate}",
+            "(predicate: (value: T, index: number, array: T[]) => unknown): boolean",
+            "Check if any element matches",
+        ),
+        (
+            "every",
+            "${1:predicate}",
+            "(predicate: (value: T, index: number, array: T[]) => unknown): boolean",
+            "Check if all elements match",
+        ),
+        (
+            "flat",
+            "${1:depth}",
+            "<D extends number = 1>(depth?: D): FlatArray<T[], D>[]",
+            "Flatten nested arrays",
+        ),
+        (
+            "flatMap",
+            "${1:callback}",
+            "<U>(callback: (value: T, index: number, array: T[]) => U | readonly U[]): U[]",
+            "Map then flatten one level",
+        ),
+        (
+            "indexOf",
+            "${1:searchElement}",
+            "(searchElement: T, fromIndex?: number): number",
+            "Index of the first occurrence",
+        ),
+        (
+            "includes",
+            "${1:searchElement}",
+            "(searchElement: T, fromIndex?: number): boolean",
+            "Check if the array contains a value",
+        ),
+        (
+            "push",
+            "${1:items}",
+            "(...items: T[]): number",
+            "Append elements",
+        ),
+        ("pop", "", "(): T | undefined", "Remove the last element"),
+        ("shift", "", "(): T | undefined", "Remove the first element"),
+        (
+            "unshift",
+            "${1:items}",
+            "(...items: T[]): number",
+            "Prepend elements",
+        ),
+        (
+            "splice",
+            "${1:start}, ${2:deleteCount}",
+            "(start: number, deleteCount?: number, ...items: T[]): T[]",
+            "Remove or insert elements in place",
+        ),
+        (
+            "slice",
+            "${1:start}, ${2:end}",
+            "(start?: number, end?: number): T[]",
+            "Copy a section of the array",
+        ),
+        (
+            "sort",
+            "${1:compareFn}",
+            "(compareFn?: (a: T, b: T) => number): T[]",
+            "Sort the array in place",
+        ),
+        ("reverse", "", "(): T[]", "Reverse the array in place"),
+        (
+            "join",
+            "${1:separator}",
+            "(separator?: string): string",
+            "Join elements into a string",
+        ),
+        (
+            "fill",
+            "${1:value}",
+            "(value: T, start?: number, end?: number): T[]",
+            "Fill elements with a value",
+        ),
+        (
+            "copyWithin",
+            "${1:target}, ${2:start}",
+            "(target: number, start: number, end?: number): T[]",
+            "Copy a section within the array",
+        ),
+        (
+            "entries",
+            "",
+            "(): ArrayIterator<[number, T]>",
+            "Iterate over index/value pairs",
+        ),
+        (
+            "keys",
+            "",
+            "(): ArrayIterator<number>",
+            "Iterate over indices",
+        ),
+        ("values", "", "(): ArrayIterator<T>", "Iterate over values"),
+    ];
+
+    create_snippet_method_completions(&methods)
+}
+
+/// `String.prototype` methods, for string-typed values
+fn get_string_instance_completions() -> Vec<CompletionItem> {
+    let methods = [
+        (
+            "split",
+            "${1:separator}",
+            "(separator: string | RegExp, limit?: number): string[]",
+            "Split into an array of substrings",
+        ),
+        (
+            "trim",
+            "",
+            "(): string",
+            "Remove leading and trailing whitespace",
+        ),
+        ("trimStart", "", "(): string", "Remove leading whitespace"),
+        ("trimEnd", "", "(): string", "Remove trailing whitespace"),
+        ("toUpperCase", "", "(): string", "Convert to upper case"),
+        ("toLowerCase", "", "(): string", "Convert to lower case"),
+        (
+            "includes",
+            "${1:searchString}",
+            "(searchString: string, position?: number): boolean",
+            "Check if the string contains a substring",
+        ),
+        (
+            "startsWith",
+            "${1:searchString}",
+            "(searchString: string, position?: number): boolean",
+            "Check if the string starts with a substring",
+        ),
+        (
+            "endsWith",
+            "${1:searchString}",
+            "(searchString: string, endPosition?: number): boolean",
+            "Check if the string ends with a substring",
+        ),
+        (
+            "indexOf",
+            "${1:searchString}",
+            "(searchString: string, position?: number): number",
+            "Index of the first occurrence",
+        ),
+        (
+            "lastIndexOf",
+            "${1:searchString}",
+            "(searchString: string, position?: number): number",
+            "Index of the last occurrence",
+        ),
+        (
+            "replace",
+            "${1:searchValue}, ${2:replaceValue}",
+            "(searchValue: string | RegExp, replaceValue: string): string",
+            "Replace the first match",
+        ),
+        (
+            "replaceAll",
+            "${1:searchValue}, ${2:replaceValue}",
+            "(searchValue: string | RegExp, replaceValue: string): string",
+            "Replace every match",
+        ),
+        (
+            "match",
+            "${1:regexp}",
+            "(regexp: string | RegExp): RegExpMatchArray | null",
+            "Match against a regular expression",
+        ),
+        (
+            "matchAll",
+            "${1:regexp}",
+            "(regexp: RegExp): RegExpStringIterator<RegExpExecArray>",
+            "Iterate over all regular expression matches",
+        ),
+        (
+            "search",
+            "${1:regexp}",
+            "(regexp: string | RegExp): number",
+            "Index of the first regular expression match",
+        ),
+        (
+            "slice",
+            "${1:start}, ${2:end}",
+            "(start?: number, end?: number): string",
+            "Extract a section of the string",
+        ),
+        (
+            "substring",
+            "${1:start}, ${2:end}",
+            "(start: number, end?: number): string",
+            "Extract characters between two indices",
+        ),
+        (
+            "padStart",
+            "${1:maxLength}, ${2:fillString}",
+            "(maxLength: number, fillString?: string): string",
+            "Pad the start to a given length",
+        ),
+        (
+            "padEnd",
+            "${1:maxLength}, ${2:fillString}",
+            "(maxLength: number, fillString?: string): string",
+            "Pad the end to a given length",
+        ),
+        (
+            "repeat",
+            "${1:count}",
+            "(count: number): string",
+            "Repeat the string",
+        ),
+        (
+            "charAt",
+            "${1:pos}",
+            "(pos: number): string",
+            "Character at an index",
+        ),
+        (
+            "charCodeAt",
+            "${1:index}",
+            "(index: number): number",
+            "UTF-16 code unit at an index",
+        ),
+        (
+            "codePointAt",
+            "${1:pos}",
+            "(pos: number): number | undefined",
+            "Code point at an index",
+        ),
+        (
+            "normalize",
+            "${1:form}",
+            "(form?: \"NFC\" | \"NFD\" | \"NFKC\" | \"NFKD\"): string",
+            "Unicode normalization form",
+        ),
+        (
+            "at",
+            "${1:index}",
+            "(index: number): string | undefined",
+            "Character at an index, counting back from the end if negative",
+        ),
+    ];
+
+    create_snippet_method_completions(&methods)
+}
+
+/// Build method completions from `(name, snippet arguments, signature, description)`
+fn create_snippet_method_completions(methods: &[(&str, &str, &str, &str)]) -> Vec<CompletionItem> {
+    methods
+        .iter()
+        .map(|(name, args, signature, description)| CompletionItem {
+            label: (*name).to_string(),
+            kind: Some(CompletionItemKind::METHOD),
+            detail: Some((*signature).to_string()),
+            documentation: Some(Documentation::String((*description).to_string())),
+            insert_text: Some(format!("{}({})", name, args)),
+            insert_text_format: Some(InsertTextFormat::SNIPPET),
+            ..Default::default()
+        })
+        .collect()
+}
+
+fn get_string_static_completions() -> Vec<CompletionItem> {
+    vec![
+        create_method_completion(
+            "fromCharCode",
+            "(...codes: number[]): string",
+            "Create from char codes",
+        ),
+        create_method_completion(
+            "fromCodePoint",
+            "(...codePoints: number[]): string",
+            "Create from code points",
+        ),
+        create_method_completion(
             "raw",
             "(template: TemplateStringsArray, ...substitutions: any[]): string",
             "Raw template string",
@@ -595,6 +1736,180 @@ fn get_string_static_completions() -> Vec<CompletionItem> {
     ]
 }
 
+fn get_number_static_completions() -> Vec<CompletionItem> {
+    vec![
+        create_method_completion(
+            "isInteger",
+            "(number: unknown): boolean",
+            "Check for an integer",
+        ),
+        create_method_completion(
+            "isFinite",
+            "(number: unknown): boolean",
+            "Check for a finite number",
+        ),
+        create_method_completion("isNaN", "(number: unknown): boolean", "Check for NaN"),
+        create_method_completion(
+            "isSafeInteger",
+            "(number: unknown): boolean",
+            "Check for a safe integer",
+        ),
+        create_method_completion("parseFloat", "(string: string): number", "Parse a float"),
+        create_method_completion(
+            "parseInt",
+            "(string: string, radix?: number): number",
+            "Parse an integer",
+        ),
+        create_property_completion("MAX_SAFE_INTEGER", "number", "Largest safe integer"),
+        create_property_completion("MIN_SAFE_INTEGER", "number", "Smallest safe integer"),
+        create_property_completion("MAX_VALUE", "number", "Largest representable number"),
+        create_property_completion("MIN_VALUE", "number", "Smallest positive number"),
+        create_property_completion("EPSILON", "number", "Smallest difference between numbers"),
+        create_property_completion("NaN", "number", "Not a number"),
+        create_property_completion("POSITIVE_INFINITY", "number", "Positive infinity"),
+        create_property_completion("NEGATIVE_INFINITY", "number", "Negative infinity"),
+    ]
+}
+
+fn get_number_instance_completions() -> Vec<CompletionItem> {
+    let methods = [
+        (
+            "toFixed",
+            "${1:fractionDigits}",
+            "(fractionDigits?: number): string",
+            "Format with a fixed number of decimals",
+        ),
+        (
+            "toPrecision",
+            "${1:precision}",
+            "(precision?: number): string",
+            "Format with a number of significant digits",
+        ),
+        (
+            "toExponential",
+            "${1:fractionDigits}",
+            "(fractionDigits?: number): string",
+            "Format in exponential notation",
+        ),
+        (
+            "toString",
+            "${1:radix}",
+            "(radix?: number): string",
+            "Convert to a string",
+        ),
+        (
+            "toLocaleString",
+            "",
+            "(locales?: string | string[], options?: Intl.NumberFormatOptions): string",
+            "Format for a locale",
+        ),
+        ("valueOf", "", "(): number", "Primitive value"),
+    ];
+
+    create_snippet_method_completions(&methods)
+}
+
+fn get_date_static_completions() -> Vec<CompletionItem> {
+    vec![
+        create_method_completion("now", "(): number", "Current time in milliseconds"),
+        create_method_completion("parse", "(s: string): number", "Parse a date string"),
+        create_method_completion(
+            "UTC",
+            "(year: number, monthIndex?: number, ...): number",
+            "Milliseconds for a UTC date",
+        ),
+    ]
+}
+
+fn get_date_instance_completions() -> Vec<CompletionItem> {
+    let methods = [
+        ("getTime", "", "(): number", "Milliseconds since the epoch"),
+        ("getFullYear", "", "(): number", "Year"),
+        ("getMonth", "", "(): number", "Month (0-11)"),
+        ("getDate", "", "(): number", "Day of the month"),
+        ("getDay", "", "(): number", "Day of the week (0-6)"),
+        ("getHours", "", "(): number", "Hours"),
+        ("getMinutes", "", "(): number", "Minutes"),
+        ("getSeconds", "", "(): number", "Seconds"),
+        ("getMilliseconds", "", "(): number", "Milliseconds"),
+        (
+            "setFullYear",
+            "${1:year}",
+            "(year: number): number",
+            "Set the year",
+        ),
+        (
+            "setMonth",
+            "${1:month}",
+            "(month: number): number",
+            "Set the month",
+        ),
+        (
+            "setDate",
+            "${1:date}",
+            "(date: number): number",
+            "Set the day of the month",
+        ),
+        (
+            "setHours",
+            "${1:hours}",
+            "(hours: number): number",
+            "Set the hours",
+        ),
+        (
+            "setTime",
+            "${1:time}",
+            "(time: number): number",
+            "Set the time in milliseconds",
+        ),
+        ("toISOString", "", "(): string", "ISO 8601 string"),
+        ("toJSON", "", "(): string", "JSON representation"),
+        ("toDateString", "", "(): string", "Date portion as a string"),
+        ("toTimeString", "", "(): string", "Time portion as a string"),
+        (
+            "toLocaleDateString",
+            "",
+            "(): string",
+            "Date portion for a locale",
+        ),
+        (
+            "toLocaleTimeString",
+            "",
+            "(): string",
+            "Time portion for a locale",
+        ),
+        (
+            "toLocaleString",
+            "",
+            "(): string",
+            "Date and time for a locale",
+        ),
+        ("toUTCString", "", "(): string", "UTC string"),
+    ];
+
+    create_snippet_method_completions(&methods)
+}
+
+fn get_symbol_static_completions() -> Vec<CompletionItem> {
+    vec![
+        create_method_completion(
+            "for",
+            "(key: string): symbol",
+            "Symbol from the global registry",
+        ),
+        create_method_completion(
+            "keyFor",
+            "(sym: symbol): string | undefined",
+            "Registry key of a symbol",
+        ),
+        create_property_completion("iterator", "symbol", "Default iterator"),
+        create_property_completion("asyncIterator", "symbol", "Default async iterator"),
+        create_property_completion("hasInstance", "symbol", "instanceof behavior"),
+        create_property_completion("toPrimitive", "symbol", "Conversion to a primitive"),
+        create_property_completion("toStringTag", "symbol", "Default string description"),
+    ]
+}
+
 fn get_promise_completions() -> Vec<CompletionItem> {
     vec![
         create_method_completion(
@@ -735,7 +2050,7 @@ fn get_import_completions() -> Vec<CompletionItem> {
 }
 
 /// Get JSX tag completions
-fn get_jsx_completions() -> Vec<CompletionItem> {
+fn get_jsx_completions(symbol_table: &SymbolTable, position: Position) -> Vec<CompletionItem> {
     let html_tags = [
         "div", "span", "p", "a", "button", "input", "form", "label", "h1", "h2", "h3", "h4", "h5",
         "h6", "header", "footer", "main", "nav", "section", "article", "aside", "ul", "ol", "li",
@@ -753,9 +2068,37 @@ fn get_jsx_completions() -> Vec<CompletionItem> {
             insert_text_format: Some(InsertTextFormat::SNIPPET),
             ..Default::default()
         })
+        .chain(get_jsx_component_completions(symbol_table, position))
         .collect()
 }
 
+/// Capitalized functions, classes and imports in scope, which JSX treats
+/// as components rather than HTML elements
+fn get_jsx_component_completions(
+    symbol_table: &SymbolTable,
+    position: Position,
+) -> Vec<CompletionItem> {
+    let scope_id = symbol_table.scope_at_position(position);
+    let component = SymbolFlags::FUNCTION | SymbolFlags::CLASS | SymbolFlags::IMPORT;
+
+    let mut completions: Vec<_> = symbol_table
+        .all_symbols()
+        .filter(|symbol| symbol.name.starts_with(|c: char| c.is_ascii_uppercase()))
+        .filter(|symbol| symbol.flags.intersects(component))
+        .filter(|symbol| symbol_table.lookup(&symbol.name, scope_id) == Some(symbol.id))
+        .map(|symbol| CompletionItem {
+            label: symbol.name.clone(),
+            kind: Some(symbol_flags_to_completion_kind(symbol.flags)),
+            detail: Some("Component".to_string()),
+            insert_text: Some(format!("{} $1/>$0", symbol.name)),
+            insert_text_format: Some(InsertTextFormat::SNIPPET),
+            ..Default::default()
+        })
+        .collect();
+    completions.sort_by(|a, b| a.label.cmp(&b.label));
+    completions
+}
+
 /// Get JSX attribute completions
 fn get_jsx_attribute_completions() -> Vec<CompletionItem> {
     let common_attrs = [
@@ -790,6 +2133,91 @@ fn get_jsx_attribute_completions() -> Vec<CompletionItem> {
         .collect()
 }
 
+/// The props of the component a JSX element at `position` renders, from
+/// the type of the component function's first parameter. `None` for HTML
+/// elements and components whose props type isn't known.
+fn get_component_prop_completions(
+    tree: &Tree,
+    source: &str,
+    symbol_table: &SymbolTable,
+    checker: &TypeChecker,
+    position: Position,
+) -> Option<Vec<CompletionItem>> {
+    let index = LineIndex::new(source);
+    let point = index.point(position);
+    let root = tree.root_node();
+
+    let mut element = root.descendant_for_point_range(point, point)?;
+    while !matches!(
+        element.kind(),
+        "jsx_opening_element" | "jsx_self_closing_element"
+    ) {
+        element = element.parent()?;
+    }
+    let tag = element
+        .child_by_field_name("name")?
+        .utf8_text(source.as_bytes())
+        .ok()?;
+    if !tag.starts_with(|c: char| c.is_ascii_uppercase()) {
+        return None;
+    }
+
+    let scope_id = symbol_table.scope_at_position(position);
+    let component = symbol_table.get_symbol(symbol_table.lookup(tag, scope_id)?)?;
+    let declared = index.point(component.name_range.start);
+    let declaration = root
+        .descendant_for_point_range(declared, declared)?
+        .parent()?;
+    let function = match declaration.kind() {
+        "function_declaration" => declaration,
+        "variable_declarator" => declaration
+            .child_by_field_name("value")
+            .filter(|value| matches!(value.kind(), "arrow_function" | "function_expression"))?,
+        _ => return None,
+    };
+
+    let parameters = function.child_by_field_name("parameters")?;
+    let props = parameters
+        .named_children(&mut parameters.walk())
+        .find(|param| param.kind() != "comment")?
+        .child_by_field_name("pattern")?;
+    let props = symbol_table.get_symbol(
+        symbol_table.symbol_at_position(index.position_of_point(props.start_position()))?,
+    )?;
+
+    let mut ty = checker.get_type(props.inferred_type?)?;
+    if let Type::Reference(reference) = ty {
+        let declared = symbol_table.lookup_type(&reference.name, scope_id)?;
+        ty = checker.get_type(symbol_table.get_symbol(declared)?.inferred_type?)?;
+    }
+
+    let mut properties = Vec::new();
+    collect_properties(ty, &mut properties);
+    if properties.is_empty() {
+        return None;
+    }
+    properties.sort_by(|a, b| a.name.cmp(&b.name));
+    properties.dedup_by(|a, b| a.name == b.name);
+
+    Some(
+        properties
+            .into_iter()
+            .map(|prop| CompletionItem {
+                label: prop.name.clone(),
+                kind: Some(CompletionItemKind::PROPERTY),
+                detail: Some(if prop.optional {
+                    format!("{}?: {}", prop.name, print_type(&prop.ty))
+                } else {
+                    format!("{}: {}", prop.name, print_type(&prop.ty))
+                }),
+                insert_text: Some(format!("{}=$0", prop.name)),
+                insert_text_format: Some(InsertTextFormat::SNIPPET),
+                ..Default::default()
+            })
+            .collect(),
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -839,11 +2267,78 @@ mod tests {
 
     #[test]
     fn test_jsx_completions() {
-        let completions = get_jsx_completions();
+        let completions = get_jsx_completions(&SymbolTable::new(), Position::new(0, 0));
         assert!(!completions.is_empty());
         assert!(completions.iter().any(|c| c.label == "div"));
     }
 
+    #[test]
+    fn test_jsx_component_completions() {
+        let source = "import { Button } from './button';\nfunction Card() {}\nfunction helper() {}\nconst Title = 'x';\n";
+        let (_, symbol_table) = parse_and_bind(source);
+        let completions = get_jsx_completions(&symbol_table, Position::new(4, 0));
+
+        assert!(completions.iter().any(|c| c.label == "div"));
+        let button = completions.iter().find(|c| c.label == "Button").unwrap();
+        assert_eq!(button.insert_text.as_deref(), Some("Button $1/>$0"));
+        assert!(completions.iter().any(|c| c.label == "Card"));
+        assert!(
+            !completions
+                .iter()
+                .any(|c| c.label == "helper" || c.label == "Title")
+        );
+    }
+
+    fn tsx_completions_at(source: &str, line: u32, character: u32) -> Vec<CompletionItem> {
+        let uri = Url::parse("file:///test.tsx").unwrap();
+        let mut parser = tree_sitter::Parser::new();
+        parser
+            .set_language(&tree_sitter_typescript::LANGUAGE_TSX.into())
+            .unwrap();
+        let tree = parser.parse(source, None).unwrap();
+        let mut symbol_table = crate::analysis::binder::bind_document(&tree, source);
+        let mut checker = TypeChecker::new();
+        checker.declare_types_from_tree(tree.root_node(), source);
+        checker.infer_symbol_types(&mut symbol_table, tree.root_node(), source);
+
+        let params = CompletionParams {
+            text_document_position: tower_lsp::lsp_types::TextDocumentPositionParams {
+                text_document: tower_lsp::lsp_types::TextDocumentIdentifier { uri: uri.clone() },
+                position: Position::new(line, character),
+            },
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+            context: None,
+        };
+        get_completions(
+            &tree,
+            source,
+            &symbol_table,
+            &checker,
+            &Project::new(std::path::PathBuf::from("/")),
+            &DocumentContext { uri },
+            &params,
+        )
+    }
+
+    #[test]
+    fn test_jsx_component_prop_completions() {
+        let source = "function B(props: { size: number; label?: string }) { return null; }\nconst x = <B  />;";
+        let completions = tsx_completions_at(source, 1, 13);
+
+        assert_eq!(labels(&completions), vec!["label", "size"]);
+        let size = completions.iter().find(|c| c.label == "size").unwrap();
+        assert_eq!(size.detail.as_deref(), Some("size: number"));
+
+        let source = "interface Props { variant: string }\nconst C = (props: Props) => null;\nconst x = <C v />;";
+        let completions = tsx_completions_at(source, 2, 14);
+        assert_eq!(labels(&completions), vec!["variant"]);
+
+        // HTML elements keep the common attributes
+        let completions = tsx_completions_at("const x = <div c />;", 0, 16);
+        assert!(completions.iter().any(|c| c.label == "className"));
+    }
+
     #[test]
     fn test_jsx_attribute_completions() {
         let completions = get_jsx_attribute_completions();
@@ -870,4 +2365,684 @@ mod tests {
             CompletionItemKind::CONSTANT
         );
     }
+
+    fn completions_at(source: &str, line: u32, character: u32) -> Vec<CompletionItem> {
+        let uri = Url::parse("file:///test.ts").unwrap();
+        let project = Project::new(std::path::PathBuf::from("/"));
+        completions_in(&uri, &project, source, line, character)
+    }
+
+    fn completions_in(
+        uri: &Url,
+        project: &Project,
+        source: &str,
+        line: u32,
+        character: u32,
+    ) -> Vec<CompletionItem> {
+        use tower_lsp::lsp_types::{
+            PartialResultParams, TextDocumentIdentifier, TextDocumentPositionParams,
+            WorkDoneProgressParams,
+        };
+
+        let mut parser = tree_sitter::Parser::new();
+        parser
+            .set_language(&tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into())
+            .unwrap();
+        let tree = parser.parse(source, None).unwrap();
+        let mut symbol_table = crate::analysis::binder::bind_document(&tree, source);
+        let mut checker = TypeChecker::new();
+        checker.declare_types_from_tree(tree.root_node(), source);
+        checker.infer_symbol_types(&mut symbol_table, tree.root_node(), source);
+
+        let params = CompletionParams {
+            text_document_position: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier { uri: uri.clone() },
+                position: Position::new(line, character),
+            },
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            partial_result_params: PartialResultParams::default(),
+            context: None,
+        };
+        let document = DocumentContext { uri: uri.clone() };
+        get_completions(
+            &tree,
+            source,
+            &symbol_table,
+            &checker,
+            project,
+            &document,
+            &params,
+        )
+    }
+
+    fn path_fixture() -> (tempfile::TempDir, Url) {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path();
+        std::fs::create_dir(root.join("components")).unwrap();
+        std::fs::write(root.join("components/Button.tsx"), "").unwrap();
+        std::fs::write(root.join("utils.ts"), "").unwrap();
+        std::fs::write(root.join("legacy.js"), "").unwrap();
+        std::fs::write(root.join("types.d.ts"), "").unwrap();
+        std::fs::write(root.join("notes.md"), "").unwrap();
+        std::fs::write(root.join("main.ts"), "").unwrap();
+
+        let uri = Url::from_file_path(root.join("main.ts")).unwrap();
+        (temp_dir, uri)
+    }
+
+    fn path_completions_at(uri: &Url, source: &str, character: u32) -> Vec<CompletionItem> {
+        let project = Project::new(std::path::PathBuf::from("/"));
+        completions_in(uri, &project, source, 0, character)
+    }
+
+    fn labels(completions: &[CompletionItem]) -> Vec<&str> {
+        completions.iter().map(|c| c.label.as_str()).collect()
+    }
+
+    fn parse_and_bind(source: &str) -> (Tree, SymbolTable) {
+        let mut parser = tree_sitter::Parser::new();
+        parser
+            .set_language(&tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into())
+            .unwrap();
+        let tree = parser.parse(source, None).unwrap();
+        let symbol_table = crate::analysis::binder::bind_document(&tree, source);
+        (tree, symbol_table)
+    }
+
+    #[test]
+    fn test_resolve_scope_completion() {
+        let source = "/** Greets someone */\nfunction greet() {}\ng";
+        let completions = completions_at(source, 2, 1);
+        let item = completions.iter().find(|c| c.label == "greet").unwrap();
+        assert!(item.documentation.is_none());
+        assert!(item.detail.is_none());
+        assert_eq!(
+            completion_uri(item),
+            Some(Url::parse("file:///test.ts").unwrap())
+        );
+
+        let (tree, symbol_table) = parse_and_bind(source);
+        let resolved = resolve_completion(item.clone(), &tree, source, &symbol_table);
+        assert_eq!(
+            resolved.detail.as_deref(),
+            Some(get_symbol_detail(SymbolFlags::FUNCTION | SymbolFlags::HOISTED).as_str())
+        );
+        match resolved.documentation {
+            Some(Documentation::MarkupContent(content)) => {
+                assert!(content.value.contains("Greets someone"))
+            }
+            other => panic!("expected documentation, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_general_completions_ranking() {
+        let source = "const bar = 2;\nconst foo = 1;\nf";
+        let completions = completions_at(source, 2, 1);
+        let sort_text = |label: &str, kind| {
+            completions
+                .iter()
+                .find(|c| c.label == label && c.kind == Some(kind))
+                .and_then(|c| c.sort_text.clone())
+                .unwrap()
+        };
+
+        assert!(
+            sort_text("foo", CompletionItemKind::CONSTANT)
+                < sort_text("for", CompletionItemKind::KEYWORD)
+        );
+        assert!(
+            sort_text("for", CompletionItemKind::KEYWORD)
+                < sort_text("for", CompletionItemKind::SNIPPET)
+        );
+
+        let preselected: Vec<_> = completions
+            .iter()
+            .filter(|c| c.preselect == Some(true))
+            .map(|c| c.label.as_str())
+            .collect();
+        assert_eq!(preselected, vec!["foo"]);
+        assert_eq!(completions.iter().filter(|c| c.label == "foo").count(), 1);
+    }
+
+    #[test]
+    fn test_auto_import_completions() {
+        let mut project = Project::new(std::path::PathBuf::from("/test"));
+        for (path, source) in [
+            (
+                "/test/user.ts",
+                "export class UserService {}\nexport default 1;",
+            ),
+            ("/test/main.ts", "export const UserMain = 1;"),
+        ] {
+            let mut parser = tree_sitter::Parser::new();
+            parser
+                .set_language(&tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into())
+                .unwrap();
+            let tree = parser.parse(source, None).unwrap();
+            project.cache_file(std::path::PathBuf::from(path), &tree, source);
+        }
+        let uri = Url::parse("file:///test/main.ts").unwrap();
+
+        let source = "const local = 1;\nUse";
+        let completions = completions_in(&uri, &project, source, 1, 3);
+        let item = completions
+            .iter()
+            .find(|c| c.label == "UserService")
+            .unwrap();
+
+        assert_eq!(item.kind, Some(CompletionItemKind::CLASS));
+        assert!(item.additional_text_edits.is_none());
+        let (tree, symbol_table) = parse_and_bind(source);
+        let resolved = resolve_completion(item.clone(), &tree, source, &symbol_table);
+        let edits = resolved.additional_text_edits.as_ref().unwrap();
+        assert_eq!(edits[0].new_text, "import { UserService } from './user';\n");
+        assert_eq!(edits[0].range.start, Position::new(0, 0));
+        assert_eq!(
+            item.label_details.as_ref().unwrap().description.as_deref(),
+            Some("./user")
+        );
+        // Ranked below the symbols in scope
+        let local = completions.iter().find(|c| c.label == "local").unwrap();
+        assert!(local.sort_text < item.sort_text);
+        // The document's own exports are already in scope
+        assert!(!completions.iter().any(|c| c.label == "UserMain"));
+    }
+
+    #[test]
+    fn test_named_import_completions_flatten_barrel() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path();
+        std::fs::write(
+            root.join("utils.ts"),
+            "export function formatDate() {}\nexport const VERSION = 1;\nexport default 1;",
+        )
+        .unwrap();
+        std::fs::write(root.join("types.ts"), "export interface User {}").unwrap();
+        std::fs::write(
+            root.join("index.ts"),
+            "export * from './utils';\nexport { User as Account } from './types';\nexport class Client {}",
+        )
+        .unwrap();
+
+        let main = root.join("main.ts");
+        let uri = Url::from_file_path(&main).unwrap();
+        let mut project = Project::new(root.to_path_buf());
+        project.load_module_exports(&root.join("index.ts"));
+
+        let source = "import { VERSION,  } from './index';";
+        let completions = completions_in(&uri, &project, source, 0, 18);
+
+        assert_eq!(
+            labels(&completions),
+            vec!["Account", "Client", "formatDate"]
+        );
+        assert!(
+            completions
+                .iter()
+                .all(|c| c.kind == Some(CompletionItemKind::MODULE))
+        );
+        let client = completions.iter().find(|c| c.label == "Client").unwrap();
+        assert_eq!(client.detail.as_deref(), Some("class from './index'"));
+    }
+
+    #[test]
+    fn test_named_import_specifier() {
+        let source = "import { a } from './mod';\nimport b from './other';";
+        let mut parser = tree_sitter::Parser::new();
+        parser
+            .set_language(&tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into())
+            .unwrap();
+        let tree = parser.parse(source, None).unwrap();
+
+        assert_eq!(
+            named_import_specifier(&tree, source, Position::new(0, 9)),
+            Some("./mod".to_string())
+        );
+        assert_eq!(
+            named_import_specifier(&tree, source, Position::new(0, 20)),
+            None
+        );
+        assert_eq!(
+            named_import_specifier(&tree, source, Position::new(1, 7)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_import_path_completions_unterminated() {
+        let (_dir, uri) = path_fixture();
+        let completions = path_completions_at(&uri, "import foo from './", 19);
+
+        assert_eq!(
+            labels(&completions),
+            vec!["components/", "legacy", "types", "utils"]
+        );
+        let folder = completions
+            .iter()
+            .find(|c| c.label == "components/")
+            .unwrap();
+        assert_eq!(folder.kind, Some(CompletionItemKind::FOLDER));
+        let file = completions.iter().find(|c| c.label == "utils").unwrap();
+        assert_eq!(file.kind, Some(CompletionItemKind::FILE));
+    }
+
+    #[test]
+    fn test_import_path_completions_nested_prefix() {
+        let (_dir, uri) = path_fixture();
+        let source = "import { Button } from './components/Bu';";
+        let completions = path_completions_at(&uri, source, 39);
+
+        assert_eq!(labels(&completions), vec!["Button"]);
+    }
+
+    #[test]
+    fn test_import_path_completions_after_closing_quote() {
+        let (_dir, uri) = path_fixture();
+        let source = "import foo from './utils'";
+        let completions = path_completions_at(&uri, source, 25);
+
+        assert!(
+            !completions
+                .iter()
+                .any(|c| c.kind == Some(CompletionItemKind::FILE))
+        );
+    }
+
+    #[test]
+    fn test_member_completions_for_new_class_instance() {
+        let source = "class Foo { bar: string; baz(): void {} }\nnew Foo().";
+        let completions = completions_at(source, 1, 10);
+
+        let bar = completions.iter().find(|c| c.label == "bar").unwrap();
+        assert_eq!(bar.kind, Some(CompletionItemKind::PROPERTY));
+        assert_eq!(bar.detail.as_deref(), Some("string"));
+
+        let baz = completions.iter().find(|c| c.label == "baz").unwrap();
+        assert_eq!(baz.kind, Some(CompletionItemKind::METHOD));
+        assert_eq!(completions.len(), 2);
+    }
+
+    #[test]
+    fn test_member_completions_for_annotated_variable() {
+        let source =
+            "interface Point { x: number; y: number }\nconst p: Point = { x: 0, y: 0 };\np.";
+        let completions = completions_at(source, 2, 2);
+
+        let labels: Vec<_> = completions.iter().map(|c| c.label.as_str()).collect();
+        assert_eq!(labels, vec!["x", "y"]);
+    }
+
+    #[test]
+    fn test_member_completions_include_base_class() {
+        let source =
+            "class A { a: number; }\nclass B extends A { b: string; }\nconst b = new B();\nb.";
+        let completions = completions_at(source, 3, 2);
+
+        let labels: Vec<_> = completions.iter().map(|c| c.label.as_str()).collect();
+        assert_eq!(labels, vec!["a", "b"]);
+    }
+
+    #[test]
+    fn test_function_completions_commit_on_paren() {
+        let completions = completions_at("function greet() {}\nconst n = 1;\n", 2, 0);
+
+        let greet = completions.iter().find(|c| c.label == "greet").unwrap();
+        assert_eq!(greet.commit_characters, Some(vec!["(".to_string()]));
+        let n = completions.iter().find(|c| c.label == "n").unwrap();
+        assert!(n.commit_characters.is_none());
+    }
+
+    #[test]
+    fn test_snippet_completions_adjust_indentation() {
+        let completions = completions_at("", 0, 0);
+
+        let snippet = completions.iter().find(|c| c.label == "fn").unwrap();
+        assert_eq!(
+            snippet.insert_text_mode,
+            Some(InsertTextMode::ADJUST_INDENTATION)
+        );
+        let keyword = completions
+            .iter()
+            .find(|c| c.kind == Some(CompletionItemKind::KEYWORD))
+            .unwrap();
+        assert!(keyword.insert_text_mode.is_none());
+    }
+
+    #[test]
+    fn test_filter_text_for_computed_labels() {
+        let mut item = CompletionItem {
+            label: "[Symbol.iterator]".to_string(),
+            kind: Some(CompletionItemKind::METHOD),
+            ..Default::default()
+        };
+        apply_client_hints(&mut item);
+        assert_eq!(item.filter_text.as_deref(), Some("Symbol.iterator"));
+        assert!(item.commit_characters.is_none());
+
+        let mut item = CompletionItem {
+            label: "length".to_string(),
+            ..Default::default()
+        };
+        apply_client_hints(&mut item);
+        assert!(item.filter_text.is_none());
+    }
+
+    #[test]
+    fn test_postfix_completions() {
+        let source = "const items = [1, 2];\nitems.i";
+        let completions = completions_at(source, 1, 7);
+
+        let postfix = completions.iter().find(|c| c.label == "if").unwrap();
+        assert_eq!(postfix.kind, Some(CompletionItemKind::SNIPPET));
+        let Some(CompletionTextEdit::Edit(edit)) = &postfix.text_edit else {
+            panic!("expected a text edit");
+        };
+        assert_eq!(
+            edit.range,
+            Range::new(Position::new(1, 6), Position::new(1, 7))
+        );
+        assert_eq!(edit.new_text, "if (items) {\n\t$0\n}");
+        assert_eq!(
+            postfix.additional_text_edits.as_deref(),
+            Some(
+                &[TextEdit::new(
+                    Range::new(Position::new(1, 0), Position::new(1, 6)),
+                    String::new()
+                )][..]
+            )
+        );
+        assert_eq!(
+            postfix.data,
+            Some(serde_json::json!({ "codeActionKind": "refactor.rewrite" }))
+        );
+
+        // Members of the array are still offered alongside the templates
+        assert!(completions.iter().any(|c| c.label == "map"));
+        let labels = labels(&completions);
+        for label in ["const", "return", "forEach", "await", "log"] {
+            assert!(labels.contains(&label), "missing {label}");
+        }
+    }
+
+    #[test]
+    fn test_postfix_completions_call_expression() {
+        let source = "fetchData().aw";
+        let completions = completions_at(source, 0, 14);
+
+        let postfix = completions.iter().find(|c| c.label == "await").unwrap();
+        let Some(CompletionTextEdit::Edit(edit)) = &postfix.text_edit else {
+            panic!("expected a text edit");
+        };
+        assert_eq!(edit.new_text, "await fetchData()");
+        let delete = &postfix.additional_text_edits.as_ref().unwrap()[0];
+        assert_eq!(
+            delete.range,
+            Range::new(Position::new(0, 0), Position::new(0, 12))
+        );
+    }
+
+    #[test]
+    fn test_no_postfix_completions_for_known_objects() {
+        let completions = completions_at("console.l", 0, 9);
+        assert!(completions.iter().any(|c| c.label == "log"));
+        assert!(completions.iter().all(|c| c.text_edit.is_none()));
+
+        // Right after the dot only members are offered
+        let completions = completions_at("const s = 'a';\ns.", 1, 2);
+        assert!(!completions.is_empty());
+        assert!(completions.iter().all(|c| c.label != "if"));
+    }
+
+    #[test]
+    fn test_string_literal_member_completions() {
+        let completions = completions_at("\"x\".", 0, 4);
+        let labels = labels(&completions);
+        assert!(labels.contains(&"toUpperCase"));
+        assert!(labels.contains(&"slice"));
+        assert_eq!(labels[0], "length");
+    }
+
+    #[test]
+    fn test_array_literal_member_completions() {
+        let completions = completions_at("const a = [].", 0, 13);
+        let labels = labels(&completions);
+        assert!(labels.contains(&"map"));
+        assert!(labels.contains(&"length"));
+    }
+
+    #[test]
+    fn test_builtin_instance_member_completions() {
+        let completions = completions_at("(42).", 0, 5);
+        assert!(labels(&completions).contains(&"toFixed"));
+
+        let completions = completions_at("new Date().", 0, 11);
+        assert!(labels(&completions).contains(&"getTime"));
+    }
+
+    #[test]
+    fn test_global_object_member_completions() {
+        let completions = completions_at("Number.", 0, 7);
+        assert!(labels(&completions).contains(&"isInteger"));
+
+        let completions = completions_at("Date.", 0, 5);
+        assert!(labels(&completions).contains(&"now"));
+
+        let completions = completions_at("Symbol.", 0, 7);
+        assert!(labels(&completions).contains(&"iterator"));
+    }
+
+    #[test]
+    fn test_this_member_completions() {
+        let source = "class Counter {
+    count = 0;
+    static instances = 0;
+    get doubled(): number { return this.count * 2; }
+    increment(): void {
+        this.
+    }
+    reset(): void {}
+}";
+        let completions = completions_at(source, 5, 13);
+
+        assert_eq!(
+            labels(&completions),
+            vec!["count", "doubled", "increment", "reset"]
+        );
+        let reset = completions.iter().find(|c| c.label == "reset").unwrap();
+        assert_eq!(reset.kind, Some(CompletionItemKind::METHOD));
+    }
+
+    #[test]
+    fn test_this_member_completions_include_base_class() {
+        let source = "class Base { id: number; }
+class Derived extends Base {
+    name: string;
+    describe() { this. }
+}";
+        let completions = completions_at(source, 3, 22);
+        assert_eq!(labels(&completions), vec!["describe", "id", "name"]);
+    }
+
+    #[test]
+    fn test_this_in_nested_functions() {
+        let source = "class Counter {
+    count = 0;
+    increment() {
+        const add = () => { this. };
+        function helper() { this. }
+        [1].forEach(function () { this. });
+    }
+}";
+        // Arrow functions keep the class's `this`, other functions rebind it
+        assert_eq!(
+            labels(&completions_at(source, 3, 33)),
+            vec!["count", "increment"]
+        );
+        assert!(completions_at(source, 4, 33).is_empty());
+        assert!(completions_at(source, 5, 39).is_empty());
+    }
+
+    #[test]
+    fn test_this_outside_class_has_no_members() {
+        let completions = completions_at("function f() {\n    this.\n}", 1, 9);
+        assert!(completions.is_empty());
+    }
+
+    #[test]
+    fn test_completion_context_through_chain_operators() {
+        let context_at = |source: &str, character: u32| {
+            let (tree, _) = parse_and_bind(source);
+            get_completion_context(&tree, source, Position::new(0, character))
+        };
+
+        assert_eq!(
+            context_at("foo?.", 5),
+            CompletionContext::MemberAccess("foo".to_string())
+        );
+        assert_eq!(
+            context_at("foo!.", 5),
+            CompletionContext::MemberAccess("foo".to_string())
+        );
+        assert_eq!(
+            context_at("foo?.bar!.", 10),
+            CompletionContext::MemberAccess("foo.bar".to_string())
+        );
+        assert_eq!(
+            context_at("foo!.bar;", 5),
+            CompletionContext::MemberAccess("foo".to_string())
+        );
+    }
+
+    #[test]
+    fn test_member_completions_after_non_ascii_text() {
+        // UTF-16 columns: `é` is one code unit (two bytes), `😀` two (four bytes)
+        for (source, character) in [("const s = 'é'; Math.", 20), ("/* 😀 */ Math.", 14)] {
+            let (tree, _) = parse_and_bind(source);
+            assert_eq!(
+                get_completion_context(&tree, source, Position::new(0, character)),
+                CompletionContext::MemberAccess("Math".to_string()),
+                "{source}"
+            );
+            let labels: Vec<_> = completions_at(source, 0, character)
+                .into_iter()
+                .map(|item| item.label)
+                .collect();
+            assert!(labels.contains(&"abs".to_string()), "{source}");
+        }
+    }
+
+    #[test]
+    fn test_no_completions_in_string_text() {
+        let source = "const user = 1;\nconst s = `hello ${us} u`;\nconst t = 'u';";
+        assert!(completions_at(source, 1, 13).is_empty());
+        assert!(completions_at(source, 1, 24).is_empty());
+        assert!(completions_at(source, 2, 12).is_empty());
+
+        // Substitutions are ordinary expressions
+        let completions = completions_at(source, 1, 21);
+        assert!(completions.iter().any(|c| c.label == "user"));
+    }
+
+    #[test]
+    fn test_case_completions_offer_uncovered_union_members() {
+        let source = "function f(k: \"a\" | \"b\" | \"c\") {\n  switch (k) {\n    case \"a\":\n      break;\n    case \n    case 'c':\n  }\n}";
+        let completions = completions_at(source, 4, 9);
+        assert_eq!(labels(&completions), vec!["\"b\""]);
+        let Some(CompletionTextEdit::Edit(edit)) = &completions[0].text_edit else {
+            panic!("expected a text edit");
+        };
+        assert_eq!(
+            edit.range,
+            Range::new(Position::new(4, 9), Position::new(4, 9))
+        );
+
+        // The quote already typed is kept
+        let source = "function f(k: \"a\" | \"b\" | \"c\") {\n  switch (k) {\n    case \"a\":\n      break;\n    case 'b\n  }\n}";
+        let completions = completions_at(source, 4, 11);
+        assert_eq!(labels(&completions), vec!["'b'", "'c'"]);
+        let Some(CompletionTextEdit::Edit(edit)) = &completions[0].text_edit else {
+            panic!("expected a text edit");
+        };
+        assert_eq!(edit.range.start, Position::new(4, 9));
+    }
+
+    #[test]
+    fn test_case_completions_fall_back_without_literal_union() {
+        let source =
+            "function f(k: string) {\n  const other = 1;\n  switch (k) {\n    case \n  }\n}";
+        let completions = completions_at(source, 3, 9);
+        assert!(completions.iter().any(|c| c.label == "other"));
+    }
+
+    #[test]
+    fn test_member_completions_through_optional_chain() {
+        let source = "class Foo { bar: string; }\nconst foo = new Foo();\nfoo?.";
+        let completions = completions_at(source, 2, 5);
+        assert_eq!(labels(&completions), vec!["bar"]);
+    }
+
+    #[test]
+    fn test_member_completions_unknown_object() {
+        let completions = completions_at("let x = 1;\nunknownThing.", 1, 13);
+        assert!(completions.is_empty());
+    }
+
+    #[test]
+    fn test_array_instance_completions() {
+        let completions = get_array_instance_completions();
+        assert_eq!(completions.len(), 26);
+
+        let map = completions.iter().find(|c| c.label == "map").unwrap();
+        assert_eq!(map.insert_text.as_deref(), Some("map(${1:callback})"));
+        assert_eq!(map.insert_text_format, Some(InsertTextFormat::SNIPPET));
+        assert!(map.detail.as_deref().unwrap().contains("callbackfn"));
+
+        let pop = completions.iter().find(|c| c.label == "pop").unwrap();
+        assert_eq!(pop.insert_text.as_deref(), Some("pop()"));
+    }
+
+    #[test]
+    fn test_member_completions_for_array_variables() {
+        let source = "const xs: number[] = [];\nconst ys = [1, 2];\nxs.\nys.";
+
+        for line in [2, 3] {
+            let completions = completions_at(source, line, 3);
+            assert!(completions.iter().any(|c| c.label == "filter"));
+            assert!(completions.iter().any(|c| c.label == "copyWithin"));
+        }
+    }
+
+    #[test]
+    fn test_string_instance_completions() {
+        let completions = get_string_instance_completions();
+        assert_eq!(completions.len(), 26);
+
+        let replace = completions.iter().find(|c| c.label == "replace").unwrap();
+        assert_eq!(
+            replace.insert_text.as_deref(),
+            Some("replace(${1:searchValue}, ${2:replaceValue})")
+        );
+        assert!(replace.detail.is_some());
+    }
+
+    #[test]
+    fn test_member_completions_for_string_variables() {
+        let source = "let name: string = '';\nconst greeting = `hi`;\nname.\ngreeting.";
+
+        let completions = completions_at(source, 2, 5);
+        assert!(completions.iter().any(|c| c.label == "toUpperCase"));
+
+        let completions = completions_at(source, 3, 9);
+        assert!(completions.iter().any(|c| c.label == "padStart"));
+        assert!(!completions.iter().any(|c| c.label == "map"));
+    }
+
+    #[test]
+    fn test_trailing_expression() {
+        assert_eq!(trailing_expression("const x = new Foo()"), "new Foo()");
+        assert_eq!(trailing_expression("  foo.bar"), "foo.bar");
+        assert_eq!(trailing_expression("renew"), "renew");
+        assert_eq!(trailing_expression("f(a, b)"), "f(a, b)");
+    }
 }
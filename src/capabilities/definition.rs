@@ -1,33 +1,95 @@
-use tower_lsp::lsp_types::{GotoDefinitionResponse, Location, Position, Url};
+use tower_lsp::lsp_types::{GotoDefinitionResponse, Location, Position, Range, Url};
+use tree_sitter::Tree;
 
-use crate::analysis::SymbolTable;
+use crate::analysis::modules::{self, ImportBinding};
+use crate::analysis::{SymbolFlags, SymbolTable, binder};
+use crate::line_index::LineIndex;
 
-/// Find the definition of the symbol at the given position
+/// Find the definition of the symbol at the given position: the symbol
+/// declared there, or the one the name under the cursor refers to
 pub fn get_definition(
     symbol_table: &SymbolTable,
     source: &str,
     position: Position,
     uri: &Url,
 ) -> Option<GotoDefinitionResponse> {
-    // First, find what identifier is at the position
-    let identifier = find_identifier_at_position(source, position)?;
-
-    // Find the scope at this position
-    let scope_id = symbol_table.scope_at_position(position);
-
-    // Look up the symbol
-    let symbol_id = symbol_table.lookup(&identifier, scope_id)?;
+    let symbol_id = match symbol_table.symbol_at_position(position) {
+        Some(symbol_id) => symbol_id,
+        None => {
+            // First, find what identifier is at the position
+            let identifier = find_identifier_at_position(source, position)?;
+
+            // Look it up from the scope at this position, among types too
+            let scope_id = symbol_table.scope_at_position(position);
+            symbol_table
+                .lookup(&identifier, scope_id)
+                .or_else(|| symbol_table.lookup_type(&identifier, scope_id))?
+        }
+    };
     let symbol = symbol_table.get_symbol(symbol_id)?;
 
     // Return the definition location
     let location = Location {
         uri: uri.clone(),
-        range: symbol.name_range,
+        range: symbol.declaration_range,
     };
 
     Some(GotoDefinitionResponse::Scalar(location))
 }
 
+/// Find the import binding for the imported symbol at the given position
+pub fn find_import_at(
+    tree: &Tree,
+    symbol_table: &SymbolTable,
+    source: &str,
+    position: Position,
+) -> Option<ImportBinding> {
+    let identifier = find_identifier_at_position(source, position)?;
+    let scope_id = symbol_table.scope_at_position(position);
+    let symbol = symbol_table.get_symbol(symbol_table.lookup(&identifier, scope_id)?)?;
+
+    if !symbol.flags.contains(SymbolFlags::IMPORT) {
+        return None;
+    }
+
+    modules::collect_imports(tree, source)
+        .into_iter()
+        .find(|import| import.local == symbol.name && import.local_range == symbol.name_range)
+}
+
+/// Find the declaration that `import` refers to in the module it imports
+pub fn get_export_definition(
+    import: &ImportBinding,
+    target_uri: &Url,
+    target_tree: &Tree,
+    target_source: &str,
+) -> Option<GotoDefinitionResponse> {
+    let location = |range| {
+        Some(GotoDefinitionResponse::Scalar(Location {
+            uri: target_uri.clone(),
+            range,
+        }))
+    };
+
+    let name = match import.imported.export_name() {
+        Some(name) => name,
+        // A namespace import refers to the module itself
+        None => return location(Range::default()),
+    };
+
+    let export = modules::collect_exports(target_tree, target_source)
+        .into_iter()
+        .find(|export| export.exported == name)?;
+
+    let symbol_table = binder::bind_document(target_tree, target_source);
+    match modules::exported_symbol(&symbol_table, &export)
+        .and_then(|id| symbol_table.get_symbol(id))
+    {
+        Some(symbol) => location(symbol.name_range),
+        None => location(export.range),
+    }
+}
+
 /// Find the identifier at a given position in the source
 fn find_identifier_at_position(source: &str, position: Position) -> Option<String> {
     let lines: Vec<&str> = source.lines().collect();
@@ -38,32 +100,26 @@ fn find_identifier_at_position(source: &str, position: Position) -> Option<Strin
     }
 
     let line = lines[line_idx];
-    let col = position.character as usize;
-
-    if col > line.len() {
-        return None;
-    }
+    // LSP columns count UTF-16 code units; slicing needs a byte column
+    let col = LineIndex::new(source).point(position).column;
 
     // Find the start and end of the identifier
-    let chars: Vec<char> = line.chars().collect();
-
-    // Find start of identifier
-    let mut start = col;
-    while start > 0 && is_identifier_char(chars.get(start - 1).copied()) {
-        start -= 1;
-    }
-
-    // Find end of identifier
-    let mut end = col;
-    while end < chars.len() && is_identifier_char(chars.get(end).copied()) {
-        end += 1;
-    }
+    let start = line[..col]
+        .char_indices()
+        .rev()
+        .take_while(|&(_, c)| is_identifier_char(Some(c)))
+        .last()
+        .map_or(col, |(i, _)| i);
+    let end = line[col..]
+        .char_indices()
+        .find(|&(_, c)| !is_identifier_char(Some(c)))
+        .map_or(line.len(), |(i, _)| col + i);
 
     if start == end {
         return None;
     }
 
-    let identifier: String = chars[start..end].iter().collect();
+    let identifier = line[start..end].to_string();
 
     // Validate it's a valid identifier
     if is_valid_identifier(&identifier) {
@@ -107,6 +163,107 @@ mod tests {
         Url::parse("file:///test/test.ts").unwrap()
     }
 
+    fn parse_typescript(code: &str) -> Tree {
+        let mut parser = tree_sitter::Parser::new();
+        parser
+            .set_language(&tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into())
+            .unwrap();
+        parser.parse(code, None).unwrap()
+    }
+
+    /// Jump from `position` in `main` to a definition in the in-memory module `./lib`
+    fn definition_in_lib(
+        main: &str,
+        lib: &str,
+        position: Position,
+    ) -> Option<GotoDefinitionResponse> {
+        let tree = parse_typescript(main);
+        let symbol_table = crate::analysis::binder::bind_document(&tree, main);
+        let lib_uri = Url::parse("file:///test/lib.ts").unwrap();
+
+        let import = find_import_at(&tree, &symbol_table, main, position)?;
+        assert_eq!(import.specifier, "./lib");
+        get_export_definition(&import, &lib_uri, &parse_typescript(lib), lib)
+    }
+
+    fn scalar_range(response: Option<GotoDefinitionResponse>) -> Range {
+        match response {
+            Some(GotoDefinitionResponse::Scalar(location)) => {
+                assert_eq!(location.uri.path(), "/test/lib.ts");
+                location.range
+            }
+            other => panic!("Expected a scalar location, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_import_definition_named() {
+        let main = "import { greet } from './lib';\ngreet();";
+        let lib = "const unused = 1;\nexport function greet() {}";
+
+        let range = scalar_range(definition_in_lib(main, lib, Position::new(1, 2)));
+        assert_eq!(
+            range,
+            Range::new(Position::new(1, 16), Position::new(1, 21))
+        );
+    }
+
+    #[test]
+    fn test_import_definition_aliased() {
+        let main = "import { greet as hello } from './lib';\nhello();";
+        let lib = "export function greet() {}";
+
+        let range = scalar_range(definition_in_lib(main, lib, Position::new(1, 1)));
+        assert_eq!(
+            range,
+            Range::new(Position::new(0, 16), Position::new(0, 21))
+        );
+    }
+
+    #[test]
+    fn test_import_definition_export_list_alias() {
+        let main = "import { hi } from './lib';\nhi();";
+        let lib = "function greet() {}\nexport { greet as hi };";
+
+        let range = scalar_range(definition_in_lib(main, lib, Position::new(1, 0)));
+        assert_eq!(range, Range::new(Position::new(0, 9), Position::new(0, 14)));
+    }
+
+    #[test]
+    fn test_import_definition_default() {
+        let main = "import Widget from './lib';\nnew Widget();";
+        let lib = "export default class Widget {}";
+
+        let range = scalar_range(definition_in_lib(main, lib, Position::new(1, 6)));
+        assert_eq!(
+            range,
+            Range::new(Position::new(0, 21), Position::new(0, 27))
+        );
+    }
+
+    #[test]
+    fn test_import_definition_namespace() {
+        let main = "import * as lib from './lib';\nlib.greet();";
+        let lib = "export function greet() {}";
+
+        let range = scalar_range(definition_in_lib(main, lib, Position::new(1, 1)));
+        assert_eq!(range, Range::default());
+    }
+
+    #[test]
+    fn test_import_definition_missing_export() {
+        let main = "import { nope } from './lib';\nnope();";
+        let lib = "export function greet() {}";
+
+        assert!(definition_in_lib(main, lib, Position::new(1, 1)).is_none());
+    }
+
+    #[test]
+    fn test_import_definition_local_symbol() {
+        let main = "const local = 1;\nlocal;";
+        assert!(definition_in_lib(main, "", Position::new(1, 1)).is_none());
+    }
+
     #[test]
     fn test_is_identifier_char() {
         assert!(is_identifier_char(Some('a')));
@@ -199,6 +356,14 @@ mod tests {
         assert!(result.is_none());
     }
 
+    #[test]
+    fn test_find_identifier_after_non_ascii_text() {
+        // UTF-16 column 28 is just after `total`: `😀` is two code units
+        let source = "const s = '😀é'; const total = 1;";
+        let result = find_identifier_at_position(source, Position::new(0, 28));
+        assert_eq!(result, Some("total".to_string()));
+    }
+
     #[test]
     fn test_find_identifier_dollar_sign() {
         let source = "const $element = null;";
@@ -252,6 +417,46 @@ mod tests {
         }
     }
 
+    fn bound_definition(source: &str, position: Position) -> Option<Range> {
+        let tree = parse_typescript(source);
+        let symbol_table = crate::analysis::binder::bind_document(&tree, source);
+        match get_definition(&symbol_table, source, position, &create_test_uri()) {
+            Some(GotoDefinitionResponse::Scalar(location)) => Some(location.range),
+            None => None,
+            other => panic!("Expected a scalar location, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_get_definition_of_function_call() {
+        let source = "function greet(name: string) {\n  return name;\n}\ngreet('a');";
+        let declaration = Range::new(Position::new(0, 0), Position::new(2, 1));
+
+        assert_eq!(
+            bound_definition(source, Position::new(3, 2)),
+            Some(declaration)
+        );
+        // On the declaration's own name
+        assert_eq!(
+            bound_definition(source, Position::new(0, 11)),
+            Some(declaration)
+        );
+        // References resolve from the scope they appear in
+        assert_eq!(
+            bound_definition(source, Position::new(1, 10)).map(|range| range.start),
+            Some(Position::new(0, 15))
+        );
+    }
+
+    #[test]
+    fn test_get_definition_of_type_reference() {
+        let source = "interface User { id: number; }\nlet u: User;";
+        assert_eq!(
+            bound_definition(source, Position::new(1, 8)).map(|range| range.start),
+            Some(Position::new(0, 0))
+        );
+    }
+
     #[test]
     fn test_get_definition_not_found() {
         let table = SymbolTable::new();
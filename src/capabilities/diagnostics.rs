@@ -1,17 +1,90 @@
-use tower_lsp::lsp_types::{Diagnostic, DiagnosticSeverity, Position, Range};
+use tower_lsp::lsp_types::{Diagnostic, DiagnosticSeverity, Range};
 use tree_sitter::Tree;
 
+use crate::line_index::LineIndex;
+use crate::resolution::tsconfig::{TsConfig, TsConfigSeverity};
+
 /// Extract syntax error diagnostics from a parsed tree
 pub fn get_syntax_diagnostics(tree: &Tree, source: &str) -> Vec<Diagnostic> {
     let mut diagnostics = Vec::new();
-    collect_errors(tree.root_node(), source, &mut diagnostics);
+    let index = LineIndex::new(source);
+    collect_errors(tree.root_node(), source, &index, &mut diagnostics);
     diagnostics
 }
 
-fn collect_errors(node: tree_sitter::Node, source: &str, diagnostics: &mut Vec<Diagnostic>) {
+/// Diagnostics for the compiler options of a tsconfig.json, placed on the
+/// option's key in `source` when it is spelled there. Options inherited
+/// through `extends` are reported on the `extends` value instead.
+pub fn get_tsconfig_diagnostics(config: &TsConfig, source: &str) -> Vec<Diagnostic> {
+    let index = LineIndex::new(source);
+    let extends = extends_value(source);
+    config
+        .validate()
+        .into_iter()
+        .map(|problem| {
+            let (start, end) = problem
+                .field
+                .as_deref()
+                .and_then(|field| {
+                    key_offset(source, field).map(|start| (start, start + field.len() + 2))
+                })
+                .or(extends)
+                .unwrap_or_default();
+            let range = Range {
+                start: index.position(start),
+                end: index.position(end),
+            };
+            Diagnostic {
+                range,
+                severity: Some(match problem.severity {
+                    TsConfigSeverity::Error => DiagnosticSeverity::ERROR,
+                    TsConfigSeverity::Warning => DiagnosticSeverity::WARNING,
+                }),
+                code: None,
+                code_description: None,
+                source: Some("ts-lsp-rust".to_string()),
+                message: problem.message,
+                related_information: None,
+                tags: None,
+                data: None,
+            }
+        })
+        .collect()
+}
+
+/// Byte range of the value of the top-level `extends` key: a string, or
+/// an array of them
+fn extends_value(source: &str) -> Option<(usize, usize)> {
+    let key = key_offset(source, "extends")?;
+    let colon = key + source[key..].find(':')?;
+    let start = colon + 1 + (source[colon + 1..].len() - source[colon + 1..].trim_start().len());
+    let rest = &source[start..];
+    let len = match rest.chars().next()? {
+        '"' => rest[1..].find('"')? + 2,
+        '[' => rest.find(']')? + 1,
+        _ => return None,
+    };
+    Some((start, start + len))
+}
+
+/// Byte offset of the quoted JSON key `"key"` followed by a colon
+fn key_offset(source: &str, key: &str) -> Option<usize> {
+    let quoted = format!("\"{}\"", key);
+    source
+        .match_indices(&quoted)
+        .map(|(i, _)| i)
+        .find(|&i| source[i + quoted.len()..].trim_start().starts_with(':'))
+}
+
+fn collect_errors(
+    node: tree_sitter::Node,
+    source: &str,
+    index: &LineIndex,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
     // Check if this node is an error or missing node
     if node.is_error() {
-        let range = node_to_range(&node);
+        let range = index.node_range(&node);
         let text = node.utf8_text(source.as_bytes()).unwrap_or("unknown");
 
         diagnostics.push(Diagnostic {
@@ -29,7 +102,7 @@ fn collect_errors(node: tree_sitter::Node, source: &str, diagnostics: &mut Vec<D
             data: None,
         });
     } else if node.is_missing() {
-        let range = node_to_range(&node);
+        let range = index.node_range(&node);
 
         diagnostics.push(Diagnostic {
             range,
@@ -47,16 +120,7 @@ fn collect_errors(node: tree_sitter::Node, source: &str, diagnostics: &mut Vec<D
     // Recurse into children
     let mut cursor = node.walk();
     for child in node.children(&mut cursor) {
-        collect_errors(child, source, diagnostics);
-    }
-}
-
-fn node_to_range(node: &tree_sitter::Node) -> Range {
-    let start = node.start_position();
-    let end = node.end_position();
-    Range {
-        start: Position::new(start.row as u32, start.column as u32),
-        end: Position::new(end.row as u32, end.column as u32),
+        collect_errors(child, source, index, diagnostics);
     }
 }
 
@@ -86,6 +150,57 @@ mod tests {
         assert!(diagnostics.is_empty());
     }
 
+    #[test]
+    fn test_tsconfig_diagnostics() {
+        let source =
+            "{\n  \"compilerOptions\": {\n    \"target\": \"ES2o20\",\n    \"fooBar\": 1\n  }\n}";
+        let config: TsConfig = serde_json::from_str(source).unwrap();
+        let diagnostics = get_tsconfig_diagnostics(&config, source);
+
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(diagnostics[0].severity, Some(DiagnosticSeverity::ERROR));
+        assert_eq!(
+            diagnostics[0].range,
+            Range::new(
+                tower_lsp::lsp_types::Position::new(2, 4),
+                tower_lsp::lsp_types::Position::new(2, 12)
+            )
+        );
+        assert_eq!(diagnostics[1].severity, Some(DiagnosticSeverity::WARNING));
+        assert_eq!(diagnostics[1].range.start.line, 3);
+    }
+
+    #[test]
+    fn test_tsconfig_diagnostics_inherited_from_extends() {
+        // `fooBar` comes from the base config, `strictt` is spelled here
+        let source =
+            "{\n  \"extends\": \"./base.json\",\n  \"compilerOptions\": { \"strictt\": true }\n}";
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(
+            dir.path().join("base.json"),
+            r#"{"compilerOptions": {"fooBar": 1}}"#,
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("tsconfig.json"), source).unwrap();
+        let config = TsConfig::load(&dir.path().join("tsconfig.json")).unwrap();
+        let diagnostics = get_tsconfig_diagnostics(&config, source);
+
+        let ranges: Vec<_> = diagnostics.iter().map(|d| d.range).collect();
+        assert_eq!(
+            ranges,
+            vec![
+                Range::new(
+                    tower_lsp::lsp_types::Position::new(1, 13),
+                    tower_lsp::lsp_types::Position::new(1, 26)
+                ),
+                Range::new(
+                    tower_lsp::lsp_types::Position::new(2, 23),
+                    tower_lsp::lsp_types::Position::new(2, 32)
+                ),
+            ]
+        );
+    }
+
     #[test]
     fn test_syntax_error_detected() {
         let code = "function ( { }";
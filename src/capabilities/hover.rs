@@ -1,17 +1,48 @@
-use tower_lsp::lsp_types::{Hover, HoverContents, MarkupContent, MarkupKind, Position, Range};
-use tree_sitter::Tree;
-
-/// Get hover information for a position in the document
-pub fn get_hover(tree: &Tree, source: &str, position: Position) -> Option<Hover> {
+use tower_lsp::lsp_types::{Hover, HoverContents, MarkupContent, MarkupKind, Position};
+use tree_sitter::{Node, Tree};
+
+use crate::analysis::{Symbol, SymbolFlags, SymbolTable};
+use crate::line_index::LineIndex;
+use crate::types::TypeChecker;
+use crate::types::printer::print_type;
+
+/// Get hover information for a position in the document: the declaration
+/// of the symbol under the cursor with its type and documentation, or a
+/// description of the syntax there
+pub fn get_hover(
+    tree: &Tree,
+    source: &str,
+    symbol_table: &SymbolTable,
+    checker: &TypeChecker,
+    position: Position,
+) -> Option<Hover> {
     let root = tree.root_node();
 
     // Find the node at the given position
-    let point = tree_sitter::Point {
-        row: position.line as usize,
-        column: position.character as usize,
-    };
+    let index = LineIndex::new(source);
+    let point = index.point(position);
 
     let node = root.descendant_for_point_range(point, point)?;
+    let range = index.node_range(&node);
+
+    if let Some(symbol) = hovered_symbol(node, source, symbol_table, position) {
+        let mut content = format!(
+            "```typescript\n{}\n```",
+            symbol_declaration(symbol, checker)
+        );
+
+        let name_point = index.point(symbol.name_range.start);
+        let jsdoc = root
+            .descendant_for_point_range(name_point, name_point)
+            .and_then(|name| find_jsdoc_comment(&name, source))
+            .or_else(|| symbol.documentation.clone());
+        if let Some(doc) = jsdoc {
+            content.push_str("\n\n---\n\n");
+            content.push_str(&doc);
+        }
+
+        return Some(markdown_hover(content, range));
+    }
 
     // Get JSDoc comment if available
     let jsdoc = find_jsdoc_comment(&node, source);
@@ -43,28 +74,142 @@ pub fn get_hover(tree: &Tree, source: &str, position: Position) -> Option<Hover>
     content.push_str("\n\n---\n\n");
     content.push_str(&format!("*Node: {} → {}*", parent_kind, node_kind));
 
-    let range = Range {
-        start: Position::new(
-            node.start_position().row as u32,
-            node.start_position().column as u32,
-        ),
-        end: Position::new(
-            node.end_position().row as u32,
-            node.end_position().column as u32,
-        ),
+    Some(markdown_hover(content, range))
+}
+
+/// Rewrite a hover for clients that can only show plain text
+pub fn to_plain_text(hover: Hover) -> Hover {
+    let HoverContents::Markup(markup) = hover.contents else {
+        return hover;
     };
+    let mut lines: Vec<&str> = Vec::new();
+    for line in markup.value.lines() {
+        if line.starts_with("```") || line == "---" {
+            continue;
+        }
+        // Dropping the separators leaves runs of blank lines behind
+        if line.is_empty() && lines.last().is_some_and(|last| last.is_empty()) {
+            continue;
+        }
+        lines.push(line);
+    }
+    Hover {
+        contents: HoverContents::Markup(MarkupContent {
+            kind: MarkupKind::PlainText,
+            value: lines.join("\n"),
+        }),
+        range: hover.range,
+    }
+}
 
-    Some(Hover {
+fn markdown_hover(value: String, range: tower_lsp::lsp_types::Range) -> Hover {
+    Hover {
         contents: HoverContents::Markup(MarkupContent {
             kind: MarkupKind::Markdown,
-            value: content,
+            value,
         }),
         range: Some(range),
-    })
+    }
+}
+
+/// The symbol declared or referenced by the name under the cursor
+fn hovered_symbol<'a>(
+    node: Node,
+    source: &str,
+    symbol_table: &'a SymbolTable,
+    position: Position,
+) -> Option<&'a Symbol> {
+    match node.kind() {
+        "identifier"
+        | "type_identifier"
+        | "property_identifier"
+        | "shorthand_property_identifier"
+        | "shorthand_property_identifier_pattern" => {}
+        _ => return None,
+    }
+    if let Some(id) = symbol_table.symbol_at_position(position) {
+        return symbol_table.get_symbol(id);
+    }
+
+    // Member names after a `.` are not in scope; only declarations count
+    let name = node.utf8_text(source.as_bytes()).ok()?;
+    let scope_id = symbol_table.scope_at_position(position);
+    let symbol_id = match node.kind() {
+        "property_identifier" => return None,
+        "type_identifier" => symbol_table.lookup_type(name, scope_id),
+        _ => symbol_table.lookup(name, scope_id),
+    }?;
+    symbol_table.get_symbol(symbol_id)
+}
+
+/// `const x: number`, `function f(a: string): void`, `type T = ...`
+fn symbol_declaration(symbol: &Symbol, checker: &TypeChecker) -> String {
+    let name = &symbol.name;
+    let flags = symbol.flags;
+    let ty = symbol
+        .inferred_type
+        .and_then(|id| checker.get_type(id))
+        .map(print_type);
+
+    if flags.intersects(SymbolFlags::FUNCTION | SymbolFlags::METHOD) {
+        let keyword = if flags.contains(SymbolFlags::METHOD) {
+            "(method)"
+        } else {
+            "function"
+        };
+        let label = match &symbol.signature {
+            Some(signature) => signature.label(name),
+            None => format!("{}()", name),
+        };
+        return format!("{} {}", keyword, label);
+    }
+
+    // Type declarations show their expanded definition
+    if flags.contains(SymbolFlags::INTERFACE) {
+        return match ty {
+            Some(ty) => format!("interface {} {}", name, ty),
+            None => format!("interface {}", name),
+        };
+    }
+    if flags.contains(SymbolFlags::TYPE_ALIAS) {
+        return format!("type {} = {}", name, ty.as_deref().unwrap_or("unknown"));
+    }
+
+    if flags.contains(SymbolFlags::CLASS) {
+        return format!("class {}", name);
+    }
+    if flags.contains(SymbolFlags::ENUM) {
+        return format!("enum {}", name);
+    }
+    if flags.contains(SymbolFlags::NAMESPACE) {
+        return format!("namespace {}", name);
+    }
+
+    let keyword = if flags.contains(SymbolFlags::TYPE_PARAMETER) {
+        "(type parameter)"
+    } else if flags.contains(SymbolFlags::IMPORT) {
+        "(alias)"
+    } else if flags.contains(SymbolFlags::PARAMETER) {
+        "(parameter)"
+    } else if flags.contains(SymbolFlags::PROPERTY) {
+        "(property)"
+    } else if flags.contains(SymbolFlags::ENUM_MEMBER) {
+        "(enum member)"
+    } else if flags.contains(SymbolFlags::CONST) {
+        "const"
+    } else if flags.contains(SymbolFlags::LET) {
+        "let"
+    } else {
+        "var"
+    };
+    match ty {
+        Some(ty) => format!("{} {}: {}", keyword, name, ty),
+        None => format!("{} {}", keyword, name),
+    }
 }
 
 /// Find JSDoc comment associated with a node
-fn find_jsdoc_comment(node: &tree_sitter::Node, source: &str) -> Option<String> {
+pub fn find_jsdoc_comment(node: &tree_sitter::Node, source: &str) -> Option<String> {
     // Look for comment in previous siblings or parent's previous siblings
     let mut current = *node;
 
@@ -86,6 +231,11 @@ fn find_jsdoc_comment(node: &tree_sitter::Node, source: &str) -> Option<String>
         }
     }
 
+    // The comment of an exported declaration precedes the `export`
+    if let Some(parent) = current.parent().filter(|p| p.kind() == "export_statement") {
+        current = parent;
+    }
+
     // Look for a comment before the declaration
     if let Some(prev) = current.prev_sibling() {
         if prev.kind() == "comment" {
@@ -182,6 +332,7 @@ fn get_display_kind(kind: &str, parent_kind: &str) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::analysis::binder::bind_document;
     use tree_sitter::Parser;
 
     fn parse_typescript(code: &str) -> Tree {
@@ -192,13 +343,28 @@ mod tests {
         parser.parse(code, None).unwrap()
     }
 
+    fn hover_at(code: &str, position: Position) -> Option<Hover> {
+        let tree = parse_typescript(code);
+        let mut symbol_table = bind_document(&tree, code);
+        let mut checker = TypeChecker::new();
+        checker.declare_types_from_tree(tree.root_node(), code);
+        checker.infer_symbol_types(&mut symbol_table, tree.root_node(), code);
+        get_hover(&tree, code, &symbol_table, &checker, position)
+    }
+
+    fn hover_text(code: &str, position: Position) -> String {
+        match hover_at(code, position).map(|hover| hover.contents) {
+            Some(HoverContents::Markup(content)) => content.value,
+            other => panic!("expected markup hover, got {:?}", other),
+        }
+    }
+
     #[test]
     fn test_hover_on_variable() {
         let code = "const myVar = 42;";
-        let tree = parse_typescript(code);
 
         // Hover on "myVar" (position 6)
-        let hover = get_hover(&tree, code, Position::new(0, 8));
+        let hover = hover_at(code, Position::new(0, 8));
         assert!(hover.is_some());
 
         let hover = hover.unwrap();
@@ -210,20 +376,18 @@ mod tests {
     #[test]
     fn test_hover_on_function() {
         let code = "function greet(name: string) { return name; }";
-        let tree = parse_typescript(code);
 
         // Hover on "greet"
-        let hover = get_hover(&tree, code, Position::new(0, 11));
+        let hover = hover_at(code, Position::new(0, 11));
         assert!(hover.is_some());
     }
 
     #[test]
     fn test_hover_on_number() {
         let code = "const x = 42;";
-        let tree = parse_typescript(code);
 
         // Hover on "42"
-        let hover = get_hover(&tree, code, Position::new(0, 10));
+        let hover = hover_at(code, Position::new(0, 10));
         assert!(hover.is_some());
 
         let hover = hover.unwrap();
@@ -235,10 +399,9 @@ mod tests {
     #[test]
     fn test_hover_on_string() {
         let code = r#"const x = "hello";"#;
-        let tree = parse_typescript(code);
 
         // Hover on "hello"
-        let hover = get_hover(&tree, code, Position::new(0, 12));
+        let hover = hover_at(code, Position::new(0, 12));
         assert!(hover.is_some());
 
         let hover = hover.unwrap();
@@ -250,9 +413,8 @@ mod tests {
     #[test]
     fn test_hover_returns_range() {
         let code = "const x = 42;";
-        let tree = parse_typescript(code);
 
-        let hover = get_hover(&tree, code, Position::new(0, 6)).unwrap();
+        let hover = hover_at(code, Position::new(0, 6)).unwrap();
         assert!(hover.range.is_some());
 
         let range = hover.range.unwrap();
@@ -265,10 +427,9 @@ mod tests {
 /** This is a greeting function */
 function greet() { }
 "#;
-        let tree = parse_typescript(code);
 
         // Hover on "greet"
-        let hover = get_hover(&tree, code, Position::new(2, 11));
+        let hover = hover_at(code, Position::new(2, 11));
         assert!(hover.is_some());
 
         let hover = hover.unwrap();
@@ -277,6 +438,52 @@ function greet() { }
         }
     }
 
+    #[test]
+    fn test_hover_shows_declared_and_inferred_types() {
+        let code = "const count: number = 1;\nlet label = \"a\";\nfunction greet(name: string): string { return name + count; }\n";
+
+        assert!(
+            hover_text(code, Position::new(0, 8))
+                .starts_with("```typescript\nconst count: number\n```")
+        );
+        assert!(hover_text(code, Position::new(1, 5)).contains("let label: string"));
+        assert!(
+            hover_text(code, Position::new(2, 11)).contains("function greet(name: string): string")
+        );
+        // References show the declaration they refer to
+        assert!(hover_text(code, Position::new(2, 47)).contains("(parameter) name: string"));
+        assert!(hover_text(code, Position::new(2, 55)).contains("const count: number"));
+    }
+
+    #[test]
+    fn test_hover_expands_type_definitions() {
+        let code = "type Id = string | number;\ninterface User { id: Id; }\nlet u: User;\n";
+
+        assert!(hover_text(code, Position::new(0, 6)).contains("type Id = string | number"));
+        let user = hover_text(code, Position::new(2, 8));
+        assert!(user.contains("interface User {"), "{}", user);
+        assert!(user.contains("id:"), "{}", user);
+    }
+
+    #[test]
+    fn test_hover_includes_symbol_documentation() {
+        let code =
+            "/** Adds one */\nexport function inc(n: number): number { return n + 1; }\ninc(1);\n";
+
+        let text = hover_text(code, Position::new(2, 1));
+        assert!(text.contains("function inc(n: number): number"), "{}", text);
+        assert!(text.ends_with("---\n\nAdds one"), "{}", text);
+
+        let plain = to_plain_text(hover_at(code, Position::new(2, 1)).unwrap());
+        match plain.contents {
+            HoverContents::Markup(content) => {
+                assert_eq!(content.kind, MarkupKind::PlainText);
+                assert_eq!(content.value, "function inc(n: number): number\n\nAdds one");
+            }
+            other => panic!("expected markup, got {:?}", other),
+        }
+    }
+
     #[test]
     fn test_get_display_kind_identifier() {
         assert_eq!(
@@ -348,30 +555,27 @@ function greet() { }
     #[test]
     fn test_hover_empty_position() {
         let code = "const x = 42;";
-        let tree = parse_typescript(code);
 
         // Even position 0,0 should return something
-        let hover = get_hover(&tree, code, Position::new(0, 0));
+        let hover = hover_at(code, Position::new(0, 0));
         assert!(hover.is_some());
     }
 
     #[test]
     fn test_hover_on_class() {
         let code = "class MyClass { }";
-        let tree = parse_typescript(code);
 
         // Hover on "MyClass"
-        let hover = get_hover(&tree, code, Position::new(0, 8));
+        let hover = hover_at(code, Position::new(0, 8));
         assert!(hover.is_some());
     }
 
     #[test]
     fn test_hover_on_interface() {
         let code = "interface IUser { name: string; }";
-        let tree = parse_typescript(code);
 
         // Hover on "IUser"
-        let hover = get_hover(&tree, code, Position::new(0, 12));
+        let hover = hover_at(code, Position::new(0, 12));
         assert!(hover.is_some());
     }
 
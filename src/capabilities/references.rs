@@ -1,6 +1,7 @@
 use tower_lsp::lsp_types::{Location, Position, Url};
 
 use crate::analysis::SymbolTable;
+use crate::line_index::LineIndex;
 
 /// Find all references to the symbol at the given position
 pub fn get_references(
@@ -61,32 +62,26 @@ fn find_identifier_at_position(source: &str, position: Position) -> Option<Strin
     }
 
     let line = lines[line_idx];
-    let col = position.character as usize;
-
-    if col > line.len() {
-        return None;
-    }
+    // LSP columns count UTF-16 code units; slicing needs a byte column
+    let col = LineIndex::new(source).point(position).column;
 
     // Find the start and end of the identifier
-    let chars: Vec<char> = line.chars().collect();
-
-    // Find start of identifier
-    let mut start = col;
-    while start > 0 && is_identifier_char(chars.get(start - 1).copied()) {
-        start -= 1;
-    }
-
-    // Find end of identifier
-    let mut end = col;
-    while end < chars.len() && is_identifier_char(chars.get(end).copied()) {
-        end += 1;
-    }
+    let start = line[..col]
+        .char_indices()
+        .rev()
+        .take_while(|&(_, c)| is_identifier_char(Some(c)))
+        .last()
+        .map_or(col, |(i, _)| i);
+    let end = line[col..]
+        .char_indices()
+        .find(|&(_, c)| !is_identifier_char(Some(c)))
+        .map_or(line.len(), |(i, _)| col + i);
 
     if start == end {
         return None;
     }
 
-    let identifier: String = chars[start..end].iter().collect();
+    let identifier = line[start..end].to_string();
 
     if is_valid_identifier(&identifier) {
         Some(identifier)
@@ -295,6 +290,14 @@ mod tests {
         assert!(result.is_none());
     }
 
+    #[test]
+    fn test_find_identifier_after_non_ascii_text() {
+        // UTF-16 column 28 is just after `total`: `😀` is two code units
+        let source = "const s = '😀é'; const total = 1;";
+        let result = find_identifier_at_position(source, Position::new(0, 28));
+        assert_eq!(result, Some("total".to_string()));
+    }
+
     #[test]
     fn test_references_all_have_same_uri() {
         let mut table = SymbolTable::new();
@@ -1,8 +1,10 @@
 use std::collections::HashMap;
 
 use tower_lsp::lsp_types::{Position, TextEdit, Url, WorkspaceEdit};
+use tree_sitter::Tree;
 
-use crate::analysis::SymbolTable;
+use crate::analysis::{SymbolId, SymbolTable};
+use crate::line_index::LineIndex;
 
 /// Prepare rename - check if renaming is valid at this position
 pub fn prepare_rename(
@@ -10,9 +12,7 @@ pub fn prepare_rename(
     source: &str,
     position: Position,
 ) -> Option<tower_lsp::lsp_types::Range> {
-    let identifier = find_identifier_at_position(source, position)?;
-    let scope_id = symbol_table.scope_at_position(position);
-    let symbol_id = symbol_table.lookup(&identifier, scope_id)?;
+    let symbol_id = symbol_at(symbol_table, source, position)?;
     let symbol = symbol_table.get_symbol(symbol_id)?;
 
     // Return the range of the identifier being renamed
@@ -21,15 +21,14 @@ pub fn prepare_rename(
 
 /// Rename the symbol at the given position
 pub fn rename_symbol(
+    tree: &Tree,
     symbol_table: &SymbolTable,
     source: &str,
     position: Position,
     new_name: &str,
     uri: &Url,
 ) -> Option<WorkspaceEdit> {
-    let identifier = find_identifier_at_position(source, position)?;
-    let scope_id = symbol_table.scope_at_position(position);
-    let symbol_id = symbol_table.lookup(&identifier, scope_id)?;
+    let symbol_id = symbol_at(symbol_table, source, position)?;
     let symbol = symbol_table.get_symbol(symbol_id)?;
 
     let mut edits = Vec::new();
@@ -40,11 +39,23 @@ pub fn rename_symbol(
         new_text: new_name.to_string(),
     });
 
-    // Edit all references
+    // Edit all references. A shorthand property `{ x }` keeps its
+    // property name and becomes `{ x: newName }`.
+    let index = LineIndex::new(source);
     for range in &symbol.references {
+        let point = index.point(range.start);
+        let is_shorthand = tree
+            .root_node()
+            .descendant_for_point_range(point, point)
+            .is_some_and(|node| node.kind() == "shorthand_property_identifier");
+        let new_text = if is_shorthand {
+            format!("{}: {}", symbol.name, new_name)
+        } else {
+            new_name.to_string()
+        };
         edits.push(TextEdit {
             range: *range,
-            new_text: new_name.to_string(),
+            new_text,
         });
     }
 
@@ -58,6 +69,17 @@ pub fn rename_symbol(
     })
 }
 
+/// The symbol named at a position: a statement label, or the declaration
+/// the identifier there resolves to
+fn symbol_at(symbol_table: &SymbolTable, source: &str, position: Position) -> Option<SymbolId> {
+    if let Some(label) = symbol_table.label_at_position(position) {
+        return Some(label);
+    }
+    let identifier = find_identifier_at_position(source, position)?;
+    let scope_id = symbol_table.scope_at_position(position);
+    symbol_table.lookup(&identifier, scope_id)
+}
+
 fn find_identifier_at_position(source: &str, position: Position) -> Option<String> {
     let lines: Vec<&str> = source.lines().collect();
     let line_idx = position.line as usize;
@@ -67,29 +89,26 @@ fn find_identifier_at_position(source: &str, position: Position) -> Option<Strin
     }
 
     let line = lines[line_idx];
-    let col = position.character as usize;
-
-    if col > line.len() {
-        return None;
-    }
-
-    let chars: Vec<char> = line.chars().collect();
-
-    let mut start = col;
-    while start > 0 && is_identifier_char(chars.get(start - 1).copied()) {
-        start -= 1;
-    }
-
-    let mut end = col;
-    while end < chars.len() && is_identifier_char(chars.get(end).copied()) {
-        end += 1;
-    }
+    // LSP columns count UTF-16 code units; slicing needs a byte column
+    let col = LineIndex::new(source).point(position).column;
+
+    // Find the start and end of the identifier
+    let start = line[..col]
+        .char_indices()
+        .rev()
+        .take_while(|&(_, c)| is_identifier_char(Some(c)))
+        .last()
+        .map_or(col, |(i, _)| i);
+    let end = line[col..]
+        .char_indices()
+        .find(|&(_, c)| !is_identifier_char(Some(c)))
+        .map_or(line.len(), |(i, _)| col + i);
 
     if start == end {
         return None;
     }
 
-    let identifier: String = chars[start..end].iter().collect();
+    let identifier = line[start..end].to_string();
 
     if is_valid_identifier(&identifier) {
         Some(identifier)
@@ -130,6 +149,14 @@ mod tests {
         Url::parse("file:///test/test.ts").unwrap()
     }
 
+    fn parse(source: &str) -> Tree {
+        let mut parser = tree_sitter::Parser::new();
+        parser
+            .set_language(&tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into())
+            .unwrap();
+        parser.parse(source, None).unwrap()
+    }
+
     #[test]
     fn test_is_identifier_char() {
         assert!(is_identifier_char(Some('a')));
@@ -173,6 +200,14 @@ mod tests {
         assert!(result.is_none());
     }
 
+    #[test]
+    fn test_find_identifier_after_non_ascii_text() {
+        // UTF-16 column 28 is just after `total`: `😀` is two code units
+        let source = "const s = '😀é'; const total = 1;";
+        let result = find_identifier_at_position(source, Position::new(0, 28));
+        assert_eq!(result, Some("total".to_string()));
+    }
+
     #[test]
     fn test_prepare_rename() {
         let mut table = SymbolTable::new();
@@ -212,7 +247,14 @@ mod tests {
 
         table.create_symbol("x".to_string(), SymbolFlags::VARIABLE, range, range, 0);
 
-        let result = rename_symbol(&table, source, Position::new(0, 6), "y", &uri);
+        let result = rename_symbol(
+            &parse(source),
+            &table,
+            source,
+            Position::new(0, 6),
+            "y",
+            &uri,
+        );
         assert!(result.is_some());
 
         let edit = result.unwrap();
@@ -250,7 +292,14 @@ mod tests {
         };
         table.add_reference(id, ref_range);
 
-        let result = rename_symbol(&table, source, Position::new(0, 6), "newName", &uri);
+        let result = rename_symbol(
+            &parse(source),
+            &table,
+            source,
+            Position::new(0, 6),
+            "newName",
+            &uri,
+        );
         assert!(result.is_some());
 
         let edit = result.unwrap();
@@ -261,13 +310,33 @@ mod tests {
         assert_eq!(file_edits.len(), 2);
     }
 
+    #[test]
+    fn test_rename_keeps_shorthand_property_names() {
+        let uri = create_test_uri();
+        let source = "const x = 1;\nconst o = { x };";
+        let tree = parse(source);
+        let table = crate::analysis::binder::bind_document(&tree, source);
+
+        let edit = rename_symbol(&tree, &table, source, Position::new(0, 6), "y", &uri).unwrap();
+        let changes = edit.changes.unwrap();
+        let texts: Vec<&str> = changes[&uri].iter().map(|e| e.new_text.as_str()).collect();
+        assert_eq!(texts, vec!["y", "x: y"]);
+    }
+
     #[test]
     fn test_rename_symbol_not_found() {
         let table = SymbolTable::new();
         let uri = create_test_uri();
         let source = "const x = unknownVar;";
 
-        let result = rename_symbol(&table, source, Position::new(0, 15), "newName", &uri);
+        let result = rename_symbol(
+            &parse(source),
+            &table,
+            source,
+            Position::new(0, 15),
+            "newName",
+            &uri,
+        );
         assert!(result.is_none());
     }
 }
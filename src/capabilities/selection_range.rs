@@ -1,61 +1,56 @@
 use tower_lsp::lsp_types::{Position, Range, SelectionRange};
 use tree_sitter::Tree;
 
+use crate::line_index::LineIndex;
+
 /// Get selection ranges for given positions (smart expand/shrink selection)
-pub fn get_selection_ranges(tree: &Tree, positions: &[Position]) -> Vec<SelectionRange> {
+pub fn get_selection_ranges(
+    tree: &Tree,
+    source: &str,
+    positions: &[Position],
+) -> Vec<SelectionRange> {
+    let index = LineIndex::new(source);
     positions
         .iter()
-        .filter_map(|pos| get_selection_range_at(tree, *pos))
+        .filter_map(|pos| get_selection_range_at(tree, &index, *pos))
         .collect()
 }
 
-fn get_selection_range_at(tree: &Tree, position: Position) -> Option<SelectionRange> {
+fn get_selection_range_at(
+    tree: &Tree,
+    index: &LineIndex,
+    position: Position,
+) -> Option<SelectionRange> {
     let root = tree.root_node();
-
-    let point = tree_sitter::Point {
-        row: position.line as usize,
-        column: position.character as usize,
-    };
+    let point = index.point(position);
 
     // Find the smallest node containing the position
     let node = root.descendant_for_point_range(point, point)?;
 
     // Build selection range hierarchy from innermost to outermost
-    build_selection_range(node)
+    build_selection_range(node, index)
 }
 
-fn build_selection_range(node: tree_sitter::Node) -> Option<SelectionRange> {
-    let range = node_to_range(&node);
-
-    // Get parent's selection range
-    let parent = if let Some(parent_node) = node.parent() {
-        // Skip nodes that have the same range as their child
-        if parent_node.start_position() == node.start_position()
-            && parent_node.end_position() == node.end_position()
-        {
-            // Use grandparent if parent has same range
-            if let Some(grandparent) = parent_node.parent() {
-                build_selection_range(grandparent).map(Box::new)
-            } else {
-                None
-            }
-        } else {
-            build_selection_range(parent_node).map(Box::new)
+fn build_selection_range(node: tree_sitter::Node, index: &LineIndex) -> Option<SelectionRange> {
+    // Collect the ancestor chain from innermost to outermost, skipping
+    // ancestors that span exactly the same range as the previous entry
+    let mut ranges: Vec<Range> = Vec::new();
+    let mut current = Some(node);
+    while let Some(n) = current {
+        let range = index.node_range(&n);
+        if ranges.last() != Some(&range) {
+            ranges.push(range);
         }
-    } else {
-        None
-    };
-
-    Some(SelectionRange { range, parent })
-}
-
-fn node_to_range(node: &tree_sitter::Node) -> Range {
-    let start = node.start_position();
-    let end = node.end_position();
-    Range {
-        start: Position::new(start.row as u32, start.column as u32),
-        end: Position::new(end.row as u32, end.column as u32),
+        current = n.parent();
     }
+
+    // Link from the outermost range inwards so each child points at its parent
+    ranges.into_iter().rev().fold(None, |parent, range| {
+        Some(SelectionRange {
+            range,
+            parent: parent.map(Box::new),
+        })
+    })
 }
 
 #[cfg(test)]
@@ -76,7 +71,7 @@ mod tests {
         let code = "const x = 42;";
         let tree = parse_typescript(code);
 
-        let ranges = get_selection_ranges(&tree, &[Position::new(0, 6)]);
+        let ranges = get_selection_ranges(&tree, code, &[Position::new(0, 6)]);
         assert!(!ranges.is_empty());
 
         let range = &ranges[0];
@@ -90,7 +85,7 @@ mod tests {
         let code = "const x = 42;";
         let tree = parse_typescript(code);
 
-        let ranges = get_selection_ranges(&tree, &[Position::new(0, 6)]);
+        let ranges = get_selection_ranges(&tree, code, &[Position::new(0, 6)]);
         assert!(!ranges.is_empty());
 
         // The selection range should have a parent
@@ -105,7 +100,7 @@ mod tests {
         let tree = parse_typescript(code);
 
         let positions = vec![Position::new(0, 6), Position::new(1, 6)];
-        let ranges = get_selection_ranges(&tree, &positions);
+        let ranges = get_selection_ranges(&tree, code, &positions);
 
         assert_eq!(ranges.len(), 2);
     }
@@ -120,7 +115,7 @@ mod tests {
         let tree = parse_typescript(code);
 
         // Position inside the if block
-        let ranges = get_selection_ranges(&tree, &[Position::new(2, 15)]);
+        let ranges = get_selection_ranges(&tree, code, &[Position::new(2, 15)]);
         assert!(!ranges.is_empty());
 
         // Should have multiple parent levels
@@ -141,7 +136,7 @@ mod tests {
         let code = "const x = 42;";
         let tree = parse_typescript(code);
 
-        let ranges = get_selection_ranges(&tree, &[]);
+        let ranges = get_selection_ranges(&tree, code, &[]);
         assert!(ranges.is_empty());
     }
 
@@ -153,7 +148,7 @@ mod tests {
         let tree = parse_typescript(code);
 
         // Position on "return"
-        let ranges = get_selection_ranges(&tree, &[Position::new(1, 6)]);
+        let ranges = get_selection_ranges(&tree, code, &[Position::new(1, 6)]);
         assert!(!ranges.is_empty());
     }
 
@@ -167,7 +162,7 @@ mod tests {
         let tree = parse_typescript(code);
 
         // Position on "this"
-        let ranges = get_selection_ranges(&tree, &[Position::new(2, 15)]);
+        let ranges = get_selection_ranges(&tree, code, &[Position::new(2, 15)]);
         assert!(!ranges.is_empty());
     }
 
@@ -177,7 +172,7 @@ mod tests {
         let tree = parse_typescript(code);
 
         // Position inside string
-        let ranges = get_selection_ranges(&tree, &[Position::new(0, 14)]);
+        let ranges = get_selection_ranges(&tree, code, &[Position::new(0, 14)]);
         assert!(!ranges.is_empty());
     }
 
@@ -187,7 +182,7 @@ mod tests {
         let tree = parse_typescript(code);
 
         // Position on middle element
-        let ranges = get_selection_ranges(&tree, &[Position::new(0, 16)]);
+        let ranges = get_selection_ranges(&tree, code, &[Position::new(0, 16)]);
         assert!(!ranges.is_empty());
     }
 
@@ -197,7 +192,7 @@ mod tests {
         let tree = parse_typescript(code);
 
         // Position on property
-        let ranges = get_selection_ranges(&tree, &[Position::new(0, 16)]);
+        let ranges = get_selection_ranges(&tree, code, &[Position::new(0, 16)]);
         assert!(!ranges.is_empty());
     }
 
@@ -207,7 +202,7 @@ mod tests {
         let code = "const x = 1 + 2;";
         let tree = parse_typescript(code);
 
-        let ranges = get_selection_ranges(&tree, &[Position::new(0, 10)]);
+        let ranges = get_selection_ranges(&tree, code, &[Position::new(0, 10)]);
         assert!(!ranges.is_empty());
 
         let range = &ranges[0];
@@ -220,4 +215,52 @@ mod tests {
             current = parent;
         }
     }
+
+    fn collect_ranges(range: &SelectionRange) -> Vec<Range> {
+        let mut result = vec![range.range];
+        let mut current = range;
+        while let Some(ref parent) = current.parent {
+            result.push(parent.range);
+            current = parent;
+        }
+        result
+    }
+
+    #[test]
+    fn test_selection_range_binary_expression_expands_to_statement() {
+        let code = "a + b;";
+        let tree = parse_typescript(code);
+
+        // Position on "a"
+        let ranges = get_selection_ranges(&tree, code, &[Position::new(0, 0)]);
+        assert_eq!(ranges.len(), 1);
+
+        let chain = collect_ranges(&ranges[0]);
+        // identifier -> binary_expression -> expression_statement (program has the same span)
+        assert_eq!(
+            chain,
+            vec![
+                Range::new(Position::new(0, 0), Position::new(0, 1)),
+                Range::new(Position::new(0, 0), Position::new(0, 5)),
+                Range::new(Position::new(0, 0), Position::new(0, 6)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_selection_range_strictly_nested() {
+        let code = r#"function test() {
+    const y = foo(a + b);
+}"#;
+        let tree = parse_typescript(code);
+
+        let ranges = get_selection_ranges(&tree, code, &[Position::new(1, 18)]);
+        let chain = collect_ranges(&ranges[0]);
+
+        for pair in chain.windows(2) {
+            let (child, parent) = (pair[0], pair[1]);
+            assert!(parent.start <= child.start && parent.end >= child.end);
+            assert_ne!(parent, child, "consecutive ranges must differ");
+        }
+    }
 }
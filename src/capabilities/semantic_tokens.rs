@@ -1,7 +1,16 @@
+use dashmap::DashMap;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
 use tower_lsp::lsp_types::{
-    SemanticToken, SemanticTokenModifier, SemanticTokenType, SemanticTokensLegend,
+    Range, SemanticToken, SemanticTokenModifier, SemanticTokenType, SemanticTokens,
+    SemanticTokensDelta, SemanticTokensEdit, SemanticTokensFullDeltaResult, SemanticTokensLegend,
+    Url,
 };
-use tree_sitter::Tree;
+use tree_sitter::{Node, Point, Tree};
+
+use crate::analysis::globals;
+use crate::analysis::{Symbol, SymbolFlags, SymbolTable};
+use crate::line_index::LineIndex;
 
 /// Token types supported by this language server
 pub const TOKEN_TYPES: &[SemanticTokenType] = &[
@@ -41,8 +50,13 @@ pub const TOKEN_MODIFIERS: &[SemanticTokenModifier] = &[
     SemanticTokenModifier::MODIFICATION,
     SemanticTokenModifier::DOCUMENTATION,
     SemanticTokenModifier::DEFAULT_LIBRARY,
+    DECORATOR,
 ];
 
+/// Modifier for the name of a decorator (`@Component`), not one of the
+/// standard LSP modifiers
+const DECORATOR: SemanticTokenModifier = SemanticTokenModifier::new("decorator");
+
 /// Get the semantic tokens legend for capability registration
 pub fn get_legend() -> SemanticTokensLegend {
     SemanticTokensLegend {
@@ -51,15 +65,52 @@ pub fn get_legend() -> SemanticTokensLegend {
     }
 }
 
-/// Extract semantic tokens from a parsed tree
-pub fn get_semantic_tokens(tree: &Tree, source: &str) -> Vec<SemanticToken> {
+/// Extract semantic tokens from a parsed tree, with modifiers taken from
+/// the symbols the identifiers resolve to
+pub fn get_semantic_tokens(
+    tree: &Tree,
+    source: &str,
+    symbol_table: &SymbolTable,
+) -> Vec<SemanticToken> {
+    let mut tokens = Vec::new();
+    let mut prev_line = 0u32;
+    let mut prev_start = 0u32;
+    let modifiers = ModifierResolver::new(source, symbol_table);
+
+    collect_tokens(
+        tree.root_node(),
+        source,
+        &modifiers,
+        None,
+        &mut tokens,
+        &mut prev_line,
+        &mut prev_start,
+    );
+
+    tokens
+}
+
+/// Extract the semantic tokens of the nodes intersecting `range`.
+/// Deltas are encoded as for the full document: the first token is
+/// relative to the start of the document, as the protocol requires.
+pub fn get_semantic_tokens_range(
+    tree: &Tree,
+    source: &str,
+    symbol_table: &SymbolTable,
+    range: Range,
+) -> Vec<SemanticToken> {
     let mut tokens = Vec::new();
     let mut prev_line = 0u32;
     let mut prev_start = 0u32;
+    let modifiers = ModifierResolver::new(source, symbol_table);
+    let index = LineIndex::new(source);
+    let window = (index.point(range.start), index.point(range.end));
 
     collect_tokens(
         tree.root_node(),
         source,
+        &modifiers,
+        Some(window),
         &mut tokens,
         &mut prev_line,
         &mut prev_start,
@@ -68,13 +119,248 @@ pub fn get_semantic_tokens(tree: &Tree, source: &str) -> Vec<SemanticToken> {
     tokens
 }
 
+/// The last tokens sent for each document, so `semanticTokens/full/delta`
+/// can answer with the edits since then
+pub struct SemanticTokensCache {
+    entries: DashMap<Url, (String, Vec<SemanticToken>)>,
+    next_id: AtomicU64,
+}
+
+impl SemanticTokensCache {
+    pub fn new() -> Self {
+        Self {
+            entries: DashMap::new(),
+            next_id: AtomicU64::new(1),
+        }
+    }
+
+    /// Remember the tokens sent for `uri`, returning their result id
+    pub fn store(&self, uri: &Url, tokens: Vec<SemanticToken>) -> String {
+        let result_id = self.next_id.fetch_add(1, Ordering::Relaxed).to_string();
+        self.entries
+            .insert(uri.clone(), (result_id.clone(), tokens));
+        result_id
+    }
+
+    /// The response to a delta request: edits against the tokens sent as
+    /// `previous_result_id`, or all tokens when those are no longer cached
+    pub fn delta(
+        &self,
+        uri: &Url,
+        previous_result_id: &str,
+        tokens: Vec<SemanticToken>,
+    ) -> SemanticTokensFullDeltaResult {
+        let delta = self
+            .entries
+            .get(uri)
+            .filter(|entry| entry.0 == previous_result_id)
+            .map(|entry| get_semantic_tokens_delta(&entry.1, &tokens));
+        let result_id = self.store(uri, tokens.clone());
+
+        match delta {
+            Some(delta) => SemanticTokensFullDeltaResult::TokensDelta(SemanticTokensDelta {
+                result_id: Some(result_id),
+                ..delta
+            }),
+            None => SemanticTokensFullDeltaResult::Tokens(SemanticTokens {
+                result_id: Some(result_id),
+                data: tokens,
+            }),
+        }
+    }
+
+    /// Forget the tokens of a closed document
+    pub fn remove(&self, uri: &Url) {
+        self.entries.remove(uri);
+    }
+}
+
+impl Default for SemanticTokensCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The delta from the tokens last sent to `new_tokens`, without a result id
+pub fn get_semantic_tokens_delta(
+    old_tokens: &[SemanticToken],
+    new_tokens: &[SemanticToken],
+) -> SemanticTokensDelta {
+    SemanticTokensDelta {
+        result_id: None,
+        edits: diff_tokens(old_tokens, new_tokens),
+    }
+}
+
+/// Edits turning `old` into `new`: a single replacement of everything
+/// between their common prefix and suffix. Offsets count the integers of
+/// the encoded array, five per token.
+pub fn diff_tokens(old: &[SemanticToken], new: &[SemanticToken]) -> Vec<SemanticTokensEdit> {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let deleted = old.len() - prefix - suffix;
+    let inserted = &new[prefix..new.len() - suffix];
+    if deleted == 0 && inserted.is_empty() {
+        return Vec::new();
+    }
+
+    vec![SemanticTokensEdit {
+        start: (prefix * 5) as u32,
+        delete_count: (deleted * 5) as u32,
+        data: (!inserted.is_empty()).then(|| inserted.to_vec()),
+    }]
+}
+
+/// Looks up the symbol behind an identifier token to derive its modifiers
+struct ModifierResolver<'a> {
+    source: &'a str,
+    index: LineIndex<'a>,
+    symbol_table: &'a SymbolTable,
+    /// Symbols by the start of their declared name
+    declarations: HashMap<(u32, u32), &'a Symbol>,
+}
+
+impl<'a> ModifierResolver<'a> {
+    fn new(source: &'a str, symbol_table: &'a SymbolTable) -> Self {
+        let declarations = symbol_table
+            .all_symbols()
+            .map(|symbol| {
+                let start = symbol.name_range.start;
+                ((start.line, start.character), symbol)
+            })
+            .collect();
+        Self {
+            source,
+            index: LineIndex::new(source),
+            symbol_table,
+            declarations,
+        }
+    }
+
+    /// Whether an identifier inside a type annotation names a declared type
+    fn names_type_in_annotation(&self, node: Node) -> bool {
+        let mut ancestor = node.parent();
+        while let Some(current) = ancestor {
+            match current.kind() {
+                // `typeof x` refers to the value
+                "type_query" => return false,
+                "type_annotation" => {
+                    let name = node.utf8_text(self.source.as_bytes()).unwrap_or("");
+                    let range = self.index.node_range(&node);
+                    let scope = self.symbol_table.scope_at_position(range.start);
+                    return self.symbol_table.lookup_type(name, scope).is_some();
+                }
+                _ => ancestor = current.parent(),
+            }
+        }
+        false
+    }
+
+    /// Modifier bitset for a token: the flags of the symbol an identifier,
+    /// member or type name resolves to. Other tokens have no modifiers.
+    fn compute_modifiers(&self, node: Node) -> u32 {
+        if !matches!(
+            node.kind(),
+            "identifier" | "property_identifier" | "type_identifier"
+        ) {
+            return 0;
+        }
+        let name = node.utf8_text(self.source.as_bytes()).unwrap_or("");
+        let range = self.index.node_range(&node);
+        let declared = self
+            .declarations
+            .get(&(range.start.line, range.start.character))
+            .filter(|symbol| symbol.name_range == range && symbol.name == name);
+
+        let mut bitset = 0;
+        // Class fields have no symbols, their modifiers are read off the
+        // field definition
+        if let Some(field) = node
+            .parent()
+            .filter(|p| p.kind() == "public_field_definition")
+            .filter(|p| p.child_by_field_name("name") == Some(node))
+        {
+            let mut cursor = field.walk();
+            for child in field.children(&mut cursor) {
+                match child.kind() {
+                    "readonly" => bitset |= modifier_bit(SemanticTokenModifier::READONLY),
+                    "static" => bitset |= modifier_bit(SemanticTokenModifier::STATIC),
+                    _ => {}
+                }
+            }
+            return bitset | modifier_bit(SemanticTokenModifier::DECLARATION);
+        }
+
+        let symbol = match declared {
+            Some(symbol) => {
+                bitset |= modifier_bit(SemanticTokenModifier::DECLARATION);
+                Some(*symbol)
+            }
+            // Member names only resolve through their declaration
+            None if node.kind() == "identifier" => {
+                let scope = self.symbol_table.scope_at_position(range.start);
+                self.symbol_table
+                    .lookup(name, scope)
+                    .and_then(|id| self.symbol_table.get_symbol(id))
+            }
+            // Classes and enums live among the values
+            None if node.kind() == "type_identifier" => {
+                let scope = self.symbol_table.scope_at_position(range.start);
+                self.symbol_table
+                    .lookup_type(name, scope)
+                    .or_else(|| self.symbol_table.lookup(name, scope))
+                    .and_then(|id| self.symbol_table.get_symbol(id))
+                    .filter(|symbol| symbol.is_type())
+            }
+            None => None,
+        };
+
+        let Some(symbol) = symbol else {
+            if node.kind() == "identifier" && globals::is_global(name) {
+                bitset |= modifier_bit(SemanticTokenModifier::DEFAULT_LIBRARY);
+            }
+            return bitset;
+        };
+        if symbol
+            .flags
+            .intersects(SymbolFlags::CONST | SymbolFlags::READONLY)
+        {
+            bitset |= modifier_bit(SemanticTokenModifier::READONLY);
+        }
+        if symbol.flags.contains(SymbolFlags::ABSTRACT) {
+            bitset |= modifier_bit(SemanticTokenModifier::ABSTRACT);
+        }
+        if symbol.flags.contains(SymbolFlags::STATIC) {
+            bitset |= modifier_bit(SemanticTokenModifier::STATIC);
+        }
+        if symbol.flags.contains(SymbolFlags::ASYNC) {
+            bitset |= modifier_bit(SemanticTokenModifier::ASYNC);
+        }
+        bitset
+    }
+}
+
 fn collect_tokens(
     node: tree_sitter::Node,
     source: &str,
+    modifiers: &ModifierResolver,
+    window: Option<(Point, Point)>,
     tokens: &mut Vec<SemanticToken>,
     prev_line: &mut u32,
     prev_start: &mut u32,
 ) {
+    // Nothing below a node outside the requested window is emitted
+    if let Some((start, end)) = window {
+        if node.end_position() < start || node.start_position() > end {
+            return;
+        }
+    }
+
     // Map tree-sitter node types to semantic token types
     let token_type_index = match node.kind() {
         // Keywords
@@ -88,8 +374,14 @@ fn collect_tokens(
             Some(token_type_idx(SemanticTokenType::KEYWORD))
         }
 
-        // Types
+        // Types, where `T` of `function f<T>` and its uses are type parameters
+        "type_identifier" | "identifier" if is_type_parameter_name(node, source) => {
+            Some(token_type_idx(SemanticTokenType::TYPE_PARAMETER))
+        }
         "type_identifier" | "predefined_type" => Some(token_type_idx(SemanticTokenType::TYPE)),
+        "identifier" if modifiers.names_type_in_annotation(node) => {
+            Some(token_type_idx(SemanticTokenType::TYPE))
+        }
 
         // Classes and interfaces
         "class_declaration" => None, // We handle the name child separately
@@ -97,6 +389,21 @@ fn collect_tokens(
         // Functions
         "function_declaration" | "method_definition" | "arrow_function" => None,
 
+        // Decorators are called, `@Component` is a function name
+        "identifier" | "property_identifier" if is_decorator_name(node) => {
+            Some(token_type_idx(SemanticTokenType::FUNCTION))
+        }
+
+        // JSX tags: `<Button>` references a component, `<div>` an intrinsic element
+        "identifier" | "property_identifier" if is_jsx_tag_name(node) => {
+            let name = node.utf8_text(source.as_bytes()).unwrap_or("");
+            if name.starts_with(|c: char| c.is_ascii_uppercase()) {
+                Some(token_type_idx(SemanticTokenType::CLASS))
+            } else {
+                Some(token_type_idx(SemanticTokenType::PROPERTY))
+            }
+        }
+
         // Identifiers - context dependent
         "identifier" => {
             // Check parent to determine the type
@@ -176,42 +483,142 @@ fn collect_tokens(
     if let Some(type_index) = token_type_index {
         let start = node.start_position();
         let end = node.end_position();
-        let line = start.row as u32;
-        let start_char = start.column as u32;
-        let length = if start.row == end.row {
-            (end.column - start.column) as u32
-        } else {
-            // For multi-line tokens, just use the text length
-            node.utf8_text(source.as_bytes())
-                .map(|s| s.len() as u32)
-                .unwrap_or(0)
-        };
+        let mut bitset = modifiers.compute_modifiers(node);
+        if is_decorator_name(node) {
+            bitset |= modifier_bit(DECORATOR);
+        }
 
-        // Calculate delta encoding
-        let delta_line = line - *prev_line;
-        let delta_start = if delta_line == 0 {
-            start_char - *prev_start
+        // Tokens cannot span lines, so a multi-line token (template string,
+        // block comment) becomes one token per line it covers
+        let pieces: Vec<(u32, u32, u32)> = if start.row == end.row {
+            vec![(
+                start.row as u32,
+                start.column as u32,
+                (end.column - start.column) as u32,
+            )]
         } else {
-            start_char
+            let text = node.utf8_text(source.as_bytes()).unwrap_or("");
+            text.split('\n')
+                .enumerate()
+                .map(|(i, line)| {
+                    let column = if i == 0 { start.column as u32 } else { 0 };
+                    let length = line.trim_end_matches('\r').len() as u32;
+                    ((start.row + i) as u32, column, length)
+                })
+                .filter(|&(_, _, length)| length > 0)
+                .filter(|&(line, _, _)| {
+                    window
+                        .is_none_or(|(start, end)| (start.row..=end.row).contains(&(line as usize)))
+                })
+                .collect()
         };
 
-        tokens.push(SemanticToken {
-            delta_line,
-            delta_start,
-            length,
-            token_type: type_index,
-            token_modifiers_bitset: 0, // No modifiers for now
-        });
+        for (line, start_char, length) in pieces {
+            // Calculate delta encoding
+            let delta_line = line - *prev_line;
+            let delta_start = if delta_line == 0 {
+                start_char - *prev_start
+            } else {
+                start_char
+            };
+
+            tokens.push(SemanticToken {
+                delta_line,
+                delta_start,
+                length,
+                token_type: type_index,
+                token_modifiers_bitset: bitset,
+            });
+
+            *prev_line = line;
+            *prev_start = start_char;
+        }
 
-        *prev_line = line;
-        *prev_start = start_char;
+        // The lines of a split token are covered, tokens for its children
+        // would overlap them out of order
+        if start.row != end.row {
+            return;
+        }
     }
 
     // Recurse into children
     let mut cursor = node.walk();
     for child in node.children(&mut cursor) {
-        collect_tokens(child, source, tokens, prev_line, prev_start);
+        collect_tokens(
+            child, source, modifiers, window, tokens, prev_line, prev_start,
+        );
+    }
+}
+
+/// Whether `node` is a type parameter's name, in its declaration
+/// (`<T>`) or where the enclosing declaration uses it (`x: T`)
+fn is_type_parameter_name(node: Node, source: &str) -> bool {
+    if node.parent().is_some_and(|p| p.kind() == "type_parameter") {
+        return true;
     }
+    if node.kind() != "type_identifier" {
+        return false;
+    }
+    let name = node.utf8_text(source.as_bytes()).unwrap_or("");
+    let mut ancestor = node.parent();
+    while let Some(current) = ancestor {
+        if let Some(parameters) = current.child_by_field_name("type_parameters") {
+            let mut cursor = parameters.walk();
+            let declared = parameters
+                .named_children(&mut cursor)
+                .filter_map(|parameter| parameter.child_by_field_name("name"))
+                .any(|declared| declared.utf8_text(source.as_bytes()) == Ok(name));
+            if declared {
+                return true;
+            }
+        }
+        ancestor = current.parent();
+    }
+    false
+}
+
+/// Whether `node` names the element of a JSX tag: `Button` in `<Button />`,
+/// or `Bar` in `<Foo.Bar>`
+fn is_jsx_tag_name(node: Node) -> bool {
+    let mut current = node;
+    if let Some(member) = current
+        .parent()
+        .filter(|p| p.kind() == "member_expression")
+        .filter(|p| p.child_by_field_name("property") == Some(current))
+    {
+        current = member;
+    }
+    current.parent().is_some_and(|p| {
+        matches!(
+            p.kind(),
+            "jsx_opening_element" | "jsx_closing_element" | "jsx_self_closing_element"
+        ) && p.child_by_field_name("name") == Some(current)
+    })
+}
+
+/// Whether `node` names the function a decorator applies: `d` in `@d`,
+/// `@d(...)`, `@ns.d` or `@ns.d(...)`
+fn is_decorator_name(node: Node) -> bool {
+    if !matches!(node.kind(), "identifier" | "property_identifier") {
+        return false;
+    }
+    let mut current = node;
+    // Up from the property of `ns.d`, then from the callee of `d(...)`
+    if let Some(member) = current
+        .parent()
+        .filter(|p| p.kind() == "member_expression")
+        .filter(|p| p.child_by_field_name("property") == Some(current))
+    {
+        current = member;
+    }
+    if let Some(call) = current
+        .parent()
+        .filter(|p| p.kind() == "call_expression")
+        .filter(|p| p.child_by_field_name("function") == Some(current))
+    {
+        current = call;
+    }
+    current.parent().is_some_and(|p| p.kind() == "decorator")
 }
 
 fn token_type_idx(token_type: SemanticTokenType) -> u32 {
@@ -221,9 +628,17 @@ fn token_type_idx(token_type: SemanticTokenType) -> u32 {
         .unwrap_or(0) as u32
 }
 
+fn modifier_bit(modifier: SemanticTokenModifier) -> u32 {
+    TOKEN_MODIFIERS
+        .iter()
+        .position(|m| *m == modifier)
+        .map_or(0, |i| 1 << i)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::analysis::binder::bind_document;
     use tree_sitter::Parser;
 
     fn parse_typescript(code: &str) -> Tree {
@@ -265,7 +680,7 @@ mod tests {
     fn test_semantic_tokens_keywords() {
         let code = "const x = 1;";
         let tree = parse_typescript(code);
-        let tokens = get_semantic_tokens(&tree, code);
+        let tokens = get_semantic_tokens(&tree, code, &bind_document(&tree, code));
 
         // Should have a token for "const" keyword
         let keyword_idx = token_type_idx(SemanticTokenType::KEYWORD);
@@ -276,7 +691,7 @@ mod tests {
     fn test_semantic_tokens_variable() {
         let code = "const myVar = 42;";
         let tree = parse_typescript(code);
-        let tokens = get_semantic_tokens(&tree, code);
+        let tokens = get_semantic_tokens(&tree, code, &bind_document(&tree, code));
 
         let var_idx = token_type_idx(SemanticTokenType::VARIABLE);
         assert!(tokens.iter().any(|t| t.token_type == var_idx));
@@ -286,7 +701,7 @@ mod tests {
     fn test_semantic_tokens_function() {
         let code = "function greet() { }";
         let tree = parse_typescript(code);
-        let tokens = get_semantic_tokens(&tree, code);
+        let tokens = get_semantic_tokens(&tree, code, &bind_document(&tree, code));
 
         let func_idx = token_type_idx(SemanticTokenType::FUNCTION);
         let keyword_idx = token_type_idx(SemanticTokenType::KEYWORD);
@@ -300,7 +715,7 @@ mod tests {
     fn test_semantic_tokens_class() {
         let code = "class MyClass { }";
         let tree = parse_typescript(code);
-        let tokens = get_semantic_tokens(&tree, code);
+        let tokens = get_semantic_tokens(&tree, code, &bind_document(&tree, code));
 
         // Class keyword should be tokenized
         let keyword_idx = token_type_idx(SemanticTokenType::KEYWORD);
@@ -313,7 +728,7 @@ mod tests {
     fn test_semantic_tokens_interface() {
         let code = "interface IUser { }";
         let tree = parse_typescript(code);
-        let tokens = get_semantic_tokens(&tree, code);
+        let tokens = get_semantic_tokens(&tree, code, &bind_document(&tree, code));
 
         // Interface keyword should be tokenized
         let keyword_idx = token_type_idx(SemanticTokenType::KEYWORD);
@@ -325,7 +740,7 @@ mod tests {
     fn test_semantic_tokens_string() {
         let code = r#"const s = "hello";"#;
         let tree = parse_typescript(code);
-        let tokens = get_semantic_tokens(&tree, code);
+        let tokens = get_semantic_tokens(&tree, code, &bind_document(&tree, code));
 
         let string_idx = token_type_idx(SemanticTokenType::STRING);
         assert!(tokens.iter().any(|t| t.token_type == string_idx));
@@ -335,7 +750,7 @@ mod tests {
     fn test_semantic_tokens_number() {
         let code = "const n = 42;";
         let tree = parse_typescript(code);
-        let tokens = get_semantic_tokens(&tree, code);
+        let tokens = get_semantic_tokens(&tree, code, &bind_document(&tree, code));
 
         let number_idx = token_type_idx(SemanticTokenType::NUMBER);
         assert!(tokens.iter().any(|t| t.token_type == number_idx));
@@ -345,7 +760,7 @@ mod tests {
     fn test_semantic_tokens_comment() {
         let code = "// This is a comment\nconst x = 1;";
         let tree = parse_typescript(code);
-        let tokens = get_semantic_tokens(&tree, code);
+        let tokens = get_semantic_tokens(&tree, code, &bind_document(&tree, code));
 
         let comment_idx = token_type_idx(SemanticTokenType::COMMENT);
         assert!(tokens.iter().any(|t| t.token_type == comment_idx));
@@ -355,7 +770,7 @@ mod tests {
     fn test_semantic_tokens_method() {
         let code = r#"class C { method() { } }"#;
         let tree = parse_typescript(code);
-        let tokens = get_semantic_tokens(&tree, code);
+        let tokens = get_semantic_tokens(&tree, code, &bind_document(&tree, code));
 
         // Should have tokens for class and method
         assert!(!tokens.is_empty());
@@ -371,7 +786,7 @@ mod tests {
     fn test_semantic_tokens_parameter() {
         let code = "function test(x: number) { }";
         let tree = parse_typescript(code);
-        let tokens = get_semantic_tokens(&tree, code);
+        let tokens = get_semantic_tokens(&tree, code, &bind_document(&tree, code));
 
         let param_idx = token_type_idx(SemanticTokenType::PARAMETER);
         assert!(tokens.iter().any(|t| t.token_type == param_idx));
@@ -381,7 +796,7 @@ mod tests {
     fn test_semantic_tokens_type() {
         let code = "const x: string = 'hello';";
         let tree = parse_typescript(code);
-        let tokens = get_semantic_tokens(&tree, code);
+        let tokens = get_semantic_tokens(&tree, code, &bind_document(&tree, code));
 
         let type_idx = token_type_idx(SemanticTokenType::TYPE);
         assert!(tokens.iter().any(|t| t.token_type == type_idx));
@@ -391,7 +806,7 @@ mod tests {
     fn test_semantic_tokens_property() {
         let code = "const obj = { prop: 1 };";
         let tree = parse_typescript(code);
-        let tokens = get_semantic_tokens(&tree, code);
+        let tokens = get_semantic_tokens(&tree, code, &bind_document(&tree, code));
 
         let prop_idx = token_type_idx(SemanticTokenType::PROPERTY);
         assert!(tokens.iter().any(|t| t.token_type == prop_idx));
@@ -401,7 +816,7 @@ mod tests {
     fn test_semantic_tokens_delta_encoding() {
         let code = "const a = 1;\nconst b = 2;";
         let tree = parse_typescript(code);
-        let tokens = get_semantic_tokens(&tree, code);
+        let tokens = get_semantic_tokens(&tree, code, &bind_document(&tree, code));
 
         // Delta encoding should produce reasonable values
         // (delta_line is u32, so always >= 0)
@@ -417,7 +832,7 @@ mod tests {
     fn test_semantic_tokens_empty_code() {
         let code = "";
         let tree = parse_typescript(code);
-        let tokens = get_semantic_tokens(&tree, code);
+        let tokens = get_semantic_tokens(&tree, code, &bind_document(&tree, code));
         assert!(tokens.is_empty());
     }
 
@@ -435,7 +850,7 @@ mod tests {
             }
         "#;
         let tree = parse_typescript(code);
-        let tokens = get_semantic_tokens(&tree, code);
+        let tokens = get_semantic_tokens(&tree, code, &bind_document(&tree, code));
 
         // Should have tokens for multiple types
         assert!(!tokens.is_empty());
@@ -445,7 +860,7 @@ mod tests {
     fn test_semantic_tokens_function_call() {
         let code = "console.log('hello');";
         let tree = parse_typescript(code);
-        let tokens = get_semantic_tokens(&tree, code);
+        let tokens = get_semantic_tokens(&tree, code, &bind_document(&tree, code));
 
         // Should tokenize function calls
         assert!(!tokens.is_empty());
@@ -455,7 +870,7 @@ mod tests {
     fn test_semantic_tokens_arrow_function() {
         let code = "const fn = (x: number) => x * 2;";
         let tree = parse_typescript(code);
-        let tokens = get_semantic_tokens(&tree, code);
+        let tokens = get_semantic_tokens(&tree, code, &bind_document(&tree, code));
 
         let param_idx = token_type_idx(SemanticTokenType::PARAMETER);
         assert!(tokens.iter().any(|t| t.token_type == param_idx));
@@ -465,9 +880,244 @@ mod tests {
     fn test_semantic_tokens_regex() {
         let code = "const re = /test/g;";
         let tree = parse_typescript(code);
-        let tokens = get_semantic_tokens(&tree, code);
+        let tokens = get_semantic_tokens(&tree, code, &bind_document(&tree, code));
 
         let regex_idx = token_type_idx(SemanticTokenType::REGEXP);
         assert!(tokens.iter().any(|t| t.token_type == regex_idx));
     }
+
+    /// The token type and modifiers of the token starting at `column`
+    fn token_at(code: &str, line: u32, column: u32) -> (u32, u32) {
+        token_in(&parse_typescript(code), code, line, column)
+    }
+
+    fn token_in(tree: &Tree, code: &str, line: u32, column: u32) -> (u32, u32) {
+        let tokens = get_semantic_tokens(tree, code, &bind_document(tree, code));
+        let (mut current_line, mut current_start) = (0, 0);
+        for token in tokens {
+            current_line += token.delta_line;
+            current_start = if token.delta_line == 0 {
+                current_start + token.delta_start
+            } else {
+                token.delta_start
+            };
+            if (current_line, current_start) == (line, column) {
+                return (token.token_type, token.token_modifiers_bitset);
+            }
+        }
+        panic!("no token at {}:{}", line, column);
+    }
+
+    #[test]
+    fn test_semantic_token_modifiers_const_declaration() {
+        let (token_type, bitset) = token_at("const x = 1;", 0, 6);
+        assert_eq!(token_type, token_type_idx(SemanticTokenType::VARIABLE));
+        assert_eq!(
+            bitset,
+            modifier_bit(SemanticTokenModifier::READONLY)
+                | modifier_bit(SemanticTokenModifier::DECLARATION)
+        );
+
+        // A use of the constant is readonly but not a declaration
+        let code = "const x = 1;\nlet y = f(x);";
+        let (_, bitset) = token_at(code, 1, 10);
+        assert_eq!(bitset, modifier_bit(SemanticTokenModifier::READONLY));
+        let (_, bitset) = token_at(code, 1, 4);
+        assert_eq!(bitset, modifier_bit(SemanticTokenModifier::DECLARATION));
+    }
+
+    #[test]
+    fn test_semantic_token_modifiers_members_and_globals() {
+        let code = "class C {\n  static async load() {}\n  readonly id = 1;\n}\nasync function f() {}\nconsole.log(1);";
+        let declaration = modifier_bit(SemanticTokenModifier::DECLARATION);
+
+        let (_, bitset) = token_at(code, 1, 15);
+        assert_eq!(
+            bitset,
+            declaration
+                | modifier_bit(SemanticTokenModifier::STATIC)
+                | modifier_bit(SemanticTokenModifier::ASYNC)
+        );
+        let (_, bitset) = token_at(code, 2, 11);
+        assert_eq!(
+            bitset,
+            declaration | modifier_bit(SemanticTokenModifier::READONLY)
+        );
+        let (_, bitset) = token_at(code, 4, 15);
+        assert_eq!(
+            bitset,
+            declaration | modifier_bit(SemanticTokenModifier::ASYNC)
+        );
+        let (_, bitset) = token_at(code, 5, 0);
+        assert_eq!(bitset, modifier_bit(SemanticTokenModifier::DEFAULT_LIBRARY));
+    }
+
+    #[test]
+    fn test_semantic_tokens_multiline_template_string() {
+        let code = "const s = `first\n  second`;\nlet y;";
+        let tree = parse_typescript(code);
+        let tokens = get_semantic_tokens(&tree, code, &bind_document(&tree, code));
+
+        let string_idx = token_type_idx(SemanticTokenType::STRING);
+        let strings: Vec<_> = tokens
+            .iter()
+            .filter(|t| t.token_type == string_idx)
+            .map(|t| (t.delta_line, t.delta_start, t.length))
+            .collect();
+        // `first starts at column 10, the second line is `  second` up to the backtick
+        assert_eq!(strings, vec![(0, 4, 6), (1, 0, 9)]);
+
+        // Tokens after the template string continue from its last line
+        let keyword_idx = token_type_idx(SemanticTokenType::KEYWORD);
+        let last_keyword = tokens
+            .iter()
+            .rev()
+            .find(|t| t.token_type == keyword_idx)
+            .unwrap();
+        assert_eq!((last_keyword.delta_line, last_keyword.delta_start), (1, 0));
+    }
+
+    #[test]
+    fn test_semantic_tokens_range() {
+        let code: String = (0..100).map(|i| format!("let v{} = {};\n", i, i)).collect();
+        let tree = parse_typescript(&code);
+        let table = bind_document(&tree, &code);
+        let range = Range::new(
+            tower_lsp::lsp_types::Position::new(40, 0),
+            tower_lsp::lsp_types::Position::new(44, 100),
+        );
+        let tokens = get_semantic_tokens_range(&tree, &code, &table, range);
+
+        // `let`, the name and the number on each of the 5 lines
+        assert_eq!(tokens.len(), 15);
+        let mut line = 0;
+        for token in &tokens {
+            line += token.delta_line;
+            assert!((40..=44).contains(&line));
+        }
+        // The first token is relative to the start of the document
+        assert_eq!(tokens[0].delta_line, 40);
+        assert_eq!(line, 44);
+    }
+
+    #[test]
+    fn test_semantic_tokens_delta() {
+        let uri = Url::parse("file:///test.ts").unwrap();
+        let tokens = |code: &str| {
+            let tree = parse_typescript(code);
+            get_semantic_tokens(&tree, code, &bind_document(&tree, code))
+        };
+        let before = tokens("let a = 1;\nlet b = 2;\nlet c = 3;");
+        // Only the number on the middle line changes
+        let after = tokens("let a = 1;\nlet b = 20;\nlet c = 3;");
+
+        let cache = SemanticTokensCache::new();
+        let result_id = cache.store(&uri, before.clone());
+        let SemanticTokensFullDeltaResult::TokensDelta(delta) =
+            cache.delta(&uri, &result_id, after.clone())
+        else {
+            panic!("expected a delta");
+        };
+        assert_ne!(delta.result_id.as_deref(), Some(result_id.as_str()));
+        assert_eq!(delta.edits.len(), 1);
+        let edit = &delta.edits[0];
+        assert_eq!(edit.start, 5 * 5);
+        assert_eq!(edit.delete_count, 5);
+        assert_eq!(edit.data.as_deref(), Some(&after[5..6]));
+
+        // An unknown result id falls back to the full token set
+        assert!(matches!(
+            cache.delta(&uri, "stale", after.clone()),
+            SemanticTokensFullDeltaResult::Tokens(_)
+        ));
+        assert!(diff_tokens(&after, &after).is_empty());
+
+        // Dropping the last line deletes its tokens and inserts nothing
+        let delta = get_semantic_tokens_delta(&before, &before[..6]);
+        assert_eq!(delta.edits.len(), 1);
+        assert_eq!(
+            (delta.edits[0].start, delta.edits[0].delete_count),
+            (6 * 5, 3 * 5)
+        );
+        assert!(delta.edits[0].data.is_none());
+    }
+
+    #[test]
+    fn test_semantic_token_modifiers_type_names() {
+        let code = "abstract class Base {}\nlet b: Base;\ninterface Shape {}";
+        let declaration = modifier_bit(SemanticTokenModifier::DECLARATION);
+
+        let (token_type, bitset) = token_at(code, 0, 15);
+        assert_eq!(token_type, token_type_idx(SemanticTokenType::TYPE));
+        assert_eq!(
+            bitset,
+            declaration | modifier_bit(SemanticTokenModifier::ABSTRACT)
+        );
+        // A use of the class in a type position
+        let (_, bitset) = token_at(code, 1, 7);
+        assert_eq!(bitset, modifier_bit(SemanticTokenModifier::ABSTRACT));
+        let (_, bitset) = token_at(code, 2, 10);
+        assert_eq!(bitset, declaration);
+        // Keywords carry no modifiers
+        let (_, bitset) = token_at(code, 1, 0);
+        assert_eq!(bitset, 0);
+    }
+
+    #[test]
+    fn test_semantic_tokens_decorators() {
+        let code = "@Component({ selector: 'app-root' })\nclass AppComponent {\n  @Input() name: string;\n  @core.Output value: number;\n}";
+        let function_idx = token_type_idx(SemanticTokenType::FUNCTION);
+        let decorator = modifier_bit(DECORATOR);
+
+        for (line, column) in [(0, 1), (2, 3), (3, 8)] {
+            let (token_type, bitset) = token_at(code, line, column);
+            assert_eq!(token_type, function_idx);
+            assert_eq!(bitset & decorator, decorator);
+        }
+        // Arguments keep their own token types
+        let (token_type, bitset) = token_at(code, 0, 13);
+        assert_eq!(token_type, token_type_idx(SemanticTokenType::PROPERTY));
+        assert_eq!(bitset & decorator, 0);
+        // The namespace of `@core.Output` is not the decorator's name
+        let (_, bitset) = token_at(code, 3, 3);
+        assert_eq!(bitset & decorator, 0);
+        assert!(get_legend().token_modifiers.contains(&DECORATOR));
+    }
+
+    #[test]
+    fn test_semantic_tokens_type_parameters() {
+        let code = "interface Box { v: number }\nfunction f<T>(x: T, b: Box): T[] { return [x]; }\nlet T = 1;";
+        let type_parameter = token_type_idx(SemanticTokenType::TYPE_PARAMETER);
+
+        // The declaration and both uses of `T`
+        for column in [11, 17, 29] {
+            assert_eq!(token_at(code, 1, column).0, type_parameter);
+        }
+        assert_eq!(
+            token_at(code, 1, 23).0,
+            token_type_idx(SemanticTokenType::TYPE)
+        );
+        // A value named `T` outside of `f` is a variable
+        assert_eq!(
+            token_at(code, 2, 4).0,
+            token_type_idx(SemanticTokenType::VARIABLE)
+        );
+    }
+
+    #[test]
+    fn test_semantic_tokens_jsx() {
+        let code = "const a = <div className=\"x\" onClick={f}><Button /></div>;";
+        let mut parser = Parser::new();
+        parser
+            .set_language(&tree_sitter_typescript::LANGUAGE_TSX.into())
+            .unwrap();
+        let tree = parser.parse(code, None).unwrap();
+        let token = |column| token_in(&tree, code, 0, column).0;
+
+        assert_eq!(token(11), token_type_idx(SemanticTokenType::PROPERTY));
+        assert_eq!(token(15), token_type_idx(SemanticTokenType::PROPERTY));
+        assert_eq!(token(29), token_type_idx(SemanticTokenType::PROPERTY));
+        assert_eq!(token(42), token_type_idx(SemanticTokenType::CLASS));
+        assert_eq!(token(53), token_type_idx(SemanticTokenType::PROPERTY));
+    }
 }
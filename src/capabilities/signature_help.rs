@@ -1,9 +1,14 @@
 use tower_lsp::lsp_types::{
     ParameterInformation, ParameterLabel, Position, SignatureHelp, SignatureInformation,
 };
-use tree_sitter::{Node, Tree};
+use tree_sitter::{Node, Point, Tree};
 
-use crate::analysis::{SymbolFlags, SymbolTable};
+use crate::analysis::binder::signature_of;
+use crate::analysis::{Signature, Symbol, SymbolFlags, SymbolTable};
+use crate::line_index::LineIndex;
+use crate::types::printer::print_type;
+use crate::types::types::FunctionType;
+use crate::types::{Type, TypeChecker, TypeFlags};
 
 /// Get signature help at a position
 pub fn get_signature_help(
@@ -13,11 +18,8 @@ pub fn get_signature_help(
     position: Position,
 ) -> Option<SignatureHelp> {
     let root = tree.root_node();
-
-    let point = tree_sitter::Point {
-        row: position.line as usize,
-        column: position.character as usize,
-    };
+    let index = LineIndex::new(source);
+    let point = index.point(position);
 
     // Find the call expression at or containing the position
     let node = root.descendant_for_point_range(point, point)?;
@@ -25,15 +27,23 @@ pub fn get_signature_help(
     // Look for a call expression in ancestors
     let call_node = find_call_expression(&node)?;
 
-    // Get the function being called
-    let function_node = call_node.child_by_field_name("function")?;
+    // Get the function or constructor being called
+    let is_new = call_node.kind() == "new_expression";
+    let function_field = if is_new { "constructor" } else { "function" };
+    let function_node = call_node.child_by_field_name(function_field)?;
     let function_name = function_node.utf8_text(source.as_bytes()).ok()?;
 
-    // Handle built-in functions
-    if let Some(help) = get_builtin_signature_help(function_name) {
+    // Handle built-in functions, constructors and methods of built-in objects
+    let builtin_name = if is_new {
+        format!("new {}", function_name)
+    } else {
+        builtin_method_name(&function_node, tree, &index, source, symbol_table)
+            .unwrap_or_else(|| function_name.to_string())
+    };
+    if let Some(help) = get_builtin_signature_help(&builtin_name) {
         // Determine active parameter
         let args_node = call_node.child_by_field_name("arguments")?;
-        let active_param = count_args_before_position(&args_node, position);
+        let active_param = count_args_before_position(&args_node, point);
 
         return Some(SignatureHelp {
             signatures: vec![help],
@@ -51,25 +61,50 @@ pub fn get_signature_help(
 
     if let Some(symbol_id) = symbol_table.lookup(function_base, scope_id) {
         if let Some(symbol) = symbol_table.get_symbol(symbol_id) {
-            if symbol
-                .flags
-                .intersects(SymbolFlags::FUNCTION | SymbolFlags::METHOD)
-            {
+            if !symbol.overloads.is_empty() {
+                let args_node = call_node.child_by_field_name("arguments")?;
+                return Some(get_overload_signature_help(
+                    symbol, &args_node, source, point,
+                ));
+            }
+
+            if let Some(signature) = user_signature(symbol, tree, source, &index) {
                 let args_node = call_node.child_by_field_name("arguments")?;
-                let active_param = count_args_before_position(&args_node, position);
+                let type_arguments = type_arguments_of(&call_node, source);
+                // Explicit type arguments change the label, so the offsets
+                // have to come from the instantiated signature
+                let (signature, parameters) = if type_arguments.is_empty() {
+                    let parameters = extract_function_parameters(symbol, tree, source);
+                    (signature, parameters)
+                } else {
+                    let signature = signature.instantiate(&type_arguments);
+                    let parameters = parameter_information(&signature, &symbol.name);
+                    (signature, parameters)
+                };
+
+                // Type parameters come first in the parameter list
+                let mut active_param =
+                    signature.type_parameters.len() + count_args_before_position(&args_node, point);
+
+                // Extra arguments all belong to a trailing rest parameter
+                if signature.parameters.last().is_some_and(|p| p.is_rest()) {
+                    active_param = active_param.min(parameters.len() - 1);
+                }
+                let active_param =
+                    (!signature.parameters.is_empty()).then_some(active_param as u32);
 
                 return Some(SignatureHelp {
                     signatures: vec![SignatureInformation {
-                        label: format!("{}()", symbol.name),
+                        label: signature.label(&symbol.name),
                         documentation: symbol
                             .documentation
                             .clone()
                             .map(tower_lsp::lsp_types::Documentation::String),
-                        parameters: None, // Would need function signature analysis
-                        active_parameter: Some(active_param as u32),
+                        parameters: Some(parameters),
+                        active_parameter: active_param,
                     }],
                     active_signature: Some(0),
-                    active_parameter: Some(active_param as u32),
+                    active_parameter: active_param,
                 });
             }
         }
@@ -78,42 +113,341 @@ pub fn get_signature_help(
     None
 }
 
-/// Find a call expression in the node's ancestors
-fn find_call_expression<'a>(node: &'a Node<'a>) -> Option<Node<'a>> {
-    let mut current = *node;
+/// Find the function-like node that declares `symbol`: a function or method
+/// declaration, or the function assigned to a variable
+fn function_node<'t>(symbol: &Symbol, tree: &'t Tree, index: &LineIndex) -> Option<Node<'t>> {
+    if !symbol
+        .flags
+        .intersects(SymbolFlags::FUNCTION | SymbolFlags::METHOD | SymbolFlags::VARIABLE)
+    {
+        return None;
+    }
 
-    loop {
-        if current.kind() == "call_expression" {
-            return Some(current);
+    let range = symbol.declaration_range;
+    let start = index.point(range.start);
+    let end = index.point(range.end);
+    let mut node = tree.root_node().descendant_for_point_range(start, end)?;
+
+    if symbol.flags.contains(SymbolFlags::VARIABLE) {
+        let declarator = node
+            .parent()
+            .filter(|p| p.kind() == "variable_declarator")?;
+        return declarator.child_by_field_name("value").filter(|value| {
+            matches!(
+                value.kind(),
+                "arrow_function" | "function_expression" | "function"
+            )
+        });
+    }
+
+    while !matches!(
+        node.kind(),
+        "function_declaration" | "method_definition" | "generator_function_declaration"
+    ) {
+        node = node.parent()?;
+    }
+    Some(node)
+}
+
+/// `Response.json` for `response.json` when the receiver is known to be a
+/// `Response`, so it can be looked up among the built-in signatures
+fn builtin_method_name(
+    function_node: &Node,
+    tree: &Tree,
+    index: &LineIndex,
+    source: &str,
+    symbol_table: &SymbolTable,
+) -> Option<String> {
+    if function_node.kind() != "member_expression" {
+        return None;
+    }
+    let object = function_node.child_by_field_name("object")?;
+    let property = function_node.child_by_field_name("property")?;
+    let property = property.utf8_text(source.as_bytes()).ok()?;
+
+    let is_response = if object.kind() == "identifier" {
+        let name = object.utf8_text(source.as_bytes()).ok()?;
+        let scope_id = symbol_table.scope_at_position(index.position(object.start_byte()));
+        let symbol = symbol_table.get_symbol(symbol_table.lookup(name, scope_id)?)?;
+        let declarator = declarator_of(symbol, tree, index)?;
+        let annotated = declarator
+            .child_by_field_name("type")
+            .and_then(|ty| ty.utf8_text(source.as_bytes()).ok())
+            .is_some_and(|ty| ty.trim_start_matches(':').trim() == "Response");
+        annotated
+            || declarator
+                .child_by_field_name("value")
+                .is_some_and(|value| is_awaited_fetch(value, source))
+    } else {
+        is_awaited_fetch(object, source)
+    };
+
+    is_response.then(|| format!("Response.{}", property))
+}
+
+/// The variable declarator that declares `symbol`
+fn declarator_of<'t>(symbol: &Symbol, tree: &'t Tree, index: &LineIndex) -> Option<Node<'t>> {
+    if !symbol.flags.contains(SymbolFlags::VARIABLE) {
+        return None;
+    }
+    let range = symbol.declaration_range;
+    let start = index.point(range.start);
+    let end = index.point(range.end);
+    let node = tree.root_node().descendant_for_point_range(start, end)?;
+    node.parent().filter(|p| p.kind() == "variable_declarator")
+}
+
+/// Whether `node` is `await fetch(...)`, possibly parenthesized
+fn is_awaited_fetch(node: Node, source: &str) -> bool {
+    match node.kind() {
+        "parenthesized_expression" => node
+            .named_child(0)
+            .is_some_and(|inner| is_awaited_fetch(inner, source)),
+        "await_expression" => node.named_child(0).is_some_and(|call| {
+            call.kind() == "call_expression"
+                && call
+                    .child_by_field_name("function")
+                    .and_then(|f| f.utf8_text(source.as_bytes()).ok())
+                    == Some("fetch")
+        }),
+        _ => false,
+    }
+}
+
+/// Declared signature of a user-defined function, read from its syntax
+fn user_signature(
+    symbol: &Symbol,
+    tree: &Tree,
+    source: &str,
+    index: &LineIndex,
+) -> Option<Signature> {
+    signature_of(function_node(symbol, tree, index)?, source)
+}
+
+/// Parameters of a user-defined function, labelled by their offsets into
+/// the signature label (`Signature::label`)
+pub fn extract_function_parameters(
+    symbol: &Symbol,
+    tree: &Tree,
+    source: &str,
+) -> Vec<ParameterInformation> {
+    user_signature(symbol, tree, source, &LineIndex::new(source))
+        .map(|signature| parameter_information(&signature, &symbol.name))
+        .unwrap_or_default()
+}
+
+/// Texts of the explicit type arguments of a call (`f<string>(...)`)
+fn type_arguments_of(call_node: &Node, source: &str) -> Vec<String> {
+    let Some(arguments) = call_node.child_by_field_name("type_arguments") else {
+        return Vec::new();
+    };
+    let mut cursor = arguments.walk();
+    arguments
+        .named_children(&mut cursor)
+        .filter_map(|arg| arg.utf8_text(source.as_bytes()).ok())
+        .map(str::to_string)
+        .collect()
+}
+
+fn parameter_information(signature: &Signature, name: &str) -> Vec<ParameterInformation> {
+    signature
+        .parameter_offsets(name)
+        .into_iter()
+        .map(|offsets| ParameterInformation {
+            label: ParameterLabel::LabelOffsets(offsets),
+            documentation: None,
+        })
+        .collect()
+}
+
+/// One signature per overload of `symbol`, with the active signature being
+/// the first overload that accepts the arguments written so far
+fn get_overload_signature_help(
+    symbol: &Symbol,
+    args_node: &Node,
+    source: &str,
+    cursor: Point,
+) -> SignatureHelp {
+    let active_param = count_args_before_position(args_node, cursor);
+    let checker = TypeChecker::new();
+    let mut walker = args_node.walk();
+    let written: Vec<Option<Type>> = args_node
+        .named_children(&mut walker)
+        .filter(|arg| arg.end_position() <= cursor && arg.kind() != "comment")
+        .map(|arg| checker.literal_type(arg, source))
+        .collect();
+
+    let signatures: Vec<SignatureInformation> = symbol
+        .overloads
+        .iter()
+        .map(|overload| overload_information(&symbol.name, overload, active_param))
+        .collect();
+    let active_signature = symbol
+        .overloads
+        .iter()
+        .position(|overload| overload_accepts(overload, active_param + 1, &written))
+        .unwrap_or(0);
+
+    SignatureHelp {
+        signatures,
+        active_signature: Some(active_signature as u32),
+        active_parameter: Some(active_param as u32),
+    }
+}
+
+/// `name(a: A, b?: B): R` with label offsets for each parameter
+fn overload_information(
+    name: &str,
+    overload: &FunctionType,
+    active_param: usize,
+) -> SignatureInformation {
+    let mut label = name.to_string();
+    let mut parameters = Vec::new();
+    if !overload.type_parameters.is_empty() {
+        label.push('<');
+        for (i, type_parameter) in overload.type_parameters.iter().enumerate() {
+            if i > 0 {
+                label.push_str(", ");
+            }
+            let start = label.encode_utf16().count() as u32;
+            label.push_str(&type_parameter.name);
+            parameters.push(ParameterInformation {
+                label: ParameterLabel::LabelOffsets([start, label.encode_utf16().count() as u32]),
+                documentation: None,
+            });
         }
-        current = current.parent()?;
+        label.push('>');
+    }
+    label.push('(');
+    for (i, param) in overload.parameters.iter().enumerate() {
+        if i > 0 {
+            label.push_str(", ");
+        }
+        let start = label.encode_utf16().count() as u32;
+        if param.rest {
+            label.push_str("...");
+        }
+        label.push_str(&param.name);
+        if param.optional {
+            label.push('?');
+        }
+        label.push_str(": ");
+        label.push_str(&print_type(&param.ty));
+        parameters.push(ParameterInformation {
+            label: ParameterLabel::LabelOffsets([start, label.encode_utf16().count() as u32]),
+            documentation: None,
+        });
+    }
+    label.push_str("): ");
+    label.push_str(&print_type(&overload.return_type));
+
+    let active_param = overload.type_parameters.len() + active_param;
+    let rest = overload.parameters.last().is_some_and(|p| p.rest);
+    let active_parameter = if rest {
+        active_param.min(parameters.len() - 1)
+    } else {
+        active_param
+    };
+
+    SignatureInformation {
+        label,
+        documentation: None,
+        parameters: Some(parameters),
+        active_parameter: Some(active_parameter as u32),
     }
 }
 
-/// Count arguments before the cursor position
-fn count_args_before_position(args_node: &Node, position: Position) -> usize {
-    let mut count = 0;
-    let mut cursor = args_node.walk();
+/// Whether an overload can take `count` arguments, the first of which
+/// have the (literal) types in `written`
+fn overload_accepts(overload: &FunctionType, count: usize, written: &[Option<Type>]) -> bool {
+    let rest = overload.parameters.last().is_some_and(|p| p.rest);
+    if !rest && count > overload.parameters.len() {
+        return false;
+    }
 
-    for child in args_node.children(&mut cursor) {
-        // Skip parentheses and commas
-        if child.kind() == "(" || child.kind() == ")" {
-            continue;
+    written.iter().enumerate().all(|(i, arg)| {
+        let param = overload
+            .parameters
+            .get(i)
+            .or_else(|| overload.parameters.last().filter(|p| p.rest));
+        match (arg, param) {
+            (Some(arg), Some(param)) if param.rest => match param.ty.as_ref() {
+                Type::Array(element) => argument_fits(arg, element),
+                _ => true,
+            },
+            (Some(arg), Some(param)) => argument_fits(arg, &param.ty),
+            _ => true,
         }
+    })
+}
+
+/// Rough assignability of an argument's literal type to a parameter type.
+/// Anything that is not a primitive or an array is assumed to fit.
+fn argument_fits(arg: &Type, param: &Type) -> bool {
+    let primitive = |ty: &Type| {
+        let flags = ty.flags();
+        [
+            (
+                TypeFlags::STRING | TypeFlags::STRING_LITERAL,
+                TypeFlags::STRING,
+            ),
+            (
+                TypeFlags::NUMBER | TypeFlags::NUMBER_LITERAL,
+                TypeFlags::NUMBER,
+            ),
+            (
+                TypeFlags::BOOLEAN | TypeFlags::BOOLEAN_LITERAL,
+                TypeFlags::BOOLEAN,
+            ),
+        ]
+        .into_iter()
+        .find(|(kinds, _)| flags.intersects(*kinds))
+        .map(|(_, kind)| kind)
+    };
+
+    match param {
+        Type::Union(members) => members.iter().any(|member| argument_fits(arg, member)),
+        Type::Array(_) | Type::Tuple(_) => matches!(arg, Type::Array(_) | Type::Tuple(_)),
+        _ => match (primitive(arg), primitive(param)) {
+            (Some(arg), Some(param)) => arg == param,
+            (Some(_), None) => !matches!(param, Type::Object(_) | Type::Function(_)),
+            (None, Some(_)) => !matches!(arg, Type::Array(_)),
+            (None, None) => true,
+        },
+    }
+}
 
-        if child.kind() == "," {
-            // Check if comma is before position
-            let comma_pos = child.start_position();
-            if comma_pos.row < position.line as usize
-                || (comma_pos.row == position.line as usize
-                    && comma_pos.column < position.character as usize)
-            {
-                count += 1;
+/// Find the innermost call or `new` expression whose argument list contains
+/// the node
+/// (being on a callee like `bar` in `foo(bar(1))` belongs to the outer call)
+fn find_call_expression<'a>(node: &'a Node<'a>) -> Option<Node<'a>> {
+    let mut current = *node;
+
+    loop {
+        if matches!(current.kind(), "call_expression" | "new_expression") {
+            let in_arguments = current
+                .child_by_field_name("arguments")
+                .is_some_and(|args| {
+                    args.start_byte() < node.end_byte() && node.start_byte() < args.end_byte()
+                });
+            if in_arguments {
+                return Some(current);
             }
         }
+        current = current.parent()?;
     }
+}
 
-    count
+/// Index of the argument the cursor is in: the number of top-level commas
+/// of the argument list before the cursor. Commas inside nested calls,
+/// arrays or objects belong to other nodes and are not counted.
+fn count_args_before_position(args_node: &Node, cursor: Point) -> usize {
+    let mut walker = args_node.walk();
+    args_node
+        .children(&mut walker)
+        .take_while(|child| child.end_position() <= cursor)
+        .filter(|child| child.kind() == ",")
+        .count()
 }
 
 /// Get signature help for built-in functions
@@ -359,6 +693,100 @@ fn get_builtin_signature_help(name: &str) -> Option<SignatureInformation> {
             }]),
             active_parameter: None,
         }),
+        "fetch" => Some(SignatureInformation {
+            label: "fetch(input: RequestInfo | URL, init?: RequestInit): Promise<Response>".to_string(),
+            documentation: Some(tower_lsp::lsp_types::Documentation::String(
+                "Starts the process of fetching a resource from the network, returning a promise that is fulfilled once the response is available.".to_string(),
+            )),
+            parameters: Some(vec![
+                ParameterInformation {
+                    label: ParameterLabel::Simple("input: RequestInfo | URL".to_string()),
+                    documentation: Some(tower_lsp::lsp_types::Documentation::String(
+                        "The resource to fetch: a string or URL object containing the URL, or a Request object.".to_string(),
+                    )),
+                },
+                ParameterInformation {
+                    label: ParameterLabel::Simple("init?: RequestInit".to_string()),
+                    documentation: Some(tower_lsp::lsp_types::Documentation::String(
+                        "An object containing any custom settings to apply to the request, such as the method, headers and body.".to_string(),
+                    )),
+                },
+            ]),
+            active_parameter: None,
+        }),
+        "Response.json" => Some(SignatureInformation {
+            label: "json(): Promise<any>".to_string(),
+            documentation: Some(tower_lsp::lsp_types::Documentation::String(
+                "Reads the response body to completion and returns a promise that resolves with the result of parsing the body text as JSON.".to_string(),
+            )),
+            parameters: Some(Vec::new()),
+            active_parameter: None,
+        }),
+        "Response.text" => Some(SignatureInformation {
+            label: "text(): Promise<string>".to_string(),
+            documentation: Some(tower_lsp::lsp_types::Documentation::String(
+                "Reads the response body to completion and returns a promise that resolves with the body as a string, decoded as UTF-8.".to_string(),
+            )),
+            parameters: Some(Vec::new()),
+            active_parameter: None,
+        }),
+        "new Request" => Some(SignatureInformation {
+            label: "Request(input: RequestInfo | URL, init?: RequestInit): Request".to_string(),
+            documentation: Some(tower_lsp::lsp_types::Documentation::String(
+                "Creates a new Request object representing a resource request.".to_string(),
+            )),
+            parameters: Some(vec![
+                ParameterInformation {
+                    label: ParameterLabel::Simple("input: RequestInfo | URL".to_string()),
+                    documentation: Some(tower_lsp::lsp_types::Documentation::String(
+                        "The resource to fetch: a string or URL object containing the URL, or a Request object to copy.".to_string(),
+                    )),
+                },
+                ParameterInformation {
+                    label: ParameterLabel::Simple("init?: RequestInit".to_string()),
+                    documentation: Some(tower_lsp::lsp_types::Documentation::String(
+                        "An object containing any custom settings to apply to the request, such as the method, headers and body.".to_string(),
+                    )),
+                },
+            ]),
+            active_parameter: None,
+        }),
+        "new URL" => Some(SignatureInformation {
+            label: "URL(url: string | URL, base?: string | URL): URL".to_string(),
+            documentation: Some(tower_lsp::lsp_types::Documentation::String(
+                "Creates a URL object representing the URL defined by the parameters.".to_string(),
+            )),
+            parameters: Some(vec![
+                ParameterInformation {
+                    label: ParameterLabel::Simple("url: string | URL".to_string()),
+                    documentation: Some(tower_lsp::lsp_types::Documentation::String(
+                        "An absolute URL, or a relative reference to one when base is given.".to_string(),
+                    )),
+                },
+                ParameterInformation {
+                    label: ParameterLabel::Simple("base?: string | URL".to_string()),
+                    documentation: Some(tower_lsp::lsp_types::Documentation::String(
+                        "The base URL to resolve against when url is a relative reference.".to_string(),
+                    )),
+                },
+            ]),
+            active_parameter: None,
+        }),
+        "new URLSearchParams" => Some(SignatureInformation {
+            label: "URLSearchParams(init?: string[][] | Record<string, string> | string | URLSearchParams): URLSearchParams".to_string(),
+            documentation: Some(tower_lsp::lsp_types::Documentation::String(
+                "Creates a URLSearchParams object for working with the query string of a URL.".to_string(),
+            )),
+            parameters: Some(vec![
+                ParameterInformation {
+                    label: ParameterLabel::Simple("init?: string[][] | Record<string, string> | string | URLSearchParams".to_string()),
+                    documentation: Some(tower_lsp::lsp_types::Documentation::String(
+                        "A query string, a list of name-value pairs, a record of names to values, or another URLSearchParams object.".to_string(),
+                    )),
+                },
+            ]),
+            active_parameter: None,
+        }),
         _ => None,
     }
 }
@@ -529,7 +957,7 @@ mod tests {
         let call = root.child(0).unwrap().child(0).unwrap();
         if call.kind() == "call_expression" {
             let args = call.child_by_field_name("arguments").unwrap();
-            let count = count_args_before_position(&args, Position::new(0, 4));
+            let count = count_args_before_position(&args, Point::new(0, 4));
             assert_eq!(count, 0);
         }
     }
@@ -544,11 +972,60 @@ mod tests {
         if call.kind() == "call_expression" {
             let args = call.child_by_field_name("arguments").unwrap();
             // Position after the comma
-            let count = count_args_before_position(&args, Position::new(0, 7));
+            let count = count_args_before_position(&args, Point::new(0, 7));
             assert_eq!(count, 1);
         }
     }
 
+    fn arguments_of_outer_call(tree: &Tree) -> Node<'_> {
+        let call = tree.root_node().child(0).unwrap().child(0).unwrap();
+        assert_eq!(call.kind(), "call_expression");
+        call.child_by_field_name("arguments").unwrap()
+    }
+
+    #[test]
+    fn test_count_args_ignores_nested_commas() {
+        let code = "foo(bar(1, 2), x)";
+        let tree = parse_code(code);
+        let args = arguments_of_outer_call(&tree);
+
+        assert_eq!(count_args_before_position(&args, Point::new(0, 15)), 1);
+        assert_eq!(count_args_before_position(&args, Point::new(0, 16)), 1);
+        // Inside the nested call's arguments, but still the first outer argument
+        assert_eq!(count_args_before_position(&args, Point::new(0, 11)), 0);
+    }
+
+    #[test]
+    fn test_count_args_first_argument() {
+        let code = "foo([1, 2], { a: 1, b: 2 })";
+        let tree = parse_code(code);
+        let args = arguments_of_outer_call(&tree);
+
+        assert_eq!(count_args_before_position(&args, Point::new(0, 4)), 0);
+        assert_eq!(count_args_before_position(&args, Point::new(0, 6)), 0);
+        assert_eq!(count_args_before_position(&args, Point::new(0, 20)), 1);
+    }
+
+    #[test]
+    fn test_signature_help_for_outer_call_after_nested_call() {
+        let code = "function foo(a: number, b: string) {}\nfunction bar(x: number, y: number) { return 0; }\nfoo(bar(1, 2), x);";
+        let tree = parse_code(code);
+        let symbol_table = crate::analysis::binder::bind_document(&tree, code);
+
+        let help = get_signature_help(&tree, code, &symbol_table, Position::new(2, 16)).unwrap();
+        assert_eq!(help.signatures[0].label, "foo(a: number, b: string)");
+        assert_eq!(help.active_parameter, Some(1));
+
+        // On the nested callee's name, help is for the outer call
+        let help = get_signature_help(&tree, code, &symbol_table, Position::new(2, 5)).unwrap();
+        assert_eq!(help.signatures[0].label, "foo(a: number, b: string)");
+        assert_eq!(help.active_parameter, Some(0));
+
+        let help = get_signature_help(&tree, code, &symbol_table, Position::new(2, 11)).unwrap();
+        assert_eq!(help.signatures[0].label, "bar(x: number, y: number)");
+        assert_eq!(help.active_parameter, Some(1));
+    }
+
     #[test]
     fn test_find_call_expression() {
         let code = "console.log('hello')";
@@ -629,6 +1106,65 @@ mod tests {
         assert!(help.is_none());
     }
 
+    #[test]
+    fn test_web_api_signatures() {
+        for name in [
+            "fetch",
+            "Response.json",
+            "Response.text",
+            "new Request",
+            "new URL",
+            "new URLSearchParams",
+        ] {
+            let sig = get_builtin_signature_help(name).unwrap();
+            assert!(sig.documentation.is_some(), "{}", name);
+            for param in sig.parameters.unwrap() {
+                assert!(param.documentation.is_some(), "{}", name);
+            }
+        }
+    }
+
+    #[test]
+    fn test_get_signature_help_fetch() {
+        let code = "fetch('/api', {});";
+        let tree = parse_code(code);
+        let symbol_table = SymbolTable::new();
+
+        let help = get_signature_help(&tree, code, &symbol_table, Position::new(0, 14)).unwrap();
+        assert!(help.signatures[0].label.starts_with("fetch("));
+        assert_eq!(help.active_parameter, Some(1));
+    }
+
+    #[test]
+    fn test_get_signature_help_constructor() {
+        let code = "const u = new URL('a', );";
+        let tree = parse_code(code);
+        let symbol_table = SymbolTable::new();
+
+        let help = get_signature_help(&tree, code, &symbol_table, Position::new(0, 23)).unwrap();
+        assert!(help.signatures[0].label.starts_with("URL("));
+        assert_eq!(help.active_parameter, Some(1));
+    }
+
+    #[test]
+    fn test_get_signature_help_response_methods() {
+        let code = "async function f() {\n  const res = await fetch('/a');\n  res.json();\n  const r: Response = res;\n  r.text();\n  other.json();\n}";
+
+        let help = user_signature_help(code, Position::new(2, 11)).unwrap();
+        assert_eq!(help.signatures[0].label, "json(): Promise<any>");
+        let help = user_signature_help(code, Position::new(4, 9)).unwrap();
+        assert_eq!(help.signatures[0].label, "text(): Promise<string>");
+        assert!(user_signature_help(code, Position::new(5, 13)).is_none());
+    }
+
+    #[test]
+    fn test_get_signature_help_response_after_non_ascii() {
+        let code = "async function f(res: any) {\n  const s = '日本語の文字列'; const r: Response = res;\n  r.text();\n}";
+
+        let help = user_signature_help(code, Position::new(2, 9)).unwrap();
+        assert_eq!(help.signatures[0].label, "text(): Promise<string>");
+    }
+
     #[test]
     fn test_get_signature_help_outside_call() {
         let code = "const x = 1";
@@ -678,4 +1214,228 @@ mod tests {
         let help = get_signature_help(&tree, code, &symbol_table, Position::new(0, 20));
         assert!(help.is_some());
     }
+
+    fn user_signature_help(code: &str, position: Position) -> Option<SignatureHelp> {
+        let tree = parse_code(code);
+        let symbol_table = crate::analysis::binder::bind_document(&tree, code);
+        get_signature_help(&tree, code, &symbol_table, position)
+    }
+
+    fn parameter_labels(signature: &SignatureInformation) -> Vec<String> {
+        signature
+            .parameters
+            .iter()
+            .flatten()
+            .map(|param| match &param.label {
+                ParameterLabel::Simple(label) => label.clone(),
+                ParameterLabel::LabelOffsets([start, end]) => {
+                    signature.label[*start as usize..*end as usize].to_string()
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_user_function_signature_help() {
+        let code = "function add(a: number, b: number): number { return a + b; }\nadd(1, );";
+        let help = user_signature_help(code, Position::new(1, 7)).unwrap();
+
+        let signature = &help.signatures[0];
+        assert_eq!(signature.label, "add(a: number, b: number): number");
+        assert_eq!(parameter_labels(signature), vec!["a: number", "b: number"]);
+        assert_eq!(help.active_parameter, Some(1));
+    }
+
+    #[test]
+    fn test_extract_function_parameters() {
+        let code = "function add(a: number, b: number): number { return a + b; }";
+        let tree = parse_code(code);
+        let symbol_table = crate::analysis::binder::bind_document(&tree, code);
+        let symbol_id = symbol_table
+            .lookup("add", symbol_table.root_scope_id())
+            .unwrap();
+        let symbol = symbol_table.get_symbol(symbol_id).unwrap();
+
+        let labels: Vec<_> = extract_function_parameters(symbol, &tree, code)
+            .into_iter()
+            .map(|param| param.label)
+            .collect();
+        assert_eq!(
+            labels,
+            vec![
+                ParameterLabel::LabelOffsets([4, 13]),
+                ParameterLabel::LabelOffsets([15, 24]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_active_parameter_after_non_ascii_argument() {
+        // UTF-16 columns, not byte columns: the cursor is after the comma
+        let code = "function add(a: string, b: number) {}\nadd('ééé', );";
+        let help = user_signature_help(code, Position::new(1, 11)).unwrap();
+        assert_eq!(help.active_parameter, Some(1));
+    }
+
+    #[test]
+    fn test_signature_of_function_declared_after_non_ascii_text() {
+        // The declaration's UTF-16 range must map to byte columns
+        let code = "const s = 'ééé'; function add(a: number, b: number) {}\nadd(1, 2);";
+        let help = user_signature_help(code, Position::new(1, 4)).unwrap();
+        assert_eq!(help.signatures[0].label, "add(a: number, b: number)");
+    }
+
+    #[test]
+    fn test_user_function_signature_optional_and_rest() {
+        let code =
+            "function log(level?: string, prefix = '', ...args: any[]) {}\nlog('a', 'b', 1, 2);";
+        let help = user_signature_help(code, Position::new(1, 17)).unwrap();
+
+        let signature = &help.signatures[0];
+        assert_eq!(
+            parameter_labels(signature),
+            vec!["level?: string", "prefix?", "...args: any[]"]
+        );
+        assert_eq!(help.active_parameter, Some(2));
+    }
+
+    #[test]
+    fn test_method_signature_help() {
+        let code = "class Calc {\n    scale(value: number, factor: number) { return value * factor; }\n    run() { this.scale(2, 3); }\n}";
+        let help = user_signature_help(code, Position::new(2, 23)).unwrap();
+
+        let signature = &help.signatures[0];
+        assert_eq!(signature.label, "scale(value: number, factor: number)");
+        assert_eq!(help.active_parameter, Some(0));
+    }
+
+    #[test]
+    fn test_arrow_function_signature_help() {
+        let code = "const greet = (name: string, excited = false) => name;\ngreet('a', );";
+        let help = user_signature_help(code, Position::new(1, 11)).unwrap();
+
+        let signature = &help.signatures[0];
+        assert_eq!(signature.label, "greet(name: string, excited?)");
+        assert_eq!(
+            signature.parameters.as_ref().unwrap()[0].label,
+            ParameterLabel::LabelOffsets([6, 18])
+        );
+        assert_eq!(
+            parameter_labels(signature),
+            vec!["name: string", "excited?"]
+        );
+        assert_eq!(help.active_parameter, Some(1));
+    }
+
+    #[test]
+    fn test_this_parameter_signature_help() {
+        let code = "function f(this: Window, x: number) {}\nf(1);";
+        let help = user_signature_help(code, Position::new(1, 2)).unwrap();
+
+        let signature = &help.signatures[0];
+        assert_eq!(signature.label, "f(x: number)");
+        assert_eq!(parameter_labels(signature), vec!["x: number"]);
+        assert_eq!(help.active_parameter, Some(0));
+    }
+
+    #[test]
+    fn test_signature_help_without_parameters() {
+        let code = "function reset() {}\nreset();\nconst n = 1;\nn();";
+        let help = user_signature_help(code, Position::new(1, 6)).unwrap();
+
+        assert_eq!(help.signatures[0].label, "reset()");
+        assert_eq!(help.signatures[0].parameters, Some(vec![]));
+        assert_eq!(help.active_parameter, None);
+
+        // A variable that does not hold a function has no signature
+        assert!(user_signature_help(code, Position::new(3, 2)).is_none());
+    }
+
+    fn analyzed_signature_help(code: &str, position: Position) -> Option<SignatureHelp> {
+        let tree = parse_code(code);
+        let mut symbol_table = crate::analysis::binder::bind_document(&tree, code);
+        let mut checker = TypeChecker::new();
+        checker.declare_types_from_tree(tree.root_node(), code);
+        checker.infer_symbol_types(&mut symbol_table, tree.root_node(), code);
+        get_signature_help(&tree, code, &symbol_table, position)
+    }
+
+    #[test]
+    fn test_overload_signature_help_by_argument_count() {
+        let code = "function pad(s: string): string;\nfunction pad(s: string, n: number): string;\nfunction pad(s: string, n?: number) { return s; }\npad('a', );";
+
+        let help = analyzed_signature_help(code, Position::new(3, 5)).unwrap();
+        let labels: Vec<_> = help.signatures.iter().map(|s| s.label.as_str()).collect();
+        assert_eq!(
+            labels,
+            vec![
+                "pad(s: string): string",
+                "pad(s: string, n: number): string"
+            ]
+        );
+        assert_eq!(help.active_signature, Some(0));
+        assert_eq!(help.active_parameter, Some(0));
+
+        let help = analyzed_signature_help(code, Position::new(3, 9)).unwrap();
+        assert_eq!(help.active_signature, Some(1));
+        assert_eq!(help.active_parameter, Some(1));
+        assert_eq!(
+            help.signatures[1].parameters.as_ref().unwrap()[1].label,
+            ParameterLabel::LabelOffsets([15, 24])
+        );
+    }
+
+    #[test]
+    fn test_overload_signature_help_by_argument_type() {
+        let code = "export function fmt(n: number, digits: number): string;\nexport function fmt(s: string, width: number): string;\nexport function fmt(v: any, x: number) { return ''; }\nfmt('x', );";
+
+        let help = analyzed_signature_help(code, Position::new(3, 9)).unwrap();
+        assert_eq!(help.signatures.len(), 2);
+        assert_eq!(help.active_signature, Some(1));
+    }
+
+    #[test]
+    fn test_overload_signature_help_after_non_ascii_argument() {
+        let code = "function pad(s: string): string;\nfunction pad(s: string, n: number): string;\nfunction pad(s: string, n?: number) { return s; }\npad('éé', );";
+
+        let help = analyzed_signature_help(code, Position::new(3, 10)).unwrap();
+        assert_eq!(help.active_signature, Some(1));
+        assert_eq!(help.active_parameter, Some(1));
+    }
+
+    #[test]
+    fn test_overload_signature_help_ambiguous_defaults_to_first() {
+        let code = "function f(a: string): void;\nfunction f(a: number): void;\nfunction f(a: any) {}\nf(x, );";
+
+        let help = analyzed_signature_help(code, Position::new(3, 5)).unwrap();
+        assert_eq!(help.active_signature, Some(0));
+    }
+
+    #[test]
+    fn test_generic_signature_help() {
+        let code = "function identity<T>(value: T): T { return value; }\nidentity(1);";
+        let help = user_signature_help(code, Position::new(1, 10)).unwrap();
+
+        let signature = &help.signatures[0];
+        assert_eq!(signature.label, "identity<T>(value: T): T");
+        assert_eq!(parameter_labels(signature), vec!["T", "value: T"]);
+        assert_eq!(help.active_parameter, Some(1));
+    }
+
+    #[test]
+    fn test_generic_signature_help_with_type_arguments() {
+        let code = "function pick<K extends string, V>(key: K, map: Record<K, V>): V { return map[key]; }\npick<'a', number>('a', );";
+        let help = user_signature_help(code, Position::new(1, 23)).unwrap();
+
+        let signature = &help.signatures[0];
+        assert_eq!(
+            signature.label,
+            "pick<'a', number>(key: 'a', map: Record<'a', number>): number"
+        );
+        assert_eq!(
+            parameter_labels(signature),
+            vec!["'a'", "number", "key: 'a'", "map: Record<'a', number>"]
+        );
+        assert_eq!(help.active_parameter, Some(3));
+    }
 }
@@ -1,29 +1,36 @@
-use tower_lsp::lsp_types::{DocumentSymbol, Position, Range, SymbolKind};
+use tower_lsp::lsp_types::{DocumentSymbol, Location, SymbolInformation, SymbolKind, Url};
 use tree_sitter::{Node, Tree};
 
+use crate::analysis::{SymbolFlags, SymbolTable};
+use crate::line_index::LineIndex;
+
+/// Default cap on the number of `workspace/symbol` results
+pub const DEFAULT_WORKSPACE_SYMBOL_LIMIT: usize = 256;
+
 /// Extract document symbols from a parsed tree
 pub fn get_document_symbols(tree: &Tree, source: &str) -> Vec<DocumentSymbol> {
     let root = tree.root_node();
-    extract_symbols(&root, source)
+    let index = LineIndex::new(source);
+    extract_symbols(&root, source, &index)
 }
 
-fn extract_symbols(node: &Node, source: &str) -> Vec<DocumentSymbol> {
+fn extract_symbols(node: &Node, source: &str, index: &LineIndex) -> Vec<DocumentSymbol> {
     let mut symbols = Vec::new();
 
     let mut cursor = node.walk();
     for child in node.children(&mut cursor) {
-        if let Some(symbol) = node_to_symbol(&child, source) {
+        if let Some(symbol) = node_to_symbol(&child, source, index) {
             symbols.push(symbol);
         } else {
             // Recursively search for symbols in non-symbol nodes
-            symbols.extend(extract_symbols(&child, source));
+            symbols.extend(extract_symbols(&child, source, index));
         }
     }
 
     symbols
 }
 
-fn node_to_symbol(node: &Node, source: &str) -> Option<DocumentSymbol> {
+fn node_to_symbol(node: &Node, source: &str, index: &LineIndex) -> Option<DocumentSymbol> {
     let kind = node.kind();
 
     let (name, symbol_kind, detail) = match kind {
@@ -61,13 +68,13 @@ fn node_to_symbol(node: &Node, source: &str) -> Option<DocumentSymbol> {
         }
         "lexical_declaration" | "variable_declaration" => {
             // Handle const/let/var declarations
-            return extract_variable_symbols(node, source);
+            return extract_variable_symbols(node, source, index);
         }
         "export_statement" => {
             // Look inside export statements for declarations
             let mut cursor = node.walk();
             for child in node.children(&mut cursor) {
-                if let Some(sym) = node_to_symbol(&child, source) {
+                if let Some(sym) = node_to_symbol(&child, source, index) {
                     return Some(sym);
                 }
             }
@@ -76,11 +83,11 @@ fn node_to_symbol(node: &Node, source: &str) -> Option<DocumentSymbol> {
         _ => return None,
     };
 
-    let range = node_range(node);
+    let range = index.node_range(node);
     let selection_range = range;
 
     // Get children symbols
-    let children = extract_symbols(node, source);
+    let children = extract_symbols(node, source, index);
     let children = if children.is_empty() {
         None
     } else {
@@ -100,7 +107,11 @@ fn node_to_symbol(node: &Node, source: &str) -> Option<DocumentSymbol> {
     })
 }
 
-fn extract_variable_symbols(node: &Node, source: &str) -> Option<DocumentSymbol> {
+fn extract_variable_symbols(
+    node: &Node,
+    source: &str,
+    index: &LineIndex,
+) -> Option<DocumentSymbol> {
     // Find the first variable declarator
     let mut cursor = node.walk();
     for child in node.children(&mut cursor) {
@@ -118,7 +129,7 @@ fn extract_variable_symbols(node: &Node, source: &str) -> Option<DocumentSymbol>
                 SymbolKind::VARIABLE
             };
 
-            let range = node_range(node);
+            let range = index.node_range(node);
 
             #[allow(deprecated)]
             return Some(DocumentSymbol {
@@ -136,23 +147,107 @@ fn extract_variable_symbols(node: &Node, source: &str) -> Option<DocumentSymbol>
     None
 }
 
-fn get_child_by_field(node: &Node, field: &str, source: &str) -> Option<String> {
-    let child = node.child_by_field_name(field)?;
-    Some(child.utf8_text(source.as_bytes()).ok()?.to_string())
+/// Search the symbol tables of several files for names matching `query`.
+/// Exact matches rank first, then prefixes, substrings and subsequences.
+pub fn get_workspace_symbols<'a>(
+    files: impl IntoIterator<Item = (&'a Url, &'a SymbolTable)>,
+    query: &str,
+    limit: usize,
+) -> Vec<SymbolInformation> {
+    let query = query.to_lowercase();
+    let mut matches = Vec::new();
+
+    for (uri, symbol_table) in files {
+        for symbol in symbol_table.all_symbols() {
+            if symbol.flags.intersects(
+                SymbolFlags::PARAMETER | SymbolFlags::TYPE_PARAMETER | SymbolFlags::IMPORT,
+            ) {
+                continue;
+            }
+            if let Some(score) = match_score(&symbol.name, &query) {
+                matches.push((score, symbol, uri));
+            }
+        }
+    }
+
+    matches.sort_by(|(a_score, a, a_uri), (b_score, b, b_uri)| {
+        a_score
+            .cmp(b_score)
+            .then_with(|| a.name.cmp(&b.name))
+            .then_with(|| a_uri.cmp(b_uri))
+            .then_with(|| a.declaration_range.start.cmp(&b.declaration_range.start))
+    });
+
+    matches
+        .into_iter()
+        .take(limit)
+        .map(|(_, symbol, uri)| {
+            #[allow(deprecated)] // `deprecated` is a required field
+            SymbolInformation {
+                name: symbol.name.clone(),
+                kind: symbol_flags_to_kind(symbol.flags),
+                tags: None,
+                deprecated: None,
+                location: Location::new(uri.clone(), symbol.declaration_range),
+                container_name: None,
+            }
+        })
+        .collect()
+}
+
+/// Rank how well `name` matches a lowercase `query` (lower is better).
+/// Returns `None` unless the query is a case-insensitive subsequence of the name.
+fn match_score(name: &str, query: &str) -> Option<u8> {
+    let lower = name.to_lowercase();
+    if lower == query {
+        return Some(0);
+    }
+    if lower.starts_with(query) {
+        return Some(1);
+    }
+    if lower.contains(query) {
+        return Some(2);
+    }
+
+    let mut chars = lower.chars();
+    query.chars().all(|q| chars.any(|c| c == q)).then_some(3)
 }
 
-fn node_range(node: &Node) -> Range {
-    let start = node.start_position();
-    let end = node.end_position();
-    Range {
-        start: Position::new(start.row as u32, start.column as u32),
-        end: Position::new(end.row as u32, end.column as u32),
+fn symbol_flags_to_kind(flags: SymbolFlags) -> SymbolKind {
+    if flags.contains(SymbolFlags::FUNCTION) {
+        SymbolKind::FUNCTION
+    } else if flags.contains(SymbolFlags::CLASS) {
+        SymbolKind::CLASS
+    } else if flags.contains(SymbolFlags::INTERFACE) {
+        SymbolKind::INTERFACE
+    } else if flags.contains(SymbolFlags::ENUM) {
+        SymbolKind::ENUM
+    } else if flags.contains(SymbolFlags::ENUM_MEMBER) {
+        SymbolKind::ENUM_MEMBER
+    } else if flags.contains(SymbolFlags::TYPE_ALIAS) {
+        SymbolKind::TYPE_PARAMETER
+    } else if flags.contains(SymbolFlags::NAMESPACE) {
+        SymbolKind::NAMESPACE
+    } else if flags.contains(SymbolFlags::METHOD) {
+        SymbolKind::METHOD
+    } else if flags.contains(SymbolFlags::PROPERTY) {
+        SymbolKind::PROPERTY
+    } else if flags.contains(SymbolFlags::CONST) {
+        SymbolKind::CONSTANT
+    } else {
+        SymbolKind::VARIABLE
     }
 }
 
+fn get_child_by_field(node: &Node, field: &str, source: &str) -> Option<String> {
+    let child = node.child_by_field_name(field)?;
+    Some(child.utf8_text(source.as_bytes()).ok()?.to_string())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::analysis::binder::bind_document;
     use tree_sitter::Parser;
 
     fn parse_typescript(code: &str) -> Tree {
@@ -352,4 +447,57 @@ mod tests {
         assert_eq!(range.start.line, 0);
         assert_eq!(range.start.character, 0);
     }
+
+    #[test]
+    fn test_workspace_symbols_across_files() {
+        let models = "interface User { id: number }\nfunction unusedResult() {}";
+        let services = "class UserService {}\nconst other = 1;";
+        let models_uri = Url::parse("file:///src/models.ts").unwrap();
+        let services_uri = Url::parse("file:///src/services.ts").unwrap();
+        let models_table = bind_document(&parse_typescript(models), models);
+        let services_table = bind_document(&parse_typescript(services), services);
+        let files = [
+            (&models_uri, &models_table),
+            (&services_uri, &services_table),
+        ];
+
+        let results = get_workspace_symbols(files, "User", DEFAULT_WORKSPACE_SYMBOL_LIMIT);
+        let summary: Vec<_> = results
+            .iter()
+            .map(|s| (s.name.as_str(), s.kind, s.location.uri.path()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("User", SymbolKind::INTERFACE, "/src/models.ts"),
+                ("UserService", SymbolKind::CLASS, "/src/services.ts"),
+                ("unusedResult", SymbolKind::FUNCTION, "/src/models.ts"),
+            ]
+        );
+        assert_eq!(results[0].location.range.start.line, 0);
+    }
+
+    #[test]
+    fn test_workspace_symbols_fuzzy_and_limit() {
+        let code = "function getSymbol() {}\nfunction gather() {}\nconst gs = 1;";
+        let uri = Url::parse("file:///a.ts").unwrap();
+        let table = bind_document(&parse_typescript(code), code);
+
+        let results = get_workspace_symbols([(&uri, &table)], "gsym", 10);
+        let names: Vec<_> = results.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["getSymbol"]);
+
+        let results = get_workspace_symbols([(&uri, &table)], "g", 2);
+        let names: Vec<_> = results.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["gather", "getSymbol"]);
+    }
+
+    #[test]
+    fn test_match_score_ranking() {
+        assert_eq!(match_score("User", "user"), Some(0));
+        assert_eq!(match_score("UserService", "user"), Some(1));
+        assert_eq!(match_score("AppUser", "user"), Some(2));
+        assert_eq!(match_score("getSymbol", "gsym"), Some(3));
+        assert_eq!(match_score("getSymbol", "xyz"), None);
+    }
 }
@@ -3,27 +3,50 @@
 
 #![allow(dead_code)]
 
+use std::collections::HashSet;
+
+use serde::Deserialize;
 use tower_lsp::lsp_types::{
-    Diagnostic, DiagnosticSeverity, DiagnosticTag, NumberOrString, Position, Range,
+    Diagnostic, DiagnosticSeverity, DiagnosticTag, NumberOrString, Range, Url,
 };
 use tree_sitter::{Node, Tree};
 
-use crate::analysis::{SymbolFlags, SymbolTable};
+use crate::analysis::binder::{is_foreign_export_name, jump_target};
+use crate::analysis::globals;
+use crate::analysis::{ScopeKind, Symbol, SymbolFlags, SymbolTable};
+use crate::capabilities::code_actions::collect_type_names;
+use crate::line_index::LineIndex;
+use crate::project::FileGraph;
+use crate::resolution::tsconfig::CompilerOptions;
+use crate::types::{NarrowingGuard, Type, TypeChecker, TypeId};
 
 /// Diagnostic codes for type errors
 /// These match TypeScript's error codes for compatibility
 #[derive(Debug, Clone, Copy)]
 pub enum TypeDiagnosticCode {
     UndefinedVariable = 2304,
+    DuplicateIdentifier = 2300,
+    RedeclaredBlockScopedVariable = 2451,
+    UsedBeforeDeclaration = 2448,
+    UndefinedLabel = 1116,
     UndefinedType = 2552,
     TypeMismatch = 2322,
     MissingProperty = 2339,
     UnusedVariable = 6133,
     UnusedParameter = 6138,
+    UnusedImports = 6192,
     CannotReassignConst = 2588,
     ArgumentCountMismatch = 2554,
     NotCallable = 2349,
     NoImplicitAny = 7006,
+    ImplicitThis = 2683,
+    UnreachableCode = 7027,
+    FallthroughCase = 7029,
+    NotAllPathsReturn = 7030,
+    UnhandledPromise = 80007,
+    CircularImport = 80008,
+    ShadowedVariable = 80009,
+    NonExhaustiveSwitch = 80010,
 }
 
 impl TypeDiagnosticCode {
@@ -36,6 +59,24 @@ impl TypeDiagnosticCode {
             TypeDiagnosticCode::UndefinedVariable => {
                 format!("Cannot find name '{}'.", context)
             }
+            TypeDiagnosticCode::DuplicateIdentifier => {
+                format!("Duplicate identifier '{}'.", context)
+            }
+            TypeDiagnosticCode::UndefinedLabel => {
+                format!(
+                    "A '{}' statement can only jump to a label of an enclosing statement.",
+                    context
+                )
+            }
+            TypeDiagnosticCode::RedeclaredBlockScopedVariable => {
+                format!("Cannot redeclare block-scoped variable '{}'.", context)
+            }
+            TypeDiagnosticCode::UsedBeforeDeclaration => {
+                format!(
+                    "Block-scoped variable '{}' used before its declaration.",
+                    context
+                )
+            }
             TypeDiagnosticCode::UndefinedType => {
                 format!(
                     "Cannot find name '{}'. Did you mean '{}'?",
@@ -46,7 +87,8 @@ impl TypeDiagnosticCode {
                 format!("Type '{}' is not assignable.", context)
             }
             TypeDiagnosticCode::MissingProperty => {
-                format!("Property '{}' does not exist on type.", context)
+                // e.g. "Property 'x' does not exist on type 'T'."
+                format!("Property {}.", context)
             }
             TypeDiagnosticCode::UnusedVariable => {
                 format!("'{}' is declared but its value is never read.", context)
@@ -54,11 +96,15 @@ impl TypeDiagnosticCode {
             TypeDiagnosticCode::UnusedParameter => {
                 format!("'{}' is declared but its value is never read.", context)
             }
+            TypeDiagnosticCode::UnusedImports => {
+                "All imports in import declaration are unused.".to_string()
+            }
             TypeDiagnosticCode::CannotReassignConst => {
                 format!("Cannot assign to '{}' because it is a constant.", context)
             }
             TypeDiagnosticCode::ArgumentCountMismatch => {
-                format!("Expected {} arguments, but got more.", context)
+                // e.g. "Expected 1-2 arguments, but got 3."
+                format!("Expected {}.", context)
             }
             TypeDiagnosticCode::NotCallable => {
                 format!(
@@ -69,27 +115,138 @@ impl TypeDiagnosticCode {
             TypeDiagnosticCode::NoImplicitAny => {
                 format!("Parameter '{}' implicitly has an 'any' type.", context)
             }
+            TypeDiagnosticCode::ImplicitThis => {
+                "'this' implicitly has type 'any' because it does not have a type annotation."
+                    .to_string()
+            }
+            TypeDiagnosticCode::UnreachableCode => "Unreachable code detected.".to_string(),
+            TypeDiagnosticCode::FallthroughCase => "Fallthrough case in switch.".to_string(),
+            TypeDiagnosticCode::NotAllPathsReturn => {
+                "Not all code paths return a value.".to_string()
+            }
+            TypeDiagnosticCode::ShadowedVariable => {
+                format!("'{}' is already declared in the upper scope.", context)
+            }
+            TypeDiagnosticCode::NonExhaustiveSwitch => {
+                format!("Switch is not exhaustive. Cases not matched: {}.", context)
+            }
+            TypeDiagnosticCode::CircularImport => {
+                format!("Circular import detected: {}.", context)
+            }
+            TypeDiagnosticCode::UnhandledPromise => {
+                format!(
+                    "The promise returned by '{}' is neither awaited nor handled.",
+                    context
+                )
+            }
         }
     }
 }
 
-/// Get type-aware diagnostics for a document
+/// Lint-style checks tsc has no compiler option for, enabled through the
+/// server's `initializationOptions`
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct LintOptions {
+    /// Hint at declarations shadowing an outer one, like the `no-shadow`
+    /// lint rule
+    pub no_shadow: bool,
+    /// Report switches over a union or enum that leave members unhandled,
+    /// like the `switch-exhaustiveness-check` lint rule
+    pub switch_exhaustiveness_check: bool,
+}
+
+/// Get type-aware diagnostics for a document, honoring the effective
+/// compiler options of its project
 pub fn get_type_diagnostics(
     tree: &Tree,
     source: &str,
     symbol_table: &SymbolTable,
+    options: &CompilerOptions,
+) -> Vec<Diagnostic> {
+    get_type_diagnostics_with_lints(tree, source, symbol_table, options, &LintOptions::default())
+}
+
+/// Get type-aware diagnostics for a document, including the enabled lint
+/// checks
+pub fn get_type_diagnostics_with_lints(
+    tree: &Tree,
+    source: &str,
+    symbol_table: &SymbolTable,
+    options: &CompilerOptions,
+    lints: &LintOptions,
 ) -> Vec<Diagnostic> {
     let mut diagnostics = Vec::new();
 
+    // Checks that need declaration types share one checker, which infers
+    // them into a copy so the caller's table is untouched
+    let root = tree.root_node();
+    let mut checker = TypeChecker::new();
+    checker.declare_types_from_tree(root, source);
+    let mut typed = symbol_table.clone();
+    checker.infer_symbol_types(&mut typed, root, source);
+
     // Check for undefined variables
     check_undefined_references(tree, source, symbol_table, &mut diagnostics);
 
+    // Check for let and const used in their temporal dead zone
+    check_temporal_dead_zone(tree, source, symbol_table, &mut diagnostics);
+
+    // Check for break and continue targeting labels that don't enclose them
+    check_label_references(tree, source, &mut diagnostics);
+
+    // Check for names declared twice in the same scope
+    check_duplicate_declarations(symbol_table, &mut diagnostics);
+
+    // Check for declarations hiding one of an enclosing scope
+    check_variable_shadowing(symbol_table, lints, &mut diagnostics);
+
     // Check for unused variables
-    check_unused_variables(symbol_table, &mut diagnostics);
+    check_unused_variables(symbol_table, options, &mut diagnostics);
+
+    // Check for unused imports
+    check_unused_imports(tree, source, symbol_table, options, &mut diagnostics);
 
     // Check for const reassignment
     check_const_reassignment(tree, source, symbol_table, &mut diagnostics);
 
+    // Check property accesses against declared types
+    check_property_access(tree, source, &typed, &mut checker, &mut diagnostics);
+
+    // Check argument counts of calls to declared functions
+    check_call_arguments(tree, source, symbol_table, &mut diagnostics);
+
+    // Check calls of values that are not functions
+    check_callable(tree, source, &typed, &checker, &mut diagnostics);
+
+    // Check for parameters that implicitly have an `any` type
+    check_implicit_any(tree, source, options, &mut diagnostics);
+
+    // Check for `this` in functions that aren't methods
+    check_implicit_this(tree, source, options, &mut diagnostics);
+
+    // Check for statements after return, throw, break or continue
+    check_unreachable_code(tree, source, &mut diagnostics);
+
+    // Check for switch cases that fall through into the next one
+    check_switch_fallthrough(tree, source, options, &mut diagnostics);
+
+    // Check for switches over a union that miss some of its members
+    check_switch_exhaustiveness(tree, source, &typed, &mut checker, lints, &mut diagnostics);
+
+    // Check for functions that only return a value on some paths
+    check_implicit_returns(
+        tree,
+        source,
+        &typed,
+        &mut checker,
+        options,
+        &mut diagnostics,
+    );
+
+    // Check for calls whose promise is dropped on the floor
+    check_unhandled_promises(tree, source, symbol_table, &mut diagnostics);
+
     diagnostics
 }
 
@@ -101,44 +258,33 @@ fn check_undefined_references(
     diagnostics: &mut Vec<Diagnostic>,
 ) {
     let root = tree.root_node();
-    check_node_references(root, source, symbol_table, diagnostics);
+    let index = LineIndex::new(source);
+    check_node_references(root, source, &index, symbol_table, diagnostics);
 }
 
 fn check_node_references(
     node: Node,
     source: &str,
+    index: &LineIndex,
     symbol_table: &SymbolTable,
     diagnostics: &mut Vec<Diagnostic>,
 ) {
     // Check identifiers that are references (not declarations)
-    if node.kind() == "identifier" && is_reference_identifier(&node) {
+    if node.kind() == "identifier" && is_reference_identifier(&node, source) {
         let name = node.utf8_text(source.as_bytes()).unwrap_or("");
 
-        // Skip built-in globals
-        if is_builtin_global(name) {
+        let range = index.node_range(&node);
+        let scope_id = symbol_table.scope_at_position(range.start);
+
+        // Skip built-in globals, and `arguments` inside a function
+        if globals::is_global(name) || (name == "arguments" && in_function(symbol_table, scope_id))
+        {
             // Continue to children
         } else {
-            let position = Position::new(
-                node.start_position().row as u32,
-                node.start_position().column as u32,
-            );
-            let scope_id = symbol_table.scope_at_position(position);
-
             // Check if the symbol exists
             if symbol_table.lookup(name, scope_id).is_none()
                 && symbol_table.lookup_type(name, scope_id).is_none()
             {
-                let range = Range {
-                    start: Position::new(
-                        node.start_position().row as u32,
-                        node.start_position().column as u32,
-                    ),
-                    end: Position::new(
-                        node.end_position().row as u32,
-                        node.end_position().column as u32,
-                    ),
-                };
-
                 diagnostics.push(Diagnostic {
                     range,
                     severity: Some(DiagnosticSeverity::ERROR),
@@ -159,12 +305,27 @@ fn check_node_references(
     // Recurse into children
     let mut cursor = node.walk();
     for child in node.children(&mut cursor) {
-        check_node_references(child, source, symbol_table, diagnostics);
+        check_node_references(child, source, index, symbol_table, diagnostics);
+    }
+}
+
+/// Whether a scope lies inside a non-arrow function, whose `arguments`
+/// object arrow functions share
+fn in_function(symbol_table: &SymbolTable, scope_id: u32) -> bool {
+    let mut scope = symbol_table.get_scope(scope_id);
+    while let Some(current) = scope {
+        if current.kind == ScopeKind::Function {
+            return true;
+        }
+        scope = current
+            .parent
+            .and_then(|parent| symbol_table.get_scope(parent));
     }
+    false
 }
 
 /// Check if an identifier node is a reference (not a declaration)
-fn is_reference_identifier(node: &Node) -> bool {
+fn is_reference_identifier(node: &Node, source: &str) -> bool {
     if let Some(parent) = node.parent() {
         match parent.kind() {
             // Declaration contexts - not references
@@ -173,11 +334,25 @@ fn is_reference_identifier(node: &Node) -> bool {
                 parent.child_by_field_name("name") != Some(*node)
             }
             "function_declaration"
+            | "function_expression"
+            | "generator_function"
             | "class_declaration"
             | "interface_declaration"
             | "type_alias_declaration"
             | "enum_declaration"
-            | "method_definition" => parent.child_by_field_name("name") != Some(*node),
+            | "method_definition"
+            | "function_signature"
+            | "internal_module"
+            | "module" => parent.child_by_field_name("name") != Some(*node),
+            // `namespace A.B {}`
+            "nested_identifier" => parent
+                .parent()
+                .is_none_or(|p| !matches!(p.kind(), "internal_module" | "module")),
+            // Intrinsic elements such as `<div>` name no binding
+            "jsx_opening_element" | "jsx_closing_element" | "jsx_self_closing_element" => {
+                parent.child_by_field_name("name") != Some(*node)
+                    || !is_intrinsic_tag(*node, source)
+            }
             "import_specifier"
             | "shorthand_property_identifier_pattern"
             | "required_parameter"
@@ -186,6 +361,12 @@ fn is_reference_identifier(node: &Node) -> bool {
             "property_signature" | "public_field_definition" => {
                 parent.child_by_field_name("name") != Some(*node)
             }
+            "export_specifier" => !is_foreign_export_name(*node),
+            // `for (const x of xs)` declares `x`, `for (x of xs)` assigns it
+            "for_in_statement" => {
+                parent.child_by_field_name("kind").is_none()
+                    || parent.child_by_field_name("left") != Some(*node)
+            }
             // References
             _ => true,
         }
@@ -194,8 +375,264 @@ fn is_reference_identifier(node: &Node) -> bool {
     }
 }
 
-/// Check for unused variables
-fn check_unused_variables(symbol_table: &SymbolTable, diagnostics: &mut Vec<Diagnostic>) {
+/// Whether a JSX tag name refers to an intrinsic element: `<div>` or
+/// `<my-element>`, as opposed to a component such as `<Button>`
+fn is_intrinsic_tag(name: Node, source: &str) -> bool {
+    let text = name.utf8_text(source.as_bytes()).unwrap_or("");
+    text.starts_with(|c: char| c.is_ascii_lowercase()) || text.contains('-')
+}
+
+/// Check for references to `let` and `const` variables before their
+/// declaration has run. A use inside a function or class body is deferred
+/// until it is called or instantiated, so only uses evaluated directly in the
+/// declaring scope (or its nested blocks) are reported; `var` is hoisted.
+fn check_temporal_dead_zone(
+    tree: &Tree,
+    source: &str,
+    symbol_table: &SymbolTable,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let root = tree.root_node();
+    let index = LineIndex::new(source);
+    check_tdz_references(root, root, source, &index, symbol_table, diagnostics);
+}
+
+fn check_tdz_references(
+    node: Node,
+    root: Node,
+    source: &str,
+    index: &LineIndex,
+    symbol_table: &SymbolTable,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    if node.kind() == "identifier" && is_reference_identifier(&node, source) {
+        let name = node.utf8_text(source.as_bytes()).unwrap_or("");
+        let range = index.node_range(&node);
+        let scope_id = symbol_table.scope_at_position(range.start);
+        let symbol = symbol_table
+            .lookup(name, scope_id)
+            .and_then(|id| symbol_table.get_symbol(id))
+            .filter(|symbol| {
+                symbol
+                    .flags
+                    .intersects(SymbolFlags::LET | SymbolFlags::CONST)
+            });
+
+        // A binding inside a destructuring pattern is its own declaration
+        let symbol = symbol.filter(|symbol| symbol.name_range.start != range.start);
+
+        if let Some(symbol) = symbol {
+            // The binding is initialized once its whole declarator has run,
+            // so `const x = x + 1` reads `x` too early, and a loop binding
+            // once the loop's head has run
+            let declared = index.point(symbol.name_range.start);
+            let initialized = root
+                .descendant_for_point_range(declared, declared)
+                .and_then(|name| name.parent())
+                .and_then(|parent| match parent.kind() {
+                    "variable_declarator" => Some(parent.end_position()),
+                    "for_in_statement" => parent
+                        .child_by_field_name("body")
+                        .map(|body| body.start_position()),
+                    _ => None,
+                })
+                .map_or(symbol.name_range.end, |point| {
+                    index.position_of_point(point)
+                });
+
+            let before_declaration = symbol_table
+                .lookup_at(name, scope_id, range.start)
+                .is_none();
+            if (before_declaration || range.start < initialized)
+                && !is_deferred_use(symbol_table, scope_id, symbol.scope_id)
+            {
+                diagnostics.push(Diagnostic {
+                    range,
+                    severity: Some(DiagnosticSeverity::ERROR),
+                    code: Some(NumberOrString::Number(
+                        TypeDiagnosticCode::UsedBeforeDeclaration.as_number(),
+                    )),
+                    code_description: None,
+                    source: Some("ts-lsp-rust".to_string()),
+                    message: TypeDiagnosticCode::UsedBeforeDeclaration.message(name),
+                    related_information: None,
+                    tags: None,
+                    data: None,
+                });
+            }
+        }
+    }
+
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        check_tdz_references(child, root, source, index, symbol_table, diagnostics);
+    }
+}
+
+/// Check that labeled jumps name an enclosing statement label
+fn check_label_references(tree: &Tree, source: &str, diagnostics: &mut Vec<Diagnostic>) {
+    let index = LineIndex::new(source);
+    check_jumps(tree.root_node(), source, &index, diagnostics);
+}
+
+fn check_jumps(node: Node, source: &str, index: &LineIndex, diagnostics: &mut Vec<Diagnostic>) {
+    if matches!(node.kind(), "break_statement" | "continue_statement") {
+        if let Some(label) = node.child_by_field_name("label") {
+            if jump_target(node, source).is_none() {
+                let keyword = node.kind().trim_end_matches("_statement");
+                diagnostics.push(Diagnostic {
+                    range: index.node_range(&label),
+                    severity: Some(DiagnosticSeverity::ERROR),
+                    code: Some(NumberOrString::Number(
+                        TypeDiagnosticCode::UndefinedLabel.as_number(),
+                    )),
+                    code_description: None,
+                    source: Some("ts-lsp-rust".to_string()),
+                    message: TypeDiagnosticCode::UndefinedLabel.message(keyword),
+                    related_information: None,
+                    tags: None,
+                    data: None,
+                });
+            }
+        }
+    }
+
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        check_jumps(child, source, index, diagnostics);
+    }
+}
+
+/// Report every declaration the binder marked as redeclaring a name of its
+/// scope, at the later declaration's name. A clash involving `let` or `const`
+/// is a redeclared block-scoped variable, others are duplicate identifiers.
+fn check_duplicate_declarations(symbol_table: &SymbolTable, diagnostics: &mut Vec<Diagnostic>) {
+    let mut duplicates: Vec<&Symbol> = symbol_table
+        .all_symbols()
+        .filter(|symbol| symbol.flags.contains(SymbolFlags::DUPLICATE))
+        .collect();
+    duplicates.sort_by_key(|symbol| {
+        (
+            symbol.name_range.start.line,
+            symbol.name_range.start.character,
+        )
+    });
+
+    let block_scoped = SymbolFlags::LET | SymbolFlags::CONST;
+    for symbol in duplicates {
+        let first = symbol_table
+            .get_scope(symbol.scope_id)
+            .and_then(|scope| scope.lookup_local(&symbol.name))
+            .and_then(|id| symbol_table.get_symbol(id));
+        let code = if symbol.flags.intersects(block_scoped)
+            || first.is_some_and(|first| first.flags.intersects(block_scoped))
+        {
+            TypeDiagnosticCode::RedeclaredBlockScopedVariable
+        } else {
+            TypeDiagnosticCode::DuplicateIdentifier
+        };
+        diagnostics.push(Diagnostic {
+            range: symbol.name_range,
+            severity: Some(DiagnosticSeverity::ERROR),
+            code: Some(NumberOrString::Number(code.as_number())),
+            code_description: None,
+            source: Some("ts-lsp-rust".to_string()),
+            message: code.message(&symbol.name),
+            related_information: None,
+            tags: None,
+            data: None,
+        });
+    }
+}
+
+/// Hint at values declared in a nested scope under a name an enclosing
+/// scope already declares, when the `noShadow` lint is on. Builtin globals
+/// such as `Error` may be shadowed freely.
+fn check_variable_shadowing(
+    symbol_table: &SymbolTable,
+    lints: &LintOptions,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    if !lints.no_shadow {
+        return;
+    }
+    let declarations = SymbolFlags::VARIABLE
+        | SymbolFlags::PARAMETER
+        | SymbolFlags::FUNCTION
+        | SymbolFlags::CLASS
+        | SymbolFlags::ENUM;
+
+    let mut shadowing: Vec<&Symbol> = symbol_table
+        .all_symbols()
+        .filter(|symbol| symbol.flags.intersects(declarations))
+        .filter(|symbol| !symbol.flags.contains(SymbolFlags::DUPLICATE))
+        .filter(|symbol| !globals::is_global(&symbol.name))
+        .filter(|symbol| {
+            symbol_table
+                .get_scope(symbol.scope_id)
+                .and_then(|scope| scope.parent)
+                .and_then(|parent| symbol_table.lookup(&symbol.name, parent))
+                .and_then(|outer| symbol_table.get_symbol(outer))
+                .is_some_and(|outer| outer.is_value())
+        })
+        .collect();
+    shadowing.sort_by_key(|symbol| {
+        (
+            symbol.name_range.start.line,
+            symbol.name_range.start.character,
+        )
+    });
+
+    for symbol in shadowing {
+        diagnostics.push(Diagnostic {
+            range: symbol.name_range,
+            severity: Some(DiagnosticSeverity::HINT),
+            code: Some(NumberOrString::Number(
+                TypeDiagnosticCode::ShadowedVariable.as_number(),
+            )),
+            code_description: None,
+            source: Some("ts-lsp-rust".to_string()),
+            message: TypeDiagnosticCode::ShadowedVariable.message(&symbol.name),
+            related_information: None,
+            tags: None,
+            data: None,
+        });
+    }
+}
+
+/// Whether a function or class body lies between the scope of a use and the
+/// scope of the declaration it refers to
+fn is_deferred_use(symbol_table: &SymbolTable, use_scope: u32, declaration_scope: u32) -> bool {
+    let mut current = use_scope;
+    while current != declaration_scope {
+        let Some(scope) = symbol_table.get_scope(current) else {
+            return false;
+        };
+        if matches!(
+            scope.kind,
+            ScopeKind::Function | ScopeKind::ArrowFunction | ScopeKind::Class
+        ) {
+            return true;
+        }
+        let Some(parent) = scope.parent else {
+            return false;
+        };
+        current = parent;
+    }
+    false
+}
+
+/// Check for unused variables.
+/// Unused locals and parameters are reported only when `noUnusedLocals` or
+/// `noUnusedParameters` is turned on, as tsc does.
+fn check_unused_variables(
+    symbol_table: &SymbolTable,
+    options: &CompilerOptions,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let report_locals = options.no_unused_locals.unwrap_or(false);
+    let report_parameters = options.no_unused_parameters.unwrap_or(false);
+
     for symbol in symbol_table.all_symbols() {
         // Skip if not a variable or parameter
         if !symbol
@@ -205,8 +642,11 @@ fn check_unused_variables(symbol_table: &SymbolTable, diagnostics: &mut Vec<Diag
             continue;
         }
 
-        // Skip imported symbols
-        if symbol.flags.contains(SymbolFlags::IMPORT) {
+        // Skip imported symbols, and exported ones which other modules read
+        if symbol
+            .flags
+            .intersects(SymbolFlags::IMPORT | SymbolFlags::EXPORTED)
+        {
             continue;
         }
 
@@ -215,9 +655,34 @@ fn check_unused_variables(symbol_table: &SymbolTable, diagnostics: &mut Vec<Diag
             continue;
         }
 
+        let is_parameter = symbol.flags.contains(SymbolFlags::PARAMETER);
+        if !(if is_parameter {
+            report_parameters
+        } else {
+            report_locals
+        }) {
+            continue;
+        }
+
+        // Parameter properties such as `constructor(private x: number)`
+        // are class members, read through `this`
+        let property_modifiers = SymbolFlags::PUBLIC
+            | SymbolFlags::PRIVATE
+            | SymbolFlags::PROTECTED
+            | SymbolFlags::READONLY;
+        if is_parameter && symbol.flags.intersects(property_modifiers) {
+            continue;
+        }
+
+        // A parameter has to stay in place when a later one is used,
+        // as in `(a, b) => b`
+        if is_parameter && later_parameter_is_used(symbol_table, symbol) {
+            continue;
+        }
+
         // Check if the symbol has any references
-        if symbol.references.is_empty() {
-            let code = if symbol.flags.contains(SymbolFlags::PARAMETER) {
+        if symbol.references.is_empty() && !redeclaration_is_used(symbol_table, symbol) {
+            let code = if is_parameter {
                 TypeDiagnosticCode::UnusedParameter
             } else {
                 TypeDiagnosticCode::UnusedVariable
@@ -238,204 +703,1628 @@ fn check_unused_variables(symbol_table: &SymbolTable, diagnostics: &mut Vec<Diag
     }
 }
 
-/// Check for reassignment of const variables
-fn check_const_reassignment(
+/// Check for imports that are never read, when `noUnusedLocals` is
+/// enabled. A statement none of whose bindings is used is reported as a
+/// whole, otherwise each unused binding is. Names used in type positions
+/// count as used.
+fn check_unused_imports(
     tree: &Tree,
     source: &str,
     symbol_table: &SymbolTable,
+    options: &CompilerOptions,
     diagnostics: &mut Vec<Diagnostic>,
 ) {
-    let root = tree.root_node();
-    check_assignments(root, source, symbol_table, diagnostics);
-}
+    if !options.no_unused_locals.unwrap_or(false) {
+        return;
+    }
 
-fn check_assignments(
-    node: Node,
-    source: &str,
-    symbol_table: &SymbolTable,
-    diagnostics: &mut Vec<Diagnostic>,
-) {
-    // Check assignment expressions
-    if node.kind() == "assignment_expression" {
-        if let Some(left) = node.child_by_field_name("left") {
-            if left.kind() == "identifier" {
-                let name = left.utf8_text(source.as_bytes()).unwrap_or("");
-                let position = Position::new(
-                    left.start_position().row as u32,
-                    left.start_position().column as u32,
-                );
-                let scope_id = symbol_table.scope_at_position(position);
+    let root = tree.root_node();
+    let mut type_names = Vec::new();
+    collect_type_names(root, source, &mut type_names);
+    let index = LineIndex::new(source);
 
-                if let Some(symbol_id) = symbol_table.lookup(name, scope_id) {
-                    if let Some(symbol) = symbol_table.get_symbol(symbol_id) {
-                        if symbol.flags.contains(SymbolFlags::CONST) {
-                            let range = Range {
-                                start: Position::new(
-                                    left.start_position().row as u32,
-                                    left.start_position().column as u32,
-                                ),
-                                end: Position::new(
-                                    left.end_position().row as u32,
-                                    left.end_position().column as u32,
-                                ),
-                            };
+    let mut cursor = root.walk();
+    for statement in root.children(&mut cursor) {
+        if statement.kind() != "import_statement" {
+            continue;
+        }
+        let statement_range = index.node_range(&statement);
+        let bindings: Vec<&Symbol> = symbol_table
+            .all_symbols()
+            .filter(|symbol| symbol.flags.contains(SymbolFlags::IMPORT))
+            .filter(|symbol| {
+                statement_range.start <= symbol.declaration_range.start
+                    && symbol.declaration_range.end <= statement_range.end
+            })
+            .collect();
+        let mut unused: Vec<&Symbol> = bindings
+            .iter()
+            .filter(|symbol| symbol.references.is_empty())
+            .filter(|symbol| !type_names.contains(&symbol.name.as_str()))
+            .copied()
+            .collect();
+        unused.sort_by_key(|symbol| symbol.declaration_range.start);
 
-                            diagnostics.push(Diagnostic {
-                                range,
-                                severity: Some(DiagnosticSeverity::ERROR),
-                                code: Some(NumberOrString::Number(
-                                    TypeDiagnosticCode::CannotReassignConst.as_number(),
-                                )),
-                                code_description: None,
-                                source: Some("ts-lsp-rust".to_string()),
-                                message: TypeDiagnosticCode::CannotReassignConst.message(name),
-                                related_information: None,
-                                tags: None,
-                                data: None,
-                            });
-                        }
-                    }
-                }
+        let reports: Vec<(Range, TypeDiagnosticCode, &str)> = match unused.as_slice() {
+            [] => Vec::new(),
+            [only] if bindings.len() == 1 => vec![(
+                statement_range,
+                TypeDiagnosticCode::UnusedVariable,
+                only.name.as_str(),
+            )],
+            all if all.len() == bindings.len() => {
+                vec![(statement_range, TypeDiagnosticCode::UnusedImports, "")]
             }
+            some => some
+                .iter()
+                .map(|symbol| {
+                    (
+                        symbol.declaration_range,
+                        TypeDiagnosticCode::UnusedVariable,
+                        symbol.name.as_str(),
+                    )
+                })
+                .collect(),
+        };
+
+        for (range, code, name) in reports {
+            diagnostics.push(Diagnostic {
+                range,
+                severity: Some(DiagnosticSeverity::HINT),
+                code: Some(NumberOrString::Number(code.as_number())),
+                code_description: None,
+                source: Some("ts-lsp-rust".to_string()),
+                message: code.message(name),
+                related_information: None,
+                tags: Some(vec![DiagnosticTag::UNNECESSARY]),
+                data: None,
+            });
         }
     }
+}
 
-    // Check update expressions (++, --)
-    if node.kind() == "update_expression" {
-        let mut cursor = node.walk();
-        for child in node.children(&mut cursor) {
-            if child.kind() == "identifier" {
-                let name = child.utf8_text(source.as_bytes()).unwrap_or("");
-                let position = Position::new(
-                    child.start_position().row as u32,
-                    child.start_position().column as u32,
-                );
-                let scope_id = symbol_table.scope_at_position(position);
+/// Whether a parameter declared after `parameter` in the same list is read
+fn later_parameter_is_used(symbol_table: &SymbolTable, parameter: &Symbol) -> bool {
+    symbol_table.all_symbols().any(|other| {
+        other.flags.contains(SymbolFlags::PARAMETER)
+            && other.scope_id == parameter.scope_id
+            && other.name_range.start > parameter.name_range.start
+            && !other.references.is_empty()
+    })
+}
 
-                if let Some(symbol_id) = symbol_table.lookup(name, scope_id) {
-                    if let Some(symbol) = symbol_table.get_symbol(symbol_id) {
-                        if symbol.flags.contains(SymbolFlags::CONST) {
-                            let range = Range {
-                                start: Position::new(
-                                    child.start_position().row as u32,
-                                    child.start_position().column as u32,
-                                ),
-                                end: Position::new(
-                                    child.end_position().row as u32,
-                                    child.end_position().column as u32,
-                                ),
-                            };
+/// Whether another `var` declaration of `symbol`'s name in the same function
+/// scope is read: `var a = 1; var a = 2; use(a);` declares one variable
+fn redeclaration_is_used(symbol_table: &SymbolTable, symbol: &Symbol) -> bool {
+    let var = SymbolFlags::VARIABLE | SymbolFlags::HOISTED;
+    symbol.flags.contains(var)
+        && symbol_table.all_symbols().any(|other| {
+            other.flags.contains(var)
+                && other.id != symbol.id
+                && other.scope_id == symbol.scope_id
+                && other.name == symbol.name
+                && !other.references.is_empty()
+        })
+}
 
-                            diagnostics.push(Diagnostic {
-                                range,
-                                severity: Some(DiagnosticSeverity::ERROR),
-                                code: Some(NumberOrString::Number(
-                                    TypeDiagnosticCode::CannotReassignConst.as_number(),
-                                )),
-                                code_description: None,
-                                source: Some("ts-lsp-rust".to_string()),
-                                message: TypeDiagnosticCode::CannotReassignConst.message(name),
-                                related_information: None,
-                                tags: None,
-                                data: None,
-                            });
-                        }
-                    }
-                }
-            }
+/// Report parameters without a type annotation or default value when
+/// `noImplicitAny` is enabled, directly or through `strict`
+fn check_implicit_any(
+    tree: &Tree,
+    source: &str,
+    options: &CompilerOptions,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let enabled = options.no_implicit_any.or(options.strict).unwrap_or(false);
+    if !enabled {
+        return;
+    }
+    let index = LineIndex::new(source);
+    check_parameter_types(tree.root_node(), source, &index, diagnostics);
+}
+
+fn check_parameter_types(
+    node: Node,
+    source: &str,
+    index: &LineIndex,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    if matches!(node.kind(), "required_parameter" | "optional_parameter")
+        && node.child_by_field_name("type").is_none()
+        && node.child_by_field_name("value").is_none()
+        && !node
+            .parent()
+            .and_then(|parameters| parameters.parent())
+            .is_some_and(is_contextually_typed)
+    {
+        // Destructured and rest parameters are reported by other codes
+        if let Some(name) = node
+            .child_by_field_name("pattern")
+            .filter(|pattern| pattern.kind() == "identifier")
+        {
+            let name_text = name.utf8_text(source.as_bytes()).unwrap_or("");
+            diagnostics.push(Diagnostic {
+                range: index.node_range(&name),
+                severity: Some(DiagnosticSeverity::ERROR),
+                code: Some(NumberOrString::Number(
+                    TypeDiagnosticCode::NoImplicitAny.as_number(),
+                )),
+                code_description: None,
+                source: Some("ts-lsp-rust".to_string()),
+                message: TypeDiagnosticCode::NoImplicitAny.message(name_text),
+                related_information: None,
+                tags: None,
+                data: None,
+            });
         }
     }
 
-    // Recurse into children
     let mut cursor = node.walk();
     for child in node.children(&mut cursor) {
-        check_assignments(child, source, symbol_table, diagnostics);
+        check_parameter_types(child, source, index, diagnostics);
     }
 }
 
-/// Check if a name is a built-in global
-fn is_builtin_global(name: &str) -> bool {
-    matches!(
-        name,
-        "console"
-            | "window"
-            | "document"
-            | "global"
-            | "globalThis"
-            | "process"
-            | "require"
-            | "module"
-            | "exports"
-            | "__dirname"
-            | "__filename"
-            | "Buffer"
-            | "setTimeout"
-            | "setInterval"
-            | "clearTimeout"
-            | "clearInterval"
-            | "setImmediate"
-            | "clearImmediate"
-            | "Promise"
-            | "Array"
-            | "Object"
-            | "String"
-            | "Number"
-            | "Boolean"
-            | "Symbol"
-            | "BigInt"
-            | "Function"
-            | "Date"
-            | "RegExp"
-            | "Error"
-            | "TypeError"
-            | "ReferenceError"
-            | "SyntaxError"
-            | "RangeError"
-            | "EvalError"
-            | "URIError"
-            | "Map"
-            | "Set"
-            | "WeakMap"
-            | "WeakSet"
-            | "Proxy"
-            | "Reflect"
-            | "JSON"
-            | "Math"
-            | "Intl"
-            | "Atomics"
-            | "SharedArrayBuffer"
-            | "ArrayBuffer"
-            | "DataView"
-            | "Int8Array"
-            | "Uint8Array"
-            | "Uint8ClampedArray"
-            | "Int16Array"
-            | "Uint16Array"
-            | "Int32Array"
-            | "Uint32Array"
-            | "Float32Array"
-            | "Float64Array"
-            | "BigInt64Array"
-            | "BigUint64Array"
-            | "NaN"
-            | "Infinity"
-            | "undefined"
-            | "eval"
-            | "isFinite"
-            | "isNaN"
-            | "parseFloat"
-            | "parseInt"
-            | "decodeURI"
-            | "decodeURIComponent"
-            | "encodeURI"
-            | "encodeURIComponent"
-            | "escape"
-            | "unescape"
-            | "React"
-            | "JSX"
+/// Whether a function expression takes its parameter types from where it
+/// appears: as a call argument, the value of an annotated declaration or of
+/// an assignment, or a property of an object literal that is itself typed
+fn is_contextually_typed(function: Node) -> bool {
+    if !matches!(function.kind(), "arrow_function" | "function_expression") {
+        return false;
+    }
+    let mut node = function;
+    while let Some(parent) = node.parent() {
+        match parent.kind() {
+            "parenthesized_expression" => {}
+            "pair" if parent.child_by_field_name("value") == Some(node) => {
+                // Continue from the object literal
+                let Some(object) = parent.parent() else {
+                    return false;
+                };
+                node = object;
+                continue;
+            }
+            "arguments" | "as_expression" | "satisfies_expression" => return true,
+            "variable_declarator" => {
+                return parent.child_by_field_name("value") == Some(node)
+                    && parent.child_by_field_name("type").is_some();
+            }
+            "assignment_expression" => {
+                return parent.child_by_field_name("right") == Some(node);
+            }
+            _ => return false,
+        }
+        node = parent;
+    }
+    false
+}
+
+/// Flag `this` inside plain functions, where it isn't bound to a class
+/// instance or object, when `noImplicitThis` (or `strict`) is enabled.
+/// Arrow functions take `this` from where they are defined.
+fn check_implicit_this(
+    tree: &Tree,
+    source: &str,
+    options: &CompilerOptions,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let enabled = options.no_implicit_this.or(options.strict).unwrap_or(false);
+    if !enabled {
+        return;
+    }
+    let index = LineIndex::new(source);
+    check_this_nodes(tree.root_node(), &index, diagnostics);
+}
+
+fn check_this_nodes(node: Node, index: &LineIndex, diagnostics: &mut Vec<Diagnostic>) {
+    if node.kind() == "this" && this_is_unbound(node) {
+        diagnostics.push(Diagnostic {
+            range: index.node_range(&node),
+            severity: Some(DiagnosticSeverity::ERROR),
+            code: Some(NumberOrString::Number(
+                TypeDiagnosticCode::ImplicitThis.as_number(),
+            )),
+            code_description: None,
+            source: Some("ts-lsp-rust".to_string()),
+            message: TypeDiagnosticCode::ImplicitThis.message(""),
+            related_information: None,
+            tags: None,
+            data: None,
+        });
+    }
+
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        check_this_nodes(child, index, diagnostics);
+    }
+}
+
+/// Whether the function `this` belongs to is neither a method nor declares
+/// a `this` parameter
+fn this_is_unbound(this: Node) -> bool {
+    let mut current = this.parent();
+    while let Some(node) = current {
+        match node.kind() {
+            // Methods, accessors, field initializers and static blocks
+            "method_definition" | "class_body" => return false,
+            "function_declaration"
+            | "function_expression"
+            | "generator_function"
+            | "generator_function_declaration" => {
+                let has_this_parameter = node
+                    .child_by_field_name("parameters")
+                    .and_then(|params| params.named_child(0))
+                    .and_then(|first| first.child_by_field_name("pattern"))
+                    .is_some_and(|pattern| pattern.kind() == "this");
+                // `{ m: function () { ... } }` is a method of the object
+                let is_property = node.parent().is_some_and(|p| p.kind() == "pair");
+                return !has_this_parameter && !is_property;
+            }
+            _ => current = node.parent(),
+        }
+    }
+    // Module-level `this`
+    false
+}
+
+/// Flag statements of a block that follow an unconditional `return`,
+/// `throw`, `break` or `continue`
+fn check_unreachable_code(tree: &Tree, source: &str, diagnostics: &mut Vec<Diagnostic>) {
+    let index = LineIndex::new(source);
+    check_block_reachability(tree.root_node(), &index, diagnostics);
+}
+
+fn check_block_reachability(node: Node, index: &LineIndex, diagnostics: &mut Vec<Diagnostic>) {
+    if node.kind() == "statement_block" {
+        let mut terminated = false;
+        let mut cursor = node.walk();
+        for statement in node.named_children(&mut cursor) {
+            if terminated {
+                // Declarations are hoisted and can still be used
+                let hoisted = matches!(
+                    statement.kind(),
+                    "function_declaration"
+                        | "generator_function_declaration"
+                        | "class_declaration"
+                        | "abstract_class_declaration"
+                        | "interface_declaration"
+                        | "type_alias_declaration"
+                        | "comment"
+                );
+                if !hoisted {
+                    diagnostics.push(Diagnostic {
+                        range: index.node_range(&statement),
+                        severity: Some(DiagnosticSeverity::HINT),
+                        code: Some(NumberOrString::Number(
+                            TypeDiagnosticCode::UnreachableCode.as_number(),
+                        )),
+                        code_description: None,
+                        source: Some("ts-lsp-rust".to_string()),
+                        message: TypeDiagnosticCode::UnreachableCode.message(""),
+                        related_information: None,
+                        tags: Some(vec![DiagnosticTag::UNNECESSARY]),
+                        data: None,
+                    });
+                }
+            } else if matches!(
+                statement.kind(),
+                "return_statement" | "throw_statement" | "break_statement" | "continue_statement"
+            ) {
+                terminated = true;
+            }
+        }
+    }
+
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        check_block_reachability(child, index, diagnostics);
+    }
+}
+
+/// Flag non-empty switch clauses that fall through into the next clause
+/// when `noFallthroughCasesInSwitch` is enabled
+fn check_switch_fallthrough(
+    tree: &Tree,
+    source: &str,
+    options: &CompilerOptions,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    if !options.no_fallthrough_cases_in_switch.unwrap_or(false) {
+        return;
+    }
+    let index = LineIndex::new(source);
+    check_switch_cases(tree.root_node(), &index, diagnostics);
+}
+
+fn check_switch_cases(node: Node, index: &LineIndex, diagnostics: &mut Vec<Diagnostic>) {
+    if node.kind() == "switch_body" {
+        let mut cursor = node.walk();
+        let clauses: Vec<Node> = node
+            .named_children(&mut cursor)
+            .filter(|child| matches!(child.kind(), "switch_case" | "switch_default"))
+            .collect();
+
+        // The last clause has nothing to fall into
+        for clause in clauses.iter().take(clauses.len().saturating_sub(1)) {
+            let mut cursor = clause.walk();
+            let last = clause
+                .children_by_field_name("body", &mut cursor)
+                .filter(|statement| statement.kind() != "comment")
+                .last();
+            // Stacked labels (`case 1: case 2:`) have no statements
+            let Some(last) = last else {
+                continue;
+            };
+            if ends_with_jump(last) {
+                continue;
+            }
+            if let Some(keyword) = clause.child(0) {
+                diagnostics.push(Diagnostic {
+                    range: index.node_range(&keyword),
+                    severity: Some(DiagnosticSeverity::ERROR),
+                    code: Some(NumberOrString::Number(
+                        TypeDiagnosticCode::FallthroughCase.as_number(),
+                    )),
+                    code_description: None,
+                    source: Some("ts-lsp-rust".to_string()),
+                    message: TypeDiagnosticCode::FallthroughCase.message(""),
+                    related_information: None,
+                    tags: None,
+                    data: None,
+                });
+            }
+        }
+    }
+
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        check_switch_cases(child, index, diagnostics);
+    }
+}
+
+/// Whether a statement ends in `break`, `return`, `throw` or `continue`,
+/// looking into the last statement of a block and both branches of an `if`
+fn ends_with_jump(statement: Node) -> bool {
+    match statement.kind() {
+        "break_statement" | "return_statement" | "throw_statement" | "continue_statement" => true,
+        "statement_block" => {
+            let mut cursor = statement.walk();
+            statement
+                .named_children(&mut cursor)
+                .filter(|child| child.kind() != "comment")
+                .last()
+                .is_some_and(ends_with_jump)
+        }
+        "if_statement" => {
+            let consequence = statement
+                .child_by_field_name("consequence")
+                .is_some_and(ends_with_jump);
+            let alternative = statement
+                .child_by_field_name("alternative")
+                .and_then(|clause| clause.named_child(0))
+                .is_some_and(ends_with_jump);
+            consequence && alternative
+        }
+        "try_statement" => {
+            let finalizer = statement
+                .child_by_field_name("finalizer")
+                .and_then(|clause| clause.child_by_field_name("body"))
+                .is_some_and(ends_with_jump);
+            let body = statement
+                .child_by_field_name("body")
+                .is_some_and(ends_with_jump);
+            let handler = statement
+                .child_by_field_name("handler")
+                .map(|clause| {
+                    clause
+                        .child_by_field_name("body")
+                        .is_some_and(ends_with_jump)
+                })
+                .unwrap_or(true);
+            finalizer || (body && handler)
+        }
+        _ => false,
+    }
+}
+
+/// Report switches without a `default` whose discriminant has a finite
+/// type - a union of literals, or an enum - with members no `case` handles,
+/// when the `switchExhaustivenessCheck` lint is on
+fn check_switch_exhaustiveness(
+    tree: &Tree,
+    source: &str,
+    typed: &SymbolTable,
+    checker: &mut TypeChecker,
+    lints: &LintOptions,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    if !lints.switch_exhaustiveness_check {
+        return;
+    }
+    let root = tree.root_node();
+    let index = LineIndex::new(source);
+
+    let mut stack = vec![root];
+    while let Some(node) = stack.pop() {
+        let mut cursor = node.walk();
+        stack.extend(node.children(&mut cursor));
+
+        if node.kind() != "switch_statement" || has_default_clause(node) {
+            continue;
+        }
+        let Some(missing) = unmatched_members(node, source, &index, typed, checker) else {
+            continue;
+        };
+        if missing.is_empty() {
+            continue;
+        }
+
+        if let Some(keyword) = node.child(0) {
+            let code = TypeDiagnosticCode::NonExhaustiveSwitch;
+            diagnostics.push(Diagnostic {
+                range: index.node_range(&keyword),
+                severity: Some(DiagnosticSeverity::WARNING),
+                code: Some(NumberOrString::Number(code.as_number())),
+                code_description: None,
+                source: Some("ts-lsp-rust".to_string()),
+                message: code.message(&missing.join(" | ")),
+                related_information: None,
+                tags: None,
+                data: None,
+            });
+        }
+    }
+}
+
+fn has_default_clause(switch: Node) -> bool {
+    switch.child_by_field_name("body").is_some_and(|body| {
+        body.named_children(&mut body.walk())
+            .any(|clause| clause.kind() == "switch_default")
+    })
+}
+
+/// The members of a switch discriminant's finite type that no `case`
+/// matches, or `None` when the type is not finite
+fn unmatched_members(
+    switch: Node,
+    source: &str,
+    index: &LineIndex,
+    symbol_table: &SymbolTable,
+    checker: &mut TypeChecker,
+) -> Option<Vec<String>> {
+    let members = switch_members(switch, source, index, symbol_table, checker)?;
+    let body = switch.child_by_field_name("body")?;
+    let covered: Vec<String> = body
+        .named_children(&mut body.walk())
+        .filter_map(|clause| clause.child_by_field_name("value"))
+        .filter_map(|value| case_label(value, source, checker))
+        .collect();
+    Some(
+        members
+            .into_iter()
+            .filter(|member| !covered.contains(member))
+            .collect(),
     )
 }
 
+/// Switches without a `default` whose cases cover every member of the
+/// discriminant's finite type, by node id
+fn exhaustive_switches(
+    tree: &Tree,
+    source: &str,
+    typed: &SymbolTable,
+    checker: &mut TypeChecker,
+) -> HashSet<usize> {
+    let index = LineIndex::new(source);
+    let mut exhaustive = HashSet::new();
+    let mut stack = vec![tree.root_node()];
+    while let Some(node) = stack.pop() {
+        let mut cursor = node.walk();
+        stack.extend(node.children(&mut cursor));
+
+        if node.kind() == "switch_statement"
+            && !has_default_clause(node)
+            && unmatched_members(node, source, &index, typed, checker)
+                .is_some_and(|missing| missing.is_empty())
+        {
+            exhaustive.insert(node.id());
+        }
+    }
+    exhaustive
+}
+
+/// The members of a switch discriminant's finite type, printed the way
+/// `case_label` prints case values: literals as types (`"a"`, `1`), enum
+/// members qualified by the enum (`Color.Red`)
+fn switch_members(
+    switch: Node,
+    source: &str,
+    index: &LineIndex,
+    symbol_table: &SymbolTable,
+    checker: &mut TypeChecker,
+) -> Option<Vec<String>> {
+    let value = switch.child_by_field_name("value")?;
+    let discriminant = match value.kind() {
+        "parenthesized_expression" => value.named_child(0)?,
+        _ => value,
+    };
+    if discriminant.kind() != "identifier" {
+        return None;
+    }
+    let name = discriminant.utf8_text(source.as_bytes()).ok()?;
+    let scope_id = symbol_table.scope_at_position(index.node_range(&discriminant).start);
+    let symbol = symbol_table.get_symbol(symbol_table.lookup(name, scope_id)?)?;
+    if !symbol
+        .flags
+        .intersects(SymbolFlags::VARIABLE | SymbolFlags::PARAMETER)
+    {
+        return None;
+    }
+
+    match checker.get_type(symbol.inferred_type?)? {
+        Type::Union(members) => members
+            .iter()
+            .map(|member| match member {
+                Type::StringLiteral(_)
+                | Type::NumberLiteral(_)
+                | Type::BooleanLiteral(_)
+                | Type::Null
+                | Type::Undefined => Some(crate::types::printer::print_type(member)),
+                _ => None,
+            })
+            .collect(),
+        Type::Reference(reference) if reference.type_arguments.is_empty() => {
+            let enum_symbol = symbol_table
+                .get_symbol(symbol_table.lookup(&reference.name, scope_id)?)
+                .filter(|symbol| symbol.flags.contains(SymbolFlags::ENUM))?;
+            let start = index.point(enum_symbol.declaration_range.start);
+            let end = index.point(enum_symbol.declaration_range.end);
+            let root = std::iter::successors(Some(switch), |node| node.parent()).last()?;
+            let declaration = root.descendant_for_point_range(start, end)?;
+            let enum_body = declaration.child_by_field_name("body")?;
+            let members = enum_body
+                .named_children(&mut enum_body.walk())
+                .filter_map(|member| match member.kind() {
+                    "enum_assignment" => member.child_by_field_name("name"),
+                    "property_identifier" | "string" => Some(member),
+                    _ => None,
+                })
+                .filter_map(|name| name.utf8_text(source.as_bytes()).ok())
+                .map(|name| {
+                    format!(
+                        "{}.{}",
+                        reference.name,
+                        name.trim_matches(|c| c == '"' || c == '\'')
+                    )
+                })
+                .collect();
+            Some(members)
+        }
+        _ => None,
+    }
+}
+
+/// A case value printed like the members `switch_members` lists
+fn case_label(value: Node, source: &str, checker: &mut TypeChecker) -> Option<String> {
+    let text = value.utf8_text(source.as_bytes()).ok()?;
+    match value.kind() {
+        "member_expression" => {
+            let object = value.child_by_field_name("object")?;
+            let property = value.child_by_field_name("property")?;
+            Some(format!(
+                "{}.{}",
+                object.utf8_text(source.as_bytes()).ok()?,
+                property.utf8_text(source.as_bytes()).ok()?
+            ))
+        }
+        "string" | "number" | "true" | "false" | "null" | "undefined" => {
+            let ty = checker.type_of_literal(value.kind(), text);
+            checker.get_type(ty).map(crate::types::printer::print_type)
+        }
+        _ => None,
+    }
+}
+
+/// Flag functions that return a value on some paths but can also reach
+/// the end of their body, when `noImplicitReturns` is enabled
+fn check_implicit_returns(
+    tree: &Tree,
+    source: &str,
+    typed: &SymbolTable,
+    checker: &mut TypeChecker,
+    options: &CompilerOptions,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    if !options.no_implicit_returns.unwrap_or(false) {
+        return;
+    }
+    let index = LineIndex::new(source);
+    let exhaustive = exhaustive_switches(tree, source, typed, checker);
+    check_function_returns(tree.root_node(), source, &index, &exhaustive, diagnostics);
+}
+
+fn check_function_returns(
+    node: Node,
+    source: &str,
+    index: &LineIndex,
+    exhaustive: &HashSet<usize>,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    if matches!(
+        node.kind(),
+        "function_declaration" | "function_expression" | "arrow_function" | "method_definition"
+    ) && !has_child_kind(node, "set")
+    {
+        if let Some(body) = node
+            .child_by_field_name("body")
+            .filter(|body| body.kind() == "statement_block")
+        {
+            // Falling off the end is fine when the declared type admits it,
+            // including unions such as `number | undefined`
+            let returns_nothing = node
+                .child_by_field_name("return_type")
+                .and_then(|annotation| annotation.named_child(0))
+                .and_then(|ty| ty.utf8_text(source.as_bytes()).ok())
+                .is_some_and(|ty| {
+                    let ty = ty
+                        .strip_prefix("Promise<")
+                        .and_then(|inner| inner.strip_suffix('>'))
+                        .unwrap_or(ty);
+                    ty.split('|').map(str::trim).any(|member| {
+                        matches!(member, "void" | "undefined" | "any" | "unknown" | "never")
+                    })
+                });
+
+            if !returns_nothing && returns_value(body) && !always_exits(body, exhaustive) {
+                // The name, or the closing brace of an anonymous function
+                let target = node
+                    .child_by_field_name("name")
+                    .or_else(|| body.child(body.child_count().saturating_sub(1)))
+                    .unwrap_or(body);
+                diagnostics.push(Diagnostic {
+                    range: index.node_range(&target),
+                    severity: Some(DiagnosticSeverity::ERROR),
+                    code: Some(NumberOrString::Number(
+                        TypeDiagnosticCode::NotAllPathsReturn.as_number(),
+                    )),
+                    code_description: None,
+                    source: Some("ts-lsp-rust".to_string()),
+                    message: TypeDiagnosticCode::NotAllPathsReturn.message(""),
+                    related_information: None,
+                    tags: None,
+                    data: None,
+                });
+            }
+        }
+    }
+
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        check_function_returns(child, source, index, exhaustive, diagnostics);
+    }
+}
+
+fn has_child_kind(node: Node, kind: &str) -> bool {
+    let mut cursor = node.walk();
+    node.children(&mut cursor).any(|child| child.kind() == kind)
+}
+
+/// Whether `node` has a `return <value>` outside of nested functions
+fn returns_value(node: Node) -> bool {
+    let mut cursor = node.walk();
+    node.named_children(&mut cursor)
+        .any(|child| match child.kind() {
+            "return_statement" => child.named_child(0).is_some_and(|v| v.kind() != "comment"),
+            "function_declaration"
+            | "function_expression"
+            | "arrow_function"
+            | "method_definition"
+            | "class_declaration"
+            | "class" => false,
+            _ => returns_value(child),
+        })
+}
+
+/// Whether every path through `statements` ends in `return` or `throw`
+fn always_exits_all<'t>(
+    mut statements: impl Iterator<Item = Node<'t>>,
+    exhaustive: &HashSet<usize>,
+) -> bool {
+    statements.any(|statement| always_exits(statement, exhaustive))
+}
+
+/// Whether every path through a statement ends in `return` or `throw`.
+/// `exhaustive` holds the switches whose cases cover their discriminant's
+/// type, which exit without a `default`.
+fn always_exits(statement: Node, exhaustive: &HashSet<usize>) -> bool {
+    let exits = |node: Node| always_exits(node, exhaustive);
+    match statement.kind() {
+        "return_statement" | "throw_statement" => true,
+        "statement_block" => {
+            let mut cursor = statement.walk();
+            always_exits_all(statement.named_children(&mut cursor), exhaustive)
+        }
+        "if_statement" => {
+            let consequence = statement
+                .child_by_field_name("consequence")
+                .is_some_and(exits);
+            let alternative = statement
+                .child_by_field_name("alternative")
+                .and_then(|clause| clause.named_child(0))
+                .is_some_and(exits);
+            consequence && alternative
+        }
+        "switch_statement" => {
+            let covered = has_default_clause(statement) || exhaustive.contains(&statement.id());
+            covered
+                && statement
+                    .child_by_field_name("body")
+                    .is_some_and(|body| clauses_always_exit(body, exhaustive))
+        }
+        // `while (true)` and `for (;;)` only end through `break`
+        "while_statement" | "do_statement" | "for_statement" => {
+            let endless = match statement.child_by_field_name("condition") {
+                None => true,
+                Some(condition) => match condition.kind() {
+                    "empty_statement" => true,
+                    "parenthesized_expression" => {
+                        condition.named_child(0).is_some_and(|c| c.kind() == "true")
+                    }
+                    _ => condition.kind() == "true",
+                },
+            };
+            endless
+                && !statement
+                    .child_by_field_name("body")
+                    .is_some_and(breaks_out)
+        }
+        "try_statement" => {
+            let finalizer = statement
+                .child_by_field_name("finalizer")
+                .and_then(|clause| clause.child_by_field_name("body"))
+                .is_some_and(exits);
+            let body = statement.child_by_field_name("body").is_some_and(exits);
+            let handler = statement
+                .child_by_field_name("handler")
+                .map(|clause| clause.child_by_field_name("body").is_some_and(exits))
+                .unwrap_or(true);
+            finalizer || (body && handler)
+        }
+        _ => false,
+    }
+}
+
+/// Whether `node` has a `break` leaving the loop it belongs to: an unlabeled
+/// one outside nested loops and switches, or any labeled one
+fn breaks_out(node: Node) -> bool {
+    let mut cursor = node.walk();
+    node.named_children(&mut cursor)
+        .any(|child| match child.kind() {
+            "break_statement" => true,
+            "while_statement" | "do_statement" | "for_statement" | "for_in_statement"
+            | "switch_statement" => has_labeled_break(child),
+            "function_declaration"
+            | "function_expression"
+            | "arrow_function"
+            | "method_definition"
+            | "class_declaration"
+            | "class" => false,
+            _ => breaks_out(child),
+        })
+}
+
+fn has_labeled_break(node: Node) -> bool {
+    let mut cursor = node.walk();
+    node.named_children(&mut cursor)
+        .any(|child| match child.kind() {
+            "break_statement" => child.child_by_field_name("label").is_some(),
+            "function_declaration"
+            | "function_expression"
+            | "arrow_function"
+            | "method_definition"
+            | "class_declaration"
+            | "class" => false,
+            _ => has_labeled_break(child),
+        })
+}
+
+/// Whether every clause of a switch body exits, either itself or by
+/// falling through into a clause that does
+fn clauses_always_exit(body: Node, exhaustive: &HashSet<usize>) -> bool {
+    let mut cursor = body.walk();
+    let clauses: Vec<Node> = body
+        .named_children(&mut cursor)
+        .filter(|child| matches!(child.kind(), "switch_case" | "switch_default"))
+        .collect();
+
+    let mut next_exits = false;
+    for clause in clauses.iter().rev() {
+        let mut cursor = clause.walk();
+        let statements: Vec<Node> = clause
+            .children_by_field_name("body", &mut cursor)
+            .filter(|statement| statement.kind() != "comment")
+            .collect();
+        let exits = if statements.is_empty() {
+            next_exits
+        } else if always_exits_all(statements.iter().copied(), exhaustive) {
+            true
+        } else if statements
+            .last()
+            .is_some_and(|last| last.kind() == "break_statement")
+        {
+            false
+        } else {
+            next_exits
+        };
+        if !exits {
+            return false;
+        }
+        next_exits = exits;
+    }
+    true
+}
+
+/// Warn about statements that call an `async` function of this file (or one
+/// declared to return a `Promise`) and discard the result. Awaiting,
+/// assigning, chaining or `void`-ing the call all count as handling it.
+fn check_unhandled_promises(
+    tree: &Tree,
+    source: &str,
+    symbol_table: &SymbolTable,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let index = LineIndex::new(source);
+    check_promise_statements(tree.root_node(), source, &index, symbol_table, diagnostics);
+}
+
+fn check_promise_statements(
+    node: Node,
+    source: &str,
+    index: &LineIndex,
+    symbol_table: &SymbolTable,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    if node.kind() == "expression_statement" {
+        let callee = node
+            .named_child(0)
+            .filter(|expression| expression.kind() == "call_expression")
+            .and_then(|call| Some((call, call.child_by_field_name("function")?)))
+            .filter(|(_, callee)| callee.kind() == "identifier");
+
+        if let Some((call, callee)) = callee {
+            let name = callee.utf8_text(source.as_bytes()).unwrap_or("");
+            let range = index.node_range(&call);
+            let returns_promise = symbol_table
+                .lookup(name, symbol_table.scope_at_position(range.start))
+                .and_then(|id| symbol_table.get_symbol(id))
+                .is_some_and(|symbol| {
+                    symbol.flags.contains(SymbolFlags::ASYNC)
+                        || symbol
+                            .signature
+                            .as_ref()
+                            .and_then(|signature| signature.return_type.as_deref())
+                            .is_some_and(|ty| ty.starts_with("Promise<"))
+                });
+
+            if returns_promise {
+                diagnostics.push(Diagnostic {
+                    range,
+                    severity: Some(DiagnosticSeverity::WARNING),
+                    code: Some(NumberOrString::Number(
+                        TypeDiagnosticCode::UnhandledPromise.as_number(),
+                    )),
+                    code_description: None,
+                    source: Some("ts-lsp-rust".to_string()),
+                    message: TypeDiagnosticCode::UnhandledPromise.message(name),
+                    related_information: None,
+                    tags: None,
+                    data: None,
+                });
+            }
+        }
+    }
+
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        check_promise_statements(child, source, index, symbol_table, diagnostics);
+    }
+}
+
+/// Report the import that closes an import cycle through `uri`, e.g.
+/// `a.ts → b.ts → a.ts`. Cycles start at their first (smallest) document and
+/// only the file importing it back reports the cycle. `resolve` maps a
+/// module specifier of this file to the document it imports.
+pub fn check_circular_imports(
+    file_graph: &FileGraph,
+    uri: &Url,
+    tree: &Tree,
+    source: &str,
+    resolve: &dyn Fn(&str) -> Option<Url>,
+) -> Option<Diagnostic> {
+    let cycle = file_graph
+        .find_cycles()
+        .into_iter()
+        .find(|cycle| cycle.last() == Some(uri))?;
+    let start = cycle.first()?;
+
+    let root = tree.root_node();
+    let mut cursor = root.walk();
+    let statement = root
+        .children(&mut cursor)
+        .filter(|statement| matches!(statement.kind(), "import_statement" | "export_statement"))
+        .find(|statement| {
+            statement
+                .child_by_field_name("source")
+                .and_then(|specifier| specifier.utf8_text(source.as_bytes()).ok())
+                .map(|specifier| specifier.trim_matches(|c| c == '"' || c == '\'' || c == '`'))
+                .and_then(resolve)
+                .as_ref()
+                == Some(start)
+        })?;
+
+    // Spell the cycle from its first document back around to it
+    let file_name = |uri: &Url| {
+        uri.path_segments()
+            .and_then(|mut segments| segments.next_back())
+            .unwrap_or(uri.as_str())
+            .to_string()
+    };
+    let mut names: Vec<String> = cycle.iter().map(file_name).collect();
+    names.push(file_name(start));
+
+    Some(Diagnostic {
+        range: LineIndex::new(source).node_range(&statement),
+        severity: Some(DiagnosticSeverity::WARNING),
+        code: Some(NumberOrString::Number(
+            TypeDiagnosticCode::CircularImport.as_number(),
+        )),
+        code_description: None,
+        source: Some("ts-lsp-rust".to_string()),
+        message: TypeDiagnosticCode::CircularImport.message(&names.join(" → ")),
+        related_information: None,
+        tags: None,
+        data: None,
+    })
+}
+
+/// Check for reassignment of const variables
+fn check_const_reassignment(
+    tree: &Tree,
+    source: &str,
+    symbol_table: &SymbolTable,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let root = tree.root_node();
+    let index = LineIndex::new(source);
+    check_assignments(root, source, &index, symbol_table, diagnostics);
+}
+
+fn check_assignments(
+    node: Node,
+    source: &str,
+    index: &LineIndex,
+    symbol_table: &SymbolTable,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    // Check assignment expressions
+    if node.kind() == "assignment_expression" {
+        if let Some(left) = node.child_by_field_name("left") {
+            if left.kind() == "identifier" {
+                let name = left.utf8_text(source.as_bytes()).unwrap_or("");
+                let range = index.node_range(&left);
+                let scope_id = symbol_table.scope_at_position(range.start);
+
+                if let Some(symbol_id) = symbol_table.lookup(name, scope_id) {
+                    if let Some(symbol) = symbol_table.get_symbol(symbol_id) {
+                        if symbol.flags.contains(SymbolFlags::CONST) {
+                            diagnostics.push(Diagnostic {
+                                range,
+                                severity: Some(DiagnosticSeverity::ERROR),
+                                code: Some(NumberOrString::Number(
+                                    TypeDiagnosticCode::CannotReassignConst.as_number(),
+                                )),
+                                code_description: None,
+                                source: Some("ts-lsp-rust".to_string()),
+                                message: TypeDiagnosticCode::CannotReassignConst.message(name),
+                                related_information: None,
+                                tags: None,
+                                data: None,
+                            });
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    // Check update expressions (++, --)
+    if node.kind() == "update_expression" {
+        let mut cursor = node.walk();
+        for child in node.children(&mut cursor) {
+            if child.kind() == "identifier" {
+                let name = child.utf8_text(source.as_bytes()).unwrap_or("");
+                let range = index.node_range(&child);
+                let scope_id = symbol_table.scope_at_position(range.start);
+
+                if let Some(symbol_id) = symbol_table.lookup(name, scope_id) {
+                    if let Some(symbol) = symbol_table.get_symbol(symbol_id) {
+                        if symbol.flags.contains(SymbolFlags::CONST) {
+                            diagnostics.push(Diagnostic {
+                                range,
+                                severity: Some(DiagnosticSeverity::ERROR),
+                                code: Some(NumberOrString::Number(
+                                    TypeDiagnosticCode::CannotReassignConst.as_number(),
+                                )),
+                                code_description: None,
+                                source: Some("ts-lsp-rust".to_string()),
+                                message: TypeDiagnosticCode::CannotReassignConst.message(name),
+                                related_information: None,
+                                tags: None,
+                                data: None,
+                            });
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    // Recurse into children
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        check_assignments(child, source, index, symbol_table, diagnostics);
+    }
+}
+
+/// Check that calls to user functions pass as many arguments as the
+/// function's signature accepts
+fn check_call_arguments(
+    tree: &Tree,
+    source: &str,
+    symbol_table: &SymbolTable,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let root = tree.root_node();
+    let index = LineIndex::new(source);
+    let overloaded = overloaded_functions(root, source);
+    check_calls(root, source, &index, symbol_table, &overloaded, diagnostics);
+}
+
+/// Names of functions declared with overload signatures, whose
+/// implementation signature is not the one callers see
+fn overloaded_functions<'a>(root: Node, source: &'a str) -> HashSet<&'a str> {
+    let mut names = HashSet::new();
+    let mut stack = vec![root];
+    while let Some(node) = stack.pop() {
+        if node.kind() == "function_signature" {
+            if let Some(name) = node.child_by_field_name("name") {
+                names.insert(name.utf8_text(source.as_bytes()).unwrap_or(""));
+            }
+        }
+        let mut cursor = node.walk();
+        stack.extend(node.named_children(&mut cursor));
+    }
+    names
+}
+
+fn check_calls(
+    node: Node,
+    source: &str,
+    index: &LineIndex,
+    symbol_table: &SymbolTable,
+    overloaded: &HashSet<&str>,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    if node.kind() == "call_expression" {
+        if let Some(diagnostic) = check_call(node, source, index, symbol_table, overloaded) {
+            diagnostics.push(diagnostic);
+        }
+    }
+
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        check_calls(child, source, index, symbol_table, overloaded, diagnostics);
+    }
+}
+
+fn check_call(
+    call: Node,
+    source: &str,
+    index: &LineIndex,
+    symbol_table: &SymbolTable,
+    overloaded: &HashSet<&str>,
+) -> Option<Diagnostic> {
+    let function = call
+        .child_by_field_name("function")
+        .filter(|f| f.kind() == "identifier")?;
+    let name = function.utf8_text(source.as_bytes()).ok()?;
+    if overloaded.contains(name) {
+        return None;
+    }
+    let args_node = call
+        .child_by_field_name("arguments")
+        .filter(|args| args.kind() == "arguments")?;
+
+    let scope_id = symbol_table.scope_at_position(index.node_range(&function).start);
+    let symbol = symbol_table.get_symbol(symbol_table.lookup(name, scope_id)?)?;
+    if !symbol.flags.contains(SymbolFlags::FUNCTION) {
+        return None;
+    }
+    let signature = symbol.signature.as_ref()?;
+
+    let mut cursor = args_node.walk();
+    let args: Vec<Node> = args_node
+        .named_children(&mut cursor)
+        .filter(|arg| arg.kind() != "comment")
+        .collect();
+    // A spread argument can supply any number of values
+    if args.iter().any(|arg| arg.kind() == "spread_element") {
+        return None;
+    }
+
+    let rest = signature.parameters.iter().any(|p| p.is_rest());
+    let min = signature
+        .parameters
+        .iter()
+        .filter(|p| !p.optional && !p.is_rest())
+        .count();
+    let max = signature.parameters.iter().filter(|p| !p.is_rest()).count();
+
+    let got = args.len();
+    let range = if got < min {
+        index.node_range(&args_node)
+    } else if got > max && !rest {
+        Range::new(
+            index.node_range(&args[max]).start,
+            index.node_range(&args[got - 1]).end,
+        )
+    } else {
+        return None;
+    };
+
+    let expected = if rest {
+        format!("at least {}", min)
+    } else if min == max {
+        min.to_string()
+    } else {
+        format!("{}-{}", min, max)
+    };
+    let context = format!("{} arguments, but got {}", expected, got);
+
+    Some(Diagnostic {
+        range,
+        severity: Some(DiagnosticSeverity::ERROR),
+        code: Some(NumberOrString::Number(
+            TypeDiagnosticCode::ArgumentCountMismatch.as_number(),
+        )),
+        code_description: None,
+        source: Some("ts-lsp-rust".to_string()),
+        message: TypeDiagnosticCode::ArgumentCountMismatch.message(&context),
+        related_information: None,
+        tags: None,
+        data: None,
+    })
+}
+
+/// Check calls whose callee is a variable with a known, non-callable type
+fn check_callable(
+    tree: &Tree,
+    source: &str,
+    typed: &SymbolTable,
+    checker: &TypeChecker,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let root = tree.root_node();
+    let index = LineIndex::new(source);
+
+    let mut stack = vec![root];
+    while let Some(node) = stack.pop() {
+        let mut cursor = node.walk();
+        stack.extend(node.children(&mut cursor));
+
+        if node.kind() != "call_expression" {
+            continue;
+        }
+        let Some(callee) = node
+            .child_by_field_name("function")
+            .filter(|f| f.kind() == "identifier")
+        else {
+            continue;
+        };
+        let name = callee.utf8_text(source.as_bytes()).unwrap_or("");
+        let range = index.node_range(&callee);
+        let scope_id = typed.scope_at_position(range.start);
+        let Some(ty) = typed
+            .lookup(name, scope_id)
+            .and_then(|id| typed.get_symbol(id))
+            .filter(|symbol| {
+                symbol
+                    .flags
+                    .intersects(SymbolFlags::VARIABLE | SymbolFlags::PARAMETER)
+            })
+            .and_then(|symbol| symbol.inferred_type)
+            .and_then(|id| checker.get_type(id))
+        else {
+            continue;
+        };
+
+        if is_not_callable(ty) {
+            let printed = crate::types::printer::print_type(ty);
+            diagnostics.push(Diagnostic {
+                range,
+                severity: Some(DiagnosticSeverity::ERROR),
+                code: Some(NumberOrString::Number(
+                    TypeDiagnosticCode::NotCallable.as_number(),
+                )),
+                code_description: None,
+                source: Some("ts-lsp-rust".to_string()),
+                message: TypeDiagnosticCode::NotCallable.message(&printed),
+                related_information: None,
+                tags: None,
+                data: None,
+            });
+        }
+    }
+}
+
+/// Whether a type certainly has no call signatures. Types we cannot see
+/// through (`any`, references, type parameters, ...) are assumed callable.
+fn is_not_callable(ty: &Type) -> bool {
+    match ty {
+        Type::String
+        | Type::Number
+        | Type::Boolean
+        | Type::BigInt
+        | Type::Symbol
+        | Type::Null
+        | Type::Undefined
+        | Type::Void
+        | Type::StringLiteral(_)
+        | Type::NumberLiteral(_)
+        | Type::BooleanLiteral(_)
+        | Type::BigIntLiteral(_)
+        | Type::Array(_)
+        | Type::Tuple(_) => true,
+        Type::Object(obj) => obj.call_signatures.is_empty(),
+        Type::Union(members) => members.iter().all(is_not_callable),
+        _ => false,
+    }
+}
+
+/// Check `x.prop` accesses where `x` has a declared object type, taking
+/// discriminant narrowing (`if (x.kind === 'a') { ... }`) into account
+fn check_property_access(
+    tree: &Tree,
+    source: &str,
+    typed: &SymbolTable,
+    checker: &mut TypeChecker,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let root = tree.root_node();
+    let index = LineIndex::new(source);
+    check_member_expressions(root, root, source, &index, typed, checker, diagnostics);
+}
+
+fn check_member_expressions(
+    node: Node,
+    root: Node,
+    source: &str,
+    index: &LineIndex,
+    symbol_table: &SymbolTable,
+    checker: &mut TypeChecker,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    if node.kind() == "member_expression" {
+        if let (Some(object), Some(property)) = (
+            node.child_by_field_name("object"),
+            node.child_by_field_name("property"),
+        ) {
+            if object.kind() == "identifier" && property.kind() == "property_identifier" {
+                let name = object.utf8_text(source.as_bytes()).unwrap_or("");
+                let prop_name = property.utf8_text(source.as_bytes()).unwrap_or("");
+
+                if let Some((declared, annotation)) =
+                    declared_type_of(object, root, source, index, symbol_table, checker)
+                {
+                    let narrowed = narrow_at(node, name, declared, source, checker);
+                    let missing = narrowed.and_then(|narrowed| {
+                        let ty = checker.get_type(narrowed)?;
+                        (has_property(ty, prop_name) == Some(false)).then(|| {
+                            if narrowed == declared {
+                                annotation.clone()
+                            } else {
+                                crate::types::printer::print_type(ty)
+                            }
+                        })
+                    });
+
+                    if let Some(type_text) = missing {
+                        diagnostics.push(Diagnostic {
+                            range: index.node_range(&property),
+                            severity: Some(DiagnosticSeverity::ERROR),
+                            code: Some(NumberOrString::Number(
+                                TypeDiagnosticCode::MissingProperty.as_number(),
+                            )),
+                            code_description: None,
+                            source: Some("ts-lsp-rust".to_string()),
+                            message: TypeDiagnosticCode::MissingProperty.message(&format!(
+                                "'{}' does not exist on type '{}'",
+                                prop_name, type_text
+                            )),
+                            related_information: None,
+                            tags: None,
+                            data: None,
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        check_member_expressions(
+            child,
+            root,
+            source,
+            index,
+            symbol_table,
+            checker,
+            diagnostics,
+        );
+    }
+}
+
+/// Resolve the annotated type of the variable or parameter an identifier
+/// refers to, along with the annotation's text
+fn declared_type_of(
+    identifier: Node,
+    root: Node,
+    source: &str,
+    index: &LineIndex,
+    symbol_table: &SymbolTable,
+    checker: &mut TypeChecker,
+) -> Option<(TypeId, String)> {
+    let name = identifier.utf8_text(source.as_bytes()).ok()?;
+    let scope_id = symbol_table.scope_at_position(index.node_range(&identifier).start);
+    let symbol = symbol_table.get_symbol(symbol_table.lookup(name, scope_id)?)?;
+
+    if !symbol
+        .flags
+        .intersects(SymbolFlags::VARIABLE | SymbolFlags::PARAMETER)
+    {
+        return None;
+    }
+
+    let start = index.point(symbol.name_range.start);
+    let end = index.point(symbol.name_range.end);
+    let declaration = root.descendant_for_point_range(start, end)?.parent()?;
+    let annotation = declaration.child_by_field_name("type")?;
+    let text = annotation
+        .named_child(0)
+        .unwrap_or(annotation)
+        .utf8_text(source.as_bytes())
+        .ok()?
+        .to_string();
+
+    let declared = checker.type_from_node(annotation, source);
+    Some((
+        checker.instantiate(declared, &std::collections::HashMap::new()),
+        text,
+    ))
+}
+
+/// Apply the narrowing guards of every enclosing `if` whose branch contains
+/// `node`. Returns `None` when `name` is narrowed in a way that isn't modelled
+/// (other guards, `switch`, early returns, assignments), since reporting
+/// against the declared type would then be a false positive.
+fn narrow_at(
+    node: Node,
+    name: &str,
+    declared: TypeId,
+    source: &str,
+    checker: &mut TypeChecker,
+) -> Option<TypeId> {
+    let mut narrowed = declared;
+    let mut child = node;
+
+    while let Some(parent) = child.parent() {
+        let branch = match parent.kind() {
+            "if_statement" | "ternary_expression" => {
+                let in_consequence = parent.child_by_field_name("consequence") == Some(child);
+                let in_alternative = parent.child_by_field_name("alternative") == Some(child);
+                (in_consequence || in_alternative)
+                    .then(|| (parent.child_by_field_name("condition"), in_alternative))
+            }
+            // `cond && x.p` reads `x` where `cond` holds, `cond || x.p` where it doesn't
+            "binary_expression" if parent.child_by_field_name("right") == Some(child) => {
+                match parent.child_by_field_name("operator").map(|op| op.kind()) {
+                    Some("&&") => Some((parent.child_by_field_name("left"), false)),
+                    Some("||") => Some((parent.child_by_field_name("left"), true)),
+                    _ => None,
+                }
+            }
+            "switch_statement"
+                if parent
+                    .child_by_field_name("value")
+                    .is_some_and(|value| mentions(value, name, source)) =>
+            {
+                return None;
+            }
+            _ => None,
+        };
+
+        if let Some((Some(condition), in_alternative)) = branch {
+            match discriminant_guard(condition, name, source, checker) {
+                // `===` narrows the consequence, `!==` narrows the else branch
+                Some((guard, negated)) if negated == in_alternative => {
+                    narrowed = checker.narrow_union_by_property(narrowed, &guard);
+                }
+                Some(_) => {}
+                None if mentions(condition, name, source) => return None,
+                None => {}
+            }
+        }
+
+        // Earlier statements of the enclosing block may narrow `name` for the
+        // rest of it, e.g. `if (!("a" in x)) return;` or `x = other;`
+        let mut sibling = child.prev_named_sibling();
+        while let Some(statement) = sibling {
+            if narrows_later_statements(statement, name, source) {
+                return None;
+            }
+            sibling = statement.prev_named_sibling();
+        }
+
+        child = parent;
+    }
+
+    Some(narrowed)
+}
+
+/// Whether a statement can narrow `name` for the statements following it
+fn narrows_later_statements(statement: Node, name: &str, source: &str) -> bool {
+    match statement.kind() {
+        "if_statement" | "switch_statement" | "while_statement" | "do_statement" => statement
+            .child_by_field_name("condition")
+            .or_else(|| statement.child_by_field_name("value"))
+            .is_some_and(|condition| mentions(condition, name, source)),
+        _ => assigns(statement, name, source),
+    }
+}
+
+/// Whether `node` contains an assignment to the identifier `name`
+fn assigns(node: Node, name: &str, source: &str) -> bool {
+    if matches!(
+        node.kind(),
+        "assignment_expression" | "augmented_assignment_expression"
+    ) && node.child_by_field_name("left").is_some_and(|left| {
+        left.kind() == "identifier" && left.utf8_text(source.as_bytes()) == Ok(name)
+    }) {
+        return true;
+    }
+    let mut cursor = node.walk();
+    node.named_children(&mut cursor)
+        .any(|child| assigns(child, name, source))
+}
+
+/// Whether `node` contains a reference to the identifier `name`
+fn mentions(node: Node, name: &str, source: &str) -> bool {
+    if node.kind() == "identifier" {
+        return node.utf8_text(source.as_bytes()) == Ok(name);
+    }
+    let mut cursor = node.walk();
+    node.named_children(&mut cursor)
+        .any(|child| mentions(child, name, source))
+}
+
+/// Recognize `name.prop === literal` (or `literal === name.prop`) conditions.
+/// Returns the guard and whether the comparison is negated (`!==`).
+fn discriminant_guard(
+    condition: Node,
+    name: &str,
+    source: &str,
+    checker: &mut TypeChecker,
+) -> Option<(NarrowingGuard, bool)> {
+    let mut expr = condition;
+    while expr.kind() == "parenthesized_expression" {
+        expr = expr.named_child(0)?;
+    }
+    if expr.kind() != "binary_expression" {
+        return None;
+    }
+
+    let negated = match expr.child_by_field_name("operator")?.kind() {
+        "===" | "==" => false,
+        "!==" | "!=" => true,
+        _ => return None,
+    };
+
+    let left = expr.child_by_field_name("left")?;
+    let right = expr.child_by_field_name("right")?;
+    let (access, literal) = if left.kind() == "member_expression" {
+        (left, right)
+    } else {
+        (right, left)
+    };
+
+    let object = access.child_by_field_name("object")?;
+    if access.kind() != "member_expression"
+        || object.kind() != "identifier"
+        || object.utf8_text(source.as_bytes()).ok()? != name
+    {
+        return None;
+    }
+    let property = access
+        .child_by_field_name("property")?
+        .utf8_text(source.as_bytes())
+        .ok()?;
+
+    if !matches!(literal.kind(), "string" | "number" | "true" | "false") {
+        return None;
+    }
+    let text = literal.utf8_text(source.as_bytes()).ok()?;
+    let value_id = checker.type_of_literal(literal.kind(), text);
+    let value = checker.get_type(value_id)?.clone();
+
+    Some((
+        NarrowingGuard::PropertyEqual {
+            property: property.to_string(),
+            value,
+        },
+        negated,
+    ))
+}
+
+/// Whether `ty` has a property named `name`; `None` when that can't be decided
+fn has_property(ty: &Type, name: &str) -> Option<bool> {
+    // Members inherited from `Object.prototype` exist on every object type
+    if matches!(
+        name,
+        "constructor"
+            | "toString"
+            | "toLocaleString"
+            | "valueOf"
+            | "hasOwnProperty"
+            | "isPrototypeOf"
+            | "propertyIsEnumerable"
+    ) {
+        return None;
+    }
+    match ty {
+        Type::Object(obj) => Some(
+            obj.properties.contains_key(name)
+                || !obj.index_signatures.is_empty()
+                || !obj.call_signatures.is_empty()
+                || !obj.construct_signatures.is_empty(),
+        ),
+        // A property is only accessible on a union if every member has it
+        Type::Union(members) => {
+            let mut result = Some(true);
+            for member in members {
+                match has_property(member, name) {
+                    Some(true) => {}
+                    Some(false) => return Some(false),
+                    None => result = None,
+                }
+            }
+            result
+        }
+        Type::Intersection(members) => {
+            let mut result = Some(false);
+            for member in members {
+                match has_property(member, name) {
+                    Some(true) => return Some(true),
+                    Some(false) => {}
+                    None => result = None,
+                }
+            }
+            result
+        }
+        _ => None,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -456,16 +2345,151 @@ mod tests {
     fn test_undefined_variable() {
         let code = "const x = unknownVar;";
         let (tree, symbol_table) = parse_and_bind(code);
-        let diagnostics = get_type_diagnostics(&tree, code, &symbol_table);
+        let diagnostics =
+            get_type_diagnostics(&tree, code, &symbol_table, &CompilerOptions::default());
 
         assert!(diagnostics.iter().any(|d| d.message.contains("unknownVar")));
     }
 
+    fn codes_of(tree: &Tree, code: &str) -> Vec<(i32, String)> {
+        let symbol_table = bind_document(tree, code);
+        get_type_diagnostics(tree, code, &symbol_table, &CompilerOptions::default())
+            .into_iter()
+            .filter_map(|d| match d.code {
+                Some(NumberOrString::Number(n)) => Some((n, d.message)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_valid_code_has_no_name_errors() {
+        for code in [
+            "fetch(new URL('/a', location.href));",
+            "new TextEncoder().encode('x'); new AbortController().abort();",
+            "queueMicrotask(() => structuredClone({}));",
+            "navigator.userAgent; localStorage.getItem('k'); new WeakRef({});",
+            "function f() { return arguments.length; }\nf();",
+            "function f() { const g = () => arguments[0]; return g(); }\nf();",
+            "namespace NS { export const a = 1; }\nNS.a;",
+            "namespace A.B { export const c = 1; }\nA.B.c;",
+            "declare function df(x: number): void;\ndf(1);",
+            "function ov(a: string): void;\nfunction ov(a: number): void;\nfunction ov(a: any) { return a; }\nov('');",
+            "export class C { constructor(private x: number, readonly y: string, public z = 1) {} }",
+        ] {
+            let (tree, _) = parse_and_bind(code);
+            assert!(
+                codes_of(&tree, code).is_empty(),
+                "{code}: {:?}",
+                codes_of(&tree, code)
+            );
+        }
+
+        let (tree, _) = parse_and_bind("const a = arguments;");
+        assert_eq!(codes_of(&tree, "const a = arguments;")[0].0, 2304);
+    }
+
+    #[test]
+    fn test_jsx_intrinsic_tags_are_not_references() {
+        let code =
+            "export const a = <div className=\"x\"><my-el></my-el><Foo.Bar /><Missing /></div>;";
+        let mut parser = Parser::new();
+        parser
+            .set_language(&tree_sitter_typescript::LANGUAGE_TSX.into())
+            .unwrap();
+        let tree = parser.parse(code, None).unwrap();
+
+        let names: Vec<_> = codes_of(&tree, code)
+            .into_iter()
+            .filter(|(code, _)| *code == 2304)
+            .map(|(_, message)| message)
+            .collect();
+        assert_eq!(names.len(), 2, "{names:?}");
+        assert!(names[0].contains("'Foo'"));
+        assert!(names[1].contains("'Missing'"));
+    }
+
+    #[test]
+    fn test_loop_and_switch_bindings_do_not_leak() {
+        // Every diagnostic, so a loop binding reported against itself shows up
+        let messages_of = |code: &str| -> Vec<String> {
+            let (tree, symbol_table) = parse_and_bind(code);
+            get_type_diagnostics(&tree, code, &symbol_table, &CompilerOptions::default())
+                .into_iter()
+                .map(|d| d.message)
+                .collect()
+        };
+
+        let messages = messages_of(
+            "for (let i = 0; i < 3; i++) { i; }
+i;",
+        );
+        assert_eq!(messages, vec!["Cannot find name 'i'.".to_string()]);
+
+        let messages = messages_of(
+            "const xs = [1];
+for (const x of xs) { x; }
+x;",
+        );
+        assert_eq!(messages, vec!["Cannot find name 'x'.".to_string()]);
+
+        let messages = messages_of(
+            "const a = 1;
+switch (a) { case 1: let y = 2; y; }
+y;",
+        );
+        assert_eq!(messages, vec!["Cannot find name 'y'.".to_string()]);
+
+        // `var` belongs to the enclosing function
+        let messages = messages_of(
+            "for (var j = 0; j < 3; j++) {}
+j;
+{ var k = 1; }
+k;",
+        );
+        assert!(messages.is_empty(), "{:?}", messages);
+
+        // Loop heads declare their bindings before the body reads them
+        let messages = messages_of(
+            "const xs = [[1]];
+const o = { p: 1 };
+for (const x of xs) { x; }
+for (const k in o) { k; }
+for (const [first] of xs) { first; }",
+        );
+        assert!(messages.is_empty(), "{:?}", messages);
+    }
+
+    #[test]
+    fn test_export_aliases_not_undefined() {
+        let code = "const x = 1;\nexport { x as y };\nexport { z } from './z';";
+        let (tree, symbol_table) = parse_and_bind(code);
+        let diagnostics =
+            get_type_diagnostics(&tree, code, &symbol_table, &CompilerOptions::default());
+        assert!(
+            !diagnostics
+                .iter()
+                .any(|d| d.code == Some(NumberOrString::Number(2304)))
+        );
+
+        // Exported declarations are read by other modules
+        let code = "export const a = 1;\nconst b = 2;\nexport default b;";
+        let (tree, symbol_table) = parse_and_bind(code);
+        let diagnostics =
+            get_type_diagnostics(&tree, code, &symbol_table, &CompilerOptions::default());
+        assert!(
+            !diagnostics
+                .iter()
+                .any(|d| d.code == Some(NumberOrString::Number(6133)))
+        );
+    }
+
     #[test]
     fn test_defined_variable_no_error() {
         let code = "const x = 1;\nconst y = x;";
         let (tree, symbol_table) = parse_and_bind(code);
-        let diagnostics = get_type_diagnostics(&tree, code, &symbol_table);
+        let diagnostics =
+            get_type_diagnostics(&tree, code, &symbol_table, &CompilerOptions::default());
 
         // Should not report x as undefined
         assert!(!diagnostics.iter().any(|d| {
@@ -477,11 +2501,307 @@ mod tests {
         }));
     }
 
+    fn tdz_names(code: &str) -> Vec<String> {
+        let (tree, symbol_table) = parse_and_bind(code);
+        get_type_diagnostics(&tree, code, &symbol_table, &CompilerOptions::default())
+            .into_iter()
+            .filter(|d| {
+                d.code
+                    == Some(NumberOrString::Number(
+                        TypeDiagnosticCode::UsedBeforeDeclaration.as_number(),
+                    ))
+            })
+            .map(|d| d.message)
+            .collect()
+    }
+
+    #[test]
+    fn test_temporal_dead_zone() {
+        assert_eq!(
+            tdz_names("const x = x + 1;"),
+            vec!["Block-scoped variable 'x' used before its declaration."]
+        );
+        assert_eq!(
+            tdz_names("{ console.log(y); const y = 1; }"),
+            vec!["Block-scoped variable 'y' used before its declaration."]
+        );
+        assert_eq!(
+            tdz_names("let a = 1;\n{ a; }\nif (a) { let b = a; }").len(),
+            0
+        );
+        assert_eq!(
+            tdz_names("console.log(a); let a = 1;"),
+            vec!["Block-scoped variable 'a' used before its declaration."]
+        );
+        // The later inner `let` hides the outer binding for the whole block
+        assert_eq!(tdz_names("let a = 1;\n{ a; let a = 2; }").len(), 1);
+        assert_eq!(tdz_names("f();\nfunction f() {}\nv;\nvar v = 1;").len(), 0);
+    }
+
+    /// Lines and codes of duplicate declaration diagnostics
+    fn duplicate_lines(code: &str) -> Vec<(u32, i32)> {
+        let (tree, symbol_table) = parse_and_bind(code);
+        get_type_diagnostics(&tree, code, &symbol_table, &CompilerOptions::default())
+            .into_iter()
+            .filter_map(|d| match d.code {
+                Some(NumberOrString::Number(code @ (2300 | 2451))) => {
+                    Some((d.range.start.line, code))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_duplicate_declarations() {
+        assert_eq!(
+            duplicate_lines("const x = 1;\nconst x = 2;"),
+            vec![(1, 2451)]
+        );
+        assert_eq!(
+            duplicate_lines("let a = 1;\nfunction a() {}"),
+            vec![(1, 2451)]
+        );
+        assert_eq!(duplicate_lines("class C {}\nclass C {}"), vec![(1, 2300)]);
+        assert_eq!(duplicate_lines("var v = 1;\nlet v = 2;"), vec![(1, 2451)]);
+
+        let (tree, symbol_table) = parse_and_bind("const x = 1;\nconst x = 2;");
+        let diagnostics = get_type_diagnostics(
+            &tree,
+            "const x = 1;\nconst x = 2;",
+            &symbol_table,
+            &CompilerOptions::default(),
+        );
+        assert!(
+            diagnostics
+                .iter()
+                .any(|d| d.message == "Cannot redeclare block-scoped variable 'x'.")
+        );
+        // Both declarations stay addressable
+        assert_eq!(
+            symbol_table.all_symbols().filter(|s| s.name == "x").count(),
+            2
+        );
+
+        // `var` may be redeclared, and shadowing in an inner scope is fine
+        assert!(duplicate_lines("var v = 1;\nvar v = 2;").is_empty());
+        assert!(duplicate_lines("function f(p) { var p; }").is_empty());
+        assert!(duplicate_lines("const s = 1;\n{ const s = 2; }").is_empty());
+        assert!(duplicate_lines("class A { get x() { return 1; } set x(v) {} }").is_empty());
+        // Overload signatures precede a single implementation
+        assert!(
+            duplicate_lines("function f(a: string): void;\nfunction f(a: any) {}\nf('');")
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_variable_shadowing() {
+        let code = r#"const value = 1;
+interface Shape {}
+function area(value: number, Shape: number) {
+  const Error = 2;
+  { let value = 3; }
+  return value + Shape + Error;
+}
+area(value, 0);
+"#;
+        let shadowed = |lints: &LintOptions| -> Vec<(u32, u32)> {
+            let (tree, symbol_table) = parse_and_bind(code);
+            let options = CompilerOptions::default();
+            get_type_diagnostics_with_lints(&tree, code, &symbol_table, &options, lints)
+                .into_iter()
+                .filter(|d| d.code == Some(NumberOrString::Number(80009)))
+                .map(|d| (d.range.start.line, d.range.start.character))
+                .collect()
+        };
+
+        // The parameter and the block's `let` both hide an outer `value`;
+        // `Shape` only names an interface outside, `Error` a builtin
+        let lints = LintOptions {
+            no_shadow: true,
+            ..Default::default()
+        };
+        assert_eq!(shadowed(&lints), vec![(2, 14), (4, 8)]);
+        assert!(shadowed(&LintOptions::default()).is_empty());
+    }
+
+    #[test]
+    fn test_switch_exhaustiveness() {
+        let code = r#"enum Color { Red, Green = 2, "Blue" }
+function paint(c: Color, k: "a" | "b" | 1) {
+  switch (c) {
+    case Color.Red:
+    case Color.Blue:
+      break;
+  }
+  switch (k) {
+    case "a":
+      break;
+  }
+  switch (k) {
+    case "a":
+      break;
+    default:
+  }
+}
+"#;
+        let reported = |lints: &LintOptions| -> Vec<(u32, String)> {
+            let (tree, symbol_table) = parse_and_bind(code);
+            let options = CompilerOptions::default();
+            get_type_diagnostics_with_lints(&tree, code, &symbol_table, &options, lints)
+                .into_iter()
+                .filter(|d| d.code == Some(NumberOrString::Number(80010)))
+                .map(|d| (d.range.start.line, d.message))
+                .collect()
+        };
+
+        let lints = LintOptions {
+            switch_exhaustiveness_check: true,
+            ..Default::default()
+        };
+        let mut diagnostics = reported(&lints);
+        diagnostics.sort();
+        assert_eq!(
+            diagnostics,
+            vec![
+                (
+                    2,
+                    "Switch is not exhaustive. Cases not matched: Color.Green.".to_string()
+                ),
+                (
+                    7,
+                    "Switch is not exhaustive. Cases not matched: \"b\" | 1.".to_string()
+                ),
+            ]
+        );
+        assert!(reported(&LintOptions::default()).is_empty());
+    }
+
+    #[test]
+    fn test_implicit_this() {
+        let code = r#"class Foo {
+  x = this;
+  f() { return () => this; }
+}
+function bar() { return this; }
+function typed(this: Foo) { return this; }
+const obj = { m: function () { return this; }, n() { return this; } };
+class Nested { g() { return function () { return this; }; } }
+"#;
+        let lines = |options: &CompilerOptions| -> Vec<u32> {
+            let (tree, symbol_table) = parse_and_bind(code);
+            get_type_diagnostics(&tree, code, &symbol_table, options)
+                .into_iter()
+                .filter(|d| d.code == Some(NumberOrString::Number(2683)))
+                .map(|d| d.range.start.line)
+                .collect()
+        };
+
+        let strict = CompilerOptions {
+            strict: Some(true),
+            ..Default::default()
+        };
+        assert_eq!(lines(&strict), vec![4, 7]);
+        assert!(lines(&CompilerOptions::default()).is_empty());
+    }
+
+    #[test]
+    fn test_undefined_labels() {
+        let code = "outer: for (;;) { break outer; }\nfor (;;) { break missing; }\nl: for (;;) { const f = () => { while (1) { continue l; } }; }";
+        let (tree, symbol_table) = parse_and_bind(code);
+        let labels: Vec<(u32, String)> =
+            get_type_diagnostics(&tree, code, &symbol_table, &CompilerOptions::default())
+                .into_iter()
+                .filter(|d| d.code == Some(NumberOrString::Number(1116)))
+                .map(|d| (d.range.start.line, d.message))
+                .collect();
+
+        assert_eq!(
+            labels,
+            vec![
+                (
+                    1,
+                    "A 'break' statement can only jump to a label of an enclosing statement."
+                        .to_string()
+                ),
+                // Jumps cannot cross the arrow function
+                (
+                    2,
+                    "A 'continue' statement can only jump to a label of an enclosing statement."
+                        .to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unhandled_promises() {
+        let code = r#"async function load() {}
+function later(): Promise<number> { return Promise.resolve(1); }
+function sync() {}
+load();
+later();
+sync();
+async function main() {
+  await load();
+  const p = load();
+  load().then(sync);
+  void load();
+  return p;
+}
+main();
+"#;
+        let (tree, symbol_table) = parse_and_bind(code);
+        let lines: Vec<u32> =
+            get_type_diagnostics(&tree, code, &symbol_table, &CompilerOptions::default())
+                .into_iter()
+                .filter(|d| d.code == Some(NumberOrString::Number(80007)))
+                .map(|d| d.range.start.line)
+                .collect();
+        assert_eq!(lines, vec![3, 4, 13]);
+    }
+
+    #[test]
+    fn test_circular_imports() {
+        let uri = |name: &str| Url::parse(&format!("file:///src/{}", name)).unwrap();
+        let (a, b, c) = (uri("a.ts"), uri("b.ts"), uri("c.ts"));
+        let mut graph = FileGraph::new();
+        graph.add_edge(&a, &b);
+        graph.add_edge(&b, &c);
+        graph.add_edge(&c, &a);
+
+        let resolve = |specifier: &str| Some(uri(&format!("{}.ts", &specifier[2..])));
+        let check = |file: &Url, code: &str| {
+            let (tree, _) = parse_and_bind(code);
+            check_circular_imports(&graph, file, &tree, code, &resolve)
+        };
+
+        // Only `c.ts`, whose import leads back to `a.ts`, reports the cycle
+        let diagnostic = check(&c, "import { x } from './z';\nimport { a } from './a';").unwrap();
+        assert_eq!(diagnostic.range.start.line, 1);
+        assert_eq!(
+            diagnostic.message,
+            "Circular import detected: a.ts → b.ts → c.ts → a.ts."
+        );
+        assert!(check(&a, "import { b } from './b';").is_none());
+        assert!(check(&b, "export * from './c';").is_none());
+    }
+
+    #[test]
+    fn test_temporal_dead_zone_exemptions() {
+        // `var` is hoisted
+        assert!(tdz_names("console.log(v); var v = 1;").is_empty());
+        // Uses inside functions run later
+        assert!(tdz_names("function f() { return z; }\nconst z = 1;").is_empty());
+        assert!(tdz_names("const g = () => g();").is_empty());
+    }
+
     #[test]
     fn test_unused_variable() {
         let code = "const unusedVar = 1;";
         let (tree, symbol_table) = parse_and_bind(code);
-        let diagnostics = get_type_diagnostics(&tree, code, &symbol_table);
+        let diagnostics = get_type_diagnostics(&tree, code, &symbol_table, &unused_options());
 
         assert!(diagnostics.iter().any(|d| d.message.contains("unusedVar")));
     }
@@ -490,7 +2810,7 @@ mod tests {
     fn test_used_variable_no_unused_warning() {
         let code = "const x = 1;\nconsole.log(x);";
         let (tree, symbol_table) = parse_and_bind(code);
-        let diagnostics = get_type_diagnostics(&tree, code, &symbol_table);
+        let diagnostics = get_type_diagnostics(&tree, code, &symbol_table, &unused_options());
 
         // Should not report x as unused
         assert!(!diagnostics.iter().any(|d| {
@@ -502,11 +2822,331 @@ mod tests {
         }));
     }
 
+    #[test]
+    fn test_var_redeclarations_share_references() {
+        let options = unused_options();
+        assert!(unused_codes("var a = 1; var a = 2; console.log(a);", &options).is_empty());
+        assert!(
+            unused_codes(
+                "function f() { var a = 1; if (a) { var a = 2; } }",
+                &options
+            )
+            .is_empty()
+        );
+        assert_eq!(unused_codes("var b = 1; var b = 2;", &options).len(), 2);
+    }
+
+    /// Options that turn on the unused locals and parameters checks
+    fn unused_options() -> CompilerOptions {
+        CompilerOptions {
+            no_unused_locals: Some(true),
+            no_unused_parameters: Some(true),
+            ..Default::default()
+        }
+    }
+
+    fn unused_codes(code: &str, options: &CompilerOptions) -> Vec<(i32, String)> {
+        let (tree, symbol_table) = parse_and_bind(code);
+        get_type_diagnostics(&tree, code, &symbol_table, options)
+            .into_iter()
+            .filter_map(|d| match d.code {
+                Some(NumberOrString::Number(n)) if n == 6133 || n == 6138 => Some((n, d.message)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_unused_checks_honor_compiler_options() {
+        let code = "function f(p) { const local = 1; }\nf(1);";
+        // Both checks are off unless enabled, as in tsc
+        assert!(unused_codes(code, &CompilerOptions::default()).is_empty());
+        assert_eq!(unused_codes(code, &unused_options()).len(), 2);
+
+        let options = CompilerOptions {
+            no_unused_locals: Some(true),
+            ..Default::default()
+        };
+        let reported = unused_codes(code, &options);
+        assert_eq!(reported.len(), 1);
+        assert_eq!(reported[0].0, 6133);
+
+        let options = CompilerOptions {
+            no_unused_parameters: Some(true),
+            ..Default::default()
+        };
+        let reported = unused_codes(code, &options);
+        assert_eq!(reported.len(), 1);
+        assert_eq!(reported[0].0, 6138);
+    }
+
+    fn implicit_any_diagnostics(code: &str, options: &CompilerOptions) -> Vec<Diagnostic> {
+        let (tree, symbol_table) = parse_and_bind(code);
+        get_type_diagnostics(&tree, code, &symbol_table, options)
+            .into_iter()
+            .filter(|d| d.code == Some(NumberOrString::Number(7006)))
+            .collect()
+    }
+
+    #[test]
+    fn test_implicit_any_parameter() {
+        let strict = CompilerOptions {
+            strict: Some(true),
+            ..Default::default()
+        };
+
+        let diagnostics = implicit_any_diagnostics("function f(x) {}\nf(1);", &strict);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            diagnostics[0].message,
+            "Parameter 'x' implicitly has an 'any' type."
+        );
+        assert_eq!(
+            diagnostics[0].range.start,
+            tower_lsp::lsp_types::Position::new(0, 11)
+        );
+
+        assert!(implicit_any_diagnostics("function f(x = 0) {}\nf();", &strict).is_empty());
+        assert!(implicit_any_diagnostics("function f(x: number) {}\nf(1);", &strict).is_empty());
+    }
+
+    #[test]
+    fn test_implicit_any_skips_contextually_typed_callbacks() {
+        let strict = CompilerOptions {
+            strict: Some(true),
+            ..Default::default()
+        };
+
+        let code = "const xs = [1];
+xs.map((x) => x);
+xs.forEach(function (v) {});
+const h: (e: Event) => void = (e) => {};
+const o: { f: (a: number) => void } = { f: (a) => {} };
+window.onclick = function (ev) {};";
+        assert!(implicit_any_diagnostics(code, &strict).is_empty());
+
+        // Without a context the parameter is still implicitly `any`
+        let diagnostics = implicit_any_diagnostics("const g = (y) => y;", &strict);
+        assert_eq!(diagnostics.len(), 1);
+    }
+
+    #[test]
+    fn test_implicit_any_follows_options() {
+        let code = "function f(x) {}\nf(1);";
+        assert!(implicit_any_diagnostics(code, &CompilerOptions::default()).is_empty());
+
+        let options = CompilerOptions {
+            strict: Some(true),
+            no_implicit_any: Some(false),
+            ..Default::default()
+        };
+        assert!(implicit_any_diagnostics(code, &options).is_empty());
+
+        let options = CompilerOptions {
+            no_implicit_any: Some(true),
+            ..Default::default()
+        };
+        assert_eq!(implicit_any_diagnostics(code, &options).len(), 1);
+    }
+
+    fn unreachable_ranges(code: &str) -> Vec<Range> {
+        let (tree, symbol_table) = parse_and_bind(code);
+        get_type_diagnostics(&tree, code, &symbol_table, &CompilerOptions::default())
+            .into_iter()
+            .filter(|d| d.code == Some(NumberOrString::Number(7027)))
+            .map(|d| d.range)
+            .collect()
+    }
+
+    #[test]
+    fn test_unreachable_code_after_return() {
+        let code = "function f() {\n  return 1;\n  console.log(2);\n}\nf();";
+        let ranges = unreachable_ranges(code);
+        assert_eq!(ranges.len(), 1);
+        assert_eq!(LineIndex::new(code).slice(ranges[0]), "console.log(2);");
+    }
+
+    #[test]
+    fn test_unreachable_code_skips_hoisted_declarations() {
+        let code = "function f() {\n  return g();\n  function g() { return 1; }\n}\nf();\nfor (;;) { if (f()) { break; } f(); }";
+        assert!(unreachable_ranges(code).is_empty());
+
+        let code = "for (const x of [1]) {\n  continue;\n  x;\n  throw x;\n}";
+        assert_eq!(unreachable_ranges(code).len(), 2);
+    }
+
+    fn fallthrough_lines(code: &str, options: &CompilerOptions) -> Vec<u32> {
+        let (tree, symbol_table) = parse_and_bind(code);
+        get_type_diagnostics(&tree, code, &symbol_table, options)
+            .into_iter()
+            .filter(|d| d.code == Some(NumberOrString::Number(7029)))
+            .map(|d| d.range.start.line)
+            .collect()
+    }
+
+    #[test]
+    fn test_switch_fallthrough() {
+        let options = CompilerOptions {
+            no_fallthrough_cases_in_switch: Some(true),
+            ..Default::default()
+        };
+        let code = "function f(x: number) {\n  switch (x) {\n    case 1:\n      f(0);\n    case 2:\n    case 3:\n      { f(1); break; }\n    case 4:\n      return;\n    default:\n      f(2);\n  }\n}";
+
+        assert_eq!(fallthrough_lines(code, &options), vec![2]);
+        assert!(fallthrough_lines(code, &CompilerOptions::default()).is_empty());
+    }
+
+    #[test]
+    fn test_switch_fallthrough_branching_cases() {
+        let options = CompilerOptions {
+            no_fallthrough_cases_in_switch: Some(true),
+            ..Default::default()
+        };
+        let code = r#"function f(x: number) {
+  switch (x) {
+    case 1:
+      f(0);
+      if (x) { return; } else { break; }
+    case 2:
+      try { f(1); break; } catch (e) { throw e; }
+    case 3:
+      f(2);
+      if (x) { break; }
+    default:
+      f(3);
+  }
+}"#;
+        assert_eq!(fallthrough_lines(code, &options), vec![7]);
+    }
+
+    fn implicit_return_ranges(code: &str) -> Vec<Range> {
+        let options = CompilerOptions {
+            no_implicit_returns: Some(true),
+            ..Default::default()
+        };
+        let (tree, symbol_table) = parse_and_bind(code);
+        get_type_diagnostics(&tree, code, &symbol_table, &options)
+            .into_iter()
+            .filter(|d| d.code == Some(NumberOrString::Number(7030)))
+            .map(|d| d.range)
+            .collect()
+    }
+
+    #[test]
+    fn test_implicit_returns_exhaustive_switch_without_default() {
+        let code = r#"function f(x: "a" | "b"): number {
+  switch (x) {
+    case "a":
+      return 1;
+    case "b":
+      return 2;
+  }
+}
+function g(x: "a" | "b"): number {
+  switch (x) {
+    case "a":
+      return 1;
+  }
+}
+f("a");
+g("a");
+"#;
+        let ranges = implicit_return_ranges(code);
+        assert_eq!(ranges.len(), 1);
+        assert_eq!(ranges[0].start.line, 8);
+    }
+
+    #[test]
+    fn test_implicit_returns() {
+        let code = "function f(x) { if (x) return 1; }\nf(1);";
+        let ranges = implicit_return_ranges(code);
+        assert_eq!(ranges.len(), 1);
+        assert_eq!(LineIndex::new(code).slice(ranges[0]), "f");
+
+        // Anonymous functions are reported on their closing brace
+        let code = "const g = (x) => { if (x) { return 1; } };\ng(1);";
+        let ranges = implicit_return_ranges(code);
+        assert_eq!(ranges.len(), 1);
+        assert_eq!(ranges[0].start.character, 40);
+    }
+
+    #[test]
+    fn test_implicit_returns_all_paths_return() {
+        let code = r#"
+function a(x) { if (x) { return 1; } else { return 2; } }
+function b(x) { switch (x) { case 1: case 2: return 1; default: throw x; } }
+function c(x) { try { return 1; } catch (e) { return 2; } }
+function d(x): void { if (x) return; }
+function e(x) { if (x) { f(); } }
+a(1); b(1); c(1); d(1); e(1);
+"#;
+        assert!(implicit_return_ranges(code).is_empty());
+
+        let code = "function s(x) { switch (x) { case 1: return 1; case 2: break; default: return 3; } }\ns(1);";
+        assert_eq!(implicit_return_ranges(code).len(), 1);
+    }
+
+    #[test]
+    fn test_implicit_returns_endless_loops_and_optional_types() {
+        let code = r#"
+function a(x) { while (true) { if (x) return 1; } }
+function b(x) { for (;;) { for (const y of x) { break; } if (x) return 1; } }
+function c(x): number | undefined { if (x) return 1; }
+async function d(x): Promise<string | void> { if (x) return ""; }
+a(1); b(1); c(1); d(1);
+"#;
+        assert!(implicit_return_ranges(code).is_empty());
+
+        let code = "function e(x) { while (true) { if (x) return 1; break; } }\ne(1);";
+        assert_eq!(implicit_return_ranges(code).len(), 1);
+    }
+
+    #[test]
+    fn test_implicit_returns_exhaustive_enum_switch_in_method() {
+        let code = r#"
+enum Color { Red, Green }
+class Palette {
+    hex(c: Color): string {
+        if (c === Color.Red) {
+            switch (c) {
+                case Color.Red: return "red";
+                case Color.Green: return "green";
+            }
+        } else {
+            return "other";
+        }
+    }
+    partial(c: Color): string {
+        switch (c) {
+            case Color.Red: return "red";
+        }
+    }
+}
+new Palette();
+"#;
+        let ranges = implicit_return_ranges(code);
+        assert_eq!(ranges.len(), 1);
+        assert_eq!(ranges[0].start.line, 13);
+    }
+
+    #[test]
+    fn test_parameter_before_used_parameter_not_reported() {
+        let code = "const g = (a, b) => b;\ng(1, 2);\nconst h = (c, d) => c;\nh(1, 2);";
+        let reported = unused_codes(code, &unused_options());
+        assert_eq!(
+            reported,
+            vec![(
+                6138,
+                "'d' is declared but its value is never read.".to_string()
+            )]
+        );
+    }
+
     #[test]
     fn test_underscore_prefix_not_reported() {
         let code = "const _unused = 1;";
         let (tree, symbol_table) = parse_and_bind(code);
-        let diagnostics = get_type_diagnostics(&tree, code, &symbol_table);
+        let diagnostics = get_type_diagnostics(&tree, code, &symbol_table, &unused_options());
 
         // Variables starting with _ should not be reported
         assert!(!diagnostics.iter().any(|d| d.message.contains("_unused")));
@@ -516,7 +3156,8 @@ mod tests {
     fn test_const_reassignment() {
         let code = "const x = 1;\nx = 2;";
         let (tree, symbol_table) = parse_and_bind(code);
-        let diagnostics = get_type_diagnostics(&tree, code, &symbol_table);
+        let diagnostics =
+            get_type_diagnostics(&tree, code, &symbol_table, &CompilerOptions::default());
 
         assert!(diagnostics.iter().any(|d| {
             d.code
@@ -530,7 +3171,8 @@ mod tests {
     fn test_let_reassignment_allowed() {
         let code = "let x = 1;\nx = 2;";
         let (tree, symbol_table) = parse_and_bind(code);
-        let diagnostics = get_type_diagnostics(&tree, code, &symbol_table);
+        let diagnostics =
+            get_type_diagnostics(&tree, code, &symbol_table, &CompilerOptions::default());
 
         // Should not report reassignment error for let
         assert!(!diagnostics.iter().any(|d| {
@@ -545,7 +3187,8 @@ mod tests {
     fn test_builtin_global_not_undefined() {
         let code = "console.log('hello');";
         let (tree, symbol_table) = parse_and_bind(code);
-        let diagnostics = get_type_diagnostics(&tree, code, &symbol_table);
+        let diagnostics =
+            get_type_diagnostics(&tree, code, &symbol_table, &CompilerOptions::default());
 
         // console should not be reported as undefined
         assert!(!diagnostics.iter().any(|d| d.message.contains("console")));
@@ -557,4 +3200,295 @@ mod tests {
         assert_eq!(TypeDiagnosticCode::UnusedVariable.as_number(), 6133);
         assert_eq!(TypeDiagnosticCode::CannotReassignConst.as_number(), 2588);
     }
+
+    #[test]
+    fn test_discriminated_union_narrowing() {
+        let code = r#"
+type Shape = { kind: 'circle'; r: number } | { kind: 'square'; s: number };
+function area(s: Shape) {
+    if (s.kind === 'circle') {
+        return s.r;
+    }
+    return 0;
+}
+"#;
+        let (tree, symbol_table) = parse_and_bind(code);
+        let diagnostics =
+            get_type_diagnostics(&tree, code, &symbol_table, &CompilerOptions::default());
+
+        assert!(!diagnostics.iter().any(|d| d.code
+            == Some(NumberOrString::Number(
+                TypeDiagnosticCode::MissingProperty.as_number()
+            ))));
+    }
+
+    #[test]
+    fn test_missing_property_without_narrowing() {
+        let code = r#"
+type Shape = { kind: 'circle'; r: number } | { kind: 'square'; s: number };
+function area(s: Shape) {
+    return s.r;
+}
+"#;
+        let (tree, symbol_table) = parse_and_bind(code);
+        let diagnostics =
+            get_type_diagnostics(&tree, code, &symbol_table, &CompilerOptions::default());
+
+        let missing: Vec<_> = diagnostics
+            .iter()
+            .filter(|d| {
+                d.code
+                    == Some(NumberOrString::Number(
+                        TypeDiagnosticCode::MissingProperty.as_number(),
+                    ))
+            })
+            .collect();
+        assert_eq!(missing.len(), 1);
+        assert!(missing[0].message.contains("'r'"));
+    }
+
+    #[test]
+    fn test_narrowing_wrong_branch_reports_missing_property() {
+        let code = r#"
+type Shape = { kind: 'circle'; r: number } | { kind: 'square'; s: number };
+function area(s: Shape) {
+    if (s.kind === 'square') {
+        return s.r;
+    }
+    return 0;
+}
+"#;
+        let (tree, symbol_table) = parse_and_bind(code);
+        let diagnostics =
+            get_type_diagnostics(&tree, code, &symbol_table, &CompilerOptions::default());
+
+        assert!(diagnostics.iter().any(|d| d.code
+            == Some(NumberOrString::Number(
+                TypeDiagnosticCode::MissingProperty.as_number()
+            ))));
+    }
+
+    #[test]
+    fn test_interface_property_access() {
+        let code = r#"
+interface User { name: string; }
+const u: User = { name: "a" };
+console.log(u.name, u.age);
+"#;
+        let (tree, symbol_table) = parse_and_bind(code);
+        let diagnostics =
+            get_type_diagnostics(&tree, code, &symbol_table, &CompilerOptions::default());
+
+        let missing: Vec<_> = diagnostics
+            .iter()
+            .filter(|d| {
+                d.code
+                    == Some(NumberOrString::Number(
+                        TypeDiagnosticCode::MissingProperty.as_number(),
+                    ))
+            })
+            .collect();
+        assert_eq!(missing.len(), 1);
+        assert!(missing[0].message.contains("'age'"));
+    }
+
+    fn missing_property_diagnostics(code: &str) -> Vec<Diagnostic> {
+        let (tree, symbol_table) = parse_and_bind(code);
+        get_type_diagnostics(&tree, code, &symbol_table, &CompilerOptions::default())
+            .into_iter()
+            .filter(|d| {
+                d.code
+                    == Some(NumberOrString::Number(
+                        TypeDiagnosticCode::MissingProperty.as_number(),
+                    ))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_missing_property_message_names_type() {
+        let missing = missing_property_diagnostics(
+            r#"
+interface User { name: string; }
+function greet(u: User) {
+    return u.age;
+}
+"#,
+        );
+        assert_eq!(missing.len(), 1);
+        assert_eq!(
+            missing[0].message,
+            "Property 'age' does not exist on type 'User'."
+        );
+    }
+
+    #[test]
+    fn test_switch_narrowing_is_not_missing_property() {
+        let missing = missing_property_diagnostics(
+            r#"
+type Shape = { kind: 'circle'; r: number } | { kind: 'square'; s: number };
+function area(x: Shape) {
+    switch (x.kind) {
+        case 'circle':
+            return x.r;
+        case 'square':
+            return x.s;
+    }
+}
+"#,
+        );
+        assert!(missing.is_empty(), "{:?}", missing);
+    }
+
+    #[test]
+    fn test_early_return_narrowing_is_not_missing_property() {
+        let missing = missing_property_diagnostics(
+            r#"
+type AB = { a: number } | { b: string };
+function f(x: AB) {
+    if (!("a" in x)) return;
+    return x.a;
+}
+"#,
+        );
+        assert!(missing.is_empty(), "{:?}", missing);
+    }
+
+    #[test]
+    fn test_object_prototype_members_are_not_missing_property() {
+        let missing = missing_property_diagnostics(
+            r#"
+interface Point { x: number; }
+function f(o: Point) {
+    return o.toString() + o.hasOwnProperty("x");
+}
+"#,
+        );
+        assert!(missing.is_empty(), "{:?}", missing);
+    }
+
+    #[test]
+    fn test_class_members_from_parameters_setters_and_open_bases() {
+        let missing = missing_property_diagnostics(
+            r#"
+class P {
+    constructor(private x: number, public y: string) {}
+    set z(v: number) {}
+}
+class M extends mixin(Base) {}
+class N extends ns.Base {}
+function f(p: P, m: M, n: N) {
+    return [p.x, p.y, p.z, m.fromMixin, n.fromBase];
+}
+"#,
+        );
+        assert!(missing.is_empty(), "{:?}", missing);
+    }
+
+    fn argument_count_diagnostics(code: &str) -> Vec<Diagnostic> {
+        let (tree, symbol_table) = parse_and_bind(code);
+        get_type_diagnostics(&tree, code, &symbol_table, &CompilerOptions::default())
+            .into_iter()
+            .filter(|d| {
+                d.code
+                    == Some(NumberOrString::Number(
+                        TypeDiagnosticCode::ArgumentCountMismatch.as_number(),
+                    ))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_too_many_arguments() {
+        let diagnostics = argument_count_diagnostics("function f(a) {} f(1, 2);");
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].message, "Expected 1 arguments, but got 2.");
+        // The excess argument is highlighted
+        assert_eq!(diagnostics[0].range.start.character, 22);
+        assert_eq!(diagnostics[0].range.end.character, 23);
+    }
+
+    #[test]
+    fn test_matching_argument_count() {
+        assert!(argument_count_diagnostics("function f(a) {} f(1);").is_empty());
+    }
+
+    #[test]
+    fn test_this_parameter_takes_no_argument() {
+        let code = "function f(this: Window, x: number) {}\nf(1);\nf();";
+        let diagnostics = argument_count_diagnostics(code);
+        let messages: Vec<_> = diagnostics.iter().map(|d| d.message.as_str()).collect();
+        assert_eq!(messages, vec!["Expected 1 arguments, but got 0."]);
+    }
+
+    #[test]
+    fn test_too_few_arguments_with_optional() {
+        let code =
+            "function f(a: number, b?: number, c = 1) {}\nf();\nf(1);\nf(1, 2, 3);\nf(1, 2, 3, 4);";
+        let diagnostics = argument_count_diagnostics(code);
+        let messages: Vec<_> = diagnostics.iter().map(|d| d.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "Expected 1-3 arguments, but got 0.",
+                "Expected 1-3 arguments, but got 4.",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_rest_parameter_arguments() {
+        let code = "function log(level: string, ...args: any[]) {}\nlog('a', 1, 2, 3);\nlog();";
+        let diagnostics = argument_count_diagnostics(code);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            diagnostics[0].message,
+            "Expected at least 1 arguments, but got 0."
+        );
+    }
+
+    #[test]
+    fn test_spread_and_overloads_not_checked() {
+        let code = "function f(a) {}\nf(...[1, 2]);\nfunction g(a: string): void;\nfunction g(a: string, b: number): void;\nfunction g(a: string, b?: number) {}\ng('a', 1, 2);";
+        assert!(argument_count_diagnostics(code).is_empty());
+    }
+
+    fn not_callable_diagnostics(code: &str) -> Vec<Diagnostic> {
+        let (tree, symbol_table) = parse_and_bind(code);
+        get_type_diagnostics(&tree, code, &symbol_table, &CompilerOptions::default())
+            .into_iter()
+            .filter(|d| {
+                d.code
+                    == Some(NumberOrString::Number(
+                        TypeDiagnosticCode::NotCallable.as_number(),
+                    ))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_calling_number_is_not_callable() {
+        let diagnostics = not_callable_diagnostics("const x = 1; x();");
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            diagnostics[0].message,
+            "This expression is not callable. Type 'number' has no call signatures."
+        );
+        assert_eq!(diagnostics[0].range.start.character, 13);
+    }
+
+    #[test]
+    fn test_calling_function_values_is_allowed() {
+        let code = "const f = () => {}; f();\nfunction g() {} g();\nlet h: any = 1; h();\nconst s: string | (() => void) = ''; s();";
+        assert!(not_callable_diagnostics(code).is_empty());
+    }
+
+    #[test]
+    fn test_calling_annotated_object_is_not_callable() {
+        let code = "interface Point { x: number }\nconst p: Point = { x: 1 };\np();\nconst items = [1];\nitems();";
+        let diagnostics = not_callable_diagnostics(code);
+        let lines: Vec<_> = diagnostics.iter().map(|d| d.range.start.line).collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines.contains(&2) && lines.contains(&4));
+    }
 }
@@ -3,7 +3,9 @@ use tower_lsp::lsp_types::Url;
 use tree_sitter::Tree;
 
 use crate::analysis::{SymbolTable, binder};
+use crate::line_index::LineIndex;
 use crate::parser::{SourceLanguage, SourceParser};
+use crate::types::TypeChecker;
 
 /// Represents an open document with its content and parsed tree
 pub struct Document {
@@ -12,6 +14,8 @@ pub struct Document {
     pub version: i32,
     pub language: SourceLanguage,
     pub symbol_table: Option<SymbolTable>,
+    /// Checker holding the types referenced by `Symbol::inferred_type`
+    pub type_checker: TypeChecker,
 }
 
 impl Document {
@@ -21,7 +25,7 @@ impl Document {
         let tree = parser.parse(&content, None);
 
         // Bind the document to create the symbol table
-        let symbol_table = tree.as_ref().map(|t| binder::bind_document(t, &content));
+        let (symbol_table, type_checker) = analyze(tree.as_ref(), &content);
 
         Self {
             content,
@@ -29,6 +33,7 @@ impl Document {
             version,
             language,
             symbol_table,
+            type_checker,
         }
     }
 
@@ -39,69 +44,43 @@ impl Document {
         new_version: i32,
         parser: &mut SourceParser,
     ) {
-        for change in changes {
-            if let Some(range) = change.range {
-                // Incremental update
-                let start_offset = self.offset_at_position(range.start);
-                let end_offset = self.offset_at_position(range.end);
-
-                self.content
-                    .replace_range(start_offset..end_offset, &change.text);
-            } else {
-                // Full document replacement
-                self.content = change.text;
-            }
-        }
-
-        self.version = new_version;
         // Ensure parser is set to correct language before re-parsing
         parser.set_language(self.language);
-        // Re-parse with old tree for incremental parsing
-        self.tree = parser.parse(&self.content, self.tree.as_ref());
+        // Apply the edits to the old tree and re-parse incrementally
+        let (content, tree) = parser.parse_with_changes(&self.content, self.tree.take(), &changes);
+        self.content = content;
+        self.tree = tree;
+        self.version = new_version;
 
         // Re-bind the document
-        self.symbol_table = self
-            .tree
-            .as_ref()
-            .map(|t| binder::bind_document(t, &self.content));
+        (self.symbol_table, self.type_checker) = analyze(self.tree.as_ref(), &self.content);
     }
 
     /// Convert LSP position to byte offset
+    #[allow(dead_code)] // Edits are now applied by `SourceParser::parse_with_changes`
     fn offset_at_position(&self, position: tower_lsp::lsp_types::Position) -> usize {
-        let mut offset = 0;
-        for (i, line) in self.content.lines().enumerate() {
-            if i == position.line as usize {
-                return offset + position.character as usize;
-            }
-            offset += line.len() + 1; // +1 for newline
-        }
-        offset
+        LineIndex::new(&self.content).offset(position)
     }
 
     /// Convert byte offset to LSP position
     #[allow(dead_code)] // Reserved for future incremental parsing
     pub fn position_at_offset(&self, offset: usize) -> tower_lsp::lsp_types::Position {
-        let mut line = 0;
-        let mut col = 0;
-        let mut current_offset = 0;
-
-        for ch in self.content.chars() {
-            if current_offset >= offset {
-                break;
-            }
-            if ch == '\n' {
-                line += 1;
-                col = 0;
-            } else {
-                col += 1;
-            }
-            current_offset += ch.len_utf8();
-        }
-
-        tower_lsp::lsp_types::Position::new(line, col)
+        LineIndex::new(&self.content).position(offset)
     }
 }
 
+/// Bind a document and record the types of its declarations
+fn analyze(tree: Option<&Tree>, content: &str) -> (Option<SymbolTable>, TypeChecker) {
+    let mut type_checker = TypeChecker::new();
+    let symbol_table = tree.map(|t| {
+        let mut table = binder::bind_document(t, content);
+        type_checker.declare_types_from_tree(t.root_node(), content);
+        type_checker.infer_symbol_types(&mut table, t.root_node(), content);
+        table
+    });
+    (symbol_table, type_checker)
+}
+
 /// Manages all open documents
 pub struct DocumentManager {
     documents: DashMap<Url, Document>,
@@ -138,6 +117,11 @@ impl DocumentManager {
         self.documents.remove(uri);
     }
 
+    /// Iterate over all open documents
+    pub fn iter(&self) -> dashmap::iter::Iter<'_, Url, Document> {
+        self.documents.iter()
+    }
+
     /// Get a reference to a document
     pub fn get(&self, uri: &Url) -> Option<dashmap::mapref::one::Ref<'_, Url, Document>> {
         self.documents.get(uri)
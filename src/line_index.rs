@@ -0,0 +1,190 @@
+//! Conversion between byte offsets and LSP positions
+//!
+//! LSP positions count characters in UTF-16 code units, while tree-sitter and
+//! Rust string slicing work in bytes. `LineIndex` maps between the two for a
+//! given source text.
+
+use tower_lsp::lsp_types::{Position, Range};
+use tree_sitter::{Node, Point};
+
+/// Line start table for a source text
+pub struct LineIndex<'a> {
+    source: &'a str,
+    /// Byte offset of the start of each line
+    line_starts: Vec<usize>,
+}
+
+impl<'a> LineIndex<'a> {
+    pub fn new(source: &'a str) -> Self {
+        let mut line_starts = vec![0];
+        line_starts.extend(source.match_indices('\n').map(|(i, _)| i + 1));
+        Self {
+            source,
+            line_starts,
+        }
+    }
+
+    /// Text of a line without its line terminator
+    fn line_text(&self, line: usize) -> &'a str {
+        let start = self.line_starts[line];
+        let end = self
+            .line_starts
+            .get(line + 1)
+            .map_or(self.source.len(), |next| next - 1);
+        self.source[start..end].trim_end_matches('\r')
+    }
+
+    /// Convert an LSP position to a byte offset.
+    /// Positions past the end of a line or the document are clamped.
+    pub fn offset(&self, position: Position) -> usize {
+        let line = position.line as usize;
+        if line >= self.line_starts.len() {
+            return self.source.len();
+        }
+        self.line_starts[line] + self.utf16_to_byte_col(line, position.character)
+    }
+
+    /// Convert a byte offset to an LSP position
+    pub fn position(&self, offset: usize) -> Position {
+        let offset = offset.min(self.source.len());
+        let line = self.line_starts.partition_point(|&start| start <= offset) - 1;
+        let col = self.byte_to_utf16_col(line, offset - self.line_starts[line]);
+        Position::new(line as u32, col)
+    }
+
+    /// Convert an LSP position to a tree-sitter point (byte column)
+    pub fn point(&self, position: Position) -> Point {
+        let line = position.line as usize;
+        if line >= self.line_starts.len() {
+            let last = self.line_starts.len() - 1;
+            return Point::new(last, self.source.len() - self.line_starts[last]);
+        }
+        Point::new(line, self.utf16_to_byte_col(line, position.character))
+    }
+
+    /// Convert a tree-sitter point (byte column) to an LSP position
+    pub fn position_of_point(&self, point: Point) -> Position {
+        if point.row >= self.line_starts.len() {
+            return Position::new(point.row as u32, point.column as u32);
+        }
+        Position::new(
+            point.row as u32,
+            self.byte_to_utf16_col(point.row, point.column),
+        )
+    }
+
+    /// LSP range covered by a syntax node
+    pub fn node_range(&self, node: &Node) -> Range {
+        Range {
+            start: self.position_of_point(node.start_position()),
+            end: self.position_of_point(node.end_position()),
+        }
+    }
+
+    /// Source text covered by an LSP range (clamped to the document)
+    pub fn slice(&self, range: Range) -> &'a str {
+        let start = self.offset(range.start);
+        let end = self.offset(range.end).max(start);
+        &self.source[start..end]
+    }
+
+    fn utf16_to_byte_col(&self, line: usize, character: u32) -> usize {
+        let text = self.line_text(line);
+        let mut utf16 = 0;
+        for (byte, ch) in text.char_indices() {
+            if utf16 >= character as usize {
+                return byte;
+            }
+            utf16 += ch.len_utf16();
+        }
+        text.len()
+    }
+
+    fn byte_to_utf16_col(&self, line: usize, byte_col: usize) -> u32 {
+        let text = self.line_text(line);
+        let mut end = byte_col.min(text.len());
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        text[..end].encode_utf16().count() as u32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_offset_ascii() {
+        let index = LineIndex::new("line1\nline2\nline3");
+        assert_eq!(index.offset(Position::new(0, 0)), 0);
+        assert_eq!(index.offset(Position::new(0, 3)), 3);
+        assert_eq!(index.offset(Position::new(1, 0)), 6);
+        assert_eq!(index.offset(Position::new(2, 2)), 14);
+    }
+
+    #[test]
+    fn test_offset_clamps() {
+        let index = LineIndex::new("ab\ncd");
+        assert_eq!(index.offset(Position::new(0, 10)), 2);
+        assert_eq!(index.offset(Position::new(5, 0)), 5);
+    }
+
+    #[test]
+    fn test_offset_multibyte() {
+        // 'é' is 2 bytes / 1 UTF-16 unit, '😀' is 4 bytes / 2 UTF-16 units
+        let source = "let s = 'é😀x';";
+        let index = LineIndex::new(source);
+
+        let x_offset = source.find('x').unwrap();
+        assert_eq!(index.offset(Position::new(0, 12)), x_offset);
+        assert_eq!(index.position(x_offset), Position::new(0, 12));
+    }
+
+    #[test]
+    fn test_slice_after_multibyte() {
+        let source = "const a = \"é😀\"; const b = 1;";
+        let index = LineIndex::new(source);
+
+        // Select `b` which follows both multibyte characters
+        let range = Range::new(Position::new(0, 23), Position::new(0, 24));
+        assert_eq!(index.slice(range), "b");
+
+        // A range ending inside the surrogate pair does not panic
+        let range = Range::new(Position::new(0, 11), Position::new(0, 13));
+        let _ = index.slice(range);
+    }
+
+    #[test]
+    fn test_crlf_line_endings() {
+        let index = LineIndex::new("ab\r\ncd");
+        assert_eq!(index.offset(Position::new(1, 1)), 5);
+        assert_eq!(index.offset(Position::new(0, 5)), 2);
+        assert_eq!(index.position(5), Position::new(1, 1));
+    }
+
+    #[test]
+    fn test_node_range_multibyte() {
+        let source = "const é = 1; const y = 2;";
+        let mut parser = tree_sitter::Parser::new();
+        parser
+            .set_language(&tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into())
+            .unwrap();
+        let tree = parser.parse(source, None).unwrap();
+        let index = LineIndex::new(source);
+
+        let second = tree.root_node().child(1).unwrap();
+        let range = index.node_range(&second);
+        assert_eq!(range.start, Position::new(0, 13));
+        assert_eq!(index.slice(range), "const y = 2;");
+    }
+
+    #[test]
+    fn test_point_roundtrip() {
+        let source = "é\n😀a";
+        let index = LineIndex::new(source);
+        let point = index.point(Position::new(1, 2));
+        assert_eq!(point, Point::new(1, 4));
+        assert_eq!(index.position_of_point(point), Position::new(1, 2));
+    }
+}
@@ -1,6 +1,7 @@
 mod analysis;
 mod capabilities;
 mod document;
+mod line_index;
 mod parser;
 mod project;
 mod resolution;
@@ -1,5 +1,7 @@
-use tower_lsp::lsp_types::Url;
-use tree_sitter::{Parser, Tree};
+use tower_lsp::lsp_types::{TextDocumentContentChangeEvent, Url};
+use tree_sitter::{InputEdit, Parser, Point, Tree};
+
+use crate::line_index::LineIndex;
 
 /// Supported source languages
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -86,6 +88,54 @@ impl SourceParser {
         self.parser.parse(source, old_tree)
     }
 
+    /// Apply content changes to `old_source` and reparse incrementally.
+    ///
+    /// Each ranged change is mirrored onto `old_tree` with `Tree::edit` so
+    /// tree-sitter can reuse the unchanged parts of the previous parse. A
+    /// full-document change discards the old tree. Returns the new source
+    /// together with the new tree.
+    pub fn parse_with_changes(
+        &mut self,
+        old_source: &str,
+        old_tree: Option<Tree>,
+        changes: &[TextDocumentContentChangeEvent],
+    ) -> (String, Option<Tree>) {
+        let mut source = old_source.to_string();
+        let mut tree = old_tree;
+
+        for change in changes {
+            match change.range {
+                Some(range) => {
+                    let index = LineIndex::new(&source);
+                    let start_byte = index.offset(range.start);
+                    let old_end_byte = index.offset(range.end).max(start_byte);
+                    let start_position = index.point(range.start);
+                    let old_end_position = index.point(range.end);
+
+                    source.replace_range(start_byte..old_end_byte, &change.text);
+
+                    if let Some(ref mut tree) = tree {
+                        tree.edit(&InputEdit {
+                            start_byte,
+                            old_end_byte,
+                            new_end_byte: start_byte + change.text.len(),
+                            start_position,
+                            old_end_position,
+                            new_end_position: point_after(start_position, &change.text),
+                        });
+                    }
+                }
+                None => {
+                    source = change.text.clone();
+                    tree = None;
+                }
+            }
+        }
+
+        let new_tree = self.parser.parse(&source, tree.as_ref());
+        (source, new_tree)
+    }
+
     /// Change the parser's language
     pub fn set_language(&mut self, language: SourceLanguage) {
         if self.language != language {
@@ -104,6 +154,17 @@ impl SourceParser {
     }
 }
 
+/// The point reached after inserting `text` at `start`
+fn point_after(start: Point, text: &str) -> Point {
+    match text.rfind('\n') {
+        Some(last_newline) => Point::new(
+            start.row + text.matches('\n').count(),
+            text.len() - last_newline - 1,
+        ),
+        None => Point::new(start.row, start.column + text.len()),
+    }
+}
+
 impl Default for SourceParser {
     fn default() -> Self {
         Self::new(SourceLanguage::TypeScript)
@@ -117,6 +178,7 @@ pub type TsParser = SourceParser;
 #[cfg(test)]
 mod tests {
     use super::*;
+    use tower_lsp::lsp_types::Position;
 
     #[test]
     fn test_source_language_from_extension() {
@@ -370,4 +432,105 @@ mod tests {
         assert!(tree.is_some());
         assert!(!tree.unwrap().root_node().has_error());
     }
+
+    #[test]
+    fn test_parse_with_changes_matches_full_reparse() {
+        let mut parser = SourceParser::new(SourceLanguage::TypeScript);
+        let original = "function a() {\n    return 1;\n}\n\nfunction b() {\n    return 2;\n}\n";
+        let tree = parser.parse(original, None).unwrap();
+
+        // Replace "1" on line 1 with "foo + 10"
+        let changes = vec![TextDocumentContentChangeEvent {
+            range: Some(tower_lsp::lsp_types::Range {
+                start: Position::new(1, 11),
+                end: Position::new(1, 12),
+            }),
+            range_length: Some(1),
+            text: "foo + 10".to_string(),
+        }];
+
+        let (source, incremental) = parser.parse_with_changes(original, Some(tree), &changes);
+        assert_eq!(
+            source,
+            "function a() {\n    return foo + 10;\n}\n\nfunction b() {\n    return 2;\n}\n"
+        );
+
+        let full = parser.parse(&source, None).unwrap();
+        let incremental = incremental.unwrap();
+        assert_eq!(
+            incremental.root_node().to_sexp(),
+            full.root_node().to_sexp()
+        );
+        assert_eq!(
+            incremental.root_node().end_byte(),
+            full.root_node().end_byte()
+        );
+    }
+
+    #[test]
+    fn test_parse_with_changes_multiline_insert() {
+        let mut parser = SourceParser::new(SourceLanguage::TypeScript);
+        let original = "const a = 1;\nconst b = 2;\n";
+        let tree = parser.parse(original, None).unwrap();
+
+        let changes = vec![TextDocumentContentChangeEvent {
+            range: Some(tower_lsp::lsp_types::Range {
+                start: Position::new(1, 0),
+                end: Position::new(1, 0),
+            }),
+            range_length: Some(0),
+            text: "let x = a;\nlet y = x;\n".to_string(),
+        }];
+
+        let (source, incremental) = parser.parse_with_changes(original, Some(tree), &changes);
+        let full = parser.parse(&source, None).unwrap();
+        assert_eq!(
+            incremental.unwrap().root_node().to_sexp(),
+            full.root_node().to_sexp()
+        );
+    }
+
+    #[test]
+    fn test_parse_with_changes_full_replacement() {
+        let mut parser = SourceParser::new(SourceLanguage::TypeScript);
+        let tree = parser.parse("const a = 1;", None).unwrap();
+
+        let changes = vec![TextDocumentContentChangeEvent {
+            range: None,
+            range_length: None,
+            text: "let b = 2;".to_string(),
+        }];
+
+        let (source, new_tree) = parser.parse_with_changes("const a = 1;", Some(tree), &changes);
+        assert_eq!(source, "let b = 2;");
+        assert!(!new_tree.unwrap().root_node().has_error());
+    }
+
+    #[test]
+    fn test_parse_with_changes_utf16_positions() {
+        let mut parser = SourceParser::new(SourceLanguage::TypeScript);
+        let original = "const s = \"😀\"; const n = 1;";
+        let tree = parser.parse(original, None).unwrap();
+
+        // `1` is at UTF-16 column 26 but byte column 28
+        let changes = vec![TextDocumentContentChangeEvent {
+            range: Some(tower_lsp::lsp_types::Range {
+                start: Position::new(0, 26),
+                end: Position::new(0, 27),
+            }),
+            range_length: Some(1),
+            text: "2".to_string(),
+        }];
+
+        let (source, new_tree) = parser.parse_with_changes(original, Some(tree), &changes);
+        assert_eq!(source, "const s = \"😀\"; const n = 2;");
+        assert!(!new_tree.unwrap().root_node().has_error());
+    }
+
+    #[test]
+    fn test_point_after() {
+        assert_eq!(point_after(Point::new(2, 4), "abc"), Point::new(2, 7));
+        assert_eq!(point_after(Point::new(2, 4), "ab\ncd"), Point::new(3, 2));
+        assert_eq!(point_after(Point::new(0, 0), "a\n"), Point::new(1, 0));
+    }
 }
@@ -6,6 +6,8 @@
 use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
 
+use tower_lsp::lsp_types::Url;
+
 /// Represents the dependency graph of files in a project
 #[derive(Debug, Default)]
 pub struct FileGraph {
@@ -169,6 +171,188 @@ impl FileGraph {
         false
     }
 
+    /// Add an import edge between two documents.
+    /// Non-file URIs are ignored.
+    pub fn add_edge(&mut self, from: &Url, to: &Url) {
+        if let (Ok(from), Ok(to)) = (from.to_file_path(), to.to_file_path()) {
+            self.add_import(&from, &to);
+        }
+    }
+
+    /// Replace the imports of a document after it changed. Returns the
+    /// documents whose transitive dependencies changed, sorted: the document
+    /// itself and everything importing it, or nothing if its imports are the same.
+    pub fn update_file(&mut self, uri: &Url, imports: Vec<Url>) -> Vec<Url> {
+        let Ok(path) = uri.to_file_path() else {
+            return Vec::new();
+        };
+        let new_imports: HashSet<PathBuf> = imports
+            .iter()
+            .filter_map(|import| import.to_file_path().ok())
+            .collect();
+        if self.imports.get(&path) == Some(&new_imports) {
+            return Vec::new();
+        }
+
+        self.clear_imports(&path);
+        self.importers.entry(path.clone()).or_default();
+        for import in &new_imports {
+            self.add_import(&path, import);
+        }
+
+        let mut changed = self.dependents_of(uri);
+        changed.push(uri.clone());
+        changed.sort();
+        changed
+    }
+
+    /// Remove a document and its edges in both directions. Returns the
+    /// documents that imported it, directly or indirectly, sorted.
+    pub fn remove_document(&mut self, uri: &Url) -> Vec<Url> {
+        let Ok(path) = uri.to_file_path() else {
+            return Vec::new();
+        };
+        let dependents = self.dependents_of(uri);
+        self.remove_file(&path);
+        dependents
+    }
+
+    /// Documents that directly or indirectly import the given document, sorted
+    pub fn dependents_of(&self, uri: &Url) -> Vec<Url> {
+        let Ok(path) = uri.to_file_path() else {
+            return Vec::new();
+        };
+        let mut dependents: Vec<Url> = self
+            .get_affected_files(&path)
+            .into_iter()
+            .filter(|affected| *affected != path)
+            .filter_map(|affected| Url::from_file_path(affected).ok())
+            .collect();
+        dependents.sort();
+        dependents
+    }
+
+    /// Documents the given document imports directly, sorted
+    pub fn import_edges(&self, uri: &Url) -> Vec<Url> {
+        let Some(imports) = uri
+            .to_file_path()
+            .ok()
+            .and_then(|path| self.imports.get(&path))
+        else {
+            return Vec::new();
+        };
+        let mut edges: Vec<Url> = imports
+            .iter()
+            .filter_map(|path| Url::from_file_path(path).ok())
+            .collect();
+        edges.sort();
+        edges
+    }
+
+    /// The shortest import chain leading from a document back to itself,
+    /// starting with the document: `[a, b]` when `a` imports `b` and `b`
+    /// imports `a`
+    pub fn import_cycle(&self, uri: &Url) -> Option<Vec<Url>> {
+        let mut previous: HashMap<Url, Url> = HashMap::new();
+        let mut queue = std::collections::VecDeque::from([uri.clone()]);
+
+        while let Some(current) = queue.pop_front() {
+            for next in self.import_edges(&current) {
+                if next == *uri {
+                    let mut cycle = vec![current.clone()];
+                    while let Some(prior) = previous.get(cycle.last()?) {
+                        cycle.push(prior.clone());
+                    }
+                    if cycle.last() != Some(uri) {
+                        cycle.push(uri.clone());
+                    }
+                    cycle.reverse();
+                    return Some(cycle);
+                }
+                if !previous.contains_key(&next) {
+                    previous.insert(next.clone(), current.clone());
+                    queue.push_back(next);
+                }
+            }
+        }
+        None
+    }
+
+    /// Import cycles in the graph: strongly connected components with more
+    /// than one file, or a single file importing itself
+    pub fn detect_cycles(&self) -> Vec<Vec<Url>> {
+        let mut tarjan = Tarjan::default();
+        let mut paths: Vec<&PathBuf> = self.imports.keys().collect();
+        paths.sort();
+        for path in paths {
+            if !tarjan.index.contains_key(path) {
+                tarjan.visit(self, path);
+            }
+        }
+
+        let mut cycles: Vec<Vec<Url>> = tarjan
+            .components
+            .into_iter()
+            .filter(|component| {
+                component.len() > 1
+                    || self
+                        .imports
+                        .get(&component[0])
+                        .is_some_and(|imports| imports.contains(&component[0]))
+            })
+            .map(|component| {
+                let mut uris: Vec<Url> = component
+                    .into_iter()
+                    .filter_map(|path| Url::from_file_path(path).ok())
+                    .collect();
+                uris.sort();
+                uris
+            })
+            .collect();
+        cycles.sort();
+        cycles
+    }
+
+    /// One import cycle per strongly connected component: the shortest
+    /// chain from the component's first (smallest) document back to itself,
+    /// in import order
+    pub fn find_cycles(&self) -> Vec<Vec<Url>> {
+        self.detect_cycles()
+            .iter()
+            .filter_map(|component| self.import_cycle(component.first()?))
+            .collect()
+    }
+
+    /// The shortest import chain from one document to another, including
+    /// both ends, e.g. to explain why a transitive import is included
+    pub fn shortest_path(&self, from: &Url, to: &Url) -> Option<Vec<Url>> {
+        if from == to {
+            return Some(vec![from.clone()]);
+        }
+
+        let mut previous: HashMap<Url, Url> = HashMap::new();
+        let mut queue = std::collections::VecDeque::from([from.clone()]);
+
+        while let Some(current) = queue.pop_front() {
+            for next in self.import_edges(&current) {
+                if next == *from || previous.contains_key(&next) {
+                    continue;
+                }
+                previous.insert(next.clone(), current.clone());
+                if next == *to {
+                    let mut path = vec![next];
+                    while let Some(prior) = previous.get(path.last()?) {
+                        path.push(prior.clone());
+                    }
+                    path.reverse();
+                    return Some(path);
+                }
+                queue.push_back(next);
+            }
+        }
+        None
+    }
+
     /// Clear all import relationships for a file (used before re-analyzing)
     pub fn clear_imports(&mut self, path: &Path) {
         if let Some(imports) = self.imports.remove(path) {
@@ -192,6 +376,55 @@ impl FileGraph {
     }
 }
 
+/// State for Tarjan's strongly connected components algorithm
+#[derive(Default)]
+struct Tarjan {
+    next_index: usize,
+    index: HashMap<PathBuf, usize>,
+    low_link: HashMap<PathBuf, usize>,
+    stack: Vec<PathBuf>,
+    on_stack: HashSet<PathBuf>,
+    components: Vec<Vec<PathBuf>>,
+}
+
+impl Tarjan {
+    fn visit(&mut self, graph: &FileGraph, path: &Path) {
+        let path_buf = path.to_path_buf();
+        self.index.insert(path_buf.clone(), self.next_index);
+        self.low_link.insert(path_buf.clone(), self.next_index);
+        self.next_index += 1;
+        self.stack.push(path_buf.clone());
+        self.on_stack.insert(path_buf.clone());
+
+        if let Some(imports) = graph.imports.get(path) {
+            for import in imports {
+                if !self.index.contains_key(import) {
+                    self.visit(graph, import);
+                    let low = self.low_link[import].min(self.low_link[&path_buf]);
+                    self.low_link.insert(path_buf.clone(), low);
+                } else if self.on_stack.contains(import) {
+                    let low = self.index[import].min(self.low_link[&path_buf]);
+                    self.low_link.insert(path_buf.clone(), low);
+                }
+            }
+        }
+
+        // `path` is the root of a component: pop it off the stack
+        if self.low_link[&path_buf] == self.index[&path_buf] {
+            let mut component = Vec::new();
+            while let Some(member) = self.stack.pop() {
+                self.on_stack.remove(&member);
+                let is_root = member == path_buf;
+                component.push(member);
+                if is_root {
+                    break;
+                }
+            }
+            self.components.push(component);
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -229,7 +462,7 @@ mod tests {
         graph.remove_file(&main);
         assert!(!graph.contains_file(&main));
         // utils should still exist but have no importers
-        assert!(graph.get_importers(&utils).map_or(true, |i| i.is_empty()));
+        assert!(graph.get_importers(&utils).is_none_or(|i| i.is_empty()));
     }
 
     #[test]
@@ -259,7 +492,7 @@ mod tests {
         graph.remove_import(&main, &utils);
 
         let imports = graph.get_imports(&main);
-        assert!(imports.map_or(true, |i| !i.contains(&utils)));
+        assert!(imports.is_none_or(|i| !i.contains(&utils)));
     }
 
     #[test]
@@ -341,7 +574,7 @@ mod tests {
         graph.clear_imports(&main);
 
         let imports = graph.get_imports(&main);
-        assert!(imports.map_or(true, |i| i.is_empty()));
+        assert!(imports.is_none_or(|i| i.is_empty()));
     }
 
     #[test]
@@ -407,4 +640,156 @@ mod tests {
         assert_eq!(affected.len(), 1);
         assert!(affected.contains(&a));
     }
+
+    fn file_uri(name: &str) -> Url {
+        Url::from_file_path(PathBuf::from("/src").join(name)).unwrap()
+    }
+
+    #[test]
+    fn test_detect_cycles_two_files() {
+        let mut graph = FileGraph::new();
+        let a = file_uri("a.ts");
+        let b = file_uri("b.ts");
+        let c = file_uri("c.ts");
+
+        graph.add_edge(&a, &b);
+        graph.add_edge(&b, &a);
+        graph.add_edge(&c, &a);
+
+        assert_eq!(graph.detect_cycles(), vec![vec![a.clone(), b.clone()]]);
+        assert_eq!(graph.dependents_of(&a), vec![b, c]);
+    }
+
+    #[test]
+    fn test_import_cycle() {
+        let mut graph = FileGraph::new();
+        let a = file_uri("a.ts");
+        let b = file_uri("b.ts");
+        let c = file_uri("c.ts");
+        let d = file_uri("d.ts");
+
+        graph.add_edge(&a, &b);
+        graph.add_edge(&b, &c);
+        graph.add_edge(&c, &a);
+        graph.add_edge(&d, &a);
+
+        assert_eq!(graph.import_edges(&a), vec![b.clone()]);
+        assert_eq!(
+            graph.import_cycle(&a),
+            Some(vec![a.clone(), b.clone(), c.clone()])
+        );
+        assert_eq!(graph.import_cycle(&c), Some(vec![c.clone(), a, b]));
+        // `d` reaches the cycle but isn't part of it
+        assert_eq!(graph.import_cycle(&d), None);
+
+        graph.add_edge(&d, &d);
+        assert_eq!(graph.import_cycle(&d), Some(vec![d]));
+    }
+
+    #[test]
+    fn test_find_cycles_in_import_order() {
+        let mut graph = FileGraph::new();
+        let a = file_uri("a.ts");
+        let b = file_uri("b.ts");
+        let c = file_uri("c.ts");
+        let x = file_uri("x.ts");
+        let y = file_uri("y.ts");
+
+        graph.add_edge(&a, &c);
+        graph.add_edge(&c, &b);
+        graph.add_edge(&b, &a);
+        graph.add_edge(&y, &x);
+        graph.add_edge(&x, &y);
+        graph.add_edge(&x, &a);
+
+        assert_eq!(
+            graph.find_cycles(),
+            vec![vec![a.clone(), c.clone(), b.clone()], vec![x, y]]
+        );
+
+        graph.remove_import(&b.to_file_path().unwrap(), &a.to_file_path().unwrap());
+        assert_eq!(graph.find_cycles().len(), 1);
+    }
+
+    #[test]
+    fn test_shortest_path() {
+        let mut graph = FileGraph::new();
+        let main = file_uri("main.ts");
+        let app = file_uri("app.ts");
+        let routes = file_uri("routes.ts");
+        let utils = file_uri("utils.ts");
+
+        graph.add_edge(&main, &app);
+        graph.add_edge(&app, &routes);
+        graph.add_edge(&routes, &utils);
+        graph.add_edge(&main, &routes);
+
+        assert_eq!(
+            graph.shortest_path(&main, &utils),
+            Some(vec![main.clone(), routes.clone(), utils.clone()])
+        );
+        assert_eq!(graph.shortest_path(&app, &app), Some(vec![app.clone()]));
+        assert_eq!(graph.shortest_path(&utils, &main), None);
+    }
+
+    #[test]
+    fn test_update_file_marks_reverse_reachable_files() {
+        let mut graph = FileGraph::new();
+        let main = file_uri("main.ts");
+        let app = file_uri("app.ts");
+        let routes = file_uri("routes.ts");
+        let utils = file_uri("utils.ts");
+        let lib = file_uri("lib.ts");
+
+        graph.add_edge(&main, &app);
+        graph.add_edge(&app, &routes);
+        graph.add_edge(&routes, &utils);
+        graph.add_edge(&lib, &utils);
+
+        // `routes` now imports `app` instead of `utils`; `lib` and `utils`
+        // don't reach `routes` and keep their dependencies
+        let changed = graph.update_file(&routes, vec![app.clone()]);
+        assert_eq!(changed, vec![app.clone(), main.clone(), routes.clone()]);
+        assert_eq!(graph.import_edges(&routes), vec![app.clone()]);
+        assert_eq!(graph.dependents_of(&utils), vec![lib.clone()]);
+
+        // Same imports again: nothing changed
+        assert!(graph.update_file(&routes, vec![app.clone()]).is_empty());
+
+        assert_eq!(
+            graph.remove_document(&routes),
+            vec![app.clone(), main.clone()]
+        );
+        assert!(graph.import_edges(&app).is_empty());
+        assert!(graph.dependents_of(&app).contains(&main));
+    }
+
+    #[test]
+    fn test_detect_cycles_self_import() {
+        let mut graph = FileGraph::new();
+        let a = file_uri("a.ts");
+
+        graph.add_edge(&a, &a);
+
+        assert_eq!(graph.detect_cycles(), vec![vec![a]]);
+    }
+
+    #[test]
+    fn test_detect_cycles_diamond() {
+        let mut graph = FileGraph::new();
+        let top = file_uri("top.ts");
+        let left = file_uri("left.ts");
+        let right = file_uri("right.ts");
+        let bottom = file_uri("bottom.ts");
+
+        // top imports left and right, which both import bottom
+        graph.add_edge(&top, &left);
+        graph.add_edge(&top, &right);
+        graph.add_edge(&left, &bottom);
+        graph.add_edge(&right, &bottom);
+
+        assert!(graph.detect_cycles().is_empty());
+        assert_eq!(graph.dependents_of(&bottom), vec![left, right, top]);
+        assert!(graph.dependents_of(&file_uri("unknown.ts")).is_empty());
+    }
 }
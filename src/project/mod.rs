@@ -1,5 +1,7 @@
 pub mod file_graph;
+#[allow(clippy::module_inception)]
 pub mod project;
+pub mod vfs;
 pub mod workspace;
 
 // Re-export public API for future use
@@ -8,4 +10,6 @@ pub use file_graph::FileGraph;
 #[allow(unused_imports)]
 pub use project::Project;
 #[allow(unused_imports)]
+pub use vfs::VirtualFileSystem;
+#[allow(unused_imports)]
 pub use workspace::Workspace;
@@ -3,13 +3,19 @@
 
 #![allow(dead_code)]
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
 
+use tower_lsp::lsp_types::{Range, Url};
+use tree_sitter::Tree;
+
+use crate::analysis::modules::{self, ExportBinding};
+use crate::analysis::{SymbolFlags, SymbolTable, binder};
+use crate::parser::{SourceLanguage, SourceParser};
 use crate::resolution::ModuleResolver;
-use crate::resolution::tsconfig::TsConfig;
+use crate::resolution::tsconfig::{CompilerOptions, TsConfig};
 
-use super::FileGraph;
+use super::{FileGraph, VirtualFileSystem};
 
 /// Represents a TypeScript project (usually corresponds to a tsconfig.json)
 pub struct Project {
@@ -25,6 +31,26 @@ pub struct Project {
     pub files: HashSet<PathBuf>,
     /// File dependency graph
     pub file_graph: FileGraph,
+    /// Bound exports of files, keyed by path
+    file_cache: HashMap<PathBuf, ModuleExports>,
+    /// Unsaved contents of open documents, read instead of the disk
+    pub vfs: VirtualFileSystem,
+}
+
+/// The exports of a bound project file
+pub struct ModuleExports {
+    pub symbol_table: SymbolTable,
+    pub exports: Vec<ExportBinding>,
+    /// Specifiers of `export * from '...'`
+    pub star_exports: Vec<String>,
+}
+
+/// A name a module exports, directly or through re-exports
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExportedName {
+    pub name: String,
+    /// Flags of the exporting declaration, `NONE` if it isn't known
+    pub flags: SymbolFlags,
 }
 
 impl Project {
@@ -37,6 +63,8 @@ impl Project {
             resolver: ModuleResolver::new(root),
             files: HashSet::new(),
             file_graph: FileGraph::new(),
+            file_cache: HashMap::new(),
+            vfs: VirtualFileSystem::new(),
         }
     }
 
@@ -55,6 +83,8 @@ impl Project {
             resolver,
             files: HashSet::new(),
             file_graph: FileGraph::new(),
+            file_cache: HashMap::new(),
+            vfs: VirtualFileSystem::new(),
         };
 
         // Discover project files
@@ -63,116 +93,54 @@ impl Project {
         Ok(project)
     }
 
-    /// Discover files based on tsconfig include/exclude patterns
-    fn discover_files(&mut self) -> Result<(), String> {
-        // Clone patterns to avoid borrow issues
-        let include_patterns: Vec<String> = match &self.config {
-            Some(c) => c.include.clone().unwrap_or_default(),
-            None => return Ok(()),
-        };
-
-        if include_patterns.is_empty() {
-            // Default: include all .ts/.tsx files in the project
-            self.discover_default_files()?;
-        } else {
-            // Use include patterns
-            for pattern in &include_patterns {
-                self.discover_files_by_pattern(pattern)?;
-            }
-        }
-
-        Ok(())
-    }
-
-    /// Discover files with default pattern (all .ts/.tsx in project)
-    fn discover_default_files(&mut self) -> Result<(), String> {
-        let extensions = ["ts", "tsx", "mts", "cts"];
-
-        if let Ok(entries) = std::fs::read_dir(&self.root) {
-            for entry in entries.filter_map(|e| e.ok()) {
-                let path = entry.path();
-                if path.is_file() {
-                    if let Some(ext) = path.extension() {
-                        if extensions.contains(&ext.to_string_lossy().as_ref()) {
-                            self.files.insert(path);
-                        }
-                    }
-                } else if path.is_dir() {
-                    // Skip node_modules and hidden directories
-                    if let Some(name) = path.file_name() {
-                        let name = name.to_string_lossy();
-                        if !name.starts_with('.') && name != "node_modules" {
-                            self.discover_files_in_dir(&path, &extensions)?;
-                        }
-                    }
-                }
-            }
-        }
-
-        Ok(())
+    /// The compiler options of the tsconfig, or the defaults without one
+    pub fn compiler_options(&self) -> CompilerOptions {
+        self.config
+            .as_ref()
+            .and_then(|config| config.compiler_options.clone())
+            .unwrap_or_default()
     }
 
-    /// Recursively discover files in a directory
-    fn discover_files_in_dir(&mut self, dir: &Path, extensions: &[&str]) -> Result<(), String> {
-        if let Ok(entries) = std::fs::read_dir(dir) {
-            for entry in entries.filter_map(|e| e.ok()) {
-                let path = entry.path();
-                if path.is_file() {
-                    if let Some(ext) = path.extension() {
-                        if extensions.contains(&ext.to_string_lossy().as_ref()) {
-                            self.files.insert(path);
-                        }
-                    }
-                } else if path.is_dir() {
-                    if let Some(name) = path.file_name() {
-                        let name = name.to_string_lossy();
-                        if !name.starts_with('.') && name != "node_modules" {
-                            self.discover_files_in_dir(&path, extensions)?;
-                        }
-                    }
-                }
+    /// Discover files based on tsconfig files/include/exclude patterns
+    fn discover_files(&mut self) -> Result<(), String> {
+        let Some(config) = self.config.take() else {
+            return Ok(());
+        };
+        let root = self.root.clone();
+        self.discover_files_in_dir(&root, &config);
+
+        // Listed files are part of the project even inside excluded directories
+        for file in config.files.iter().flatten() {
+            let path = root.join(file);
+            if path.is_file() {
+                self.file_graph.add_file(path.clone());
+                self.files.insert(path);
             }
         }
-
+        self.config = Some(config);
         Ok(())
     }
 
-    /// Discover files by glob pattern
-    fn discover_files_by_pattern(&mut self, pattern: &str) -> Result<(), String> {
-        // Simple pattern handling - supports ** and *
-        let pattern_path = self.root.join(pattern);
-        let pattern_str = pattern_path.to_string_lossy();
-
-        if pattern_str.contains("**") {
-            // Recursive pattern
-            let parts: Vec<&str> = pattern.split("**").collect();
-            if let Some(base) = parts.first() {
-                let base_path = self.root.join(base.trim_end_matches('/'));
-                if base_path.is_dir() {
-                    let extensions = ["ts", "tsx", "mts", "cts", "js", "jsx", "mjs", "cjs"];
-                    self.discover_files_in_dir(&base_path, &extensions)?;
-                }
-            }
-        } else if pattern_str.contains('*') {
-            // Single-level wildcard
-            if let Some(parent) = pattern_path.parent() {
-                if parent.is_dir() {
-                    if let Ok(entries) = std::fs::read_dir(parent) {
-                        for entry in entries.filter_map(|e| e.ok()) {
-                            let path = entry.path();
-                            if path.is_file() {
-                                // Simple matching - could be improved
-                                self.files.insert(path);
-                            }
-                        }
-                    }
+    /// Recursively add the files below `dir` that the tsconfig matches,
+    /// without descending into excluded directories
+    fn discover_files_in_dir(&mut self, dir: &Path, config: &TsConfig) {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return;
+        };
+        for entry in entries.filter_map(|e| e.ok()) {
+            let path = entry.path();
+            let Ok(relative) = path.strip_prefix(&self.root) else {
+                continue;
+            };
+            if path.is_dir() {
+                if !config.is_excluded(&relative.to_string_lossy().replace('\\', "/")) {
+                    self.discover_files_in_dir(&path, config);
                 }
+            } else if path.is_file() && config.matches_file(relative) {
+                self.file_graph.add_file(path.clone());
+                self.files.insert(path);
             }
-        } else if pattern_path.is_file() {
-            self.files.insert(pattern_path);
         }
-
-        Ok(())
     }
 
     /// Add a file to the project
@@ -191,6 +159,18 @@ impl Project {
         self.files.contains(path)
     }
 
+    /// Check if a file belongs to this project: it is below the root and,
+    /// when there is a tsconfig, listed in or matched by its patterns
+    pub fn includes_file(&self, path: &Path) -> bool {
+        let Ok(relative) = path.strip_prefix(&self.root) else {
+            return false;
+        };
+        match &self.config {
+            Some(config) => self.files.contains(path) || config.matches_file(relative),
+            None => true,
+        }
+    }
+
     /// Get all files in the project
     pub fn get_files(&self) -> impl Iterator<Item = &PathBuf> {
         self.files.iter()
@@ -200,6 +180,180 @@ impl Project {
     pub fn file_count(&self) -> usize {
         self.files.len()
     }
+
+    /// Cache the exports of a freshly parsed file
+    pub fn cache_file(&mut self, path: PathBuf, tree: &Tree, source: &str) {
+        self.file_cache.insert(
+            path,
+            ModuleExports {
+                symbol_table: binder::bind_document(tree, source),
+                exports: modules::collect_exports(tree, source),
+                star_exports: modules::collect_star_exports(tree, source),
+            },
+        );
+    }
+
+    /// Record the new contents of a changed file: re-cache its exports and
+    /// replace its edges in the file graph. Returns the files whose
+    /// transitive dependencies changed.
+    pub fn on_file_change(&mut self, uri: &Url, tree: &Tree, source: &str) -> Vec<Url> {
+        let Ok(path) = uri.to_file_path() else {
+            return Vec::new();
+        };
+        self.cache_file(path.clone(), tree, source);
+
+        let specifiers: HashSet<String> = modules::collect_imports(tree, source)
+            .into_iter()
+            .map(|import| import.specifier)
+            .chain(self.reexport_specifiers(&path))
+            .collect();
+        let imports = specifiers
+            .iter()
+            .filter_map(|specifier| self.resolver.resolve(specifier, &path))
+            .filter_map(|resolved| Url::from_file_path(resolved.path).ok())
+            .collect();
+        self.file_graph.update_file(uri, imports)
+    }
+
+    /// Forget what is cached about the file at `path` after it changed on disk
+    pub fn invalidate_file(&mut self, path: &Path) {
+        self.resolver.invalidate(path);
+        self.file_cache.remove(path);
+    }
+
+    /// Make sure the exports of `path`, and of every module it re-exports
+    /// from, are cached. Files that aren't cached yet are read from disk.
+    pub fn load_module_exports(&mut self, path: &Path) {
+        let mut pending = vec![path.to_path_buf()];
+        let mut visited = HashSet::new();
+
+        while let Some(path) = pending.pop() {
+            if !visited.insert(path.clone()) {
+                continue;
+            }
+            if !self.file_cache.contains_key(&path) {
+                let Some(source) = self.vfs.read_file(&path) else {
+                    continue;
+                };
+                let language = SourceLanguage::from_extension(&path.to_string_lossy());
+                let Some(tree) = SourceParser::new(language).parse(&source, None) else {
+                    continue;
+                };
+                self.cache_file(path.clone(), &tree, &source);
+            }
+
+            for specifier in self.reexport_specifiers(&path) {
+                if let Some(resolved) = self.resolver.resolve(&specifier, &path) {
+                    pending.push(resolved.path);
+                }
+            }
+        }
+    }
+
+    /// Names exported by a cached module, following `export * from` into
+    /// other cached modules. Sorted and deduplicated by name.
+    pub fn exported_names(&self, path: &Path) -> Vec<ExportedName> {
+        let mut names = Vec::new();
+        let mut visited = HashSet::new();
+        self.collect_exported_names(path, true, &mut names, &mut visited);
+
+        names.sort_by(|a, b| a.name.cmp(&b.name));
+        names.dedup_by(|a, b| a.name == b.name);
+        names
+    }
+
+    /// Where a cached module declares its export `name`: the name of the
+    /// exported symbol, or the export itself when it has none. Re-exports
+    /// are not followed.
+    pub fn export_location(&self, path: &Path, name: &str) -> Option<Range> {
+        let module = self.file_cache.get(path)?;
+        let export = module
+            .exports
+            .iter()
+            .find(|export| export.exported == name && export.from.is_none())?;
+        Some(
+            modules::exported_symbol(&module.symbol_table, export)
+                .and_then(|id| module.symbol_table.get_symbol(id))
+                .map_or(export.range, |symbol| symbol.name_range),
+        )
+    }
+
+    /// Paths of the cached modules, sorted
+    pub fn cached_modules(&self) -> Vec<PathBuf> {
+        let mut paths: Vec<PathBuf> = self.file_cache.keys().cloned().collect();
+        paths.sort();
+        paths
+    }
+
+    /// The bound symbols of every cached module
+    pub fn cached_symbol_tables(&self) -> impl Iterator<Item = (&PathBuf, &SymbolTable)> {
+        self.file_cache
+            .iter()
+            .map(|(path, module)| (path, &module.symbol_table))
+    }
+
+    /// Cached modules that export `name`, sorted by path
+    pub fn modules_exporting(&self, name: &str) -> Vec<PathBuf> {
+        let mut paths: Vec<PathBuf> = self
+            .file_cache
+            .keys()
+            .filter(|path| {
+                self.exported_names(path)
+                    .iter()
+                    .any(|export| export.name == name)
+            })
+            .cloned()
+            .collect();
+        paths.sort();
+        paths
+    }
+
+    fn collect_exported_names(
+        &self,
+        path: &Path,
+        include_default: bool,
+        names: &mut Vec<ExportedName>,
+        visited: &mut HashSet<PathBuf>,
+    ) {
+        if !visited.insert(path.to_path_buf()) {
+            return;
+        }
+        let Some(module) = self.file_cache.get(path) else {
+            return;
+        };
+
+        for export in &module.exports {
+            if export.exported == "default" && !include_default {
+                continue;
+            }
+            let flags = modules::exported_symbol(&module.symbol_table, export)
+                .and_then(|id| module.symbol_table.get_symbol(id))
+                .map_or(SymbolFlags::NONE, |symbol| symbol.flags);
+            names.push(ExportedName {
+                name: export.exported.clone(),
+                flags,
+            });
+        }
+
+        // `export *` re-exports everything except the default export
+        for specifier in &module.star_exports {
+            if let Some(resolved) = self.resolver.resolve(specifier, path) {
+                self.collect_exported_names(&resolved.path, false, names, visited);
+            }
+        }
+    }
+
+    fn reexport_specifiers(&self, path: &Path) -> Vec<String> {
+        let Some(module) = self.file_cache.get(path) else {
+            return Vec::new();
+        };
+        module
+            .exports
+            .iter()
+            .filter_map(|export| export.from.clone())
+            .chain(module.star_exports.iter().cloned())
+            .collect()
+    }
 }
 
 #[cfg(test)]
@@ -348,10 +502,7 @@ mod tests {
         // Should discover .ts and .tsx files but not .md
         let ts_files: Vec<_> = project
             .get_files()
-            .filter(|p| {
-                p.extension()
-                    .map_or(false, |ext| ext == "ts" || ext == "tsx")
-            })
+            .filter(|p| p.extension().is_some_and(|ext| ext == "ts" || ext == "tsx"))
             .collect();
 
         assert_eq!(ts_files.len(), 2);
@@ -427,6 +578,86 @@ mod tests {
         assert!(!project.contains_file(&main));
     }
 
+    #[test]
+    fn test_load_module_exports_reads_unsaved_buffer() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        let path = root.join("a.ts");
+        fs::write(&path, "export const saved = 1;").unwrap();
+
+        let mut project = Project::new(root.to_path_buf());
+        let uri = Url::from_file_path(&path).unwrap();
+        project
+            .vfs
+            .open(uri, "export const unsaved = 1;".to_string(), 1);
+        project.load_module_exports(&path);
+
+        let names: Vec<String> = project
+            .exported_names(&path)
+            .into_iter()
+            .map(|export| export.name)
+            .collect();
+        assert_eq!(names, vec!["unsaved"]);
+    }
+
+    #[test]
+    fn test_export_location() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        let path = root.join("lib.ts");
+        fs::write(
+            &path,
+            "function greet() {}\nexport { greet as hi };\nexport { x } from './x';",
+        )
+        .unwrap();
+
+        let mut project = Project::new(root.to_path_buf());
+        project.load_module_exports(&path);
+
+        assert_eq!(
+            project.export_location(&path, "hi"),
+            Some(Range::new(
+                tower_lsp::lsp_types::Position::new(0, 9),
+                tower_lsp::lsp_types::Position::new(0, 14)
+            ))
+        );
+        assert!(project.export_location(&path, "greet").is_none());
+        assert!(project.export_location(&path, "x").is_none());
+    }
+
+    #[test]
+    fn test_on_file_change_updates_graph() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::write(root.join("a.ts"), "export const a = 1;").unwrap();
+        fs::write(root.join("b.ts"), "export const b = 1;").unwrap();
+
+        let mut project = Project::new(root.to_path_buf());
+        let main = Url::from_file_path(root.join("main.ts")).unwrap();
+        let uri = |name: &str| Url::from_file_path(root.join(name)).unwrap();
+
+        let parse = |source: &str| SourceParser::default().parse(source, None).unwrap();
+        let source = "import { a } from './a';\nexport * from './b';";
+        let changed = project.on_file_change(&main, &parse(source), source);
+        assert_eq!(changed, vec![main.clone()]);
+        assert_eq!(
+            project.file_graph.import_edges(&main),
+            vec![uri("a.ts"), uri("b.ts")]
+        );
+
+        // Unchanged imports leave nothing to invalidate
+        let source = "import { a } from './a';\nexport * from './b';\nconst x = 1;";
+        assert!(
+            project
+                .on_file_change(&main, &parse(source), source)
+                .is_empty()
+        );
+
+        let source = "import { b } from './b';";
+        project.on_file_change(&main, &parse(source), source);
+        assert_eq!(project.file_graph.import_edges(&main), vec![uri("b.ts")]);
+    }
+
     #[test]
     fn test_wildcard_pattern() {
         let temp_dir = TempDir::new().unwrap();
@@ -449,6 +680,48 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn test_discover_include_and_exclude() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("src/generated")).unwrap();
+        fs::create_dir_all(root.join("scripts")).unwrap();
+        for file in [
+            "src/main.ts",
+            "src/main.spec.ts",
+            "src/generated/schema.ts",
+            "src/legacy.js",
+            "scripts/build.ts",
+        ] {
+            fs::write(root.join(file), "export {};").unwrap();
+        }
+
+        let tsconfig_path = root.join("tsconfig.json");
+        fs::write(
+            &tsconfig_path,
+            r#"{
+                "include": ["src"],
+                "exclude": ["src/generated", "**/*.spec.ts"],
+                "files": ["scripts/build.ts"]
+            }"#,
+        )
+        .unwrap();
+
+        let project = Project::from_tsconfig(tsconfig_path).unwrap();
+        let mut files: Vec<_> = project
+            .get_files()
+            .map(|path| {
+                path.strip_prefix(root)
+                    .unwrap()
+                    .to_string_lossy()
+                    .into_owned()
+            })
+            .collect();
+        files.sort();
+        assert_eq!(files, vec!["scripts/build.ts", "src/main.ts"]);
+        assert!(project.file_graph.contains_file(&root.join("src/main.ts")));
+    }
+
     #[test]
     fn test_specific_file_pattern() {
         let temp_dir = TempDir::new().unwrap();
@@ -471,4 +744,28 @@ mod tests {
         let project = result.unwrap();
         assert!(project.contains_file(&main_file));
     }
+
+    #[test]
+    fn test_modules_exporting() {
+        let mut project = Project::new(PathBuf::from("/test"));
+        for (path, source) in [
+            ("/test/b.ts", "export function helper() {}"),
+            (
+                "/test/a.ts",
+                "export const helper = 1;\nexport const other = 2;",
+            ),
+            ("/test/c.ts", "const helper = 1;"),
+        ] {
+            let tree = SourceParser::new(SourceLanguage::TypeScript)
+                .parse(source, None)
+                .unwrap();
+            project.cache_file(PathBuf::from(path), &tree, source);
+        }
+
+        assert_eq!(
+            project.modules_exporting("helper"),
+            vec![PathBuf::from("/test/a.ts"), PathBuf::from("/test/b.ts")]
+        );
+        assert!(project.modules_exporting("missing").is_empty());
+    }
 }
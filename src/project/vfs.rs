@@ -0,0 +1,213 @@
+//! In-memory contents of open documents
+//! Lets project analysis see edits that haven't been saved yet
+
+#![allow(dead_code)]
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use tower_lsp::lsp_types::{TextDocumentContentChangeEvent, Url};
+
+use crate::line_index::LineIndex;
+
+/// An open buffer
+#[derive(Debug, Clone)]
+pub struct VirtualFile {
+    pub content: String,
+    pub version: i32,
+}
+
+/// Errors that can occur when updating a buffer
+#[derive(Debug, PartialEq, Eq)]
+pub enum VfsError {
+    /// The document was never opened, or was closed
+    NotOpen(Url),
+    /// The change is not newer than the buffer
+    StaleVersion { current: i32, received: i32 },
+    /// A change range ends before it starts or lies past the end of the buffer
+    InvalidRange,
+}
+
+impl std::fmt::Display for VfsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VfsError::NotOpen(uri) => write!(f, "Document is not open: {}", uri),
+            VfsError::StaleVersion { current, received } => write!(
+                f,
+                "Stale change: version {} is not newer than {}",
+                received, current
+            ),
+            VfsError::InvalidRange => write!(f, "Change range is outside the document"),
+        }
+    }
+}
+
+impl std::error::Error for VfsError {}
+
+/// Buffers of open documents, which take precedence over the files on disk
+#[derive(Debug, Default)]
+pub struct VirtualFileSystem {
+    files: HashMap<Url, VirtualFile>,
+}
+
+impl VirtualFileSystem {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start tracking a document opened by the client
+    pub fn open(&mut self, uri: Url, content: String, version: i32) {
+        self.files.insert(uri, VirtualFile { content, version });
+    }
+
+    /// Apply full or incremental changes to an open document. Nothing is
+    /// changed when an error is returned.
+    pub fn update(
+        &mut self,
+        uri: &Url,
+        changes: Vec<TextDocumentContentChangeEvent>,
+        version: i32,
+    ) -> Result<(), VfsError> {
+        let file = self
+            .files
+            .get_mut(uri)
+            .ok_or_else(|| VfsError::NotOpen(uri.clone()))?;
+        if version <= file.version {
+            return Err(VfsError::StaleVersion {
+                current: file.version,
+                received: version,
+            });
+        }
+
+        let mut content = file.content.clone();
+        for change in changes {
+            let Some(range) = change.range else {
+                content = change.text;
+                continue;
+            };
+            let line_count = content.split('\n').count() as u32;
+            if range.end < range.start || range.end.line >= line_count {
+                return Err(VfsError::InvalidRange);
+            }
+            let index = LineIndex::new(&content);
+            let start = index.offset(range.start);
+            let end = index.offset(range.end);
+            content.replace_range(start..end, &change.text);
+        }
+
+        file.content = content;
+        file.version = version;
+        Ok(())
+    }
+
+    /// Stop tracking a document; reads fall back to the disk again
+    pub fn close(&mut self, uri: &Url) {
+        self.files.remove(uri);
+    }
+
+    /// The buffer of an open document
+    pub fn get(&self, uri: &Url) -> Option<&VirtualFile> {
+        self.files.get(uri)
+    }
+
+    pub fn is_open(&self, uri: &Url) -> bool {
+        self.files.contains_key(uri)
+    }
+
+    /// Contents of a file: the open buffer if there is one, else the file on disk
+    pub fn read_file(&self, path: &Path) -> Option<String> {
+        let buffer = Url::from_file_path(path)
+            .ok()
+            .and_then(|uri| self.files.get(&uri));
+        match buffer {
+            Some(file) => Some(file.content.clone()),
+            None => std::fs::read_to_string(path).ok(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tower_lsp::lsp_types::{Position, Range};
+
+    fn test_uri() -> Url {
+        Url::parse("file:///test/main.ts").unwrap()
+    }
+
+    fn edit(start: (u32, u32), end: (u32, u32), text: &str) -> TextDocumentContentChangeEvent {
+        TextDocumentContentChangeEvent {
+            range: Some(Range::new(
+                Position::new(start.0, start.1),
+                Position::new(end.0, end.1),
+            )),
+            range_length: None,
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_update_full_and_incremental() {
+        let mut vfs = VirtualFileSystem::new();
+        let uri = test_uri();
+        vfs.open(uri.clone(), "const x = 1;".to_string(), 1);
+
+        let full = TextDocumentContentChangeEvent {
+            range: None,
+            range_length: None,
+            text: "const é = 1;\nlet y;".to_string(),
+        };
+        vfs.update(&uri, vec![full], 2).unwrap();
+
+        // Columns count UTF-16 units, so `=` is at 8 despite the two-byte `é`
+        let changes = vec![edit((0, 8), (0, 11), "= 42"), edit((1, 4), (1, 5), "z")];
+        vfs.update(&uri, changes, 3).unwrap();
+
+        let file = vfs.get(&uri).unwrap();
+        assert_eq!(file.content, "const é = 42;\nlet z;");
+        assert_eq!(file.version, 3);
+    }
+
+    #[test]
+    fn test_update_errors() {
+        let mut vfs = VirtualFileSystem::new();
+        let uri = test_uri();
+        assert_eq!(
+            vfs.update(&uri, Vec::new(), 1),
+            Err(VfsError::NotOpen(uri.clone()))
+        );
+
+        vfs.open(uri.clone(), "a\nb".to_string(), 2);
+        assert_eq!(
+            vfs.update(&uri, Vec::new(), 2),
+            Err(VfsError::StaleVersion {
+                current: 2,
+                received: 2
+            })
+        );
+
+        // A failing change leaves earlier changes of the batch unapplied
+        let changes = vec![edit((0, 0), (0, 1), "c"), edit((5, 0), (5, 0), "d")];
+        assert_eq!(vfs.update(&uri, changes, 3), Err(VfsError::InvalidRange));
+        assert_eq!(vfs.get(&uri).unwrap().content, "a\nb");
+        assert_eq!(vfs.get(&uri).unwrap().version, 2);
+    }
+
+    #[test]
+    fn test_read_file_prefers_open_buffer() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("a.ts");
+        std::fs::write(&path, "saved").unwrap();
+        let uri = Url::from_file_path(&path).unwrap();
+
+        let mut vfs = VirtualFileSystem::new();
+        assert_eq!(vfs.read_file(&path).as_deref(), Some("saved"));
+
+        vfs.open(uri.clone(), "unsaved".to_string(), 1);
+        assert_eq!(vfs.read_file(&path).as_deref(), Some("unsaved"));
+
+        vfs.close(&uri);
+        assert_eq!(vfs.read_file(&path).as_deref(), Some("saved"));
+        assert!(vfs.read_file(&temp_dir.path().join("missing.ts")).is_none());
+    }
+}
@@ -3,17 +3,44 @@
 
 #![allow(dead_code)]
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
 
+use tower_lsp::lsp_types::Url;
+use tree_sitter::Tree;
+
+use crate::analysis::modules::{collect_exports, exported_symbol};
+use crate::analysis::{SymbolId, SymbolTable};
+
 use super::Project;
 
+/// Identifies a project within a workspace for as long as it is loaded
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ProjectId(pub u32);
+
 /// Manages multiple projects in a workspace
 pub struct Workspace {
     /// Root directory of the workspace
     pub root: PathBuf,
     /// All projects in the workspace
     projects: HashMap<PathBuf, Project>,
+    /// Id -> key in `projects`
+    project_keys: HashMap<ProjectId, PathBuf>,
+    next_project_id: u32,
+    /// Exported name -> files and symbols exporting it
+    exports: HashMap<String, HashSet<(Url, SymbolId)>>,
+    /// Names each file currently contributes to `exports`
+    file_exports: HashMap<Url, Vec<String>>,
+    /// Modules each file re-exports wholesale
+    reexports: HashMap<Url, Vec<ReExport>>,
+}
+
+/// A module whose exports a file passes on: `export * from './m'`, or
+/// `export * as alias from './m'`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReExport {
+    pub target: Url,
+    pub alias: Option<String>,
 }
 
 impl Workspace {
@@ -21,6 +48,11 @@ impl Workspace {
         Self {
             root,
             projects: HashMap::new(),
+            project_keys: HashMap::new(),
+            next_project_id: 0,
+            exports: HashMap::new(),
+            file_exports: HashMap::new(),
+            reexports: HashMap::new(),
         }
     }
 
@@ -29,72 +61,115 @@ impl Workspace {
         let tsconfigs = find_tsconfig_files(&self.root);
 
         for config_path in tsconfigs {
-            match Project::from_tsconfig(config_path.clone()) {
-                Ok(project) => {
-                    self.projects.insert(config_path, project);
-                }
-                Err(e) => {
-                    // Log error but continue
-                    eprintln!("Failed to load project from {:?}: {}", config_path, e);
-                }
+            if let Err(e) = self.add_config(config_path.clone()) {
+                // Log error but continue
+                eprintln!("Failed to load project from {:?}: {}", config_path, e);
             }
         }
 
         // If no projects found, create a default one from workspace root
         if self.projects.is_empty() {
-            self.projects
-                .insert(self.root.clone(), Project::new(self.root.clone()));
+            self.add_project(self.root.clone(), Project::new(self.root.clone()));
         }
 
         Ok(())
     }
 
+    /// Load the project configured by a tsconfig file
+    pub fn add_config(&mut self, config_path: PathBuf) -> Result<ProjectId, String> {
+        let project = Project::from_tsconfig(config_path.clone())?;
+        Ok(self.add_project(config_path, project))
+    }
+
+    /// The id of the project a file belongs to: the deepest project whose
+    /// tsconfig includes the file, or failing that, whose root contains it
+    pub fn project_id_for_file(&self, uri: &Url) -> Option<ProjectId> {
+        let path = uri.to_file_path().ok()?;
+        let key = self.project_key_for_file(&path)?;
+        self.project_keys
+            .iter()
+            .find(|(_, k)| **k == key)
+            .map(|(id, _)| *id)
+    }
+
+    /// Get a project by its id
+    pub fn project(&self, id: ProjectId) -> Option<&Project> {
+        self.projects.get(self.project_keys.get(&id)?)
+    }
+
+    /// Get a mutable reference to a project by its id
+    pub fn project_mut(&mut self, id: ProjectId) -> Option<&mut Project> {
+        self.projects.get_mut(self.project_keys.get(&id)?)
+    }
+
     /// Get the project that contains a given file
     pub fn project_for_file(&self, path: &Path) -> Option<&Project> {
-        // Find the project whose root is closest to the file
-        let mut best_project: Option<&Project> = None;
-        let mut best_depth = 0;
-
-        for project in self.projects.values() {
-            if path.starts_with(&project.root) {
-                let depth = project.root.components().count();
-                if depth > best_depth {
-                    best_depth = depth;
-                    best_project = Some(project);
-                }
-            }
-        }
-
-        best_project
+        let key = self.project_key_for_file(path)?;
+        self.projects.get(&key)
     }
 
     /// Get a mutable reference to the project that contains a given file
     pub fn project_for_file_mut(&mut self, path: &Path) -> Option<&mut Project> {
-        // Find the project whose root is closest to the file
-        let mut best_key: Option<PathBuf> = None;
-        let mut best_depth = 0;
-
-        for (key, project) in &self.projects {
-            if path.starts_with(&project.root) {
-                let depth = project.root.components().count();
-                if depth > best_depth {
-                    best_depth = depth;
-                    best_key = Some(key.clone());
-                }
-            }
-        }
+        let key = self.project_key_for_file(path)?;
+        self.projects.get_mut(&key)
+    }
 
-        best_key.and_then(|key| self.projects.get_mut(&key))
+    /// Key of the project a file belongs to. Projects that include the file
+    /// win over those that merely contain it, so a package's tsconfig only
+    /// claims the files its patterns match; among equals the root closest
+    /// to the file wins.
+    fn project_key_for_file(&self, path: &Path) -> Option<PathBuf> {
+        self.projects
+            .iter()
+            .filter(|(_, project)| path.starts_with(&project.root))
+            .max_by_key(|(_, project)| {
+                (
+                    project.includes_file(path),
+                    project.root.components().count(),
+                )
+            })
+            .map(|(key, _)| key.clone())
     }
 
-    /// Add a project to the workspace
-    pub fn add_project(&mut self, config_path: PathBuf, project: Project) {
-        self.projects.insert(config_path, project);
+    /// Get the project that contains a given file, creating an ad-hoc project
+    /// rooted at the file's directory if no project does
+    pub fn project_for_file_or_insert(&mut self, path: &Path) -> &mut Project {
+        let key = match self.project_key_for_file(path) {
+            Some(key) => key,
+            None => {
+                let root = path.parent().unwrap_or(Path::new(".")).to_path_buf();
+                self.add_project(root.clone(), Project::new(root.clone()));
+                root
+            }
+        };
+        self.projects
+            .entry(key)
+            .or_insert_with_key(|root| Project::new(root.clone()))
+    }
+
+    /// Add a project to the workspace, replacing any under the same key
+    pub fn add_project(&mut self, config_path: PathBuf, project: Project) -> ProjectId {
+        self.projects.insert(config_path.clone(), project);
+        if let Some((id, _)) = self.project_keys.iter().find(|(_, k)| **k == config_path) {
+            return *id;
+        }
+        let id = ProjectId(self.next_project_id);
+        self.next_project_id += 1;
+        self.project_keys.insert(id, config_path);
+        id
     }
 
     /// Remove a project from the workspace
     pub fn remove_project(&mut self, config_path: &Path) {
         self.projects.remove(config_path);
+        self.project_keys.retain(|_, key| key != config_path);
+    }
+
+    /// Forget what every project cached about a file that changed on disk
+    pub fn invalidate_file(&mut self, path: &Path) {
+        for project in self.projects.values_mut() {
+            project.invalidate_file(path);
+        }
     }
 
     /// Get all projects in the workspace
@@ -106,6 +181,142 @@ impl Workspace {
     pub fn get_project(&self, config_path: &Path) -> Option<&Project> {
         self.projects.get(config_path)
     }
+
+    /// Record the exports of a freshly (re)bound file, replacing its previous entries.
+    /// Re-exports are not indexed here; the originating file is, and
+    /// `index_file_reexports` records the edges between the two.
+    pub fn index_file_exports(
+        &mut self,
+        uri: &Url,
+        tree: &Tree,
+        source: &str,
+        symbol_table: &SymbolTable,
+    ) {
+        self.remove_file_exports(uri);
+
+        let mut names = Vec::new();
+        for export in collect_exports(tree, source) {
+            if let Some(symbol_id) = exported_symbol(symbol_table, &export) {
+                self.exports
+                    .entry(export.exported.clone())
+                    .or_default()
+                    .insert((uri.clone(), symbol_id));
+                names.push(export.exported);
+            }
+        }
+        self.file_exports.insert(uri.clone(), names);
+    }
+
+    /// Record the modules a file re-exports, replacing its previous entries
+    pub fn index_file_reexports(&mut self, uri: &Url, reexports: Vec<ReExport>) {
+        if reexports.is_empty() {
+            self.reexports.remove(uri);
+        } else {
+            self.reexports.insert(uri.clone(), reexports);
+        }
+    }
+
+    /// Drop every export contributed by a file
+    pub fn remove_file_exports(&mut self, uri: &Url) {
+        self.reexports.remove(uri);
+        for name in self.file_exports.remove(uri).unwrap_or_default() {
+            if let Some(entries) = self.exports.get_mut(&name) {
+                entries.retain(|(file, _)| file != uri);
+                if entries.is_empty() {
+                    self.exports.remove(&name);
+                }
+            }
+        }
+    }
+
+    /// Names a module exports, including those it re-exports from others
+    /// (`export *` passes on everything but `default`), sorted
+    pub fn module_export_names(&self, module: &Url) -> Vec<String> {
+        let mut names = HashSet::new();
+        self.collect_export_names(module, &mut HashSet::new(), &mut names);
+        let mut names: Vec<String> = names.into_iter().collect();
+        names.sort();
+        names
+    }
+
+    fn collect_export_names<'a>(
+        &'a self,
+        module: &'a Url,
+        visited: &mut HashSet<&'a Url>,
+        names: &mut HashSet<String>,
+    ) {
+        // Re-export cycles between barrels end here
+        if !visited.insert(module) {
+            return;
+        }
+        let is_entry = visited.len() == 1;
+        for name in self.file_exports.get(module).into_iter().flatten() {
+            if is_entry || name != "default" {
+                names.insert(name.clone());
+            }
+        }
+        for reexport in self.reexports.get(module).into_iter().flatten() {
+            match &reexport.alias {
+                Some(alias) => {
+                    names.insert(alias.clone());
+                }
+                None => self.collect_export_names(&reexport.target, visited, names),
+            }
+        }
+    }
+
+    /// The declarations `name` refers to when imported from `module`,
+    /// following `export *` re-exports through barrel files
+    pub fn resolve_module_export(&self, module: &Url, name: &str) -> Vec<(Url, SymbolId)> {
+        let mut found = Vec::new();
+        self.collect_module_export(module, name, &mut HashSet::new(), &mut found);
+        found.sort_by(|a, b| a.0.as_str().cmp(b.0.as_str()).then(a.1.0.cmp(&b.1.0)));
+        found
+    }
+
+    fn collect_module_export<'a>(
+        &'a self,
+        module: &'a Url,
+        name: &str,
+        visited: &mut HashSet<&'a Url>,
+        found: &mut Vec<(Url, SymbolId)>,
+    ) {
+        if !visited.insert(module) {
+            return;
+        }
+        let direct: Vec<_> = self
+            .exports
+            .get(name)
+            .into_iter()
+            .flatten()
+            .filter(|(file, _)| file == module)
+            .cloned()
+            .collect();
+        if !direct.is_empty() {
+            // A local export shadows anything re-exported under the same name
+            found.extend(direct);
+            return;
+        }
+        if name == "default" && visited.len() > 1 {
+            return;
+        }
+        for reexport in self.reexports.get(module).into_iter().flatten() {
+            if reexport.alias.is_none() {
+                self.collect_module_export(&reexport.target, name, visited, found);
+            }
+        }
+    }
+
+    /// Files and symbols exporting `name`, ordered by file
+    pub fn exported_symbols(&self, name: &str) -> Vec<(Url, SymbolId)> {
+        let mut symbols: Vec<_> = self
+            .exports
+            .get(name)
+            .map(|entries| entries.iter().cloned().collect())
+            .unwrap_or_default();
+        symbols.sort_by(|a, b| a.0.as_str().cmp(b.0.as_str()).then(a.1.0.cmp(&b.1.0)));
+        symbols
+    }
 }
 
 /// Find all tsconfig.json files in a directory (recursively)
@@ -314,6 +525,40 @@ mod tests {
         assert!(project_count >= 1);
     }
 
+    #[test]
+    fn test_route_files_to_most_specific_tsconfig() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        let core = root.join("packages").join("core");
+        fs::create_dir_all(core.join("src")).unwrap();
+        fs::create_dir_all(core.join("scripts")).unwrap();
+        fs::write(root.join("tsconfig.json"), r#"{"compilerOptions": {}}"#).unwrap();
+        fs::write(core.join("tsconfig.json"), r#"{"include": ["src"]}"#).unwrap();
+        fs::write(core.join("src").join("foo.ts"), "export const foo = 1;").unwrap();
+        fs::write(core.join("scripts").join("build.ts"), "").unwrap();
+
+        let mut workspace = Workspace::new(root.to_path_buf());
+        let root_id = workspace.add_config(root.join("tsconfig.json")).unwrap();
+        let core_id = workspace.add_config(core.join("tsconfig.json")).unwrap();
+        assert_ne!(root_id, core_id);
+
+        let foo = Url::from_file_path(core.join("src").join("foo.ts")).unwrap();
+        assert_eq!(workspace.project_id_for_file(&foo), Some(core_id));
+        assert_eq!(
+            workspace.project(core_id).unwrap().config_path,
+            Some(core.join("tsconfig.json"))
+        );
+
+        // Outside the package's include patterns the root project takes over
+        let build = Url::from_file_path(core.join("scripts").join("build.ts")).unwrap();
+        assert_eq!(workspace.project_id_for_file(&build), Some(root_id));
+
+        assert!(workspace.add_config(root.join("missing.json")).is_err());
+        workspace.remove_project(&core.join("tsconfig.json"));
+        assert!(workspace.project(core_id).is_none());
+        assert_eq!(workspace.project_id_for_file(&foo), Some(root_id));
+    }
+
     #[test]
     fn test_find_tsconfig_files_empty() {
         let temp_dir = TempDir::new().unwrap();
@@ -378,6 +623,144 @@ mod tests {
         assert!(configs.is_empty());
     }
 
+    fn index(workspace: &mut Workspace, path: &str, source: &str) -> Url {
+        let mut parser = tree_sitter::Parser::new();
+        parser
+            .set_language(&tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into())
+            .unwrap();
+        let tree = parser.parse(source, None).unwrap();
+        let symbol_table = crate::analysis::binder::bind_document(&tree, source);
+
+        let uri = Url::parse(&format!("file://{}", path)).unwrap();
+        workspace.index_file_exports(&uri, &tree, source, &symbol_table);
+        uri
+    }
+
+    #[test]
+    fn test_exported_symbols_index() {
+        let mut workspace = Workspace::new(PathBuf::from("/test"));
+        let a = index(
+            &mut workspace,
+            "/test/a.ts",
+            "export function helper() {}\nexport const shared = 1;",
+        );
+        let b = index(
+            &mut workspace,
+            "/test/b.ts",
+            "const shared = 2;\nexport { shared };\nfunction internal() {}",
+        );
+
+        let files: Vec<_> = workspace
+            .exported_symbols("shared")
+            .into_iter()
+            .map(|(uri, _)| uri)
+            .collect();
+        assert_eq!(files, vec![a.clone(), b.clone()]);
+        assert_eq!(workspace.exported_symbols("helper").len(), 1);
+        assert!(workspace.exported_symbols("internal").is_empty());
+
+        workspace.remove_file_exports(&a);
+        let files: Vec<_> = workspace
+            .exported_symbols("shared")
+            .into_iter()
+            .map(|(uri, _)| uri)
+            .collect();
+        assert_eq!(files, vec![b]);
+        assert!(workspace.exported_symbols("helper").is_empty());
+    }
+
+    #[test]
+    fn test_reexports_through_barrels() {
+        let mut workspace = Workspace::new(PathBuf::from("/test"));
+        let foo = index(
+            &mut workspace,
+            "/test/foo.ts",
+            "export class Foo {}\nexport default 1;",
+        );
+        let bar = index(&mut workspace, "/test/bar.ts", "export const bar = 1;");
+        let barrel = index(&mut workspace, "/test/index.ts", "export * from './foo';");
+        let top = index(&mut workspace, "/test/top.ts", "export * from './index';");
+
+        workspace.index_file_reexports(
+            &barrel,
+            vec![
+                ReExport {
+                    target: foo.clone(),
+                    alias: None,
+                },
+                ReExport {
+                    target: bar.clone(),
+                    alias: Some("utils".to_string()),
+                },
+            ],
+        );
+        // A cycle back into the barrel doesn't loop forever
+        workspace.index_file_reexports(
+            &top,
+            vec![ReExport {
+                target: barrel.clone(),
+                alias: None,
+            }],
+        );
+        workspace.index_file_reexports(
+            &foo,
+            vec![ReExport {
+                target: top.clone(),
+                alias: None,
+            }],
+        );
+
+        let found = workspace.resolve_module_export(&top, "Foo");
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].0, foo);
+        assert!(
+            workspace
+                .resolve_module_export(&barrel, "default")
+                .is_empty()
+        );
+        assert_eq!(
+            workspace.module_export_names(&barrel),
+            vec!["Foo".to_string(), "utils".to_string()]
+        );
+
+        workspace.remove_file_exports(&barrel);
+        assert!(workspace.resolve_module_export(&top, "Foo").is_empty());
+    }
+
+    #[test]
+    fn test_reindexing_replaces_exports() {
+        let mut workspace = Workspace::new(PathBuf::from("/test"));
+        index(&mut workspace, "/test/a.ts", "export function before() {}");
+        index(&mut workspace, "/test/a.ts", "export function after() {}");
+
+        assert!(workspace.exported_symbols("before").is_empty());
+        assert_eq!(workspace.exported_symbols("after").len(), 1);
+    }
+
+    #[test]
+    fn test_project_for_file_or_insert() {
+        let mut workspace = Workspace::new(PathBuf::from("/test"));
+        workspace.add_project(
+            PathBuf::from("/test/project/tsconfig.json"),
+            Project::new(PathBuf::from("/test/project")),
+        );
+
+        let root = &workspace
+            .project_for_file_or_insert(Path::new("/test/project/src/a.ts"))
+            .root;
+        assert_eq!(root, &PathBuf::from("/test/project"));
+
+        let root = &workspace
+            .project_for_file_or_insert(Path::new("/elsewhere/b.ts"))
+            .root;
+        assert_eq!(root, &PathBuf::from("/elsewhere"));
+        assert_eq!(workspace.get_projects().count(), 2);
+
+        // A path no project root can contain, like that of an `untitled:` URI
+        workspace.project_for_file_or_insert(Path::new(""));
+        assert_eq!(workspace.get_projects().count(), 3);
+    }
+
     #[test]
     fn test_get_project_nonexistent() {
         let workspace = Workspace::new(PathBuf::from("/test"));
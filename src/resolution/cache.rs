@@ -0,0 +1,120 @@
+//! Cache of module resolution results
+//! Avoids hitting the file system for every import on every analysis pass
+
+#![allow(dead_code)]
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use super::ResolvedModule;
+
+/// Resolution results keyed by the importing directory and the specifier
+#[derive(Debug, Default)]
+pub struct ResolutionCache {
+    entries: HashMap<(PathBuf, String), Option<ResolvedModule>>,
+    hits: usize,
+    misses: usize,
+}
+
+impl ResolutionCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Look up a cached result, counting the hit or miss.
+    /// The outer `None` means the specifier has not been resolved yet.
+    pub fn get(&mut self, from_dir: &Path, specifier: &str) -> Option<Option<ResolvedModule>> {
+        let cached = self
+            .entries
+            .get(&(from_dir.to_path_buf(), specifier.to_string()))
+            .cloned();
+        if cached.is_some() {
+            self.hits += 1;
+        } else {
+            self.misses += 1;
+        }
+        cached
+    }
+
+    /// Record the result of resolving `specifier` from `from_dir`
+    pub fn insert(&mut self, from_dir: &Path, specifier: &str, result: Option<ResolvedModule>) {
+        self.entries
+            .insert((from_dir.to_path_buf(), specifier.to_string()), result);
+    }
+
+    /// Drop the results a change to the file at `path` may affect: those
+    /// resolved from its directory or below, those resolved to the file
+    /// itself, and failed resolutions the file may now satisfy
+    pub fn invalidate(&mut self, path: &Path) {
+        let dir = path.parent().unwrap_or(path);
+        self.entries.retain(|(from_dir, _), result| match result {
+            Some(resolved) => !from_dir.starts_with(dir) && resolved.path != path,
+            None => false,
+        });
+    }
+
+    /// Forget every cached result
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// `(hits, misses, size)` for diagnostic logging
+    pub fn stats(&self) -> (usize, usize, usize) {
+        (self.hits, self.misses, self.entries.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn resolved(path: &str, specifier: &str) -> Option<ResolvedModule> {
+        Some(ResolvedModule {
+            path: PathBuf::from(path),
+            is_external: false,
+            specifier: specifier.to_string(),
+            source_remapped: false,
+        })
+    }
+
+    #[test]
+    fn test_cache_hits_and_misses() {
+        let mut cache = ResolutionCache::new();
+        let dir = Path::new("/p/src");
+
+        assert!(cache.get(dir, "./a").is_none());
+        cache.insert(dir, "./a", resolved("/p/src/a.ts", "./a"));
+        cache.insert(dir, "./missing", None);
+
+        let hit = cache.get(dir, "./a").unwrap().unwrap();
+        assert_eq!(hit.path, PathBuf::from("/p/src/a.ts"));
+        assert!(matches!(cache.get(dir, "./missing"), Some(None)));
+        assert_eq!(cache.stats(), (2, 1, 2));
+    }
+
+    #[test]
+    fn test_cache_invalidate() {
+        let mut cache = ResolutionCache::new();
+        cache.insert(Path::new("/p/src"), "./a", resolved("/p/src/a.ts", "./a"));
+        cache.insert(
+            Path::new("/p/src/deep"),
+            "../a",
+            resolved("/p/src/a.ts", "../a"),
+        );
+        cache.insert(
+            Path::new("/p/lib"),
+            "../src/b",
+            resolved("/p/src/b.ts", "../src/b"),
+        );
+        cache.insert(Path::new("/p/lib"), "./c", resolved("/p/lib/c.ts", "./c"));
+        cache.insert(Path::new("/p/lib"), "./d", None);
+
+        cache.invalidate(Path::new("/p/src/b.ts"));
+
+        assert!(cache.get(Path::new("/p/src"), "./a").is_none());
+        assert!(cache.get(Path::new("/p/src/deep"), "../a").is_none());
+        assert!(cache.get(Path::new("/p/lib"), "../src/b").is_none());
+        assert!(cache.get(Path::new("/p/lib"), "./d").is_none());
+        assert!(cache.get(Path::new("/p/lib"), "./c").is_some());
+    }
+}
@@ -1,3 +1,4 @@
+pub mod cache;
 pub mod node_modules;
 pub mod resolver;
 pub mod tsconfig;
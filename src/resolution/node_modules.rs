@@ -19,12 +19,27 @@ pub fn resolve_node_module(specifier: &str, from_dir: &Path) -> Option<PathBuf>
         if node_modules.is_dir() {
             let package_dir = node_modules.join(&package_name);
 
-            if package_dir.is_dir() {
-                // Try to resolve within the package
-                if let Some(resolved) = resolve_package_entry(&package_dir, subpath.as_deref()) {
-                    return Some(resolved);
+            // Try to resolve within the package
+            let resolved = if package_dir.is_dir() {
+                resolve_package_entry(&package_dir, subpath.as_deref())
+            } else {
+                None
+            };
+
+            // Prefer typings from `@types/<pkg>` over a plain JavaScript entry
+            if !resolved.as_deref().is_some_and(is_typescript_file) {
+                let typings = types_package_name(&package_name)
+                    .map(|name| node_modules.join(name))
+                    .filter(|dir| dir.is_dir())
+                    .and_then(|dir| resolve_package_entry(&dir, subpath.as_deref()));
+                if typings.is_some() {
+                    return typings;
                 }
             }
+
+            if resolved.is_some() {
+                return resolved;
+            }
         }
 
         // Move up to parent directory
@@ -62,10 +77,34 @@ fn parse_package_specifier(specifier: &str) -> (String, Option<String>) {
     (package_name, subpath)
 }
 
+/// Name of the DefinitelyTyped package for a package:
+/// `lodash` -> `@types/lodash`, `@scope/pkg` -> `@types/scope__pkg`
+fn types_package_name(package_name: &str) -> Option<String> {
+    if package_name.starts_with("@types/") {
+        return None;
+    }
+    match package_name.strip_prefix('@') {
+        Some(scoped) => Some(format!("@types/{}", scoped.replacen('/', "__", 1))),
+        None => Some(format!("@types/{}", package_name)),
+    }
+}
+
+/// Whether a resolved file carries TypeScript types (`.ts`, `.d.ts`, ...)
+fn is_typescript_file(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then(|e| e.to_str()),
+        Some("ts" | "tsx" | "mts" | "cts")
+    )
+}
+
 /// Resolve a package entry point
 fn resolve_package_entry(package_dir: &Path, subpath: Option<&str>) -> Option<PathBuf> {
     if let Some(subpath) = subpath {
-        // Resolve subpath within package
+        // Resolve subpath through the exports map, or within the package
+        let exported = format!("./{}", subpath);
+        if let Some(resolved) = resolve_package_exports(package_dir, &exported, TYPE_CONDITIONS) {
+            return Some(resolved);
+        }
         let target = package_dir.join(subpath);
         return try_resolve_file(&target);
     }
@@ -86,16 +125,19 @@ fn resolve_package_entry(package_dir: &Path, subpath: Option<&str>) -> Option<Pa
                 if let Some(types) = json.get("types").or_else(|| json.get("typings")) {
                     if let Some(types_str) = types.as_str() {
                         let types_path = package_dir.join(types_str);
-                        if types_path.is_file() {
-                            return Some(types_path);
+                        if let Some(resolved) = try_resolve_file(&types_path) {
+                            return Some(resolved);
                         }
                     }
                 }
 
-                // Try "main" field
+                // Try "main" field, preferring a declaration file next to it
                 if let Some(main) = json.get("main") {
                     if let Some(main_str) = main.as_str() {
                         let main_path = package_dir.join(main_str);
+                        if let Some(declaration) = declaration_for(&main_path) {
+                            return Some(declaration);
+                        }
                         if let Some(resolved) = try_resolve_file(&main_path) {
                             return Some(resolved);
                         }
@@ -105,60 +147,119 @@ fn resolve_package_entry(package_dir: &Path, subpath: Option<&str>) -> Option<Pa
         }
     }
 
-    // Fallback to index file
+    // Fallback to index file, declarations first
+    for index in ["index.d.ts", "index.ts", "index.tsx"] {
+        let path = package_dir.join(index);
+        if path.is_file() {
+            return Some(path);
+        }
+    }
     try_resolve_file(&package_dir.join("index"))
 }
 
+/// Find the declaration file that accompanies a JavaScript file,
+/// e.g. `dist/index.d.ts` for `dist/index.js`
+fn declaration_for(path: &Path) -> Option<PathBuf> {
+    let extension = match path.extension().and_then(|e| e.to_str()) {
+        Some("js" | "jsx") => "d.ts",
+        Some("mjs") => "d.mts",
+        Some("cjs") => "d.cts",
+        _ => return None,
+    };
+    let declaration = path.with_extension(extension);
+    declaration.is_file().then_some(declaration)
+}
+
+/// Export conditions matched when looking for types, in order of preference
+const TYPE_CONDITIONS: &[&str] = &["types", "import", "require", "default"];
+
+/// Resolve `subpath` (`.` or `./utils`) through the `exports` map of the
+/// package in `package_dir`, taking the first of `conditions` each
+/// conditional entry offers. `None` if the package has no `exports`.
+pub fn resolve_package_exports(
+    package_dir: &Path,
+    subpath: &str,
+    conditions: &[&str],
+) -> Option<PathBuf> {
+    let content = std::fs::read_to_string(package_dir.join("package.json")).ok()?;
+    let json = serde_json::from_str::<serde_json::Value>(&content).ok()?;
+    resolve_exports_with(json.get("exports")?, package_dir, subpath, conditions)
+}
+
 /// Resolve package.json exports field
 fn resolve_exports(
     exports: &serde_json::Value,
     package_dir: &Path,
     subpath: &str,
 ) -> Option<PathBuf> {
-    match exports {
-        serde_json::Value::String(s) => {
-            if subpath == "." {
-                let path = package_dir.join(s.trim_start_matches("./"));
-                return try_resolve_file(&path);
-            }
-        }
-        serde_json::Value::Object(map) => {
-            // Try to find the subpath
-            if let Some(entry) = map.get(subpath) {
-                return resolve_export_entry(entry, package_dir);
-            }
+    resolve_exports_with(exports, package_dir, subpath, TYPE_CONDITIONS)
+}
 
-            // Try "." for default export
-            if subpath == "." {
-                if let Some(entry) = map.get(".") {
-                    return resolve_export_entry(entry, package_dir);
-                }
-            }
-        }
-        _ => {}
-    }
-    None
+fn resolve_exports_with(
+    exports: &serde_json::Value,
+    package_dir: &Path,
+    subpath: &str,
+    conditions: &[&str],
+) -> Option<PathBuf> {
+    let subpath_map = exports
+        .as_object()
+        .filter(|map| map.keys().any(|key| key.starts_with('.')));
+
+    let Some(map) = subpath_map else {
+        // A string, array or conditions object is sugar for `{ ".": ... }`
+        return (subpath == ".")
+            .then(|| resolve_export_target(exports, package_dir, conditions, None))
+            .flatten();
+    };
+
+    if let Some(entry) = map.get(subpath) {
+        return resolve_export_target(entry, package_dir, conditions, None);
+    }
+
+    // Subpath patterns like `"./features/*": "./dist/features/*.js"`; the
+    // pattern with the longest prefix wins
+    let (_, entry, matched) = map
+        .iter()
+        .filter_map(|(key, entry)| {
+            let (prefix, suffix) = key.split_once('*')?;
+            let matched = subpath.strip_prefix(prefix)?.strip_suffix(suffix)?;
+            Some((prefix.len(), entry, matched))
+        })
+        .max_by_key(|(len, _, _)| *len)?;
+    resolve_export_target(entry, package_dir, conditions, Some(matched))
 }
 
 /// Resolve a single export entry
 fn resolve_export_entry(entry: &serde_json::Value, package_dir: &Path) -> Option<PathBuf> {
-    match entry {
+    resolve_export_target(entry, package_dir, TYPE_CONDITIONS, None)
+}
+
+/// Resolve an export target: a path (with `*` replaced by the pattern
+/// match), an array of fallbacks, or an object keyed by condition
+fn resolve_export_target(
+    target: &serde_json::Value,
+    package_dir: &Path,
+    conditions: &[&str],
+    matched: Option<&str>,
+) -> Option<PathBuf> {
+    match target {
         serde_json::Value::String(s) => {
+            let s = match matched {
+                Some(matched) => s.replace('*', matched),
+                None => s.clone(),
+            };
             let path = package_dir.join(s.trim_start_matches("./"));
-            try_resolve_file(&path)
-        }
-        serde_json::Value::Object(map) => {
-            // Try conditions in order of preference
-            let conditions = ["types", "import", "require", "default"];
-            for condition in conditions {
-                if let Some(value) = map.get(condition) {
-                    if let Some(resolved) = resolve_export_entry(value, package_dir) {
-                        return Some(resolved);
-                    }
-                }
-            }
-            None
+            declaration_for(&path).or_else(|| try_resolve_file(&path))
         }
+        serde_json::Value::Array(fallbacks) => fallbacks
+            .iter()
+            .find_map(|target| resolve_export_target(target, package_dir, conditions, matched)),
+        serde_json::Value::Object(map) => conditions
+            .iter()
+            .chain(std::iter::once(&"default"))
+            .filter_map(|condition| map.get(*condition))
+            .find_map(|target| resolve_export_target(target, package_dir, conditions, matched)),
+        // `null` blocks the subpath
         _ => None,
     }
 }
@@ -454,6 +555,78 @@ mod tests {
         assert!(result.unwrap().to_string_lossy().contains("index.d.ts"));
     }
 
+    #[test]
+    fn test_resolve_package_exports_subpaths() {
+        let temp_dir = TempDir::new().unwrap();
+        let dist = temp_dir.path().join("dist");
+        fs::create_dir_all(dist.join("features")).unwrap();
+        for file in [
+            "index.mjs",
+            "index.cjs",
+            "utils.js",
+            "utils.d.ts",
+            "features/a.js",
+        ] {
+            fs::write(dist.join(file), "").unwrap();
+        }
+        fs::write(
+            temp_dir.path().join("package.json"),
+            r#"{
+                "exports": {
+                    ".": { "import": "./dist/index.mjs", "require": "./dist/index.cjs" },
+                    "./utils": "./dist/utils.js",
+                    "./features/*": ["./missing/*.js", "./dist/features/*.js"],
+                    "./internal": null
+                }
+            }"#,
+        )
+        .unwrap();
+        let dir = temp_dir.path();
+
+        let resolve = |subpath, conditions: &[&str]| {
+            resolve_package_exports(dir, subpath, conditions).map(|path| {
+                path.strip_prefix(dir)
+                    .unwrap()
+                    .to_string_lossy()
+                    .into_owned()
+            })
+        };
+        assert_eq!(resolve(".", &["import"]).as_deref(), Some("dist/index.mjs"));
+        assert_eq!(
+            resolve(".", &["require"]).as_deref(),
+            Some("dist/index.cjs")
+        );
+        // A declaration next to the JavaScript target is preferred
+        assert_eq!(
+            resolve("./utils", &["import"]).as_deref(),
+            Some("dist/utils.d.ts")
+        );
+        assert_eq!(
+            resolve("./features/a", &["import"]).as_deref(),
+            Some("dist/features/a.js")
+        );
+        assert_eq!(resolve("./internal", &["import"]), None);
+        assert_eq!(resolve("./other", &["import"]), None);
+
+        let result = resolve_package_entry(dir, Some("utils")).unwrap();
+        assert!(result.ends_with("dist/utils.d.ts"));
+    }
+
+    #[test]
+    fn test_resolve_package_exports_absent() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("package.json"),
+            r#"{"main": "./main.js"}"#,
+        )
+        .unwrap();
+        fs::write(temp_dir.path().join("main.js"), "").unwrap();
+
+        assert!(resolve_package_exports(temp_dir.path(), ".", TYPE_CONDITIONS).is_none());
+        let result = resolve_package_entry(temp_dir.path(), None).unwrap();
+        assert!(result.ends_with("main.js"));
+    }
+
     #[test]
     fn test_resolve_export_entry_string() {
         let temp_dir = TempDir::new().unwrap();
@@ -499,4 +672,80 @@ mod tests {
         let result = resolve_package_entry(temp_dir.path(), Some("utils"));
         assert!(result.is_some());
     }
+
+    #[test]
+    fn test_resolve_node_module_types_before_main() {
+        let temp_dir = TempDir::new().unwrap();
+
+        let pkg_dir = temp_dir.path().join("node_modules").join("fake-pkg");
+        fs::create_dir_all(pkg_dir.join("dist")).unwrap();
+        fs::write(
+            pkg_dir.join("package.json"),
+            r#"{"main": "./dist/index.js", "types": "./types/index"}"#,
+        )
+        .unwrap();
+        fs::write(pkg_dir.join("dist").join("index.js"), "module.exports = {}").unwrap();
+        fs::create_dir(pkg_dir.join("types")).unwrap();
+        fs::write(pkg_dir.join("types").join("index.d.ts"), "export {}").unwrap();
+
+        let result = resolve_node_module("fake-pkg", temp_dir.path()).unwrap();
+        assert!(result.ends_with("types/index.d.ts"));
+    }
+
+    #[test]
+    fn test_resolve_node_module_declaration_next_to_main() {
+        let temp_dir = TempDir::new().unwrap();
+
+        let pkg_dir = temp_dir.path().join("node_modules").join("fake-pkg");
+        fs::create_dir_all(pkg_dir.join("lib")).unwrap();
+        fs::write(pkg_dir.join("package.json"), r#"{"main": "lib/main.js"}"#).unwrap();
+        fs::write(pkg_dir.join("lib").join("main.js"), "module.exports = {}").unwrap();
+        fs::write(pkg_dir.join("lib").join("main.d.ts"), "export {}").unwrap();
+
+        let result = resolve_node_module("fake-pkg", temp_dir.path()).unwrap();
+        assert!(result.ends_with("lib/main.d.ts"));
+    }
+
+    #[test]
+    fn test_resolve_node_module_index_declaration_fallback() {
+        let temp_dir = TempDir::new().unwrap();
+
+        let pkg_dir = temp_dir.path().join("node_modules").join("fake-pkg");
+        fs::create_dir_all(&pkg_dir).unwrap();
+        fs::write(pkg_dir.join("index.ts"), "export {}").unwrap();
+        fs::write(pkg_dir.join("index.d.ts"), "export {}").unwrap();
+
+        let result = resolve_node_module("fake-pkg", temp_dir.path()).unwrap();
+        assert!(result.ends_with("index.d.ts"));
+    }
+
+    #[test]
+    fn test_resolve_node_module_definitely_typed() {
+        let temp_dir = TempDir::new().unwrap();
+        let node_modules = temp_dir.path().join("node_modules");
+
+        let pkg_dir = node_modules.join("lodash");
+        fs::create_dir_all(&pkg_dir).unwrap();
+        fs::write(pkg_dir.join("index.js"), "module.exports = {}").unwrap();
+
+        let types_dir = node_modules.join("@types").join("lodash");
+        fs::create_dir_all(&types_dir).unwrap();
+        fs::write(types_dir.join("index.d.ts"), "export {}").unwrap();
+
+        let result = resolve_node_module("lodash", temp_dir.path()).unwrap();
+        assert!(result.ends_with("@types/lodash/index.d.ts"));
+    }
+
+    #[test]
+    fn test_types_package_name() {
+        assert_eq!(
+            types_package_name("lodash"),
+            Some("@types/lodash".to_string())
+        );
+        assert_eq!(
+            types_package_name("@babel/core"),
+            Some("@types/babel__core".to_string())
+        );
+        assert_eq!(types_package_name("@types/node"), None);
+    }
 }
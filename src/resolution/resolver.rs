@@ -4,7 +4,9 @@
 #![allow(dead_code)]
 
 use std::path::{Path, PathBuf};
+use std::sync::Mutex;
 
+use super::cache::ResolutionCache;
 use super::node_modules::resolve_node_module;
 use super::tsconfig::TsConfig;
 
@@ -17,12 +19,34 @@ pub struct ResolvedModule {
     pub is_external: bool,
     /// The original module specifier
     pub specifier: String,
+    /// Whether a `.js`-style specifier was mapped to the TypeScript source
+    /// it is compiled from, e.g. `./helper.js` to `helper.ts`
+    pub source_remapped: bool,
+}
+
+impl ResolvedModule {
+    /// A module resolved to a file inside the project
+    fn local(path: PathBuf, specifier: &str) -> Self {
+        let source_remapped = Path::new(specifier)
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .and_then(source_extensions)
+            .is_some()
+            && path.extension() != Path::new(specifier).extension();
+        Self {
+            path,
+            is_external: false,
+            specifier: specifier.to_string(),
+            source_remapped,
+        }
+    }
 }
 
 /// Module resolution mode
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum ModuleResolution {
     /// Node.js CommonJS resolution
+    #[default]
     Node,
     /// Node.js ESM resolution (Node16/NodeNext)
     NodeNext,
@@ -32,12 +56,6 @@ pub enum ModuleResolution {
     Classic,
 }
 
-impl Default for ModuleResolution {
-    fn default() -> Self {
-        Self::Node
-    }
-}
-
 /// Module resolver
 pub struct ModuleResolver {
     /// Resolution mode
@@ -48,6 +66,8 @@ pub struct ModuleResolver {
     pub path_mappings: Vec<(String, Vec<String>)>,
     /// Base URL from tsconfig
     pub base_url: Option<PathBuf>,
+    /// Results of earlier resolutions
+    cache: Mutex<ResolutionCache>,
 }
 
 impl ModuleResolver {
@@ -57,6 +77,7 @@ impl ModuleResolver {
             base_dir,
             path_mappings: Vec::new(),
             base_url: None,
+            cache: Mutex::new(ResolutionCache::new()),
         }
     }
 
@@ -95,6 +116,26 @@ impl ModuleResolver {
     pub fn resolve(&self, specifier: &str, from_file: &Path) -> Option<ResolvedModule> {
         let from_dir = from_file.parent().unwrap_or(Path::new("."));
 
+        let mut cache = self.cache.lock().unwrap();
+        if let Some(cached) = cache.get(from_dir, specifier) {
+            return cached;
+        }
+        let resolved = self.resolve_uncached(specifier, from_dir);
+        cache.insert(from_dir, specifier, resolved.clone());
+        resolved
+    }
+
+    /// Forget cached resolutions that a change to the file at `path` may affect
+    pub fn invalidate(&self, path: &Path) {
+        self.cache.lock().unwrap().invalidate(path);
+    }
+
+    /// `(hits, misses, size)` of the resolution cache
+    pub fn cache_stats(&self) -> (usize, usize, usize) {
+        self.cache.lock().unwrap().stats()
+    }
+
+    fn resolve_uncached(&self, specifier: &str, from_dir: &Path) -> Option<ResolvedModule> {
         // Try path mappings first
         if let Some(resolved) = self.resolve_with_path_mappings(specifier) {
             return Some(resolved);
@@ -116,26 +157,29 @@ impl ModuleResolver {
         self.resolve_node_module(specifier, from_dir)
     }
 
-    /// Resolve using path mappings
+    /// Resolve using path mappings.
+    /// Only the most specific matching pattern is used: an exact pattern wins,
+    /// otherwise the wildcard pattern with the longest prefix before `*`.
     fn resolve_with_path_mappings(&self, specifier: &str) -> Option<ResolvedModule> {
-        for (pattern, targets) in &self.path_mappings {
-            if let Some(matched) = match_path_pattern(pattern, specifier) {
-                for target in targets {
-                    let resolved_target = target.replace('*', &matched);
-                    let base = self.base_url.as_ref().unwrap_or(&self.base_dir);
-                    let full_path = base.join(&resolved_target);
-
-                    if let Some(resolved) = self.try_resolve_file(&full_path) {
-                        return Some(ResolvedModule {
-                            path: resolved,
-                            is_external: false,
-                            specifier: specifier.to_string(),
-                        });
-                    }
-                }
-            }
-        }
-        None
+        let (_, matched, targets) = self
+            .path_mappings
+            .iter()
+            .filter_map(|(pattern, targets)| {
+                let matched = match_path_pattern(pattern, specifier)?;
+                let specificity = match pattern.find('*') {
+                    Some(star) => (false, star, pattern.len()),
+                    None => (true, pattern.len(), pattern.len()),
+                };
+                Some((specificity, matched, targets))
+            })
+            .max_by_key(|(specificity, _, _)| *specificity)?;
+
+        let base = self.base_url.as_ref().unwrap_or(&self.base_dir);
+        targets.iter().find_map(|target| {
+            let resolved_target = target.replace('*', &matched);
+            self.try_resolve_file(&base.join(&resolved_target))
+                .map(|path| ResolvedModule::local(path, specifier))
+        })
     }
 
     /// Resolve a relative import
@@ -143,11 +187,7 @@ impl ModuleResolver {
         let target_path = from_dir.join(specifier);
 
         self.try_resolve_file(&target_path)
-            .map(|path| ResolvedModule {
-                path,
-                is_external: false,
-                specifier: specifier.to_string(),
-            })
+            .map(|path| ResolvedModule::local(path, specifier))
     }
 
     /// Resolve from base URL
@@ -155,11 +195,7 @@ impl ModuleResolver {
         let target_path = base_url.join(specifier);
 
         self.try_resolve_file(&target_path)
-            .map(|path| ResolvedModule {
-                path,
-                is_external: false,
-                specifier: specifier.to_string(),
-            })
+            .map(|path| ResolvedModule::local(path, specifier))
     }
 
     /// Resolve from node_modules
@@ -168,11 +204,27 @@ impl ModuleResolver {
             path,
             is_external: true,
             specifier: specifier.to_string(),
+            source_remapped: false,
         })
     }
 
     /// Try to resolve a file path, handling extensions
     fn try_resolve_file(&self, path: &Path) -> Option<PathBuf> {
+        // `./util.js` refers to the `util.ts` it is compiled from, even when
+        // a previous build left `util.js` next to it
+        if let Some(sources) = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .and_then(source_extensions)
+        {
+            for ext in sources {
+                let with_ext = path.with_extension(ext);
+                if with_ext.is_file() {
+                    return Some(with_ext);
+                }
+            }
+        }
+
         // If the path already has an extension and exists, use it
         if path.exists() && path.is_file() {
             return Some(path.to_path_buf());
@@ -183,9 +235,12 @@ impl ModuleResolver {
             ".ts", ".tsx", ".d.ts", ".js", ".jsx", ".mts", ".mjs", ".cts", ".cjs",
         ];
 
+        // Extensions are appended, since `user.service` names `user.service.ts`
         for ext in extensions {
-            let with_ext = path.with_extension(ext.trim_start_matches('.'));
-            if with_ext.exists() && with_ext.is_file() {
+            let mut with_ext = path.as_os_str().to_owned();
+            with_ext.push(ext);
+            let with_ext = PathBuf::from(with_ext);
+            if with_ext.is_file() {
                 return Some(with_ext);
             }
         }
@@ -213,6 +268,17 @@ impl ModuleResolver {
     }
 }
 
+/// TypeScript extensions a JavaScript output extension is compiled from
+fn source_extensions(ext: &str) -> Option<&'static [&'static str]> {
+    match ext {
+        "js" => Some(&["ts", "tsx", "d.ts"]),
+        "jsx" => Some(&["tsx"]),
+        "mjs" => Some(&["mts", "d.mts"]),
+        "cjs" => Some(&["cts", "d.cts"]),
+        _ => None,
+    }
+}
+
 /// Match a path pattern with a specifier
 /// Patterns can contain a single `*` wildcard
 fn match_path_pattern(pattern: &str, specifier: &str) -> Option<String> {
@@ -222,7 +288,10 @@ fn match_path_pattern(pattern: &str, specifier: &str) -> Option<String> {
             let prefix = parts[0];
             let suffix = parts[1];
 
-            if specifier.starts_with(prefix) && specifier.ends_with(suffix) {
+            if specifier.len() >= prefix.len() + suffix.len()
+                && specifier.starts_with(prefix)
+                && specifier.ends_with(suffix)
+            {
                 let matched_len = specifier.len() - prefix.len() - suffix.len();
                 let matched = &specifier[prefix.len()..prefix.len() + matched_len];
                 return Some(matched.to_string());
@@ -361,6 +430,140 @@ mod tests {
         assert_eq!(resolver.path_mappings.len(), 1);
     }
 
+    fn resolver_for_tsconfig(root: &Path, tsconfig: &str) -> ModuleResolver {
+        let path = root.join("tsconfig.json");
+        std::fs::write(&path, tsconfig).unwrap();
+        let tsconfig = TsConfig::load(&path).unwrap();
+        ModuleResolver::with_tsconfig(root.to_path_buf(), &tsconfig)
+    }
+
+    #[test]
+    fn test_resolve_paths_alias() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let src = temp_dir.path().join("src");
+        std::fs::create_dir(&src).unwrap();
+        std::fs::write(src.join("utils.ts"), "export {}").unwrap();
+
+        let resolver = resolver_for_tsconfig(
+            temp_dir.path(),
+            r#"{"compilerOptions": {"baseUrl": ".", "paths": {"@/*": ["src/*"]}}}"#,
+        );
+
+        let module = resolver.resolve("@/utils", &src.join("main.ts")).unwrap();
+        assert!(module.path.ends_with("src/utils.ts"));
+        assert!(!module.is_external);
+    }
+
+    #[test]
+    fn test_resolve_paths_longest_prefix_wins() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path();
+        std::fs::create_dir_all(root.join("src/components")).unwrap();
+        std::fs::create_dir_all(root.join("src/ui")).unwrap();
+        std::fs::write(root.join("src/components/Button.ts"), "export {}").unwrap();
+        std::fs::write(root.join("src/ui/Button.ts"), "export {}").unwrap();
+
+        let resolver = resolver_for_tsconfig(
+            root,
+            r#"{"compilerOptions": {"baseUrl": ".", "paths": {
+                "@/*": ["src/*"],
+                "@/components/*": ["src/ui/*"]
+            }}}"#,
+        );
+
+        let module = resolver
+            .resolve("@/components/Button", &root.join("src/main.ts"))
+            .unwrap();
+        assert!(module.path.ends_with("src/ui/Button.ts"));
+    }
+
+    #[test]
+    fn test_resolve_paths_tries_targets_in_order() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path();
+        std::fs::create_dir_all(root.join("generated")).unwrap();
+        std::fs::write(root.join("generated/schema.ts"), "export {}").unwrap();
+
+        let resolver = resolver_for_tsconfig(
+            root,
+            r#"{"compilerOptions": {"baseUrl": ".", "paths": {"~/*": ["src/*", "generated/*"]}}}"#,
+        );
+
+        let module = resolver.resolve("~/schema", &root.join("main.ts")).unwrap();
+        assert!(module.path.ends_with("generated/schema.ts"));
+    }
+
+    #[test]
+    fn test_resolve_paths_dotted_file_names() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path();
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::write(root.join("src/user.service.ts"), "export {}").unwrap();
+        std::fs::write(root.join("src/util.ts"), "export {}").unwrap();
+
+        let resolver = resolver_for_tsconfig(
+            root,
+            r#"{"compilerOptions": {"paths": {"@/*": ["src/*"]}}}"#,
+        );
+        let from = root.join("src/main.ts");
+
+        let module = resolver.resolve("@/user.service", &from).unwrap();
+        assert!(module.path.ends_with("src/user.service.ts"));
+        let module = resolver.resolve("./util.js", &from).unwrap();
+        assert!(module.path.ends_with("src/util.ts"));
+    }
+
+    #[test]
+    fn test_resolve_relative_js_to_ts_remap() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path();
+        std::fs::write(root.join("helper.ts"), "export {}").unwrap();
+        std::fs::write(root.join("helper.js"), "exports.x = 1").unwrap();
+        std::fs::write(root.join("view.tsx"), "export {}").unwrap();
+        std::fs::write(root.join("legacy.js"), "exports.x = 1").unwrap();
+
+        let resolver = ModuleResolver::new(root.to_path_buf());
+        let from = root.join("main.ts");
+
+        let module = resolver.resolve("./helper.js", &from).unwrap();
+        assert!(module.path.ends_with("helper.ts"));
+        assert!(module.source_remapped);
+
+        let module = resolver.resolve("./view.jsx", &from).unwrap();
+        assert!(module.path.ends_with("view.tsx"));
+        assert!(module.source_remapped);
+
+        let module = resolver.resolve("./helper", &from).unwrap();
+        assert!(module.path.ends_with("helper.ts"));
+        assert!(!module.source_remapped);
+
+        let module = resolver.resolve("./legacy.js", &from).unwrap();
+        assert!(module.path.ends_with("legacy.js"));
+        assert!(!module.source_remapped);
+    }
+
+    #[test]
+    fn test_resolve_caches_until_invalidated() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path();
+        let resolver = ModuleResolver::new(root.to_path_buf());
+        let from = root.join("main.ts");
+
+        assert!(resolver.resolve("./later", &from).is_none());
+        std::fs::write(root.join("later.ts"), "export {}").unwrap();
+        assert!(resolver.resolve("./later", &from).is_none());
+        assert_eq!(resolver.cache_stats(), (1, 1, 1));
+
+        resolver.invalidate(&root.join("later.ts"));
+        let module = resolver.resolve("./later", &from).unwrap();
+        assert!(module.path.ends_with("later.ts"));
+    }
+
+    #[test]
+    fn test_match_path_pattern_overlapping_affixes() {
+        assert_eq!(match_path_pattern("a*a", "a"), None);
+    }
+
     #[test]
     fn test_match_path_pattern_exact() {
         let result = match_path_pattern("lodash", "lodash");
@@ -391,6 +594,7 @@ mod tests {
             path: PathBuf::from("/test/utils.ts"),
             is_external: false,
             specifier: "./utils".to_string(),
+            source_remapped: false,
         };
 
         assert_eq!(module.path, PathBuf::from("/test/utils.ts"));
@@ -404,6 +608,7 @@ mod tests {
             path: PathBuf::from("/node_modules/lodash/index.js"),
             is_external: true,
             specifier: "lodash".to_string(),
+            source_remapped: false,
         };
 
         assert!(module.is_external);
@@ -427,6 +632,35 @@ mod tests {
         assert!(result.is_none());
     }
 
+    #[test]
+    fn test_resolve_bare_specifier_from_node_modules() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let pkg_dir = temp_dir
+            .path()
+            .join("node_modules")
+            .join("@acme")
+            .join("widgets");
+        std::fs::create_dir_all(&pkg_dir).unwrap();
+        std::fs::write(
+            pkg_dir.join("package.json"),
+            r#"{"types": "./widgets.d.ts", "main": "./widgets.js"}"#,
+        )
+        .unwrap();
+        std::fs::write(pkg_dir.join("widgets.d.ts"), "export {}").unwrap();
+        std::fs::write(pkg_dir.join("widgets.js"), "module.exports = {}").unwrap();
+
+        let src_dir = temp_dir.path().join("src");
+        std::fs::create_dir(&src_dir).unwrap();
+        let resolver = ModuleResolver::new(temp_dir.path().to_path_buf());
+
+        let module = resolver
+            .resolve("@acme/widgets", &src_dir.join("main.ts"))
+            .unwrap();
+        assert!(module.path.ends_with("@acme/widgets/widgets.d.ts"));
+        assert!(module.is_external);
+        assert_eq!(module.specifier, "@acme/widgets");
+    }
+
     #[test]
     fn test_resolve_with_base_url_not_found() {
         let mut resolver = ModuleResolver::new(PathBuf::from("/test"));
@@ -457,6 +691,7 @@ mod tests {
             path: PathBuf::from("/test/utils.ts"),
             is_external: false,
             specifier: "./utils".to_string(),
+            source_remapped: false,
         };
 
         let cloned = module.clone();
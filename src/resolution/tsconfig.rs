@@ -4,8 +4,8 @@
 #![allow(dead_code)]
 
 use serde::Deserialize;
-use std::collections::HashMap;
-use std::path::Path;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 
 /// Represents a tsconfig.json file
 #[derive(Debug, Clone, Deserialize, Default)]
@@ -60,6 +60,9 @@ pub struct CompilerOptions {
     pub lib: Option<Vec<String>>,
     pub types: Option<Vec<String>>,
     pub type_roots: Option<Vec<String>>,
+    /// Options without a field above, kept so they can be validated
+    #[serde(flatten)]
+    pub other: HashMap<String, serde_json::Value>,
 }
 
 /// Project reference
@@ -73,6 +76,19 @@ impl TsConfig {
     /// Load a tsconfig.json file
     #[allow(dead_code)] // Reserved for project system integration
     pub fn load(path: &Path) -> Result<Self, TsConfigError> {
+        Self::load_with_visited(path, &mut HashSet::new())
+    }
+
+    /// Load a tsconfig, failing if `extends` leads back to a file in `visited`
+    fn load_with_visited(
+        path: &Path,
+        visited: &mut HashSet<PathBuf>,
+    ) -> Result<Self, TsConfigError> {
+        let canonical = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        if !visited.insert(canonical) {
+            return Err(TsConfigError::CircularExtends(path.display().to_string()));
+        }
+
         let content =
             std::fs::read_to_string(path).map_err(|e| TsConfigError::IoError(e.to_string()))?;
 
@@ -95,7 +111,7 @@ impl TsConfig {
             };
 
             if extends_path.exists() {
-                let base_config = Self::load(&extends_path)?;
+                let base_config = Self::load_with_visited(&extends_path, visited)?;
                 return Ok(config.merge_with_base(base_config));
             }
         }
@@ -176,8 +192,354 @@ impl CompilerOptions {
             lib: self.lib.or(base.lib),
             types: self.types.or(base.types),
             type_roots: self.type_roots.or(base.type_roots),
+            other: {
+                let mut merged = base.other;
+                merged.extend(self.other);
+                merged
+            },
+        }
+    }
+}
+
+/// Valid values of the enumerated compiler options, lowercase
+const TARGETS: &[&str] = &[
+    "es3", "es5", "es6", "es2015", "es2016", "es2017", "es2018", "es2019", "es2020", "es2021",
+    "es2022", "es2023", "es2024", "esnext", "latest",
+];
+const MODULES: &[&str] = &[
+    "none", "commonjs", "amd", "umd", "system", "es6", "es2015", "es2020", "es2022", "esnext",
+    "node16", "node18", "nodenext", "preserve",
+];
+const JSX_MODES: &[&str] = &[
+    "preserve",
+    "react",
+    "react-jsx",
+    "react-jsxdev",
+    "react-native",
+];
+const MODULE_RESOLUTIONS: &[&str] = &["node", "node10", "node16", "nodenext", "bundler", "classic"];
+
+/// tsc options that `CompilerOptions` has no field for, by the section of
+/// the tsconfig reference that documents them
+const OTHER_COMPILER_OPTIONS: &[&str] = &[
+    // Type checking
+    "allowUnreachableCode",
+    "allowUnusedLabels",
+    "exactOptionalPropertyTypes",
+    "noImplicitOverride",
+    "noPropertyAccessFromIndexSignature",
+    "noUncheckedIndexedAccess",
+    "strictBuiltinIteratorReturn",
+    "useUnknownInCatchVariables",
+    // Modules
+    "allowArbitraryExtensions",
+    "allowImportingTsExtensions",
+    "allowUmdGlobalAccess",
+    "customConditions",
+    "moduleSuffixes",
+    "noResolve",
+    "noUncheckedSideEffectImports",
+    "resolvePackageJsonExports",
+    "resolvePackageJsonImports",
+    "rewriteRelativeImportExtensions",
+    // Emit
+    "declarationMap",
+    "downlevelIteration",
+    "emitBOM",
+    "emitDeclarationOnly",
+    "importHelpers",
+    "inlineSourceMap",
+    "inlineSources",
+    "mapRoot",
+    "newLine",
+    "noEmit",
+    "noEmitHelpers",
+    "noEmitOnError",
+    "outFile",
+    "preserveConstEnums",
+    "removeComments",
+    "sourceMap",
+    "sourceRoot",
+    "stripInternal",
+    // JavaScript support
+    "allowJs",
+    "checkJs",
+    "maxNodeModuleJsDepth",
+    // Editor support
+    "disableSizeLimit",
+    "plugins",
+    // Interop constraints
+    "erasableSyntaxOnly",
+    "isolatedDeclarations",
+    "preserveSymlinks",
+    "verbatimModuleSyntax",
+    // Backwards compatibility
+    "charset",
+    "ignoreDeprecations",
+    "importsNotUsedAsValues",
+    "keyofStringsOnly",
+    "noImplicitUseStrict",
+    "noStrictGenericChecks",
+    "out",
+    "preserveValueImports",
+    "suppressExcessPropertyErrors",
+    "suppressImplicitAnyIndexErrors",
+    // Language and environment
+    "emitDecoratorMetadata",
+    "experimentalDecorators",
+    "libReplacement",
+    "moduleDetection",
+    "noLib",
+    "reactNamespace",
+    "useDefineForClassFields",
+    // Compiler diagnostics
+    "diagnostics",
+    "explainFiles",
+    "extendedDiagnostics",
+    "generateCpuProfile",
+    "generateTrace",
+    "listEmittedFiles",
+    "listFiles",
+    "noCheck",
+    "traceResolution",
+    // Projects
+    "assumeChangesOnlyAffectDirectDependencies",
+    "composite",
+    "disableReferencedProjectLoad",
+    "disableSolutionSearching",
+    "disableSourceOfProjectReferenceRedirect",
+    "incremental",
+    "tsBuildInfoFile",
+    // Output formatting
+    "locale",
+    "noErrorTruncation",
+    "preserveWatchOutput",
+    "pretty",
+    // Completeness
+    "skipDefaultLibCheck",
+];
+
+/// Extensions `include` wildcards pick up, and with `allowJs`
+const TS_EXTENSIONS: &[&str] = &[".ts", ".tsx", ".mts", ".cts"];
+const JS_EXTENSIONS: &[&str] = &[".js", ".jsx", ".mjs", ".cjs"];
+
+/// How serious a tsconfig problem is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TsConfigSeverity {
+    Error,
+    Warning,
+}
+
+/// A problem found in the compiler options of a tsconfig
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TsConfigDiagnostic {
+    pub message: String,
+    /// The compiler option at fault, as spelled in tsconfig.json
+    pub field: Option<String>,
+    pub severity: TsConfigSeverity,
+}
+
+impl TsConfig {
+    /// Whether a file belongs to the project, with `path` relative to the
+    /// tsconfig's directory. Files listed in `files` always do; otherwise the
+    /// path must match an `include` pattern (every source file by default)
+    /// and no `exclude` pattern (`node_modules` and `outDir` by default).
+    pub fn matches_file(&self, path: &Path) -> bool {
+        let path = path.to_string_lossy().replace('\\', "/");
+        let path = path.trim_start_matches("./");
+
+        if self
+            .files
+            .iter()
+            .flatten()
+            .any(|file| file.trim_start_matches("./") == path)
+        {
+            return true;
+        }
+
+        let allow_js = self
+            .compiler_options
+            .as_ref()
+            .and_then(|options| options.other.get("allowJs"))
+            .and_then(|value| value.as_bool())
+            .unwrap_or(false);
+        let has_extension = |extensions: &[&str]| extensions.iter().any(|ext| path.ends_with(ext));
+        let is_source = has_extension(TS_EXTENSIONS) || (allow_js && has_extension(JS_EXTENSIONS));
+
+        let included = match self.include.as_deref() {
+            Some(patterns) if !patterns.is_empty() => patterns
+                .iter()
+                .any(|pattern| include_matches(pattern, path, is_source)),
+            _ => include_matches("**/*", path, is_source),
+        };
+        included && !self.is_excluded(path)
+    }
+
+    /// Whether `path`, relative to the tsconfig's directory, or one of its
+    /// parent directories matches an `exclude` pattern
+    pub fn is_excluded(&self, path: &str) -> bool {
+        let defaults;
+        let patterns = match self.exclude.as_deref() {
+            Some(patterns) => patterns,
+            None => {
+                let out_dir = self
+                    .compiler_options
+                    .as_ref()
+                    .and_then(|options| options.out_dir.clone());
+                defaults = ["node_modules".to_string()]
+                    .into_iter()
+                    .chain(out_dir)
+                    .collect::<Vec<_>>();
+                &defaults
+            }
+        };
+
+        let segments: Vec<&str> = path.split('/').collect();
+        patterns.iter().any(|pattern| {
+            let pattern = split_pattern(pattern);
+            (1..=segments.len()).any(|len| glob_match(&pattern, &segments[..len]))
+        })
+    }
+
+    /// Check the enumerated compiler options against the values tsc accepts,
+    /// and warn about options tsc doesn't know
+    pub fn validate(&self) -> Vec<TsConfigDiagnostic> {
+        let Some(options) = &self.compiler_options else {
+            return Vec::new();
+        };
+        let mut diagnostics = Vec::new();
+
+        let enumerated = [
+            ("target", &options.target, TARGETS),
+            ("module", &options.module, MODULES),
+            ("jsx", &options.jsx, JSX_MODES),
+            (
+                "moduleResolution",
+                &options.module_resolution,
+                MODULE_RESOLUTIONS,
+            ),
+        ];
+        for (field, value, valid) in enumerated {
+            let Some(value) = value else {
+                continue;
+            };
+            if !valid.contains(&value.to_lowercase().as_str()) {
+                diagnostics.push(TsConfigDiagnostic {
+                    message: format!(
+                        "Argument for '--{}' option must be: {}.",
+                        field,
+                        valid
+                            .iter()
+                            .map(|v| format!("'{}'", v))
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ),
+                    field: Some(field.to_string()),
+                    severity: TsConfigSeverity::Error,
+                });
+            }
+        }
+
+        let mut unknown: Vec<_> = options
+            .other
+            .keys()
+            .filter(|name| !OTHER_COMPILER_OPTIONS.contains(&name.as_str()))
+            .collect();
+        unknown.sort();
+        for name in unknown {
+            diagnostics.push(TsConfigDiagnostic {
+                message: format!("Unknown compiler option '{}'.", name),
+                field: Some(name.clone()),
+                severity: TsConfigSeverity::Warning,
+            });
+        }
+
+        diagnostics
+    }
+}
+
+/// Whether an `include` pattern matches a file. A pattern ending in a
+/// directory name includes everything below it, and a wildcard file name
+/// only picks up source files.
+fn include_matches(pattern: &str, path: &str, is_source: bool) -> bool {
+    let mut pattern = split_pattern(pattern);
+    let last = pattern.last().copied().unwrap_or("");
+    let names_directory = last == "**" || (!last.contains(['*', '?']) && !last.contains('.'));
+    if names_directory {
+        if last != "**" {
+            pattern.push("**");
+        }
+        pattern.push("*");
+    }
+    let wildcard_name = pattern.last().is_some_and(|name| name.contains(['*', '?']));
+
+    let segments: Vec<&str> = path.split('/').collect();
+    glob_match(&pattern, &segments) && (is_source || !wildcard_name)
+}
+
+/// Pattern segments, without `./` and empty segments
+fn split_pattern(pattern: &str) -> Vec<&str> {
+    pattern
+        .split('/')
+        .filter(|segment| !segment.is_empty() && *segment != ".")
+        .collect()
+}
+
+/// Match path segments against pattern segments, where `**` matches any
+/// number of directories. Like tsc, wildcards never match names starting
+/// with a dot, and `!` has no special meaning.
+fn glob_match(pattern: &[&str], segments: &[&str]) -> bool {
+    match (pattern.split_first(), segments.split_first()) {
+        (None, None) => true,
+        (Some((&"**", rest)), _) => {
+            glob_match(rest, segments)
+                || segments.split_first().is_some_and(|(first, remaining)| {
+                    !first.starts_with('.') && glob_match(pattern, remaining)
+                })
+        }
+        (Some((first, rest)), Some((segment, remaining))) => {
+            segment_match(first, segment) && glob_match(rest, remaining)
+        }
+        _ => false,
+    }
+}
+
+/// Match one path segment against a pattern segment with `*` and `?`
+fn segment_match(pattern: &str, segment: &str) -> bool {
+    if segment.starts_with('.') && !pattern.starts_with('.') {
+        return false;
+    }
+    let pattern: Vec<char> = pattern.chars().collect();
+    let segment: Vec<char> = segment.chars().collect();
+
+    // Backtracking over the last `*`
+    let (mut p, mut s) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+    while s < segment.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, s));
+                p += 1;
+            }
+            Some('?') => {
+                p += 1;
+                s += 1;
+            }
+            Some(c) if *c == segment[s] => {
+                p += 1;
+                s += 1;
+            }
+            _ => match star {
+                Some((star_p, star_s)) => {
+                    p = star_p + 1;
+                    s = star_s + 1;
+                    star = Some((star_p, star_s + 1));
+                }
+                None => return false,
+            },
         }
     }
+    pattern[p..].iter().all(|c| *c == '*')
 }
 
 /// Errors that can occur when loading a tsconfig
@@ -185,6 +547,8 @@ impl CompilerOptions {
 pub enum TsConfigError {
     IoError(String),
     ParseError(String),
+    /// The `extends` chain leads back to this file
+    CircularExtends(String),
 }
 
 impl std::fmt::Display for TsConfigError {
@@ -192,6 +556,9 @@ impl std::fmt::Display for TsConfigError {
         match self {
             TsConfigError::IoError(e) => write!(f, "IO error: {}", e),
             TsConfigError::ParseError(e) => write!(f, "Parse error: {}", e),
+            TsConfigError::CircularExtends(path) => {
+                write!(f, "Circular extends: {} extends itself", path)
+            }
         }
     }
 }
@@ -293,6 +660,221 @@ mod tests {
         assert_eq!(result, input);
     }
 
+    #[test]
+    fn test_validate_enumerated_options() {
+        let json = r#"{
+            "compilerOptions": {
+                "target": "ES2o20",
+                "module": "NodeNext",
+                "jsx": "react-jsx",
+                "moduleResolution": "nodee"
+            }
+        }"#;
+        let config: TsConfig = serde_json::from_str(json).unwrap();
+        let diagnostics = config.validate();
+
+        let fields: Vec<_> = diagnostics.iter().map(|d| d.field.as_deref()).collect();
+        assert_eq!(fields, vec![Some("target"), Some("moduleResolution")]);
+        assert!(
+            diagnostics
+                .iter()
+                .all(|d| d.severity == TsConfigSeverity::Error)
+        );
+        assert!(
+            diagnostics[0]
+                .message
+                .starts_with("Argument for '--target' option must be: 'es3'")
+        );
+    }
+
+    #[test]
+    fn test_validate_unknown_options() {
+        let json = r#"{"compilerOptions": {"strict": true, "sourceMap": true, "strictt": true}}"#;
+        let config: TsConfig = serde_json::from_str(json).unwrap();
+
+        assert_eq!(
+            config.validate(),
+            vec![TsConfigDiagnostic {
+                message: "Unknown compiler option 'strictt'.".to_string(),
+                field: Some("strictt".to_string()),
+                severity: TsConfigSeverity::Warning,
+            }]
+        );
+
+        // Lint checks are server settings, which tsc rejects as compiler options
+        let json = r#"{"compilerOptions": {"noShadow": true, "switchExhaustivenessCheck": true}}"#;
+        let config: TsConfig = serde_json::from_str(json).unwrap();
+        let mut fields: Vec<_> = config
+            .validate()
+            .into_iter()
+            .filter_map(|d| d.field)
+            .collect();
+        fields.sort();
+        assert_eq!(fields, vec!["noShadow", "switchExhaustivenessCheck"]);
+        assert!(TsConfig::default().validate().is_empty());
+
+        let json = r#"{"compilerOptions": {
+            "ignoreDeprecations": "5.0",
+            "strictBuiltinIteratorReturn": true,
+            "skipDefaultLibCheck": true,
+            "importsNotUsedAsValues": "remove",
+            "preserveValueImports": false,
+            "noErrorTruncation": true,
+            "traceResolution": false,
+            "extendedDiagnostics": false,
+            "listFiles": false,
+            "explainFiles": false,
+            "pretty": true,
+            "disableSizeLimit": true,
+            "assumeChangesOnlyAffectDirectDependencies": true,
+            "disableSolutionSearching": true,
+            "disableReferencedProjectLoad": true,
+            "disableSourceOfProjectReferenceRedirect": true,
+            "suppressExcessPropertyErrors": false,
+            "noStrictGenericChecks": false
+        }}"#;
+        let config: TsConfig = serde_json::from_str(json).unwrap();
+        assert!(config.validate().is_empty(), "{:?}", config.validate());
+    }
+
+    #[test]
+    fn test_load_follows_extends() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path();
+        std::fs::write(
+            root.join("base.json"),
+            r#"{"compilerOptions": {"strict": true}}"#,
+        )
+        .unwrap();
+        std::fs::write(
+            root.join("tsconfig.json"),
+            r#"{"extends": "./base", "compilerOptions": {"target": "ES2020"}}"#,
+        )
+        .unwrap();
+
+        let config = TsConfig::load(&root.join("tsconfig.json")).unwrap();
+        let options = config.compiler_options.unwrap();
+        assert_eq!(options.strict, Some(true));
+        assert_eq!(options.target.as_deref(), Some("ES2020"));
+    }
+
+    #[test]
+    fn test_load_circular_extends() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path();
+        std::fs::write(root.join("a.json"), r#"{"extends": "./b.json"}"#).unwrap();
+        std::fs::write(root.join("b.json"), r#"{"extends": "./a.json"}"#).unwrap();
+
+        match TsConfig::load(&root.join("a.json")) {
+            Err(TsConfigError::CircularExtends(path)) => assert!(path.ends_with("a.json")),
+            other => panic!("expected a circular extends error, got {:?}", other),
+        }
+    }
+
+    fn config(json: &str) -> TsConfig {
+        serde_json::from_str(json).unwrap()
+    }
+
+    fn matches(config: &TsConfig, path: &str) -> bool {
+        config.matches_file(Path::new(path))
+    }
+
+    #[test]
+    fn test_glob_segments() {
+        assert!(segment_match("*.ts", "main.ts"));
+        assert!(segment_match("ma?n.ts", "main.ts"));
+        assert!(segment_match("*.*.ts", "a.spec.ts"));
+        assert!(segment_match("*", "file"));
+        assert!(!segment_match("*.ts", "main.tsx"));
+        assert!(!segment_match("?.ts", "ab.ts"));
+        // Wildcards skip dotfiles unless the pattern spells out the dot
+        assert!(!segment_match("*", ".env"));
+        assert!(segment_match(".*", ".env"));
+    }
+
+    #[test]
+    fn test_glob_double_star() {
+        let m = |pattern: &str, path: &str| {
+            glob_match(
+                &split_pattern(pattern),
+                &path.split('/').collect::<Vec<_>>(),
+            )
+        };
+        assert!(m("**/*.ts", "a.ts"));
+        assert!(m("**/*.ts", "a/b/c.ts"));
+        assert!(m("src/**/test/*", "src/test/x.ts"));
+        assert!(m("src/**/test/*", "src/a/b/test/x.ts"));
+        assert!(!m("src/*/x.ts", "src/a/b/x.ts"));
+        assert!(!m("**/*.ts", ".git/a.ts"));
+        assert!(m("./src/*.ts", "src/a.ts"));
+    }
+
+    #[test]
+    fn test_matches_file_defaults() {
+        let config = TsConfig::default();
+        assert!(matches(&config, "main.ts"));
+        assert!(matches(&config, "src/deep/view.tsx"));
+        assert!(matches(&config, "lib/types.d.ts"));
+        assert!(!matches(&config, "src/app.js"));
+        assert!(!matches(&config, "README.md"));
+        assert!(!matches(&config, "node_modules/pkg/index.ts"));
+        assert!(!matches(&config, ".cache/main.ts"));
+
+        let with_out_dir =
+            self::config(r#"{"compilerOptions": {"outDir": "dist", "allowJs": true}}"#);
+        assert!(matches(&with_out_dir, "src/app.js"));
+        assert!(!matches(&with_out_dir, "dist/app.js"));
+    }
+
+    #[test]
+    fn test_matches_file_include() {
+        let config = config(r#"{"include": ["src", "types/*.d.ts", "tools/gen.ts"]}"#);
+        assert!(matches(&config, "src/main.ts"));
+        assert!(matches(&config, "src/a/b/c.tsx"));
+        assert!(matches(&config, "types/global.d.ts"));
+        assert!(matches(&config, "tools/gen.ts"));
+        assert!(!matches(&config, "tools/other.ts"));
+        assert!(!matches(&config, "types/nested/global.d.ts"));
+        assert!(!matches(&config, "main.ts"));
+        // Wildcard names only pick up source files
+        assert!(!matches(&config, "src/styles.css"));
+
+        // An empty include behaves like the default
+        assert!(matches(&self::config(r#"{"include": []}"#), "main.ts"));
+    }
+
+    #[test]
+    fn test_matches_file_overlapping_exclude() {
+        let config = config(
+            r#"{
+                "include": ["src/**/*"],
+                "exclude": ["src/**/*.test.ts", "src/legacy"],
+                "files": ["src/legacy/keep.ts"]
+            }"#,
+        );
+        assert!(matches(&config, "src/app.ts"));
+        assert!(!matches(&config, "src/app.test.ts"));
+        assert!(!matches(&config, "src/deep/app.test.ts"));
+        assert!(!matches(&config, "src/legacy/old.ts"));
+        // `files` wins over `exclude`
+        assert!(matches(&config, "src/legacy/keep.ts"));
+
+        // An explicit exclude replaces the default, so node_modules is included
+        assert!(matches(&config, "src/node_modules/x.ts"));
+    }
+
+    #[test]
+    fn test_matches_file_negated_patterns() {
+        // tsc has no negation: `!` is an ordinary character
+        let config = config(r#"{"include": ["!src/**/*"], "exclude": ["!src/keep.ts"]}"#);
+        assert!(!matches(&config, "src/keep.ts"));
+        assert!(matches(&config, "!src/a.ts"));
+
+        let config = self::config(r#"{"exclude": ["!node_modules"]}"#);
+        assert!(matches(&config, "src/keep.ts"));
+        assert!(matches(&config, "node_modules/x.ts"));
+    }
+
     #[test]
     fn test_tsconfig_default() {
         let config = TsConfig::default();
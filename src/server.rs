@@ -1,21 +1,41 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
 use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 
 use tower_lsp::jsonrpc::Result;
 use tower_lsp::lsp_types::*;
 use tower_lsp::{Client, LanguageServer};
+use tree_sitter::Tree;
 
+use crate::analysis::modules::{self, ImportBinding};
+use crate::analysis::{SymbolTable, binder};
+use crate::capabilities::type_diagnostics::LintOptions;
 use crate::capabilities::{
     code_actions, completions, definition, diagnostics, folding, hover, inlay_hints, references,
-    rename, selection_range, semantic_tokens, signature_help, symbols,
+    rename, selection_range, semantic_tokens, signature_help, symbols, type_diagnostics,
 };
 use crate::document::DocumentManager;
-use crate::parser::SourceParser;
+use crate::parser::{SourceLanguage, SourceParser};
+use crate::project::Workspace;
+use crate::project::workspace::ReExport;
+use crate::resolution::ModuleResolver;
+use crate::resolution::tsconfig::TsConfig;
 
 /// The LSP backend that handles all language server requests
 pub struct Backend {
     client: Client,
     document_manager: DocumentManager,
     parser: Mutex<SourceParser>,
+    workspace: Mutex<Workspace>,
+    /// Tokens last sent per document, for delta requests
+    semantic_tokens_cache: semantic_tokens::SemanticTokensCache,
+    /// Maximum number of `workspace/symbol` results
+    workspace_symbol_limit: AtomicUsize,
+    /// Whether the client renders Markdown in hovers
+    hover_markdown: AtomicBool,
+    /// Lint checks enabled through `initializationOptions`
+    lint_options: Mutex<LintOptions>,
 }
 
 impl Backend {
@@ -24,40 +44,267 @@ impl Backend {
             client,
             document_manager: DocumentManager::new(),
             parser: Mutex::new(SourceParser::default()),
+            workspace: Mutex::new(Workspace::new(PathBuf::new())),
+            semantic_tokens_cache: semantic_tokens::SemanticTokensCache::new(),
+            workspace_symbol_limit: AtomicUsize::new(symbols::DEFAULT_WORKSPACE_SYMBOL_LIMIT),
+            hover_markdown: AtomicBool::new(true),
+            lint_options: Mutex::new(LintOptions::default()),
         }
     }
 
     /// Publish diagnostics for a document
     async fn publish_diagnostics(&self, uri: Url) {
-        let diags = if let Some(doc) = self.document_manager.get(&uri) {
-            if let Some(ref tree) = doc.tree {
-                diagnostics::get_syntax_diagnostics(tree, &doc.content)
-            } else {
-                Vec::new()
+        let diags = self.collect_diagnostics(&uri);
+        self.client.publish_diagnostics(uri, diags, None).await;
+    }
+
+    /// Syntax and type diagnostics for a document, checked against the
+    /// compiler options of its project
+    fn collect_diagnostics(&self, uri: &Url) -> Vec<Diagnostic> {
+        let options = uri
+            .to_file_path()
+            .map(|path| {
+                let mut workspace = self.workspace.lock().unwrap();
+                workspace
+                    .project_for_file_or_insert(&path)
+                    .compiler_options()
+            })
+            .unwrap_or_default();
+
+        let Some(doc) = self.document_manager.get(uri) else {
+            return Vec::new();
+        };
+        let Some(tree) = &doc.tree else {
+            return Vec::new();
+        };
+        let mut diags = diagnostics::get_syntax_diagnostics(tree, &doc.content);
+        if let Some(symbol_table) = &doc.symbol_table {
+            let lints = self.lint_options.lock().unwrap().clone();
+            diags.extend(type_diagnostics::get_type_diagnostics_with_lints(
+                tree,
+                &doc.content,
+                symbol_table,
+                &options,
+                &lints,
+            ));
+        }
+        diags
+    }
+
+    /// Publish problems in the compiler options of the workspace's tsconfigs
+    async fn publish_tsconfig_diagnostics(&self) {
+        let configs: Vec<(PathBuf, TsConfig)> = {
+            let workspace = self.workspace.lock().unwrap();
+            workspace
+                .get_projects()
+                .filter_map(|project| Some((project.config_path.clone()?, project.config.clone()?)))
+                .collect()
+        };
+
+        for (path, config) in configs {
+            let (Ok(uri), Ok(source)) =
+                (Url::from_file_path(&path), std::fs::read_to_string(&path))
+            else {
+                continue;
+            };
+            let diags = diagnostics::get_tsconfig_diagnostics(&config, &source);
+            self.client.publish_diagnostics(uri, diags, None).await;
+        }
+    }
+
+    /// Refresh the workspace export index for a (re)bound document
+    fn index_exports(&self, uri: &Url) {
+        if let Some(doc) = self.document_manager.get(uri) {
+            if let (Some(tree), Some(symbol_table)) = (&doc.tree, &doc.symbol_table) {
+                self.index_source(uri, tree, &doc.content, symbol_table);
             }
-        } else {
-            Vec::new()
+        }
+    }
+
+    /// Index the files of every project that aren't open, as they are on disk
+    fn index_project_files(&self) {
+        let files: Vec<PathBuf> = {
+            let workspace = self.workspace.lock().unwrap();
+            workspace
+                .get_projects()
+                .flat_map(|project| project.get_files().cloned())
+                .collect()
         };
+        for path in files {
+            let is_open = Url::from_file_path(&path)
+                .is_ok_and(|uri| self.document_manager.get(&uri).is_some());
+            if !is_open {
+                self.index_file_from_disk(&path);
+            }
+        }
+    }
 
-        self.client.publish_diagnostics(uri, diags, None).await;
+    /// Index the file at `path` as it is on disk, or drop its entries when
+    /// it can't be read
+    fn index_file_from_disk(&self, path: &Path) {
+        let Ok(uri) = Url::from_file_path(path) else {
+            return;
+        };
+        let tree = std::fs::read_to_string(path).ok().and_then(|content| {
+            let mut parser = self.parser.lock().unwrap();
+            parser.set_language(SourceLanguage::from_uri(&uri));
+            Some((parser.parse(&content, None)?, content))
+        });
+        match tree {
+            Some((tree, content)) => {
+                let symbol_table = binder::bind_document(&tree, &content);
+                self.index_source(&uri, &tree, &content, &symbol_table);
+            }
+            None => self.workspace.lock().unwrap().remove_file_exports(&uri),
+        }
+    }
+
+    /// Record the exports and re-exports of a file in the workspace index
+    /// and in the cache of its project
+    fn index_source(&self, uri: &Url, tree: &Tree, source: &str, symbol_table: &SymbolTable) {
+        let mut workspace = self.workspace.lock().unwrap();
+        let reexports = match uri.to_file_path() {
+            Ok(path) => {
+                let project = workspace.project_for_file_or_insert(&path);
+                project.on_file_change(uri, tree, source);
+                resolve_reexports(&project.resolver, &path, tree, source)
+            }
+            Err(_) => Vec::new(),
+        };
+        workspace.index_file_exports(uri, tree, source, symbol_table);
+        workspace.index_file_reexports(uri, reexports);
+    }
+
+    /// Where the module an import refers to declares the imported name,
+    /// looked up in the bound modules of the importing file's project
+    fn cached_export_location(&self, from: &Url, import: &ImportBinding) -> Option<Location> {
+        let from_path = from.to_file_path().ok()?;
+        let name = import.imported.export_name()?;
+        let mut workspace = self.workspace.lock().unwrap();
+        let project = workspace.project_for_file_or_insert(&from_path);
+        let resolved = project.resolver.resolve(&import.specifier, &from_path)?;
+        project.load_module_exports(&resolved.path);
+        let range = project.export_location(&resolved.path, name)?;
+        Some(Location {
+            uri: Url::from_file_path(&resolved.path).ok()?,
+            range,
+        })
+    }
+
+    /// Resolve `specifier` imported from `from` and load the target module,
+    /// preferring the open document over the file on disk
+    fn load_module(&self, from: &Url, specifier: &str) -> Option<(Url, String, Tree)> {
+        let from_path = from.to_file_path().ok()?;
+        let resolved = {
+            let mut workspace = self.workspace.lock().unwrap();
+            let project = workspace.project_for_file_or_insert(&from_path);
+            project.resolver.resolve(specifier, &from_path)?
+        };
+        let target_uri = Url::from_file_path(&resolved.path).ok()?;
+
+        if let Some(doc) = self.document_manager.get(&target_uri) {
+            let tree = doc.tree.clone()?;
+            return Some((target_uri.clone(), doc.content.clone(), tree));
+        }
+
+        let content = std::fs::read_to_string(&resolved.path).ok()?;
+        let mut parser = self.parser.lock().unwrap();
+        parser.set_language(SourceLanguage::from_uri(&target_uri));
+        let tree = parser.parse(&content, None)?;
+        Some((target_uri, content, tree))
     }
 }
 
+/// The modules a file re-exports with `export * from '...'`
+fn resolve_reexports(
+    resolver: &ModuleResolver,
+    from_path: &Path,
+    tree: &Tree,
+    source: &str,
+) -> Vec<ReExport> {
+    let resolve = |specifier: &str| {
+        let resolved = resolver.resolve(specifier, from_path)?;
+        Url::from_file_path(resolved.path).ok()
+    };
+
+    let star = modules::collect_star_exports(tree, source)
+        .into_iter()
+        .filter_map(|specifier| {
+            Some(ReExport {
+                target: resolve(&specifier)?,
+                alias: None,
+            })
+        });
+    // `export * as ns from '...'` is the only re-export without a local name
+    let namespaces = modules::collect_exports(tree, source)
+        .into_iter()
+        .filter(|export| export.local.is_none())
+        .filter_map(|export| {
+            Some(ReExport {
+                target: resolve(export.from.as_deref()?)?,
+                alias: Some(export.exported),
+            })
+        });
+    star.chain(namespaces).collect()
+}
+
 #[tower_lsp::async_trait]
 impl LanguageServer for Backend {
-    async fn initialize(&self, _: InitializeParams) -> Result<InitializeResult> {
+    async fn initialize(&self, params: InitializeParams) -> Result<InitializeResult> {
+        if let Some(limit) = params
+            .initialization_options
+            .as_ref()
+            .and_then(|options| options.get("workspaceSymbolLimit"))
+            .and_then(|limit| limit.as_u64())
+        {
+            self.workspace_symbol_limit
+                .store(limit as usize, Ordering::Relaxed);
+        }
+        if let Some(lints) = params
+            .initialization_options
+            .clone()
+            .and_then(|options| serde_json::from_value(options).ok())
+        {
+            *self.lint_options.lock().unwrap() = lints;
+        }
+
+        // Clients that list hover formats without Markdown get plain text
+        if let Some(formats) = params
+            .capabilities
+            .text_document
+            .as_ref()
+            .and_then(|text_document| text_document.hover.as_ref())
+            .and_then(|hover| hover.content_format.as_ref())
+        {
+            self.hover_markdown
+                .store(formats.contains(&MarkupKind::Markdown), Ordering::Relaxed);
+        }
+
+        #[allow(deprecated)] // `root_uri` is still what most clients send
+        if let Some(root) = params.root_uri.and_then(|uri| uri.to_file_path().ok()) {
+            {
+                let mut workspace = self.workspace.lock().unwrap();
+                workspace.root = root;
+                if let Err(e) = workspace.discover_projects() {
+                    eprintln!("Failed to discover projects: {}", e);
+                }
+            }
+            self.index_project_files();
+        }
+
         Ok(InitializeResult {
             capabilities: ServerCapabilities {
                 text_document_sync: Some(TextDocumentSyncCapability::Kind(
                     TextDocumentSyncKind::INCREMENTAL,
                 )),
                 document_symbol_provider: Some(OneOf::Left(true)),
+                workspace_symbol_provider: Some(OneOf::Left(true)),
                 semantic_tokens_provider: Some(
                     SemanticTokensServerCapabilities::SemanticTokensOptions(
                         SemanticTokensOptions {
                             legend: semantic_tokens::get_legend(),
-                            full: Some(SemanticTokensFullOptions::Bool(true)),
-                            range: Some(false),
+                            full: Some(SemanticTokensFullOptions::Delta { delta: Some(true) }),
+                            range: Some(true),
                             work_done_progress_options: WorkDoneProgressOptions::default(),
                         },
                     ),
@@ -118,6 +365,28 @@ impl LanguageServer for Backend {
                 "TypeScript/JavaScript Language Server initialized!",
             )
             .await;
+        self.publish_tsconfig_diagnostics().await;
+
+        // Watch source files so cached module resolutions can be invalidated
+        let options = DidChangeWatchedFilesRegistrationOptions {
+            watchers: vec![FileSystemWatcher {
+                glob_pattern: GlobPattern::String("**/*.{ts,tsx,js,jsx,mts,cts,json}".to_string()),
+                kind: None,
+            }],
+        };
+        let registration = Registration {
+            id: "watched-files".to_string(),
+            method: "workspace/didChangeWatchedFiles".to_string(),
+            register_options: serde_json::to_value(options).ok(),
+        };
+        if let Err(err) = self.client.register_capability(vec![registration]).await {
+            self.client
+                .log_message(
+                    MessageType::WARNING,
+                    format!("Could not watch files: {}", err),
+                )
+                .await;
+        }
     }
 
     async fn shutdown(&self) -> Result<()> {
@@ -129,11 +398,20 @@ impl LanguageServer for Backend {
         let content = params.text_document.text;
         let version = params.text_document.version;
 
+        if let Ok(path) = uri.to_file_path() {
+            let mut workspace = self.workspace.lock().unwrap();
+            workspace.project_for_file_or_insert(&path).vfs.open(
+                uri.clone(),
+                content.clone(),
+                version,
+            );
+        }
         {
             let mut parser = self.parser.lock().unwrap();
             self.document_manager
                 .open(uri.clone(), content, version, &mut parser);
         }
+        self.index_exports(&uri);
 
         // Publish diagnostics for the newly opened document
         self.publish_diagnostics(uri.clone()).await;
@@ -148,19 +426,74 @@ impl LanguageServer for Backend {
         let version = params.text_document.version;
         let changes = params.content_changes;
 
+        let buffer_update = uri.to_file_path().ok().map(|path| {
+            let mut workspace = self.workspace.lock().unwrap();
+            workspace
+                .project_for_file_or_insert(&path)
+                .vfs
+                .update(&uri, changes.clone(), version)
+        });
+        if let Some(Err(err)) = buffer_update {
+            self.client
+                .log_message(MessageType::WARNING, format!("{}: {}", uri, err))
+                .await;
+        }
+
         {
             let mut parser = self.parser.lock().unwrap();
             self.document_manager
                 .change(&uri, changes, version, &mut parser);
         }
+        self.index_exports(&uri);
 
         // Update diagnostics after changes
         self.publish_diagnostics(uri).await;
     }
 
+    async fn did_change_watched_files(&self, params: DidChangeWatchedFilesParams) {
+        let stats: Vec<(usize, usize, usize)> = {
+            let mut workspace = self.workspace.lock().unwrap();
+            for change in &params.changes {
+                if let Ok(path) = change.uri.to_file_path() {
+                    workspace.invalidate_file(&path);
+                }
+            }
+            workspace
+                .get_projects()
+                .map(|project| project.resolver.cache_stats())
+                .collect()
+        };
+
+        for (hits, misses, size) in stats {
+            self.client
+                .log_message(
+                    MessageType::LOG,
+                    format!(
+                        "Module resolution cache: {} hits, {} misses, {} entries",
+                        hits, misses, size
+                    ),
+                )
+                .await;
+        }
+    }
+
     async fn did_close(&self, params: DidCloseTextDocumentParams) {
         let uri = params.text_document.uri;
         self.document_manager.close(&uri);
+        self.semantic_tokens_cache.remove(&uri);
+        match uri.to_file_path() {
+            Ok(path) => {
+                // Later reads see the file on disk again
+                {
+                    let mut workspace = self.workspace.lock().unwrap();
+                    let project = workspace.project_for_file_or_insert(&path);
+                    project.vfs.close(&uri);
+                    project.invalidate_file(&path);
+                }
+                self.index_file_from_disk(&path);
+            }
+            Err(_) => self.workspace.lock().unwrap().remove_file_exports(&uri),
+        }
 
         // Clear diagnostics for closed document
         self.client
@@ -178,7 +511,15 @@ impl LanguageServer for Backend {
 
         let result = if let Some(doc) = self.document_manager.get(uri) {
             if let Some(ref tree) = doc.tree {
-                hover::get_hover(tree, &doc.content, position)
+                doc.symbol_table.as_ref().and_then(|symbol_table| {
+                    hover::get_hover(
+                        tree,
+                        &doc.content,
+                        symbol_table,
+                        &doc.type_checker,
+                        position,
+                    )
+                })
             } else {
                 None
             }
@@ -186,7 +527,11 @@ impl LanguageServer for Backend {
             None
         };
 
-        Ok(result)
+        if self.hover_markdown.load(Ordering::Relaxed) {
+            Ok(result)
+        } else {
+            Ok(result.map(hover::to_plain_text))
+        }
     }
 
     async fn folding_range(&self, params: FoldingRangeParams) -> Result<Option<Vec<FoldingRange>>> {
@@ -217,7 +562,7 @@ impl LanguageServer for Backend {
 
         let ranges = if let Some(doc) = self.document_manager.get(uri) {
             if let Some(ref tree) = doc.tree {
-                selection_range::get_selection_ranges(tree, positions)
+                selection_range::get_selection_ranges(tree, &doc.content, positions)
             } else {
                 Vec::new()
             }
@@ -235,6 +580,35 @@ impl LanguageServer for Backend {
         let uri = &params.text_document_position_params.text_document.uri;
         let position = params.text_document_position_params.position;
 
+        // Imported symbols jump to their declaration in the imported module
+        let import =
+            self.document_manager
+                .get(uri)
+                .and_then(|doc| match (&doc.tree, &doc.symbol_table) {
+                    (Some(tree), Some(symbol_table)) => {
+                        definition::find_import_at(tree, symbol_table, &doc.content, position)
+                    }
+                    _ => None,
+                });
+        if let Some(import) = import {
+            // Modules the project has already bound answer without a reparse
+            if let Some(location) = self.cached_export_location(uri, &import) {
+                return Ok(Some(GotoDefinitionResponse::Scalar(location)));
+            }
+            if let Some((target_uri, target_source, target_tree)) =
+                self.load_module(uri, &import.specifier)
+            {
+                if let Some(result) = definition::get_export_definition(
+                    &import,
+                    &target_uri,
+                    &target_tree,
+                    &target_source,
+                ) {
+                    return Ok(Some(result));
+                }
+            }
+        }
+
         let result = if let Some(doc) = self.document_manager.get(uri) {
             if let Some(ref symbol_table) = doc.symbol_table {
                 definition::get_definition(symbol_table, &doc.content, position, uri)
@@ -300,8 +674,8 @@ impl LanguageServer for Backend {
         let new_name = &params.new_name;
 
         let result = if let Some(doc) = self.document_manager.get(uri) {
-            if let Some(ref symbol_table) = doc.symbol_table {
-                rename::rename_symbol(symbol_table, &doc.content, position, new_name, uri)
+            if let (Some(tree), Some(symbol_table)) = (&doc.tree, &doc.symbol_table) {
+                rename::rename_symbol(tree, symbol_table, &doc.content, position, new_name, uri)
             } else {
                 None
             }
@@ -331,6 +705,38 @@ impl LanguageServer for Backend {
         Ok(Some(DocumentSymbolResponse::Nested(symbols)))
     }
 
+    async fn symbol(
+        &self,
+        params: WorkspaceSymbolParams,
+    ) -> Result<Option<Vec<SymbolInformation>>> {
+        // Open documents, then the files the projects have bound from disk
+        let documents: Vec<_> = self.document_manager.iter().collect();
+        let workspace = self.workspace.lock().unwrap();
+        let mut seen: HashSet<Url> = documents.iter().map(|doc| doc.key().clone()).collect();
+        let project_files: Vec<(Url, &SymbolTable)> = workspace
+            .get_projects()
+            .flat_map(|project| project.cached_symbol_tables())
+            .filter_map(|(path, table)| Some((Url::from_file_path(path).ok()?, table)))
+            .filter(|(uri, _)| seen.insert(uri.clone()))
+            .collect();
+        let files = documents
+            .iter()
+            .filter_map(|doc| {
+                doc.value()
+                    .symbol_table
+                    .as_ref()
+                    .map(|table| (doc.key(), table))
+            })
+            .chain(project_files.iter().map(|(uri, table)| (uri, *table)));
+
+        let limit = self.workspace_symbol_limit.load(Ordering::Relaxed);
+        Ok(Some(symbols::get_workspace_symbols(
+            files,
+            &params.query,
+            limit,
+        )))
+    }
+
     async fn semantic_tokens_full(
         &self,
         params: SemanticTokensParams,
@@ -338,8 +744,8 @@ impl LanguageServer for Backend {
         let uri = &params.text_document.uri;
 
         let tokens = if let Some(doc) = self.document_manager.get(uri) {
-            if let Some(ref tree) = doc.tree {
-                semantic_tokens::get_semantic_tokens(tree, &doc.content)
+            if let (Some(tree), Some(symbol_table)) = (&doc.tree, &doc.symbol_table) {
+                semantic_tokens::get_semantic_tokens(tree, &doc.content, symbol_table)
             } else {
                 Vec::new()
             }
@@ -347,7 +753,58 @@ impl LanguageServer for Backend {
             Vec::new()
         };
 
+        let result_id = self.semantic_tokens_cache.store(uri, tokens.clone());
         Ok(Some(SemanticTokensResult::Tokens(SemanticTokens {
+            result_id: Some(result_id),
+            data: tokens,
+        })))
+    }
+
+    async fn semantic_tokens_full_delta(
+        &self,
+        params: SemanticTokensDeltaParams,
+    ) -> Result<Option<SemanticTokensFullDeltaResult>> {
+        let uri = &params.text_document.uri;
+
+        let tokens = if let Some(doc) = self.document_manager.get(uri) {
+            if let (Some(tree), Some(symbol_table)) = (&doc.tree, &doc.symbol_table) {
+                semantic_tokens::get_semantic_tokens(tree, &doc.content, symbol_table)
+            } else {
+                Vec::new()
+            }
+        } else {
+            Vec::new()
+        };
+
+        Ok(Some(self.semantic_tokens_cache.delta(
+            uri,
+            &params.previous_result_id,
+            tokens,
+        )))
+    }
+
+    async fn semantic_tokens_range(
+        &self,
+        params: SemanticTokensRangeParams,
+    ) -> Result<Option<SemanticTokensRangeResult>> {
+        let uri = &params.text_document.uri;
+
+        let tokens = if let Some(doc) = self.document_manager.get(uri) {
+            if let (Some(tree), Some(symbol_table)) = (&doc.tree, &doc.symbol_table) {
+                semantic_tokens::get_semantic_tokens_range(
+                    tree,
+                    &doc.content,
+                    symbol_table,
+                    params.range,
+                )
+            } else {
+                Vec::new()
+            }
+        } else {
+            Vec::new()
+        };
+
+        Ok(Some(SemanticTokensRangeResult::Tokens(SemanticTokens {
             result_id: None,
             data: tokens,
         })))
@@ -356,10 +813,36 @@ impl LanguageServer for Backend {
     async fn completion(&self, params: CompletionParams) -> Result<Option<CompletionResponse>> {
         let uri = &params.text_document_position.text_document.uri;
 
+        let position = params.text_document_position.position;
+        // Projects are keyed by path, so documents like `untitled:` have none
+        let Ok(file_path) = uri.to_file_path() else {
+            return Ok(None);
+        };
+
         let items = if let Some(doc) = self.document_manager.get(uri) {
             if let Some(ref tree) = doc.tree {
                 if let Some(ref symbol_table) = doc.symbol_table {
-                    completions::get_completions(tree, &doc.content, symbol_table, &params)
+                    let mut workspace = self.workspace.lock().unwrap();
+                    let project = workspace.project_for_file_or_insert(&file_path);
+
+                    // Load the exports of the module a named import refers to
+                    if let Some(specifier) =
+                        completions::named_import_specifier(tree, &doc.content, position)
+                    {
+                        if let Some(resolved) = project.resolver.resolve(&specifier, &file_path) {
+                            project.load_module_exports(&resolved.path);
+                        }
+                    }
+
+                    completions::get_completions(
+                        tree,
+                        &doc.content,
+                        symbol_table,
+                        &doc.type_checker,
+                        project,
+                        &completions::DocumentContext { uri: uri.clone() },
+                        &params,
+                    )
                 } else {
                     Vec::new()
                 }
@@ -374,9 +857,21 @@ impl LanguageServer for Backend {
     }
 
     async fn completion_resolve(&self, item: CompletionItem) -> Result<CompletionItem> {
-        // For now, just return the item as-is
-        // In a full implementation, we would fetch additional documentation here
-        Ok(item)
+        let Some(uri) = completions::completion_uri(&item) else {
+            return Ok(item);
+        };
+        let Some(doc) = self.document_manager.get(&uri) else {
+            return Ok(item);
+        };
+        let (Some(tree), Some(symbol_table)) = (&doc.tree, &doc.symbol_table) else {
+            return Ok(item);
+        };
+        Ok(completions::resolve_completion(
+            item,
+            tree,
+            &doc.content,
+            symbol_table,
+        ))
     }
 
     async fn signature_help(&self, params: SignatureHelpParams) -> Result<Option<SignatureHelp>> {
@@ -423,19 +918,224 @@ impl LanguageServer for Backend {
 
     async fn code_action(&self, params: CodeActionParams) -> Result<Option<CodeActionResponse>> {
         let uri = &params.text_document.uri;
-        let range = params.range;
-        let diagnostics = &params.context.diagnostics;
+        let Ok(file_path) = uri.to_file_path() else {
+            return Ok(None);
+        };
 
-        let actions = if let Some(doc) = self.document_manager.get(uri) {
-            if let Some(ref symbol_table) = doc.symbol_table {
-                code_actions::get_code_actions(uri, range, diagnostics, symbol_table, &doc.content)
-            } else {
-                Vec::new()
-            }
-        } else {
-            Vec::new()
+        // Copy the document out so the map isn't borrowed while the
+        // workspace is locked
+        let Some((tree, symbol_table, mut checker, content)) =
+            self.document_manager.get(uri).and_then(|doc| {
+                Some((
+                    doc.tree.clone()?,
+                    doc.symbol_table.clone()?,
+                    doc.type_checker.clone(),
+                    doc.content.clone(),
+                ))
+            })
+        else {
+            return Ok(Some(Vec::new()));
         };
 
+        // "Add Missing Imports" reads the exports the project has cached for
+        // its files, which opening and editing a document keeps current
+        let mut workspace = self.workspace.lock().unwrap();
+        let project = workspace.project_for_file_or_insert(&file_path);
+        let actions = code_actions::get_code_actions(
+            &params,
+            &tree,
+            &symbol_table,
+            &mut checker,
+            &content,
+            project,
+        );
+
         Ok(Some(actions))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tower_lsp::LspService;
+
+    /// A backend for a workspace holding `files`, with its projects loaded
+    fn workspace_backend(files: &[(&str, &str)]) -> (tempfile::TempDir, LspService<Backend>) {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        for (name, content) in files {
+            std::fs::write(temp_dir.path().join(name), content).unwrap();
+        }
+        let (service, _socket) = LspService::new(Backend::new);
+        let backend = service.inner();
+        {
+            let mut workspace = backend.workspace.lock().unwrap();
+            workspace.root = temp_dir.path().to_path_buf();
+            workspace.discover_projects().unwrap();
+        }
+        backend.index_project_files();
+        (temp_dir, service)
+    }
+
+    fn open(backend: &Backend, uri: &Url, source: &str) {
+        let mut parser = backend.parser.lock().unwrap();
+        backend
+            .document_manager
+            .open(uri.clone(), source.to_string(), 1, &mut parser);
+    }
+
+    #[test]
+    fn test_collect_diagnostics_includes_type_diagnostics() {
+        let (temp_dir, service) = workspace_backend(&[(
+            "tsconfig.json",
+            r#"{"compilerOptions": {"noUnusedLocals": true}}"#,
+        )]);
+        let backend = service.inner();
+
+        let uri = Url::from_file_path(temp_dir.path().join("main.ts")).unwrap();
+        open(backend, &uri, "const unused = 1;\nmissing();\n");
+
+        // The project's options apply: the unused local and the unknown name
+        let codes: Vec<_> = backend
+            .collect_diagnostics(&uri)
+            .into_iter()
+            .filter_map(|diagnostic| diagnostic.code)
+            .collect();
+        assert_eq!(
+            codes,
+            vec![NumberOrString::Number(2304), NumberOrString::Number(6133)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_lint_options_come_from_initialization_options() {
+        let (temp_dir, service) = workspace_backend(&[("tsconfig.json", "{}")]);
+        let backend = service.inner();
+        let uri = Url::from_file_path(temp_dir.path().join("main.ts")).unwrap();
+        open(
+            backend,
+            &uri,
+            "export const value = 1;\nexport function f(value: number) { return value; }\n",
+        );
+        let shadowing = |backend: &Backend| {
+            backend
+                .collect_diagnostics(&uri)
+                .iter()
+                .filter(|d| d.code == Some(NumberOrString::Number(80009)))
+                .count()
+        };
+        assert_eq!(shadowing(backend), 0);
+
+        backend
+            .initialize(InitializeParams {
+                initialization_options: Some(serde_json::json!({ "noShadow": true })),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(shadowing(backend), 1);
+    }
+
+    #[tokio::test]
+    async fn test_workspace_symbols_include_unopened_files() {
+        let (temp_dir, service) = workspace_backend(&[
+            ("tsconfig.json", "{}"),
+            ("a.ts", "export function onDisk() {}"),
+        ]);
+        let backend = service.inner();
+        let main = Url::from_file_path(temp_dir.path().join("main.ts")).unwrap();
+        open(backend, &main, "function onDiskToo() {}");
+
+        let symbols = backend
+            .symbol(WorkspaceSymbolParams {
+                query: "ondisk".to_string(),
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+            })
+            .await
+            .unwrap()
+            .unwrap();
+        let mut names: Vec<_> = symbols.iter().map(|s| s.name.as_str()).collect();
+        names.sort();
+        assert_eq!(names, vec!["onDisk", "onDiskToo"]);
+    }
+
+    #[tokio::test]
+    async fn test_definition_follows_tsconfig_paths() {
+        let (temp_dir, service) = workspace_backend(&[
+            (
+                "tsconfig.json",
+                r#"{"compilerOptions": {"baseUrl": ".", "paths": {"@/*": ["*"]}}}"#,
+            ),
+            ("lib.ts", "export function greet() {}"),
+        ]);
+        let backend = service.inner();
+        let main = Url::from_file_path(temp_dir.path().join("main.ts")).unwrap();
+        open(backend, &main, "import { greet } from '@/lib';\ngreet();");
+
+        let response = backend
+            .goto_definition(GotoDefinitionParams {
+                text_document_position_params: TextDocumentPositionParams::new(
+                    TextDocumentIdentifier::new(main),
+                    Position::new(1, 1),
+                ),
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+            })
+            .await
+            .unwrap();
+        let Some(GotoDefinitionResponse::Scalar(location)) = response else {
+            panic!("Expected a scalar location, got {:?}", response);
+        };
+        assert_eq!(
+            location.uri,
+            Url::from_file_path(temp_dir.path().join("lib.ts")).unwrap()
+        );
+        assert_eq!(location.range.start, Position::new(0, 16));
+    }
+
+    #[tokio::test]
+    async fn test_export_index_covers_files_on_disk() {
+        let (temp_dir, service) = workspace_backend(&[
+            ("tsconfig.json", "{}"),
+            ("a.ts", "export function fromDisk() {}"),
+            ("index.ts", "export * from './a';"),
+        ]);
+        let backend = service.inner();
+        let a = Url::from_file_path(temp_dir.path().join("a.ts")).unwrap();
+        let index = Url::from_file_path(temp_dir.path().join("index.ts")).unwrap();
+        let exported = |name: &str| backend.workspace.lock().unwrap().exported_symbols(name);
+
+        // Indexed when the project loads, without opening anything
+        assert_eq!(exported("fromDisk").len(), 1);
+        assert!(
+            backend
+                .workspace
+                .lock()
+                .unwrap()
+                .module_export_names(&index)
+                .contains(&"fromDisk".to_string())
+        );
+
+        // The open buffer replaces the file, closing it restores the disk version
+        backend
+            .did_open(DidOpenTextDocumentParams {
+                text_document: TextDocumentItem::new(
+                    a.clone(),
+                    "typescript".to_string(),
+                    1,
+                    "export function fromBuffer() {}".to_string(),
+                ),
+            })
+            .await;
+        assert!(exported("fromDisk").is_empty());
+        assert_eq!(exported("fromBuffer").len(), 1);
+
+        backend
+            .did_close(DidCloseTextDocumentParams {
+                text_document: TextDocumentIdentifier::new(a),
+            })
+            .await;
+        assert_eq!(exported("fromDisk").len(), 1);
+        assert!(exported("fromBuffer").is_empty());
+    }
+}
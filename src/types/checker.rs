@@ -5,9 +5,19 @@
 
 use std::collections::HashMap;
 
-use super::types::{Type, TypeId};
+use tree_sitter::Node;
+
+use crate::analysis::{SymbolFlags, SymbolId, SymbolTable};
+use crate::line_index::LineIndex;
+use crate::resolution::tsconfig::CompilerOptions;
+
+use super::types::{
+    ConditionalType, FunctionType, IndexSignature, IndexedAccessType, MappedType, ObjectType,
+    Parameter, Property, Type, TypeId, TypeParameter, TypeReference,
+};
 
 /// The type checker - performs type inference and checking
+#[derive(Clone)]
 pub struct TypeChecker {
     /// Type cache - maps type ids to types
     types: HashMap<TypeId, Type>,
@@ -15,9 +25,38 @@ pub struct TypeChecker {
     next_id: u32,
     /// Built-in types
     builtin_types: BuiltinTypes,
+    /// Declared type aliases, keyed by name
+    type_aliases: HashMap<String, TypeAlias>,
+    /// Aliases currently being expanded (guards against recursive aliases)
+    resolving: Vec<String>,
+    /// References expanded by `resolve_named_reference`, keyed by the
+    /// printed reference
+    resolved_references: HashMap<String, Type>,
+    /// Whether `null` and `undefined` are kept out of other types
+    strict_null_checks: bool,
+}
+
+/// Symbols whose inferred type is the declared type of the same name
+const TYPE_SYMBOLS: SymbolFlags = SymbolFlags::CLASS
+    .union(SymbolFlags::INTERFACE)
+    .union(SymbolFlags::TYPE_ALIAS);
+
+/// A control-flow guard that narrows the type of a reference
+#[derive(Debug, Clone)]
+pub enum NarrowingGuard {
+    /// `x.property === value` where `value` is a literal type
+    PropertyEqual { property: String, value: Type },
+}
+
+/// A generic type alias declaration: `type Name<T, ...> = ty`
+#[derive(Debug, Clone)]
+struct TypeAlias {
+    type_parameters: Vec<TypeParameter>,
+    ty: Type,
 }
 
 /// Pre-defined builtin types
+#[derive(Clone)]
 struct BuiltinTypes {
     any: TypeId,
     unknown: TypeId,
@@ -34,6 +73,7 @@ struct BuiltinTypes {
 }
 
 impl TypeChecker {
+    /// A checker with `strictNullChecks` on
     pub fn new() -> Self {
         let mut checker = Self {
             types: HashMap::new(),
@@ -52,14 +92,33 @@ impl TypeChecker {
                 bigint: TypeId::new(10),
                 object: TypeId::new(11),
             },
+            type_aliases: HashMap::new(),
+            resolving: Vec::new(),
+            resolved_references: HashMap::new(),
+            strict_null_checks: true,
         };
 
         // Register builtin types
         checker.register_builtin_types();
+        checker.register_builtin_aliases();
+
+        checker
+    }
 
+    /// A checker following the `strictNullChecks` (or `strict`) option
+    pub fn with_options(options: &CompilerOptions) -> Self {
+        let mut checker = Self::new();
+        checker.strict_null_checks = options
+            .strict_null_checks
+            .or(options.strict)
+            .unwrap_or(false);
         checker
     }
 
+    pub fn strict_null_checks(&self) -> bool {
+        self.strict_null_checks
+    }
+
     fn register_builtin_types(&mut self) {
         self.types.insert(self.builtin_types.any, Type::Any);
         self.types.insert(self.builtin_types.unknown, Type::Unknown);
@@ -81,6 +140,32 @@ impl TypeChecker {
         self.next_id = 12;
     }
 
+    fn register_builtin_aliases(&mut self) {
+        let t = || {
+            Box::new(Type::TypeParameter(TypeParameter {
+                name: "T".to_string(),
+                constraint: None,
+                default: None,
+            }))
+        };
+
+        // type NonNullable<T> = T extends null | undefined ? never : T
+        self.declare_type_alias(
+            "NonNullable",
+            vec![TypeParameter {
+                name: "T".to_string(),
+                constraint: None,
+                default: None,
+            }],
+            Type::Conditional(ConditionalType {
+                check_type: t(),
+                extends_type: Box::new(Type::Union(vec![Type::Null, Type::Undefined])),
+                true_type: Box::new(Type::Never),
+                false_type: t(),
+            }),
+        );
+    }
+
     /// Get the any type
     pub fn any_type(&self) -> TypeId {
         self.builtin_types.any
@@ -199,6 +284,394 @@ impl TypeChecker {
         self.create_type(Type::Intersection(members))
     }
 
+    /// Declare a (possibly generic) type alias
+    pub fn declare_type_alias(
+        &mut self,
+        name: &str,
+        type_parameters: Vec<TypeParameter>,
+        ty: Type,
+    ) {
+        self.type_aliases.insert(
+            name.to_string(),
+            TypeAlias {
+                type_parameters,
+                ty,
+            },
+        );
+    }
+
+    /// Resolve a type reference such as `NonNullable<string | null>`.
+    ///
+    /// Declared aliases are expanded with their type arguments bound to the
+    /// alias' type parameters. Unknown (or recursively referenced) names are
+    /// kept as an opaque `Type::Reference`.
+    pub fn resolve_reference(&mut self, reference: &TypeReference) -> TypeId {
+        let alias = match self.type_aliases.get(&reference.name) {
+            Some(alias) if !self.resolving.contains(&reference.name) => alias.clone(),
+            _ => return self.create_type(Type::Reference(reference.clone())),
+        };
+
+        let mut context = HashMap::new();
+        for (i, param) in alias.type_parameters.iter().enumerate() {
+            let arg = match (reference.type_arguments.get(i), &param.default) {
+                (Some(arg), _) => arg.clone(),
+                (None, Some(default)) => (**default).clone(),
+                (None, None) => Type::Unknown,
+            };
+            let arg = self.substitute(&arg, &context);
+            let id = self.create_type(arg);
+            context.insert(param.name.clone(), id);
+        }
+
+        self.resolving.push(reference.name.clone());
+        let resolved = self.substitute(&alias.ty, &context);
+        self.resolving.pop();
+
+        self.create_type(resolved)
+    }
+
+    /// Resolve a named reference to the type it stands for in a document:
+    /// `Array<T>` becomes an array type, and interfaces, type aliases and
+    /// classes declared in `symbol_table` (or built in) are expanded.
+    ///
+    /// References nested in the expansion are resolved too, except for the
+    /// ones a recursive alias makes to itself; those stay opaque.
+    pub fn resolve_named_reference(
+        &mut self,
+        reference: &TypeReference,
+        symbol_table: &SymbolTable,
+    ) -> TypeId {
+        let resolved = self.expand_reference(reference, symbol_table);
+        self.create_type(resolved)
+    }
+
+    /// Check assignability after resolving the named references on both
+    /// sides against `symbol_table`
+    pub fn is_assignable_in(
+        &mut self,
+        source: TypeId,
+        target: TypeId,
+        symbol_table: &SymbolTable,
+    ) -> bool {
+        if source == target {
+            return true;
+        }
+        let (Some(source), Some(target)) = (self.get_type(source), self.get_type(target)) else {
+            return false;
+        };
+        let (source, target) = (source.clone(), target.clone());
+        let source = self.expand_references(&source, symbol_table);
+        let target = self.expand_references(&target, symbol_table);
+        source.is_assignable_with(&target, self.strict_null_checks)
+    }
+
+    /// `ty` with the references in it (and in arrays, tuples, unions and
+    /// intersections of it) resolved
+    fn expand_references(&mut self, ty: &Type, symbol_table: &SymbolTable) -> Type {
+        let mut expand_all = |types: &[Type]| -> Vec<Type> {
+            types
+                .iter()
+                .map(|ty| self.expand_references(ty, symbol_table))
+                .collect()
+        };
+        match ty {
+            Type::Reference(reference) => self.expand_reference(reference, symbol_table),
+            Type::Array(element) => {
+                Type::Array(Box::new(self.expand_references(element, symbol_table)))
+            }
+            Type::Tuple(elements) => Type::Tuple(expand_all(elements)),
+            Type::Union(members) => Type::Union(expand_all(members)),
+            Type::Intersection(members) => Type::Intersection(expand_all(members)),
+            other => other.clone(),
+        }
+    }
+
+    fn expand_reference(&mut self, reference: &TypeReference, symbol_table: &SymbolTable) -> Type {
+        let opaque = Type::Reference(reference.clone());
+        if self.resolving.contains(&reference.name) {
+            return opaque;
+        }
+        if let ("Array" | "ReadonlyArray", [element]) =
+            (reference.name.as_str(), reference.type_arguments.as_slice())
+        {
+            return Type::Array(Box::new(self.expand_references(element, symbol_table)));
+        }
+
+        let key = super::printer::print_type(&opaque);
+        if let Some(cached) = self.resolved_references.get(&key) {
+            return cached.clone();
+        }
+
+        let root = symbol_table.root_scope_id();
+        let declared = symbol_table
+            .lookup_type(&reference.name, root)
+            .or_else(|| symbol_table.lookup(&reference.name, root))
+            .and_then(|id| symbol_table.get_symbol(id))
+            .is_some_and(|symbol| symbol.is_type());
+        if !(declared || self.type_aliases.contains_key(&reference.name)) {
+            return opaque;
+        }
+
+        let alias = self.resolve_reference(reference);
+        let Some(alias) = self.get_type(alias).cloned() else {
+            return opaque;
+        };
+        self.resolving.push(reference.name.clone());
+        let resolved = self.expand_references(&alias, symbol_table);
+        self.resolving.pop();
+
+        // Expansions made under a recursion guard may be partial
+        if self.resolving.is_empty() {
+            self.resolved_references.insert(key, resolved.clone());
+        }
+        resolved
+    }
+
+    /// Instantiate a type by substituting type parameters from `context`.
+    /// Bare references such as the `T` of `Array<T>` are substituted like
+    /// type parameters; a function's own type parameters shadow `context`.
+    ///
+    /// Conditional types encountered along the way are evaluated once their
+    /// check type is known.
+    pub fn instantiate(&mut self, ty: TypeId, context: &HashMap<String, TypeId>) -> TypeId {
+        let ty = match self.get_type(ty) {
+            Some(ty) => ty.clone(),
+            None => return ty,
+        };
+        let instantiated = self.substitute(&ty, context);
+        self.create_type(instantiated)
+    }
+
+    /// Evaluate a conditional type `check extends extends ? true : false`.
+    ///
+    /// Returns the id of the selected branch. If the check type is a type
+    /// parameter that is not bound in `context`, the conditional is deferred
+    /// and returned as a new unresolved `Conditional` type. Conditionals over
+    /// a bound type parameter distribute over unions, as in TypeScript.
+    pub fn evaluate_conditional(
+        &mut self,
+        cond: &ConditionalType,
+        context: &HashMap<String, TypeId>,
+    ) -> TypeId {
+        // Distributive case: `T extends U ? X : Y` with `T = A | B`
+        if let Type::TypeParameter(param) = cond.check_type.as_ref() {
+            let bound = context.get(&param.name).and_then(|id| self.get_type(*id));
+            if let Some(Type::Union(members)) = bound {
+                let members = members.clone();
+                let mut results = Vec::new();
+                for member in members {
+                    let member_id = self.create_type(member);
+                    let mut member_context = context.clone();
+                    member_context.insert(param.name.clone(), member_id);
+                    let result = self.evaluate_conditional(cond, &member_context);
+                    if !matches!(self.get_type(result), Some(Type::Never)) {
+                        results.push(result);
+                    }
+                }
+                if results.is_empty() {
+                    return self.never_type();
+                }
+                return self.union_type(results);
+            }
+        }
+
+        let check_type = self.substitute(&cond.check_type, context);
+        let extends_type = self.substitute(&cond.extends_type, context);
+
+        if self.is_generic(&check_type) || self.is_generic(&extends_type) {
+            let true_type = self.substitute(&cond.true_type, context);
+            let false_type = self.substitute(&cond.false_type, context);
+            return self.create_type(Type::Conditional(ConditionalType {
+                check_type: Box::new(check_type),
+                extends_type: Box::new(extends_type),
+                true_type: Box::new(true_type),
+                false_type: Box::new(false_type),
+            }));
+        }
+
+        let branch = if check_type.is_assignable_to(&extends_type) {
+            &cond.true_type
+        } else {
+            &cond.false_type
+        };
+        let result = self.substitute(branch, context);
+        self.create_type(result)
+    }
+
+    /// Whether `ty` still depends on a type parameter, which includes
+    /// references to names that are not declared (such as the `T` of a
+    /// generic function read without its type parameter scope)
+    fn is_generic(&self, ty: &Type) -> bool {
+        match ty {
+            Type::TypeParameter(_) => true,
+            Type::Reference(reference) => {
+                let known = self.type_aliases.contains_key(&reference.name)
+                    || matches!(
+                        reference.name.as_str(),
+                        "Array" | "ReadonlyArray" | "Promise"
+                    );
+                !known
+                    || reference
+                        .type_arguments
+                        .iter()
+                        .any(|arg| self.is_generic(arg))
+            }
+            Type::Array(inner) | Type::Index(inner) => self.is_generic(inner),
+            Type::Tuple(types) | Type::Union(types) | Type::Intersection(types) => {
+                types.iter().any(|ty| self.is_generic(ty))
+            }
+            Type::IndexedAccess(access) => {
+                self.is_generic(&access.object_type) || self.is_generic(&access.index_type)
+            }
+            Type::Conditional(_) | Type::Mapped(_) => true,
+            _ => false,
+        }
+    }
+
+    /// Substitute type parameters in `ty`, resolving aliases and evaluating
+    /// conditionals as they become concrete
+    fn substitute(&mut self, ty: &Type, context: &HashMap<String, TypeId>) -> Type {
+        match ty {
+            Type::TypeParameter(param) => match context.get(&param.name) {
+                Some(id) => self.get_type(*id).cloned().unwrap_or(Type::Unknown),
+                None => ty.clone(),
+            },
+            Type::Reference(reference) => {
+                if reference.type_arguments.is_empty() {
+                    if let Some(id) = context.get(&reference.name) {
+                        return self.get_type(*id).cloned().unwrap_or(Type::Unknown);
+                    }
+                }
+                let reference = TypeReference {
+                    name: reference.name.clone(),
+                    type_arguments: reference
+                        .type_arguments
+                        .iter()
+                        .map(|arg| self.substitute(arg, context))
+                        .collect(),
+                };
+                let id = self.resolve_reference(&reference);
+                self.get_type(id).cloned().unwrap_or(Type::Unknown)
+            }
+            Type::Array(element) => Type::Array(Box::new(self.substitute(element, context))),
+            Type::Tuple(elements) => Type::Tuple(
+                elements
+                    .iter()
+                    .map(|e| self.substitute(e, context))
+                    .collect(),
+            ),
+            Type::Union(members) => Type::Union(
+                members
+                    .iter()
+                    .map(|m| self.substitute(m, context))
+                    .collect(),
+            ),
+            Type::Intersection(members) => Type::Intersection(
+                members
+                    .iter()
+                    .map(|m| self.substitute(m, context))
+                    .collect(),
+            ),
+            Type::Function(func) => Type::Function(self.substitute_function(func, context)),
+            Type::Object(obj) => Type::Object(ObjectType {
+                properties: obj
+                    .properties
+                    .iter()
+                    .map(|(name, prop)| {
+                        let prop = Property {
+                            name: prop.name.clone(),
+                            ty: Box::new(self.substitute(&prop.ty, context)),
+                            optional: prop.optional,
+                            readonly: prop.readonly,
+                        };
+                        (name.clone(), prop)
+                    })
+                    .collect(),
+                index_signatures: obj
+                    .index_signatures
+                    .iter()
+                    .map(|sig| IndexSignature {
+                        key_type: Box::new(self.substitute(&sig.key_type, context)),
+                        value_type: Box::new(self.substitute(&sig.value_type, context)),
+                        readonly: sig.readonly,
+                    })
+                    .collect(),
+                call_signatures: obj
+                    .call_signatures
+                    .iter()
+                    .map(|sig| self.substitute_function(sig, context))
+                    .collect(),
+                construct_signatures: obj
+                    .construct_signatures
+                    .iter()
+                    .map(|sig| self.substitute_function(sig, context))
+                    .collect(),
+            }),
+            Type::Conditional(cond) => {
+                let id = self.evaluate_conditional(cond, context);
+                self.get_type(id).cloned().unwrap_or(Type::Unknown)
+            }
+            Type::Mapped(mapped) => {
+                let mut inner = context.clone();
+                inner.remove(&mapped.type_parameter);
+                Type::Mapped(MappedType {
+                    type_parameter: mapped.type_parameter.clone(),
+                    constraint: Box::new(self.substitute(&mapped.constraint, context)),
+                    template_type: Box::new(self.substitute(&mapped.template_type, &inner)),
+                    readonly_modifier: mapped.readonly_modifier,
+                    optional_modifier: mapped.optional_modifier,
+                })
+            }
+            Type::Index(inner) => {
+                let inner = self.substitute(inner, context);
+                evaluate_keyof(&inner).unwrap_or_else(|| Type::Index(Box::new(inner)))
+            }
+            Type::IndexedAccess(access) => {
+                let object_type = self.substitute(&access.object_type, context);
+                let index_type = self.substitute(&access.index_type, context);
+                evaluate_indexed_access(&object_type, &index_type).unwrap_or_else(|| {
+                    Type::IndexedAccess(IndexedAccessType {
+                        object_type: Box::new(object_type),
+                        index_type: Box::new(index_type),
+                    })
+                })
+            }
+            _ => ty.clone(),
+        }
+    }
+
+    /// Substitute within a function signature. The function's own type
+    /// parameters shadow outer bindings of the same name.
+    fn substitute_function(
+        &mut self,
+        func: &FunctionType,
+        context: &HashMap<String, TypeId>,
+    ) -> FunctionType {
+        let mut inner = context.clone();
+        for param in &func.type_parameters {
+            inner.remove(&param.name);
+        }
+
+        FunctionType {
+            type_parameters: func.type_parameters.clone(),
+            parameters: func
+                .parameters
+                .iter()
+                .map(|p| Parameter {
+                    name: p.name.clone(),
+                    ty: Box::new(self.substitute(&p.ty, &inner)),
+                    optional: p.optional,
+                    rest: p.rest,
+                })
+                .collect(),
+            return_type: Box::new(self.substitute(&func.return_type, &inner)),
+            this_type: func
+                .this_type
+                .as_ref()
+                .map(|t| Box::new(self.substitute(t, &inner))),
+        }
+    }
+
     /// Check if one type is assignable to another
     pub fn is_assignable_to(&self, source: TypeId, target: TypeId) -> bool {
         if source == target {
@@ -215,7 +688,894 @@ impl TypeChecker {
             None => return false,
         };
 
-        source_type.is_assignable_to(target_type)
+        source_type.is_assignable_with(target_type, self.strict_null_checks)
+    }
+
+    /// Narrow a union by a discriminant property check.
+    ///
+    /// Keeps the object members whose `property` type is assignable to the
+    /// guard value; returns `never` when no member survives. Non-union types
+    /// are returned unchanged.
+    pub fn narrow_union_by_property(&mut self, union_id: TypeId, guard: &NarrowingGuard) -> TypeId {
+        let members = match self.get_type(union_id) {
+            Some(Type::Union(members)) => members.clone(),
+            _ => return union_id,
+        };
+
+        let NarrowingGuard::PropertyEqual { property, value } = guard;
+        let kept: Vec<TypeId> = members
+            .into_iter()
+            .filter(|member| match member {
+                Type::Object(obj) => obj
+                    .properties
+                    .get(property)
+                    .is_some_and(|prop| prop.ty.is_assignable_to(value)),
+                _ => false,
+            })
+            .map(|member| self.create_type(member))
+            .collect();
+
+        if kept.is_empty() {
+            return self.never_type();
+        }
+        self.union_type(kept)
+    }
+
+    /// Register the interfaces and type aliases declared anywhere in a tree
+    pub fn declare_types_from_tree(&mut self, root: Node, source: &str) {
+        let mut cursor = root.walk();
+        for child in root.children(&mut cursor) {
+            match child.kind() {
+                "type_alias_declaration"
+                | "interface_declaration"
+                | "class_declaration"
+                | "abstract_class_declaration" => {
+                    self.declare_type_from_node(child, source);
+                }
+                "export_statement" => {
+                    if let Some(decl) = child.child_by_field_name("declaration") {
+                        self.declare_type_from_node(decl, source);
+                    }
+                }
+                _ => {
+                    if child.named_child_count() > 0 {
+                        self.declare_types_from_tree(child, source);
+                    }
+                }
+            }
+        }
+    }
+
+    fn declare_type_from_node(&mut self, node: Node, source: &str) {
+        let Some(name) = node
+            .child_by_field_name("name")
+            .and_then(|n| n.utf8_text(source.as_bytes()).ok())
+        else {
+            return;
+        };
+
+        let type_parameters = node
+            .child_by_field_name("type_parameters")
+            .map(|params| self.type_parameters_from_node(params, source, &[]))
+            .unwrap_or_default();
+        let scope: Vec<String> = type_parameters.iter().map(|p| p.name.clone()).collect();
+
+        let ty = match node.kind() {
+            "type_alias_declaration" => match node.child_by_field_name("value") {
+                Some(value) => self.node_to_type(value, source, &scope),
+                None => return,
+            },
+            "interface_declaration" => {
+                let body = match node.child_by_field_name("body") {
+                    Some(body) => self.node_to_type(body, source, &scope),
+                    None => Type::Object(ObjectType::default()),
+                };
+
+                // `interface A extends B, C { ... }` is modelled as `B & C & { ... }`
+                let mut cursor = node.walk();
+                let bases: Vec<Type> = node
+                    .children(&mut cursor)
+                    .filter(|c| c.kind() == "extends_type_clause")
+                    .flat_map(|clause| {
+                        let mut cursor = clause.walk();
+                        clause
+                            .children_by_field_name("type", &mut cursor)
+                            .collect::<Vec<_>>()
+                    })
+                    .map(|base| self.node_to_type(base, source, &scope))
+                    .collect();
+
+                // Declaration merging: later members extend earlier declarations
+                let body = match (self.type_aliases.get(name).map(|a| &a.ty), body) {
+                    (Some(Type::Object(existing)), Type::Object(mut obj)) => {
+                        for (key, prop) in &existing.properties {
+                            obj.properties
+                                .entry(key.clone())
+                                .or_insert_with(|| prop.clone());
+                        }
+                        Type::Object(obj)
+                    }
+                    (_, body) => body,
+                };
+
+                if bases.is_empty() {
+                    body
+                } else {
+                    let mut members = bases;
+                    members.push(body);
+                    Type::Intersection(members)
+                }
+            }
+            "class_declaration" | "abstract_class_declaration" => {
+                self.class_instance_type(node, source, &scope)
+            }
+            _ => return,
+        };
+
+        self.declare_type_alias(name, type_parameters, ty);
+    }
+
+    /// Build the instance type of a class from its fields, accessors and methods.
+    /// `class A extends B { ... }` is modelled as `B & { ... }`.
+    fn class_instance_type(&self, node: Node, source: &str, scope: &[String]) -> Type {
+        let mut obj = ObjectType::default();
+        // Setters only count when the class has no getter for the name
+        let mut setters = Vec::new();
+
+        if let Some(body) = node.child_by_field_name("body") {
+            let mut cursor = body.walk();
+            for member in body.named_children(&mut cursor) {
+                if !matches!(
+                    member.kind(),
+                    "public_field_definition" | "method_definition"
+                ) || has_child(member, "static")
+                {
+                    continue;
+                }
+                let Some(name) = member.child_by_field_name("name") else {
+                    continue;
+                };
+                let name = text_of(name, source)
+                    .trim_matches(|c| c == '"' || c == '\'')
+                    .to_string();
+                if name == "constructor" {
+                    self.add_parameter_properties(member, source, scope, &mut obj);
+                    continue;
+                }
+                if obj.properties.contains_key(&name) {
+                    continue;
+                }
+
+                let ty = if member.kind() == "public_field_definition" {
+                    member
+                        .child_by_field_name("type")
+                        .map(|t| self.node_to_type(t, source, scope))
+                        .unwrap_or(Type::Any)
+                } else if has_child(member, "get") {
+                    member
+                        .child_by_field_name("return_type")
+                        .map(|t| self.node_to_type(t, source, scope))
+                        .unwrap_or(Type::Any)
+                } else if has_child(member, "set") {
+                    setters.push((name, member));
+                    continue;
+                } else {
+                    Type::Function(self.signature_from_node(member, source, scope))
+                };
+
+                obj.properties.insert(
+                    name.clone(),
+                    Property {
+                        name,
+                        ty: Box::new(ty),
+                        optional: has_child(member, "?"),
+                        readonly: has_child(member, "readonly"),
+                    },
+                );
+            }
+        }
+
+        for (name, setter) in setters {
+            if obj.properties.contains_key(&name) {
+                continue;
+            }
+            let ty = setter
+                .child_by_field_name("parameters")
+                .and_then(|parameters| parameters.named_child(0))
+                .and_then(|parameter| parameter.child_by_field_name("type"))
+                .map(|t| self.node_to_type(t, source, scope))
+                .unwrap_or(Type::Any);
+            obj.properties.insert(
+                name.clone(),
+                Property {
+                    name,
+                    ty: Box::new(ty),
+                    optional: false,
+                    readonly: false,
+                },
+            );
+        }
+
+        let extends = node
+            .children(&mut node.walk())
+            .find(|c| c.kind() == "class_heritage")
+            .and_then(|heritage| {
+                heritage
+                    .children(&mut heritage.walk())
+                    .find(|c| c.kind() == "extends_clause")
+            })
+            .and_then(|clause| clause.child_by_field_name("value"));
+
+        match extends {
+            Some(base) if base.kind() == "identifier" => Type::Intersection(vec![
+                Type::Reference(TypeReference {
+                    name: text_of(base, source).to_string(),
+                    type_arguments: Vec::new(),
+                }),
+                Type::Object(obj),
+            ]),
+            // `extends mixin(Base)` or `extends ns.Base` inherit members that
+            // can't be resolved here, so the instance type stays open
+            Some(_) => Type::Intersection(vec![Type::Any, Type::Object(obj)]),
+            None => Type::Object(obj),
+        }
+    }
+
+    /// Add the properties declared by `constructor(private x: T)` parameters
+    fn add_parameter_properties(
+        &self,
+        constructor: Node,
+        source: &str,
+        scope: &[String],
+        obj: &mut ObjectType,
+    ) {
+        let Some(parameters) = constructor.child_by_field_name("parameters") else {
+            return;
+        };
+        let mut cursor = parameters.walk();
+        for parameter in parameters.named_children(&mut cursor) {
+            if !has_child(parameter, "accessibility_modifier")
+                && !has_child(parameter, "readonly")
+                && !has_child(parameter, "override_modifier")
+            {
+                continue;
+            }
+            let Some(pattern) = parameter
+                .child_by_field_name("pattern")
+                .filter(|pattern| pattern.kind() == "identifier")
+            else {
+                continue;
+            };
+            let name = text_of(pattern, source).to_string();
+            let ty = parameter
+                .child_by_field_name("type")
+                .map(|t| self.node_to_type(t, source, scope))
+                .unwrap_or(Type::Any);
+            obj.properties.insert(
+                name.clone(),
+                Property {
+                    name,
+                    ty: Box::new(ty),
+                    optional: parameter.kind() == "optional_parameter",
+                    readonly: has_child(parameter, "readonly"),
+                },
+            );
+        }
+    }
+
+    /// Record the type of every class, interface and type alias, and of
+    /// variables that are annotated or initialized with `new C()`, an array
+    /// or a string, in `symbol_table`. Classes get their instance type.
+    /// Call after `declare_types_from_tree`.
+    pub fn infer_symbol_types(&mut self, symbol_table: &mut SymbolTable, root: Node, source: &str) {
+        let index = LineIndex::new(source);
+        let empty = HashMap::new();
+        let mut symbols: Vec<(SymbolId, String, SymbolFlags, tower_lsp::lsp_types::Range)> =
+            symbol_table
+                .all_symbols()
+                .map(|s| (s.id, s.name.clone(), s.flags, s.name_range))
+                .collect();
+        // Types first, so that `new C()` initializers can see the class type
+        symbols.sort_by_key(|(id, _, flags, _)| (!flags.intersects(TYPE_SYMBOLS), id.0));
+
+        for (id, name, flags, name_range) in symbols {
+            if flags.intersects(SymbolFlags::FUNCTION | SymbolFlags::METHOD) {
+                let overloads = self.overloads_of(root, &name, name_range, source);
+                if let Some(symbol) = symbol_table.get_symbol_mut(id) {
+                    symbol.overloads = overloads;
+                }
+                continue;
+            }
+
+            let inferred = if flags.intersects(TYPE_SYMBOLS) {
+                if !self.type_aliases.contains_key(&name) {
+                    continue;
+                }
+                self.resolve_reference(&TypeReference {
+                    name,
+                    type_arguments: Vec::new(),
+                })
+            } else if flags.intersects(SymbolFlags::VARIABLE | SymbolFlags::PARAMETER) {
+                let start = index.point(name_range.start);
+                let end = index.point(name_range.end);
+                let Some(declaration) = root
+                    .descendant_for_point_range(start, end)
+                    .and_then(|n| n.parent())
+                else {
+                    continue;
+                };
+
+                if let Some(annotation) = declaration.child_by_field_name("type") {
+                    let declared = self.type_from_node(annotation, source);
+                    self.instantiate(declared, &empty)
+                } else {
+                    let scope_id = symbol_table.scope_at_position(name_range.start);
+                    let Some(ty) = declaration.child_by_field_name("value").and_then(|value| {
+                        self.infer_expression(value, source, symbol_table, scope_id)
+                    }) else {
+                        continue;
+                    };
+                    ty
+                }
+            } else {
+                continue;
+            };
+
+            if let Some(symbol) = symbol_table.get_symbol_mut(id) {
+                symbol.inferred_type = Some(inferred);
+            }
+        }
+    }
+
+    /// Infer the type of an expression, such as a variable initializer, from
+    /// its syntax and the types recorded in `symbol_table` by
+    /// `infer_symbol_types`. Literals are widened as a declaration would.
+    pub fn infer_from_node(
+        &mut self,
+        node: Node,
+        source: &str,
+        symbol_table: &SymbolTable,
+    ) -> Option<TypeId> {
+        let position = LineIndex::new(source).position(node.start_byte());
+        let scope_id = symbol_table.scope_at_position(position);
+        self.infer_expression(node, source, symbol_table, scope_id)
+    }
+
+    fn infer_expression(
+        &mut self,
+        node: Node,
+        source: &str,
+        symbol_table: &SymbolTable,
+        scope_id: u32,
+    ) -> Option<TypeId> {
+        let symbol_named = |name: Node| {
+            symbol_table
+                .lookup(text_of(name, source), scope_id)
+                .and_then(|id| symbol_table.get_symbol(id))
+        };
+
+        match node.kind() {
+            "parenthesized_expression" => {
+                return self.infer_expression(node.named_child(0)?, source, symbol_table, scope_id);
+            }
+            "new_expression" => {
+                // Instances of declared classes, falling back to built-ins
+                let class = node
+                    .child_by_field_name("constructor")
+                    .and_then(symbol_named)
+                    .filter(|class| class.flags.contains(SymbolFlags::CLASS));
+                if let Some(ty) = class.and_then(|class| class.inferred_type) {
+                    return Some(ty);
+                }
+            }
+            "array" => {
+                // The union of the (widened) element types, `any[]` when
+                // the array is empty or an element can't be inferred
+                let mut elements: Vec<TypeId> = Vec::new();
+                let mut cursor = node.walk();
+                for element in node.named_children(&mut cursor) {
+                    if element.kind() == "comment" {
+                        continue;
+                    }
+                    let Some(ty) = (element.kind() != "spread_element")
+                        .then(|| self.infer_expression(element, source, symbol_table, scope_id))
+                        .flatten()
+                    else {
+                        elements.clear();
+                        break;
+                    };
+                    let printed = self.get_type(ty).map(crate::types::printer::print_type);
+                    if !elements.iter().any(|seen| {
+                        *seen == ty
+                            || self.get_type(*seen).map(crate::types::printer::print_type)
+                                == printed
+                    }) {
+                        elements.push(ty);
+                    }
+                }
+                let element = match elements.len() {
+                    0 => self.any_type(),
+                    _ => self.union_type(elements),
+                };
+                return Some(self.array_type(element));
+            }
+            "string" | "template_string" => return Some(self.string_type()),
+            "arrow_function" | "function_expression" => {
+                let signature = self.signature_from_node(node, source, &[]);
+                return Some(self.create_type(Type::Function(signature)));
+            }
+            "identifier" => {
+                let symbol =
+                    symbol_named(node).filter(|symbol| !symbol.flags.intersects(TYPE_SYMBOLS))?;
+                return symbol.inferred_type;
+            }
+            "call_expression" => return self.infer_call(node, source, symbol_table, scope_id),
+            _ => {}
+        }
+
+        let literal = self.literal_type(node, source)?;
+        Some(match literal {
+            Type::NumberLiteral(_) => self.number_type(),
+            Type::BooleanLiteral(_) => self.boolean_type(),
+            other => self.create_type(other),
+        })
+    }
+
+    /// Declared return type of a called function, or of the function held
+    /// by the called variable
+    fn infer_call(
+        &mut self,
+        node: Node,
+        source: &str,
+        symbol_table: &SymbolTable,
+        scope_id: u32,
+    ) -> Option<TypeId> {
+        let callee = node
+            .child_by_field_name("function")
+            .filter(|f| f.kind() == "identifier")?;
+        let symbol =
+            symbol_table.get_symbol(symbol_table.lookup(text_of(callee, source), scope_id)?)?;
+        if !symbol
+            .flags
+            .intersects(SymbolFlags::FUNCTION | SymbolFlags::VARIABLE)
+        {
+            return None;
+        }
+
+        let mut root = node;
+        while let Some(parent) = root.parent() {
+            root = parent;
+        }
+        let index = LineIndex::new(source);
+        let start = index.point(symbol.name_range.start);
+        let end = index.point(symbol.name_range.end);
+        let mut declaration = root.descendant_for_point_range(start, end)?.parent()?;
+        if declaration.kind() == "variable_declarator" {
+            declaration = declaration.child_by_field_name("value")?;
+        }
+        let return_type = declaration.child_by_field_name("return_type")?;
+        Some(self.type_from_node(return_type, source))
+    }
+
+    /// Overload signatures (`function f(a: A): R;` or `m(a: A): R;` in a class)
+    /// declared next to the implementation whose name is at `name_range`
+    fn overloads_of(
+        &self,
+        root: Node,
+        name: &str,
+        name_range: tower_lsp::lsp_types::Range,
+        source: &str,
+    ) -> Vec<FunctionType> {
+        let index = LineIndex::new(source);
+        let start = index.point(name_range.start);
+        let end = index.point(name_range.end);
+        let Some(mut container) = root
+            .descendant_for_point_range(start, end)
+            .and_then(|name| name.parent())
+            .and_then(|declaration| declaration.parent())
+        else {
+            return Vec::new();
+        };
+        if container.kind() == "export_statement" {
+            let Some(parent) = container.parent() else {
+                return Vec::new();
+            };
+            container = parent;
+        }
+
+        let mut cursor = container.walk();
+        container
+            .named_children(&mut cursor)
+            .map(|child| match child.kind() {
+                "export_statement" => child.child_by_field_name("declaration").unwrap_or(child),
+                _ => child,
+            })
+            .filter(|child| matches!(child.kind(), "function_signature" | "method_signature"))
+            .filter(|child| {
+                child
+                    .child_by_field_name("name")
+                    .is_some_and(|n| text_of(n, source) == name)
+            })
+            .map(|child| self.signature_from_node(child, source, &[]))
+            .collect()
+    }
+
+    /// Type of a literal expression, or of a built-in constructor call
+    /// such as `new Date()`, without consulting any declarations
+    pub fn literal_type(&self, node: Node, source: &str) -> Option<Type> {
+        let text = text_of(node, source);
+        match node.kind() {
+            "string" => Some(Type::StringLiteral(
+                text.get(1..text.len().saturating_sub(1))
+                    .unwrap_or("")
+                    .to_string(),
+            )),
+            "template_string" => Some(Type::String),
+            "number" => Some(
+                text.parse()
+                    .map(Type::NumberLiteral)
+                    .unwrap_or(Type::Number),
+            ),
+            "true" | "false" => Some(Type::BooleanLiteral(text == "true")),
+            "array" => Some(Type::Array(Box::new(Type::Any))),
+            "parenthesized_expression" => self.literal_type(node.named_child(0)?, source),
+            "new_expression" => {
+                let constructor = node.child_by_field_name("constructor")?;
+                matches!(
+                    text_of(constructor, source),
+                    "Date" | "Map" | "Set" | "RegExp"
+                )
+                .then(|| {
+                    Type::Reference(TypeReference {
+                        name: text_of(constructor, source).to_string(),
+                        type_arguments: Vec::new(),
+                    })
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// Build a type from a tree-sitter type node (or a `type_annotation`)
+    pub fn type_from_node(&mut self, node: Node, source: &str) -> TypeId {
+        match self.node_to_type(node, source, &[]) {
+            // `string extends string ? A : B` is just `A`
+            Type::Conditional(cond) => self.evaluate_conditional(&cond, &HashMap::new()),
+            // `keyof { a: number }` is `"a"`
+            ty @ (Type::Index(_) | Type::IndexedAccess(_)) => {
+                let evaluated = self.substitute(&ty, &HashMap::new());
+                self.create_type(evaluated)
+            }
+            ty => self.create_type(ty),
+        }
+    }
+
+    fn type_parameters_from_node(
+        &self,
+        node: Node,
+        source: &str,
+        scope: &[String],
+    ) -> Vec<TypeParameter> {
+        let mut cursor = node.walk();
+        let params: Vec<Node> = node
+            .named_children(&mut cursor)
+            .filter(|c| c.kind() == "type_parameter")
+            .collect();
+
+        let mut scope = scope.to_vec();
+        scope.extend(
+            params
+                .iter()
+                .filter_map(|p| p.child_by_field_name("name"))
+                .map(|n| text_of(n, source).to_string()),
+        );
+
+        params
+            .iter()
+            .filter_map(|param| {
+                let name = text_of(param.child_by_field_name("name")?, source).to_string();
+                let constraint = param
+                    .child_by_field_name("constraint")
+                    .and_then(|c| c.named_child(0))
+                    .map(|c| Box::new(self.node_to_type(c, source, &scope)));
+                let default = param
+                    .child_by_field_name("value")
+                    .and_then(|c| c.named_child(0))
+                    .map(|c| Box::new(self.node_to_type(c, source, &scope)));
+                Some(TypeParameter {
+                    name,
+                    constraint,
+                    default,
+                })
+            })
+            .collect()
+    }
+
+    /// Convert a type node to a `Type`. Names in `scope` are type parameters.
+    /// Unsupported syntax converts to `any`.
+    fn node_to_type(&self, node: Node, source: &str, scope: &[String]) -> Type {
+        match node.kind() {
+            "type_annotation" | "parenthesized_type" | "constraint" | "default_type" => node
+                .named_child(0)
+                .map(|inner| self.node_to_type(inner, source, scope))
+                .unwrap_or(Type::Any),
+            "predefined_type" => match text_of(node, source) {
+                "string" => Type::String,
+                "number" => Type::Number,
+                "boolean" => Type::Boolean,
+                "any" => Type::Any,
+                "unknown" => Type::Unknown,
+                "never" => Type::Never,
+                "void" => Type::Void,
+                "undefined" => Type::Undefined,
+                "null" => Type::Null,
+                "symbol" => Type::Symbol,
+                "bigint" => Type::BigInt,
+                "object" => Type::Object(ObjectType::default()),
+                _ => Type::Any,
+            },
+            "literal_type" => match node.named_child(0) {
+                Some(lit) => match lit.kind() {
+                    "string" => Type::StringLiteral(
+                        text_of(lit, source)
+                            .trim_matches(|c| c == '"' || c == '\'')
+                            .to_string(),
+                    ),
+                    "number" | "unary_expression" => text_of(lit, source)
+                        .replace(' ', "")
+                        .parse::<f64>()
+                        .map(Type::NumberLiteral)
+                        .unwrap_or(Type::Number),
+                    "true" => Type::BooleanLiteral(true),
+                    "false" => Type::BooleanLiteral(false),
+                    "null" => Type::Null,
+                    "undefined" => Type::Undefined,
+                    _ => Type::Any,
+                },
+                None => Type::Any,
+            },
+            "type_identifier" => {
+                let name = text_of(node, source);
+                if scope.iter().any(|p| p == name) {
+                    Type::TypeParameter(TypeParameter {
+                        name: name.to_string(),
+                        constraint: None,
+                        default: None,
+                    })
+                } else {
+                    Type::Reference(TypeReference {
+                        name: name.to_string(),
+                        type_arguments: Vec::new(),
+                    })
+                }
+            }
+            "this_type" => Type::This,
+            "generic_type" => {
+                let name = node
+                    .child_by_field_name("name")
+                    .map(|n| text_of(n, source))
+                    .unwrap_or("");
+                let type_arguments: Vec<Type> = node
+                    .child_by_field_name("type_arguments")
+                    .map(|args| {
+                        let mut cursor = args.walk();
+                        args.named_children(&mut cursor)
+                            .map(|arg| self.node_to_type(arg, source, scope))
+                            .collect()
+                    })
+                    .unwrap_or_default();
+
+                match (name, type_arguments.as_slice()) {
+                    ("Array" | "ReadonlyArray", [element]) => {
+                        Type::Array(Box::new(element.clone()))
+                    }
+                    _ => Type::Reference(TypeReference {
+                        name: name.to_string(),
+                        type_arguments,
+                    }),
+                }
+            }
+            "array_type" | "readonly_type" => match node.named_child(0) {
+                Some(element) if node.kind() == "array_type" => {
+                    Type::Array(Box::new(self.node_to_type(element, source, scope)))
+                }
+                Some(inner) => self.node_to_type(inner, source, scope),
+                None => Type::Any,
+            },
+            "tuple_type" => {
+                let mut cursor = node.walk();
+                Type::Tuple(
+                    node.named_children(&mut cursor)
+                        .map(|element| match element.kind() {
+                            "optional_type" | "rest_type" => element
+                                .named_child(0)
+                                .map(|inner| self.node_to_type(inner, source, scope))
+                                .unwrap_or(Type::Any),
+                            _ => self.node_to_type(element, source, scope),
+                        })
+                        .collect(),
+                )
+            }
+            "union_type" => {
+                let mut members = Vec::new();
+                let mut cursor = node.walk();
+                for child in node.named_children(&mut cursor) {
+                    match self.node_to_type(child, source, scope) {
+                        Type::Union(inner) if child.kind() == "union_type" => members.extend(inner),
+                        ty => members.push(ty),
+                    }
+                }
+                Type::Union(members)
+            }
+            "intersection_type" => {
+                let mut members = Vec::new();
+                let mut cursor = node.walk();
+                for child in node.named_children(&mut cursor) {
+                    match self.node_to_type(child, source, scope) {
+                        Type::Intersection(inner) if child.kind() == "intersection_type" => {
+                            members.extend(inner)
+                        }
+                        ty => members.push(ty),
+                    }
+                }
+                Type::Intersection(members)
+            }
+            "function_type" => Type::Function(self.signature_from_node(node, source, scope)),
+            "object_type" | "interface_body" => {
+                let mut obj = ObjectType::default();
+                let mut cursor = node.walk();
+                for member in node.named_children(&mut cursor) {
+                    match member.kind() {
+                        "property_signature" | "method_signature" => {
+                            let Some(name) = member.child_by_field_name("name") else {
+                                continue;
+                            };
+                            let name = text_of(name, source)
+                                .trim_matches(|c| c == '"' || c == '\'')
+                                .to_string();
+                            let ty = if member.kind() == "method_signature" {
+                                Type::Function(self.signature_from_node(member, source, scope))
+                            } else {
+                                member
+                                    .child_by_field_name("type")
+                                    .map(|t| self.node_to_type(t, source, scope))
+                                    .unwrap_or(Type::Any)
+                            };
+                            obj.properties.insert(
+                                name.clone(),
+                                Property {
+                                    name,
+                                    ty: Box::new(ty),
+                                    optional: has_child(member, "?"),
+                                    readonly: has_child(member, "readonly"),
+                                },
+                            );
+                        }
+                        "index_signature" => {
+                            obj.index_signatures.push(IndexSignature {
+                                key_type: Box::new(
+                                    member
+                                        .child_by_field_name("index_type")
+                                        .map(|t| self.node_to_type(t, source, scope))
+                                        .unwrap_or(Type::String),
+                                ),
+                                value_type: Box::new(
+                                    member
+                                        .child_by_field_name("type")
+                                        .map(|t| self.node_to_type(t, source, scope))
+                                        .unwrap_or(Type::Any),
+                                ),
+                                readonly: has_child(member, "readonly"),
+                            });
+                        }
+                        "call_signature" => {
+                            obj.call_signatures
+                                .push(self.signature_from_node(member, source, scope));
+                        }
+                        "construct_signature" => {
+                            obj.construct_signatures
+                                .push(self.signature_from_node(member, source, scope));
+                        }
+                        _ => {}
+                    }
+                }
+                Type::Object(obj)
+            }
+            "conditional_type" => {
+                let field = |name: &str| {
+                    Box::new(
+                        node.child_by_field_name(name)
+                            .map(|t| self.node_to_type(t, source, scope))
+                            .unwrap_or(Type::Any),
+                    )
+                };
+                Type::Conditional(ConditionalType {
+                    check_type: field("left"),
+                    extends_type: field("right"),
+                    true_type: field("consequence"),
+                    false_type: field("alternative"),
+                })
+            }
+            "index_type_query" => match node.named_child(0) {
+                Some(inner) => Type::Index(Box::new(self.node_to_type(inner, source, scope))),
+                None => Type::Any,
+            },
+            "lookup_type" => match (node.named_child(0), node.named_child(1)) {
+                (Some(object), Some(index)) => Type::IndexedAccess(IndexedAccessType {
+                    object_type: Box::new(self.node_to_type(object, source, scope)),
+                    index_type: Box::new(self.node_to_type(index, source, scope)),
+                }),
+                _ => Type::Any,
+            },
+            "template_literal_type" => Type::String,
+            _ => Type::Any,
+        }
+    }
+
+    /// Build a function signature from a node with `type_parameters`,
+    /// `parameters` and `return_type` fields
+    fn signature_from_node(&self, node: Node, source: &str, scope: &[String]) -> FunctionType {
+        let type_parameters = node
+            .child_by_field_name("type_parameters")
+            .map(|params| self.type_parameters_from_node(params, source, scope))
+            .unwrap_or_default();
+        let mut scope = scope.to_vec();
+        scope.extend(type_parameters.iter().map(|p| p.name.clone()));
+
+        let mut parameters = Vec::new();
+        let mut this_type = None;
+        if let Some(params) = node.child_by_field_name("parameters") {
+            let mut cursor = params.walk();
+            for param in params.named_children(&mut cursor) {
+                if !matches!(param.kind(), "required_parameter" | "optional_parameter") {
+                    continue;
+                }
+                let Some(pattern) = param.child_by_field_name("pattern") else {
+                    continue;
+                };
+                // `this: T` types the receiver and takes no argument
+                if pattern.kind() == "this" {
+                    this_type = param
+                        .child_by_field_name("type")
+                        .map(|t| Box::new(self.node_to_type(t, source, &scope)));
+                    continue;
+                }
+                let rest = pattern.kind() == "rest_pattern";
+                let name = if rest {
+                    pattern
+                        .named_child(0)
+                        .map(|n| text_of(n, source))
+                        .unwrap_or("")
+                } else {
+                    text_of(pattern, source)
+                };
+                parameters.push(Parameter {
+                    name: name.to_string(),
+                    ty: Box::new(
+                        param
+                            .child_by_field_name("type")
+                            .map(|t| self.node_to_type(t, source, &scope))
+                            .unwrap_or(Type::Any),
+                    ),
+                    optional: param.kind() == "optional_parameter",
+                    rest,
+                });
+            }
+        }
+
+        // Construct signatures put their return type in the `type` field
+        let return_type = node
+            .child_by_field_name("return_type")
+            .or_else(|| node.child_by_field_name("type"))
+            .map(|t| self.node_to_type(t, source, &scope))
+            .unwrap_or(Type::Void);
+
+        FunctionType {
+            type_parameters,
+            parameters,
+            return_type: Box::new(return_type),
+            this_type,
+        }
     }
 
     /// Get the type of a literal value from source text
@@ -244,6 +1604,71 @@ impl TypeChecker {
     }
 }
 
+/// `keyof T` for an object type: the union of its property names, plus
+/// `string` when it has a string index signature
+fn evaluate_keyof(operand: &Type) -> Option<Type> {
+    let Type::Object(object) = operand else {
+        return None;
+    };
+    let mut names: Vec<&String> = object.properties.keys().collect();
+    names.sort();
+    let mut keys: Vec<Type> = names
+        .into_iter()
+        .map(|name| Type::StringLiteral(name.clone()))
+        .collect();
+    if object
+        .index_signatures
+        .iter()
+        .any(|signature| matches!(*signature.key_type, Type::String))
+    {
+        keys.push(Type::String);
+    }
+    Some(match keys.len() {
+        0 => Type::Never,
+        1 => keys.remove(0),
+        _ => Type::Union(keys),
+    })
+}
+
+/// `T[K]` for an object type `T` and a literal (or union of literal) key
+fn evaluate_indexed_access(object: &Type, index: &Type) -> Option<Type> {
+    match (object, index) {
+        (_, Type::Union(keys)) => {
+            let types = keys
+                .iter()
+                .map(|key| evaluate_indexed_access(object, key))
+                .collect::<Option<Vec<_>>>()?;
+            Some(Type::Union(types))
+        }
+        (Type::Object(object), Type::StringLiteral(name)) => object
+            .properties
+            .get(name)
+            .map(|property| (*property.ty).clone())
+            .or_else(|| index_signature_value(object, &Type::String)),
+        (Type::Object(object), Type::String | Type::Number) => index_signature_value(object, index),
+        (Type::Array(element), Type::Number | Type::NumberLiteral(_)) => Some((**element).clone()),
+        _ => None,
+    }
+}
+
+/// The value type of the index signature taking `key`
+fn index_signature_value(object: &ObjectType, key: &Type) -> Option<Type> {
+    object
+        .index_signatures
+        .iter()
+        .find(|signature| key.is_assignable_to(&signature.key_type))
+        .map(|signature| (*signature.value_type).clone())
+}
+
+fn text_of<'a>(node: Node, source: &'a str) -> &'a str {
+    node.utf8_text(source.as_bytes()).unwrap_or("")
+}
+
+fn has_child(node: Node, kind: &str) -> bool {
+    let mut cursor = node.walk();
+    node.children(&mut cursor).any(|c| c.kind() == kind)
+}
+
 impl Default for TypeChecker {
     fn default() -> Self {
         Self::new()
@@ -459,6 +1884,44 @@ mod tests {
         assert!(checker.is_assignable_to(string_type, unknown_type));
     }
 
+    #[test]
+    fn test_null_assignability_follows_strict_null_checks() {
+        let strict = TypeChecker::with_options(&CompilerOptions {
+            strict: Some(true),
+            ..Default::default()
+        });
+        let mut loose = TypeChecker::with_options(&CompilerOptions::default());
+        assert!(strict.strict_null_checks());
+        assert!(!loose.strict_null_checks());
+
+        let (null, undefined) = (strict.null_type(), strict.undefined_type());
+        let (string, void, never) = (
+            strict.string_type(),
+            strict.void_type(),
+            strict.never_type(),
+        );
+        assert!(!strict.is_assignable_to(null, string));
+        assert!(loose.is_assignable_to(null, string));
+
+        // Under strict null checks they only fit themselves, top types, void
+        // (undefined only) and unions containing them
+        assert!(strict.is_assignable_to(undefined, void));
+        assert!(!strict.is_assignable_to(null, void));
+        assert!(strict.is_assignable_to(null, strict.any_type()));
+        assert!(strict.is_assignable_to(null, strict.unknown_type()));
+        assert!(!strict.is_assignable_to(undefined, null));
+
+        let mut checker = TypeChecker::new();
+        let nullable = checker.union_type(vec![string, null]);
+        assert!(checker.is_assignable_to(null, nullable));
+        assert!(!checker.is_assignable_to(nullable, string));
+
+        let nullable = loose.union_type(vec![string, null]);
+        assert!(loose.is_assignable_to(nullable, string));
+        assert!(loose.is_assignable_to(undefined, null));
+        assert!(!loose.is_assignable_to(null, never));
+    }
+
     #[test]
     fn test_never_assignable_to_everything() {
         let checker = TypeChecker::new();
@@ -552,4 +2015,507 @@ mod tests {
         assert!(!checker.is_assignable_to(fake_id, string_type));
         assert!(!checker.is_assignable_to(string_type, fake_id));
     }
+
+    fn type_param(name: &str) -> Type {
+        Type::TypeParameter(super::super::types::TypeParameter {
+            name: name.to_string(),
+            constraint: None,
+            default: None,
+        })
+    }
+
+    fn non_nullable(arg: Type) -> super::super::types::TypeReference {
+        super::super::types::TypeReference {
+            name: "NonNullable".to_string(),
+            type_arguments: vec![arg],
+        }
+    }
+
+    #[test]
+    fn test_evaluate_conditional_true_branch() {
+        let mut checker = TypeChecker::new();
+
+        let cond = ConditionalType {
+            check_type: Box::new(type_param("T")),
+            extends_type: Box::new(Type::String),
+            true_type: Box::new(Type::Number),
+            false_type: Box::new(Type::Boolean),
+        };
+
+        let mut context = HashMap::new();
+        context.insert(
+            "T".to_string(),
+            checker.string_literal_type("a".to_string()),
+        );
+
+        let result = checker.evaluate_conditional(&cond, &context);
+        assert!(matches!(checker.get_type(result), Some(Type::Number)));
+    }
+
+    #[test]
+    fn test_evaluate_conditional_false_branch() {
+        let mut checker = TypeChecker::new();
+
+        let cond = ConditionalType {
+            check_type: Box::new(type_param("T")),
+            extends_type: Box::new(Type::String),
+            true_type: Box::new(Type::Number),
+            false_type: Box::new(Type::Boolean),
+        };
+
+        let mut context = HashMap::new();
+        context.insert("T".to_string(), checker.number_type());
+
+        let result = checker.evaluate_conditional(&cond, &context);
+        assert!(matches!(checker.get_type(result), Some(Type::Boolean)));
+    }
+
+    #[test]
+    fn test_evaluate_conditional_unbound_is_deferred() {
+        let mut checker = TypeChecker::new();
+
+        let cond = ConditionalType {
+            check_type: Box::new(type_param("T")),
+            extends_type: Box::new(Type::String),
+            true_type: Box::new(Type::Number),
+            false_type: Box::new(Type::Boolean),
+        };
+
+        let result = checker.evaluate_conditional(&cond, &HashMap::new());
+        assert!(matches!(
+            checker.get_type(result),
+            Some(Type::Conditional(_))
+        ));
+    }
+
+    #[test]
+    fn test_evaluate_concrete_conditionals() {
+        let code = "type A = string extends string ? number : boolean;\ntype B = number extends string ? 1 : 0;\ntype C = T[] extends string[] ? 1 : 0;";
+        let mut parser = tree_sitter::Parser::new();
+        parser
+            .set_language(&tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into())
+            .unwrap();
+        let tree = parser.parse(code, None).unwrap();
+        let mut checker = TypeChecker::new();
+
+        let printed: Vec<_> = tree
+            .root_node()
+            .named_children(&mut tree.root_node().walk())
+            .map(|alias| {
+                let value = alias.child_by_field_name("value").unwrap();
+                let ty = checker.type_from_node(value, code);
+                crate::types::printer::print_type(checker.get_type(ty).unwrap())
+            })
+            .collect();
+        assert_eq!(
+            printed,
+            vec![
+                "number".to_string(),
+                "0".to_string(),
+                // `T` is not known, so the conditional stays as it is
+                "T[] extends string[] ? 1 : 0".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_evaluate_keyof_and_indexed_access() {
+        let code = "type K = keyof { a: number; b: string };\ntype A = { a: number }[\"a\"];\ntype U = { a: number; b: string }[\"a\" | \"b\"];\ntype D = { [key: string]: boolean }[\"x\"];\ntype G = keyof T;";
+        let mut parser = tree_sitter::Parser::new();
+        parser
+            .set_language(&tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into())
+            .unwrap();
+        let tree = parser.parse(code, None).unwrap();
+        let mut checker = TypeChecker::new();
+
+        let printed: Vec<_> = tree
+            .root_node()
+            .named_children(&mut tree.root_node().walk())
+            .map(|alias| {
+                let value = alias.child_by_field_name("value").unwrap();
+                let ty = checker.type_from_node(value, code);
+                crate::types::printer::print_type(checker.get_type(ty).unwrap())
+            })
+            .collect();
+        assert_eq!(
+            printed,
+            vec![
+                "\"a\" | \"b\"".to_string(),
+                "number".to_string(),
+                "number | string".to_string(),
+                "boolean".to_string(),
+                // Not an object, so `keyof` stays unevaluated
+                "keyof T".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_non_nullable_removes_null_and_undefined() {
+        let mut checker = TypeChecker::new();
+
+        let arg = Type::Union(vec![Type::String, Type::Null, Type::Undefined]);
+        let result = checker.resolve_reference(&non_nullable(arg));
+
+        assert!(matches!(checker.get_type(result), Some(Type::String)));
+    }
+
+    #[test]
+    fn test_non_nullable_of_null_is_never() {
+        let mut checker = TypeChecker::new();
+
+        let result = checker.resolve_reference(&non_nullable(Type::Null));
+        assert!(matches!(checker.get_type(result), Some(Type::Never)));
+    }
+
+    #[test]
+    fn test_instantiate_non_nullable_reference() {
+        let mut checker = TypeChecker::new();
+
+        let reference = checker.create_type(Type::Reference(non_nullable(type_param("T"))));
+        let number_or_null = checker.create_type(Type::Union(vec![Type::Number, Type::Null]));
+
+        let mut context = HashMap::new();
+        context.insert("T".to_string(), number_or_null);
+
+        let result = checker.instantiate(reference, &context);
+        assert!(matches!(checker.get_type(result), Some(Type::Number)));
+    }
+
+    #[test]
+    fn test_instantiate_generic_function() {
+        let mut checker = TypeChecker::new();
+        let function = |type_parameters: Vec<TypeParameter>| {
+            Type::Function(FunctionType {
+                type_parameters,
+                parameters: vec![Parameter {
+                    name: "x".to_string(),
+                    ty: Box::new(type_param("T")),
+                    optional: false,
+                    rest: false,
+                }],
+                return_type: Box::new(Type::Array(Box::new(type_param("T")))),
+                this_type: None,
+            })
+        };
+        let mut context = HashMap::new();
+        context.insert("T".to_string(), checker.number_type());
+
+        // `(x: T) => T[]` with `T = number`
+        let outer = checker.create_type(function(vec![]));
+        let result = checker.instantiate(outer, &context);
+        assert_eq!(
+            crate::types::printer::print_type(checker.get_type(result).unwrap()),
+            "(x: number) => number[]"
+        );
+
+        // The function's own `T` shadows the substitution
+        let generic = checker.create_type(function(vec![TypeParameter {
+            name: "T".to_string(),
+            constraint: None,
+            default: None,
+        }]));
+        let result = checker.instantiate(generic, &context);
+        assert_eq!(
+            crate::types::printer::print_type(checker.get_type(result).unwrap()),
+            "<T>(x: T) => T[]"
+        );
+    }
+
+    #[test]
+    fn test_instantiate_reference_arguments() {
+        let mut checker = TypeChecker::new();
+        let array_of_t = checker.create_type(Type::Reference(TypeReference {
+            name: "Array".to_string(),
+            type_arguments: vec![Type::Reference(TypeReference {
+                name: "T".to_string(),
+                type_arguments: vec![],
+            })],
+        }));
+        let mut context = HashMap::new();
+        context.insert("T".to_string(), checker.string_type());
+
+        let result = checker.instantiate(array_of_t, &context);
+        assert_eq!(
+            crate::types::printer::print_type(checker.get_type(result).unwrap()),
+            "Array<string>"
+        );
+    }
+
+    #[test]
+    fn test_resolve_unknown_reference_is_opaque() {
+        let mut checker = TypeChecker::new();
+
+        let reference = super::super::types::TypeReference {
+            name: "Foo".to_string(),
+            type_arguments: vec![],
+        };
+        let result = checker.resolve_reference(&reference);
+        assert!(matches!(checker.get_type(result), Some(Type::Reference(_))));
+    }
+
+    fn shape(kind: &str, extra: &str) -> Type {
+        let mut obj = ObjectType::default();
+        for (name, ty) in [
+            ("kind", Type::StringLiteral(kind.to_string())),
+            (extra, Type::Number),
+        ] {
+            obj.properties.insert(
+                name.to_string(),
+                Property {
+                    name: name.to_string(),
+                    ty: Box::new(ty),
+                    optional: false,
+                    readonly: false,
+                },
+            );
+        }
+        Type::Object(obj)
+    }
+
+    #[test]
+    fn test_narrow_union_by_property() {
+        let mut checker = TypeChecker::new();
+
+        let union = checker.create_type(Type::Union(vec![
+            shape("circle", "r"),
+            shape("square", "s"),
+        ]));
+        let guard = NarrowingGuard::PropertyEqual {
+            property: "kind".to_string(),
+            value: Type::StringLiteral("circle".to_string()),
+        };
+
+        let narrowed = checker.narrow_union_by_property(union, &guard);
+        match checker.get_type(narrowed) {
+            Some(Type::Object(obj)) => {
+                assert!(obj.properties.contains_key("r"));
+                assert!(!obj.properties.contains_key("s"));
+            }
+            other => panic!("Expected narrowed object, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_narrow_union_by_property_no_match_is_never() {
+        let mut checker = TypeChecker::new();
+
+        let union = checker.create_type(Type::Union(vec![
+            shape("circle", "r"),
+            shape("square", "s"),
+        ]));
+        let guard = NarrowingGuard::PropertyEqual {
+            property: "kind".to_string(),
+            value: Type::StringLiteral("triangle".to_string()),
+        };
+
+        let narrowed = checker.narrow_union_by_property(union, &guard);
+        assert_eq!(narrowed, checker.never_type());
+    }
+
+    #[test]
+    fn test_type_from_node_declared_alias() {
+        let code = "type Shape = { kind: 'circle'; r: number } | { kind: 'square'; s: number };";
+        let mut parser = tree_sitter::Parser::new();
+        parser
+            .set_language(&tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into())
+            .unwrap();
+        let tree = parser.parse(code, None).unwrap();
+
+        let mut checker = TypeChecker::new();
+        checker.declare_types_from_tree(tree.root_node(), code);
+
+        let reference = super::super::types::TypeReference {
+            name: "Shape".to_string(),
+            type_arguments: vec![],
+        };
+        let resolved = checker.resolve_reference(&reference);
+        match checker.get_type(resolved) {
+            Some(Type::Union(members)) => assert_eq!(members.len(), 2),
+            other => panic!("Expected union, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_literal_type() {
+        let code = "['a', 42, `t`, [], (true), new Date(), new Foo()];";
+        let mut parser = tree_sitter::Parser::new();
+        parser
+            .set_language(&tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into())
+            .unwrap();
+        let tree = parser.parse(code, None).unwrap();
+        let array = tree.root_node().child(0).unwrap().child(0).unwrap();
+        let checker = TypeChecker::new();
+
+        let types: Vec<_> = array
+            .named_children(&mut array.walk())
+            .map(|element| {
+                checker
+                    .literal_type(element, code)
+                    .map(|ty| crate::types::printer::print_type(&ty))
+            })
+            .collect();
+        assert_eq!(
+            types,
+            vec![
+                Some("\"a\"".to_string()),
+                Some("42".to_string()),
+                Some("string".to_string()),
+                Some("any[]".to_string()),
+                Some("true".to_string()),
+                Some("Date".to_string()),
+                None,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_infer_from_node() {
+        let code = "class Foo {}\nfunction id(): Foo { return new Foo(); }\nconst seed = 1;\n[new Foo(), id(), seed, 'a', true, unknown()];";
+        let mut parser = tree_sitter::Parser::new();
+        parser
+            .set_language(&tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into())
+            .unwrap();
+        let tree = parser.parse(code, None).unwrap();
+        let mut table = crate::analysis::binder::bind_document(&tree, code);
+        let mut checker = TypeChecker::new();
+        checker.declare_types_from_tree(tree.root_node(), code);
+        checker.infer_symbol_types(&mut table, tree.root_node(), code);
+
+        let array = tree.root_node().child(3).unwrap().child(0).unwrap();
+        let types: Vec<_> = array
+            .named_children(&mut array.walk())
+            .map(|element| {
+                checker
+                    .infer_from_node(element, code, &table)
+                    .and_then(|id| checker.get_type(id))
+                    .map(crate::types::printer::print_type)
+            })
+            .collect();
+        assert_eq!(
+            types,
+            vec![
+                Some("{}".to_string()),
+                Some("Foo".to_string()),
+                Some("number".to_string()),
+                Some("string".to_string()),
+                Some("boolean".to_string()),
+                None,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_infer_symbol_types_for_class_instances() {
+        let code = "class Foo { bar: string; static s = 1; constructor() {} baz(): void {} }\nconst f = new Foo();";
+        let mut parser = tree_sitter::Parser::new();
+        parser
+            .set_language(&tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into())
+            .unwrap();
+        let tree = parser.parse(code, None).unwrap();
+        let mut table = crate::analysis::binder::bind_document(&tree, code);
+
+        let mut checker = TypeChecker::new();
+        checker.declare_types_from_tree(tree.root_node(), code);
+        checker.infer_symbol_types(&mut table, tree.root_node(), code);
+
+        let f = table
+            .all_symbols()
+            .find(|s| s.name == "f")
+            .and_then(|s| s.inferred_type)
+            .expect("f should have an inferred type");
+        match checker.get_type(f) {
+            Some(Type::Object(obj)) => {
+                let mut names: Vec<_> = obj.properties.keys().cloned().collect();
+                names.sort();
+                assert_eq!(names, vec!["bar", "baz"]);
+                assert!(matches!(
+                    obj.properties["baz"].ty.as_ref(),
+                    Type::Function(_)
+                ));
+            }
+            other => panic!("Expected object, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_class_instance_type_members() {
+        let code = "class P {\n  constructor(private x: number, readonly y?: string, z: boolean) {}\n  set w(v: number) {}\n  get both(): string { return ''; }\n  set both(v: string) {}\n}\nconst p = new P();";
+        let mut parser = tree_sitter::Parser::new();
+        parser
+            .set_language(&tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into())
+            .unwrap();
+        let tree = parser.parse(code, None).unwrap();
+        let mut table = crate::analysis::binder::bind_document(&tree, code);
+
+        let mut checker = TypeChecker::new();
+        checker.declare_types_from_tree(tree.root_node(), code);
+        checker.infer_symbol_types(&mut table, tree.root_node(), code);
+
+        let p = table
+            .all_symbols()
+            .find(|s| s.name == "p")
+            .and_then(|s| s.inferred_type)
+            .expect("p should have an inferred type");
+        let Some(Type::Object(obj)) = checker.get_type(p) else {
+            panic!("Expected object, got {:?}", checker.get_type(p));
+        };
+        let mut names: Vec<_> = obj.properties.keys().cloned().collect();
+        names.sort();
+        assert_eq!(names, vec!["both", "w", "x", "y"]);
+        assert!(matches!(obj.properties["x"].ty.as_ref(), Type::Number));
+        assert!(obj.properties["y"].optional && obj.properties["y"].readonly);
+        assert!(matches!(obj.properties["w"].ty.as_ref(), Type::Number));
+        assert!(matches!(obj.properties["both"].ty.as_ref(), Type::String));
+    }
+
+    #[test]
+    fn test_resolve_named_reference() {
+        let code =
+            "type Names = Array<string>;\ninterface Point { x: number }\ntype Tree = Tree[];";
+        let mut parser = tree_sitter::Parser::new();
+        parser
+            .set_language(&tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into())
+            .unwrap();
+        let tree = parser.parse(code, None).unwrap();
+        let table = crate::analysis::binder::bind_document(&tree, code);
+        let mut checker = TypeChecker::new();
+        checker.declare_types_from_tree(tree.root_node(), code);
+
+        let reference = |name: &str, type_arguments: Vec<Type>| TypeReference {
+            name: name.to_string(),
+            type_arguments,
+        };
+        let array =
+            checker.resolve_named_reference(&reference("Array", vec![Type::String]), &table);
+        assert!(matches!(
+            checker.get_type(array),
+            Some(Type::Array(element)) if matches!(**element, Type::String)
+        ));
+
+        // `string[]` is assignable to `Array<string>` and to an alias of it
+        let strings = checker.create_type(Type::Array(Box::new(Type::String)));
+        let numbers = checker.create_type(Type::Array(Box::new(Type::Number)));
+        let array_ref =
+            checker.create_type(Type::Reference(reference("Array", vec![Type::String])));
+        let names = checker.create_type(Type::Reference(reference("Names", vec![])));
+        assert!(checker.is_assignable_in(strings, array_ref, &table));
+        assert!(checker.is_assignable_in(strings, names, &table));
+        assert!(!checker.is_assignable_in(numbers, names, &table));
+
+        let point = checker.resolve_named_reference(&reference("Point", vec![]), &table);
+        assert!(matches!(checker.get_type(point), Some(Type::Object(_))));
+        let unknown = checker.resolve_named_reference(&reference("Missing", vec![]), &table);
+        assert!(matches!(
+            checker.get_type(unknown),
+            Some(Type::Reference(_))
+        ));
+
+        // The recursive alias expands once and keeps its self reference
+        let recursive = checker.resolve_named_reference(&reference("Tree", vec![]), &table);
+        assert_eq!(
+            crate::types::printer::print_type(checker.get_type(recursive).unwrap()),
+            "Tree[]"
+        );
+    }
 }
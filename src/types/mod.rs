@@ -1,9 +1,10 @@
 pub mod checker;
 pub mod printer;
+#[allow(clippy::module_inception)]
 pub mod types;
 
 // Re-export public API for future use
 #[allow(unused_imports)]
-pub use checker::TypeChecker;
+pub use checker::{NarrowingGuard, TypeChecker};
 #[allow(unused_imports)]
 pub use types::{Type, TypeFlags, TypeId};
@@ -3,10 +3,85 @@
 
 #![allow(dead_code)]
 
-use super::types::Type;
+use std::collections::HashSet;
+
+use super::types::{Type, TypeReference};
+
+/// Options controlling how types are printed
+#[derive(Debug, Clone)]
+pub struct PrintOptions {
+    /// Quote character used for string literal types
+    pub quote_char: char,
+    /// Wrap unions and intersections that don't fit in this many columns
+    pub max_width: Option<usize>,
+    /// Drop `undefined` from the type of optional (`?`) properties
+    pub omit_undefined_optional: bool,
+    /// Abbreviate nested object types as `{ ... }`
+    pub compact: bool,
+    /// Print union members in sorted order instead of declaration order
+    pub sort_members: bool,
+    /// Put every member of a wrapped union on its own line, each prefixed
+    /// with `| ` like in a multi-line type alias
+    pub multiline_unions: bool,
+}
+
+impl Default for PrintOptions {
+    fn default() -> Self {
+        Self {
+            quote_char: '"',
+            max_width: None,
+            omit_undefined_optional: false,
+            compact: false,
+            sort_members: false,
+            multiline_unions: false,
+        }
+    }
+}
+
+/// Expands a named type reference into its definition instantiated with the
+/// reference's type arguments, if known
+pub type ReferenceResolver<'a> = dyn Fn(&TypeReference) -> Option<Type> + 'a;
 
 /// Print a type as a string
 pub fn print_type(ty: &Type) -> String {
+    print_type_with_opts(ty, &PrintOptions::default())
+}
+
+/// Print a type as a string using the given options
+pub fn print_type_with_opts(ty: &Type, opts: &PrintOptions) -> String {
+    let ctx = PrintContext {
+        opts,
+        resolve: None,
+    };
+    print_type_recursive(ty, &mut HashSet::new(), &ctx, 0)
+}
+
+/// Print a type, inlining named references that `resolve` can expand.
+/// References that are already being expanded print as `...`.
+pub fn print_type_expanded(ty: &Type, opts: &PrintOptions, resolve: &ReferenceResolver) -> String {
+    let ctx = PrintContext {
+        opts,
+        resolve: Some(resolve),
+    };
+    print_type_recursive(ty, &mut HashSet::new(), &ctx, 0)
+}
+
+struct PrintContext<'a> {
+    opts: &'a PrintOptions,
+    resolve: Option<&'a ReferenceResolver<'a>>,
+}
+
+/// `seen` holds the reference names currently being expanded on the call
+/// stack; re-entering one of them prints the cycle marker instead
+fn print_type_recursive(
+    ty: &Type,
+    seen: &mut HashSet<String>,
+    ctx: &PrintContext,
+    depth: usize,
+) -> String {
+    let opts = ctx.opts;
+    let mut nested = |ty: &Type| print_type_recursive(ty, seen, ctx, depth + 1);
+
     match ty {
         Type::Any => "any".to_string(),
         Type::Unknown => "unknown".to_string(),
@@ -20,15 +95,29 @@ pub fn print_type(ty: &Type) -> String {
         Type::Symbol => "symbol".to_string(),
         Type::BigInt => "bigint".to_string(),
 
-        Type::StringLiteral(s) => format!("\"{}\"", s.replace('"', "\\\"")),
+        Type::StringLiteral(s) => {
+            let q = opts.quote_char;
+            let escaped = s.replace('\\', "\\\\").replace(q, &format!("\\{q}"));
+            format!("{q}{escaped}{q}")
+        }
         Type::NumberLiteral(n) => format!("{}", n),
         Type::BooleanLiteral(b) => format!("{}", b),
         Type::BigIntLiteral(s) => format!("{}n", s),
 
-        Type::Array(element) => format!("{}[]", print_type(element)),
+        Type::Array(element) => {
+            let printed = nested(element);
+            match element.as_ref() {
+                Type::Function(_)
+                | Type::Union(_)
+                | Type::Intersection(_)
+                | Type::Conditional(_)
+                | Type::Index(_) => format!("({})[]", printed),
+                _ => format!("{}[]", printed),
+            }
+        }
 
         Type::Tuple(elements) => {
-            let elems: Vec<String> = elements.iter().map(print_type).collect();
+            let elems: Vec<String> = elements.iter().map(nested).collect();
             format!("[{}]", elems.join(", "))
         }
 
@@ -40,19 +129,33 @@ pub fn print_type(ty: &Type) -> String {
                 return "{}".to_string();
             }
 
+            if opts.compact && depth > 0 {
+                return "{ ... }".to_string();
+            }
+
             let mut parts = Vec::new();
 
-            // Print properties
-            for (name, prop) in &obj.properties {
+            // Print properties in a stable order
+            let mut properties: Vec<_> = obj.properties.iter().collect();
+            properties.sort_by(|a, b| a.0.cmp(b.0));
+            for (name, prop) in properties {
                 let readonly = if prop.readonly { "readonly " } else { "" };
                 let optional = if prop.optional { "?" } else { "" };
-                parts.push(format!(
-                    "{}{}{}: {}",
-                    readonly,
-                    name,
-                    optional,
-                    print_type(&prop.ty)
-                ));
+                let ty = match prop.ty.as_ref() {
+                    Type::Union(members) if prop.optional && opts.omit_undefined_optional => {
+                        let members: Vec<Type> = members
+                            .iter()
+                            .filter(|m| !matches!(m, Type::Undefined))
+                            .cloned()
+                            .collect();
+                        match members.len() {
+                            1 => nested(&members[0]),
+                            _ => nested(&Type::Union(members)),
+                        }
+                    }
+                    ty => nested(ty),
+                };
+                parts.push(format!("{}{}{}: {}", readonly, name, optional, ty));
             }
 
             // Print index signatures
@@ -61,8 +164,8 @@ pub fn print_type(ty: &Type) -> String {
                 parts.push(format!(
                     "{}[key: {}]: {}",
                     readonly,
-                    print_type(&sig.key_type),
-                    print_type(&sig.value_type)
+                    nested(&sig.key_type),
+                    nested(&sig.value_type)
                 ));
             }
 
@@ -80,7 +183,7 @@ pub fn print_type(ty: &Type) -> String {
                         let constraint = p
                             .constraint
                             .as_ref()
-                            .map(|c| format!(" extends {}", print_type(c)))
+                            .map(|c| format!(" extends {}", nested(c)))
                             .unwrap_or_default();
                         format!("{}{}", p.name, constraint)
                     })
@@ -94,7 +197,7 @@ pub fn print_type(ty: &Type) -> String {
                 .map(|p| {
                     let rest = if p.rest { "..." } else { "" };
                     let optional = if p.optional { "?" } else { "" };
-                    format!("{}{}{}: {}", rest, p.name, optional, print_type(&p.ty))
+                    format!("{}{}{}: {}", rest, p.name, optional, nested(&p.ty))
                 })
                 .collect();
 
@@ -102,25 +205,52 @@ pub fn print_type(ty: &Type) -> String {
                 "{}({}) => {}",
                 type_params,
                 params.join(", "),
-                print_type(&func.return_type)
+                nested(&func.return_type)
             )
         }
 
         Type::Union(members) => {
-            let parts: Vec<String> = members.iter().map(print_type).collect();
-            parts.join(" | ")
+            // `true | false` is how TypeScript spells `boolean`
+            let has = |value: bool| {
+                members
+                    .iter()
+                    .any(|m| matches!(m, Type::BooleanLiteral(b) if *b == value))
+            };
+            let mut parts: Vec<String> = Vec::new();
+            for member in members {
+                let printed = match member {
+                    Type::BooleanLiteral(_) if has(true) && has(false) => "boolean".to_string(),
+                    Type::Function(_) | Type::Conditional(_) => format!("({})", nested(member)),
+                    _ => nested(member),
+                };
+                if !parts.contains(&printed) || printed != "boolean" {
+                    parts.push(printed);
+                }
+            }
+            if opts.sort_members {
+                parts.sort();
+            }
+            join_members(&parts, "|", opts, depth)
         }
 
         Type::Intersection(members) => {
-            let parts: Vec<String> = members.iter().map(print_type).collect();
-            parts.join(" & ")
+            let parts: Vec<String> = members
+                .iter()
+                .map(|member| match member {
+                    Type::Union(_) | Type::Function(_) | Type::Conditional(_) => {
+                        format!("({})", nested(member))
+                    }
+                    _ => nested(member),
+                })
+                .collect();
+            join_members(&parts, "&", opts, depth)
         }
 
         Type::TypeParameter(param) => {
             let constraint = param
                 .constraint
                 .as_ref()
-                .map(|c| format!(" extends {}", print_type(c)))
+                .map(|c| format!(" extends {}", nested(c)))
                 .unwrap_or_default();
             format!("{}{}", param.name, constraint)
         }
@@ -128,10 +258,10 @@ pub fn print_type(ty: &Type) -> String {
         Type::Conditional(cond) => {
             format!(
                 "{} extends {} ? {} : {}",
-                print_type(&cond.check_type),
-                print_type(&cond.extends_type),
-                print_type(&cond.true_type),
-                print_type(&cond.false_type)
+                nested(&cond.check_type),
+                nested(&cond.extends_type),
+                nested(&cond.true_type),
+                nested(&cond.false_type)
             )
         }
 
@@ -150,27 +280,36 @@ pub fn print_type(ty: &Type) -> String {
                 "{{ {}[{} in {}]{}: {} }}",
                 readonly,
                 mapped.type_parameter,
-                print_type(&mapped.constraint),
+                nested(&mapped.constraint),
                 optional,
-                print_type(&mapped.template_type)
+                nested(&mapped.template_type)
             )
         }
 
-        Type::Index(ty) => format!("keyof {}", print_type(ty)),
+        Type::Index(ty) => format!("keyof {}", nested(ty)),
 
         Type::IndexedAccess(access) => {
             format!(
                 "{}[{}]",
-                print_type(&access.object_type),
-                print_type(&access.index_type)
+                nested(&access.object_type),
+                nested(&access.index_type)
             )
         }
 
         Type::Reference(ref_type) => {
+            if let Some(expanded) = ctx.resolve.and_then(|resolve| resolve(ref_type)) {
+                if !seen.insert(ref_type.name.clone()) {
+                    return "...".to_string();
+                }
+                let printed = print_type_recursive(&expanded, seen, ctx, depth);
+                seen.remove(&ref_type.name);
+                return printed;
+            }
+
             if ref_type.type_arguments.is_empty() {
                 ref_type.name.clone()
             } else {
-                let args: Vec<String> = ref_type.type_arguments.iter().map(print_type).collect();
+                let args: Vec<String> = ref_type.type_arguments.iter().map(nested).collect();
                 format!("{}<{}>", ref_type.name, args.join(", "))
             }
         }
@@ -179,6 +318,30 @@ pub fn print_type(ty: &Type) -> String {
     }
 }
 
+/// Join union/intersection members, breaking before each operator with
+/// indentation when the single-line form exceeds `max_width`
+fn join_members(parts: &[String], operator: &str, opts: &PrintOptions, depth: usize) -> String {
+    let single_line = parts.join(&format!(" {} ", operator));
+
+    match opts.max_width {
+        Some(max_width)
+            if single_line.len() > max_width
+                && parts.len() > 1
+                && operator == "|"
+                && opts.multiline_unions =>
+        {
+            let indent = "    ".repeat(depth);
+            let lines: Vec<String> = parts.iter().map(|part| format!("| {}", part)).collect();
+            lines.join(&format!("\n{}", indent))
+        }
+        Some(max_width) if single_line.len() > max_width && parts.len() > 1 => {
+            let indent = "    ".repeat(depth + 1);
+            parts.join(&format!("\n{}{} ", indent, operator))
+        }
+        _ => single_line,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -212,6 +375,14 @@ mod tests {
             print_type(&Type::StringLiteral("with\"quote".to_string())),
             "\"with\\\"quote\""
         );
+        assert_eq!(
+            print_type(&Type::StringLiteral("a\\b".to_string())),
+            "\"a\\\\b\""
+        );
+        assert_eq!(
+            print_type(&Type::StringLiteral("end\\\"".to_string())),
+            "\"end\\\\\\\"\""
+        );
     }
 
     #[test]
@@ -254,6 +425,61 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_print_boolean_literal_union() {
+        let union = Type::Union(vec![
+            Type::BooleanLiteral(true),
+            Type::String,
+            Type::BooleanLiteral(false),
+        ]);
+        assert_eq!(print_type(&union), "boolean | string");
+
+        let only = Type::Union(vec![
+            Type::BooleanLiteral(true),
+            Type::BooleanLiteral(false),
+        ]);
+        assert_eq!(print_type(&only), "boolean");
+
+        // A single literal stays a literal
+        let single = Type::Union(vec![Type::BooleanLiteral(true), Type::Null]);
+        assert_eq!(print_type(&single), "true | null");
+    }
+
+    #[test]
+    fn test_print_parenthesized_members() {
+        let callback = Type::Function(FunctionType {
+            type_parameters: vec![],
+            parameters: vec![],
+            return_type: Box::new(Type::Void),
+            this_type: None,
+        });
+        assert_eq!(
+            print_type(&Type::Array(Box::new(callback.clone()))),
+            "(() => void)[]"
+        );
+        assert_eq!(
+            print_type(&Type::Union(vec![callback, Type::Null])),
+            "(() => void) | null"
+        );
+        assert_eq!(
+            print_type(&Type::Array(Box::new(Type::Union(vec![
+                Type::String,
+                Type::Number
+            ])))),
+            "(string | number)[]"
+        );
+        assert_eq!(
+            print_type(&Type::Intersection(vec![
+                Type::Union(vec![Type::String, Type::Number]),
+                Type::Reference(TypeReference {
+                    name: "Tagged".to_string(),
+                    type_arguments: vec![],
+                }),
+            ])),
+            "(string | number) & Tagged"
+        );
+    }
+
     #[test]
     fn test_print_intersection() {
         let obj1 = Type::Object(ObjectType::default());
@@ -521,4 +747,262 @@ mod tests {
     fn test_print_this() {
         assert_eq!(print_type(&Type::This), "this");
     }
+
+    fn optional_object() -> Type {
+        let mut properties = std::collections::HashMap::new();
+        properties.insert(
+            "name".to_string(),
+            Property {
+                name: "name".to_string(),
+                ty: Box::new(Type::Union(vec![Type::String, Type::Undefined])),
+                optional: true,
+                readonly: false,
+            },
+        );
+        properties.insert(
+            "tag".to_string(),
+            Property {
+                name: "tag".to_string(),
+                ty: Box::new(Type::StringLiteral("it's".to_string())),
+                optional: false,
+                readonly: false,
+            },
+        );
+        Type::Object(ObjectType {
+            properties,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_print_options_default_matches_print_type() {
+        let ty = optional_object();
+        assert_eq!(
+            print_type_with_opts(&ty, &PrintOptions::default()),
+            print_type(&ty)
+        );
+        assert_eq!(
+            print_type(&ty),
+            "{ name?: string | undefined; tag: \"it's\" }"
+        );
+    }
+
+    #[test]
+    fn test_print_options_single_quotes() {
+        let opts = PrintOptions {
+            quote_char: '\'',
+            ..Default::default()
+        };
+        assert_eq!(
+            print_type_with_opts(&Type::StringLiteral("hello".to_string()), &opts),
+            "'hello'"
+        );
+        assert_eq!(
+            print_type_with_opts(&optional_object(), &opts),
+            "{ name?: string | undefined; tag: 'it\\'s' }"
+        );
+    }
+
+    #[test]
+    fn test_print_options_omit_undefined_optional() {
+        let opts = PrintOptions {
+            omit_undefined_optional: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            print_type_with_opts(&optional_object(), &opts),
+            "{ name?: string; tag: \"it's\" }"
+        );
+    }
+
+    #[test]
+    fn test_print_options_compact() {
+        let opts = PrintOptions {
+            compact: true,
+            ..Default::default()
+        };
+        let ty = Type::Array(Box::new(optional_object()));
+        assert_eq!(print_type_with_opts(&ty, &opts), "{ ... }[]");
+        // The top-level object is still printed in full
+        assert_eq!(
+            print_type_with_opts(&optional_object(), &opts),
+            "{ name?: string | undefined; tag: \"it's\" }"
+        );
+    }
+
+    #[test]
+    fn test_print_options_max_width_wraps_union() {
+        let ty = Type::Union(vec![
+            Type::StringLiteral("first".to_string()),
+            Type::StringLiteral("second".to_string()),
+            Type::StringLiteral("third".to_string()),
+        ]);
+
+        let wide = PrintOptions {
+            max_width: Some(80),
+            ..Default::default()
+        };
+        assert_eq!(
+            print_type_with_opts(&ty, &wide),
+            "\"first\" | \"second\" | \"third\""
+        );
+
+        let narrow = PrintOptions {
+            max_width: Some(10),
+            ..Default::default()
+        };
+        assert_eq!(
+            print_type_with_opts(&ty, &narrow),
+            "\"first\"\n    | \"second\"\n    | \"third\""
+        );
+    }
+
+    #[test]
+    fn test_print_options_multiline_sorted_union() {
+        let ty = Type::Union(vec![
+            Type::StringLiteral("north".to_string()),
+            Type::StringLiteral("east".to_string()),
+            Type::StringLiteral("south".to_string()),
+            Type::StringLiteral("west".to_string()),
+        ]);
+
+        let sorted = PrintOptions {
+            sort_members: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            print_type_with_opts(&ty, &sorted),
+            "\"east\" | \"north\" | \"south\" | \"west\""
+        );
+
+        let multiline = PrintOptions {
+            max_width: Some(20),
+            multiline_unions: true,
+            ..sorted
+        };
+        assert_eq!(
+            print_type_with_opts(&ty, &multiline),
+            "| \"east\"\n| \"north\"\n| \"south\"\n| \"west\""
+        );
+
+        // Unions that fit stay on one line
+        let short = Type::Union(vec![Type::String, Type::Number]);
+        assert_eq!(print_type_with_opts(&short, &multiline), "number | string");
+    }
+
+    #[test]
+    fn test_print_options_max_width_wraps_intersection() {
+        let a = Type::Reference(TypeReference {
+            name: "VeryLongInterfaceName".to_string(),
+            type_arguments: vec![],
+        });
+        let b = Type::Reference(TypeReference {
+            name: "AnotherLongInterfaceName".to_string(),
+            type_arguments: vec![],
+        });
+        let opts = PrintOptions {
+            max_width: Some(20),
+            ..Default::default()
+        };
+        assert_eq!(
+            print_type_with_opts(&Type::Intersection(vec![a, b]), &opts),
+            "VeryLongInterfaceName\n    & AnotherLongInterfaceName"
+        );
+    }
+
+    fn reference(name: &str, type_arguments: Vec<Type>) -> Type {
+        Type::Reference(TypeReference {
+            name: name.to_string(),
+            type_arguments,
+        })
+    }
+
+    fn object_with(name: &str, ty: Type) -> Type {
+        let mut properties = std::collections::HashMap::new();
+        properties.insert(
+            name.to_string(),
+            Property {
+                name: name.to_string(),
+                ty: Box::new(ty),
+                optional: false,
+                readonly: false,
+            },
+        );
+        Type::Object(ObjectType {
+            properties,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_print_expanded_mutually_recursive() {
+        // type A = { b: B }; type B = { a: A }
+        let resolve = |r: &TypeReference| match r.name.as_str() {
+            "A" => Some(object_with("b", reference("B", vec![]))),
+            "B" => Some(object_with("a", reference("A", vec![]))),
+            _ => None,
+        };
+
+        let printed =
+            print_type_expanded(&reference("A", vec![]), &PrintOptions::default(), &resolve);
+        assert_eq!(printed, "{ b: { a: ... } }");
+    }
+
+    #[test]
+    fn test_print_expanded_self_referential() {
+        // type Tree<T> = { value: T; children: Tree<T>[] }, instantiated
+        // with the reference's argument for `T`
+        let resolve = |r: &TypeReference| match r.name.as_str() {
+            "Tree" => {
+                let t = r.type_arguments.first().cloned().unwrap_or_else(|| {
+                    Type::TypeParameter(TypeParameter {
+                        name: "T".to_string(),
+                        constraint: None,
+                        default: None,
+                    })
+                });
+                let mut properties = std::collections::HashMap::new();
+                for (name, ty) in [
+                    ("value", t.clone()),
+                    (
+                        "children",
+                        Type::Array(Box::new(reference("Tree", vec![t]))),
+                    ),
+                ] {
+                    properties.insert(
+                        name.to_string(),
+                        Property {
+                            name: name.to_string(),
+                            ty: Box::new(ty),
+                            optional: false,
+                            readonly: false,
+                        },
+                    );
+                }
+                Some(Type::Object(ObjectType {
+                    properties,
+                    ..Default::default()
+                }))
+            }
+            _ => None,
+        };
+
+        let printed = print_type_expanded(
+            &reference("Tree", vec![Type::Number]),
+            &PrintOptions::default(),
+            &resolve,
+        );
+        assert_eq!(printed, "{ children: ...[]; value: number }");
+    }
+
+    #[test]
+    fn test_print_expanded_unresolved_reference_prints_name() {
+        let resolve = |_: &TypeReference| None;
+        let printed = print_type_expanded(
+            &reference("Map", vec![Type::String, Type::Number]),
+            &PrintOptions::default(),
+            &resolve,
+        );
+        assert_eq!(printed, "Map<string, number>");
+    }
 }
@@ -165,8 +165,16 @@ impl Type {
         }
     }
 
-    /// Check if this type is assignable to another type
+    /// Check if this type is assignable to another type, with
+    /// `strictNullChecks` on
     pub fn is_assignable_to(&self, target: &Type) -> bool {
+        self.is_assignable_with(target, true)
+    }
+
+    /// Check if this type is assignable to another type. Without
+    /// `strict_null_checks`, `null` and `undefined` are assignable to
+    /// every type but `never`.
+    pub fn is_assignable_with(&self, target: &Type, strict_null_checks: bool) -> bool {
         // Any is assignable to anything
         if matches!(self, Type::Any) {
             return true;
@@ -192,6 +200,10 @@ impl Type {
             return false;
         }
 
+        if !strict_null_checks && matches!(self, Type::Null | Type::Undefined) {
+            return true;
+        }
+
         // Same types are assignable
         match (self, target) {
             (Type::String, Type::String) => true,
@@ -216,16 +228,22 @@ impl Type {
             (Type::BooleanLiteral(a), Type::BooleanLiteral(b)) => a == b,
 
             // Arrays
-            (Type::Array(a), Type::Array(b)) => a.is_assignable_to(b),
+            (Type::Array(a), Type::Array(b)) => a.is_assignable_with(b, strict_null_checks),
 
             // Unions - source must be assignable to at least one member
-            (_, Type::Union(members)) => members.iter().any(|m| self.is_assignable_to(m)),
+            (_, Type::Union(members)) => members
+                .iter()
+                .any(|m| self.is_assignable_with(m, strict_null_checks)),
 
             // Source union - all members must be assignable to target
-            (Type::Union(members), _) => members.iter().all(|m| m.is_assignable_to(target)),
+            (Type::Union(members), _) => members
+                .iter()
+                .all(|m| m.is_assignable_with(target, strict_null_checks)),
 
             // Intersections - source must be assignable to all members
-            (_, Type::Intersection(members)) => members.iter().all(|m| self.is_assignable_to(m)),
+            (_, Type::Intersection(members)) => members
+                .iter()
+                .all(|m| self.is_assignable_with(m, strict_null_checks)),
 
             // TODO: More complex type relationships
             _ => false,